@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use crate::{
+    confirmation::Confirmation,
     log::print_if_log_disabled,
-    senders::{builder::SenderBuilder, CosmosOptions, CosmosWalletKey},
+    senders::{builder::SenderBuilder, CosmosOptions},
     DaemonAsyncBase, DaemonBuilder, DaemonStateFile, Wallet,
 };
 
@@ -32,9 +33,11 @@ pub struct DaemonAsyncBuilder {
     /// State from rebuild or existing daemon
     pub(crate) state: Option<DaemonState>,
     pub(crate) write_on_change: Option<bool>,
+    pub(crate) journal: Option<bool>,
     pub(crate) is_test: bool,
 
     pub(crate) mnemonic: Option<String>,
+    pub(crate) confirmation: Option<Confirmation>,
 }
 
 impl DaemonAsyncBuilder {
@@ -45,7 +48,9 @@ impl DaemonAsyncBuilder {
             state_path: None,
             state: None,
             write_on_change: None,
+            journal: None,
             mnemonic: None,
+            confirmation: None,
             is_test: false,
         }
     }
@@ -73,18 +78,41 @@ impl DaemonAsyncBuilder {
         self
     }
 
+    /// Whether to record the uploads, instantiations and migrations in a deployment journal stored in the state file.
+    /// A re-run of an interrupted deployment skips the steps that are already confirmed on-chain.
+    /// Defaults to `false`
+    pub fn journal(&mut self, journal: bool) -> &mut Self {
+        self.journal = Some(journal);
+        self
+    }
+
     /// Set the mnemonic used for the default Cosmos wallet
     pub fn mnemonic(&mut self, mnemonic: impl Into<String>) -> &mut Self {
         self.mnemonic = Some(mnemonic.into());
         self
     }
 
+    /// Set the confirmation awaited after broadcasting a transaction with the default Cosmos wallet.
+    /// Defaults to the inclusion of the transaction in a block
+    pub fn confirmation(&mut self, confirmation: Confirmation) -> &mut Self {
+        self.confirmation = Some(confirmation);
+        self
+    }
+
     /// Overwrite the chain info
     pub fn chain(&mut self, chain: impl Into<ChainInfoOwned>) -> &mut Self {
         self.chain = chain.into();
         self
     }
 
+    /// Overwrites the endpoints used to interact with the chain with a Tendermint RPC url.
+    /// The node is then reached without gRPC, see [`RpcTransport`](crate::transport::RpcTransport)
+    pub fn rpc_url(&mut self, url: impl Into<String>) -> &mut Self {
+        self.chain.grpc_urls = vec![];
+        self.chain.rpc_urls = vec![url.into()];
+        self
+    }
+
     /// Set daemon as testing daemon
     /// when set to `true` will use temporary file for state
     pub fn is_test(&mut self, is_test: bool) -> &mut Self {
@@ -109,12 +137,13 @@ impl DaemonAsyncBuilder {
         let state = self.build_state()?;
         // if mnemonic provided, use it. Else use env variables to retrieve mnemonic
 
-        let options = CosmosOptions {
-            key: self.mnemonic.as_ref().map_or(CosmosWalletKey::Env, |m| {
-                CosmosWalletKey::Mnemonic(m.clone())
-            }),
-            ..Default::default()
-        };
+        let mut options = CosmosOptions::default();
+        if let Some(mnemonic) = &self.mnemonic {
+            options.set_mnemonic(mnemonic);
+        }
+        if let Some(confirmation) = self.confirmation {
+            options.set_confirmation(confirmation);
+        }
         let sender = options.build(&chain_info).await?;
 
         let daemon = DaemonAsyncBase::new(sender, state);
@@ -151,7 +180,7 @@ impl DaemonAsyncBuilder {
             .unwrap_or(DEFAULT_DEPLOYMENT.to_string());
         let chain_info = Arc::new(self.chain.clone());
 
-        let state = match &self.state {
+        let mut state = match &self.state {
             Some(state) => {
                 let mut state = state.clone();
                 state.chain_data = chain_info;
@@ -197,6 +226,9 @@ impl DaemonAsyncBuilder {
                 )?
             }
         };
+        if let Some(journal) = self.journal {
+            state.journal = journal.then(Default::default);
+        }
         Ok(state)
     }
 }
@@ -209,7 +241,9 @@ impl From<DaemonBuilder> for DaemonAsyncBuilder {
             state: value.state,
             state_path: value.state_path,
             write_on_change: value.write_on_change,
+            journal: value.journal,
             mnemonic: value.mnemonic,
+            confirmation: value.confirmation,
             is_test: value.is_test,
         }
     }
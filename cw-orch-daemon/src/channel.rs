@@ -1,117 +1,548 @@
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
 use cosmrs::proto::cosmos::base::tendermint::v1beta1::{
-    service_client::ServiceClient, GetNodeInfoRequest,
+    service_client::ServiceClient, GetLatestBlockRequest, GetNodeInfoRequest,
 };
 use cw_orch_core::{environment::ChainInfoOwned, log::connectivity_target};
-use tonic::transport::{Channel, ClientTlsConfig};
+use hyper::{
+    body::{Buf, Bytes, HttpBody},
+    header::HeaderValue,
+    Body, HeaderMap, Request, Response, StatusCode,
+};
+use tonic::{
+    body::BoxBody,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+    Code, Status,
+};
+use tower::{Service, ServiceExt};
 
-use super::error::DaemonError;
+use super::{
+    env::DaemonEnvVars,
+    error::DaemonError,
+    fixture::Fixture,
+    transport::{in_process_channel, RpcTransport, UnaryBody, BLOCK_HEIGHT_HEADER},
+};
 
 /// A helper for constructing a gRPC channel
 pub struct GrpcChannel {}
 
+/// How the endpoints of a gRPC channel are monitored
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HealthCheck {
+    /// Interval between two checks of the endpoints
+    pub interval: Duration,
+    /// Number of blocks an endpoint can lag behind the most advanced one
+    pub max_block_lag: u64,
+}
+
+impl HealthCheck {
+    fn from_env() -> Self {
+        Self {
+            interval: DaemonEnvVars::grpc_health_check_interval(),
+            max_block_lag: DaemonEnvVars::grpc_max_block_lag(),
+        }
+    }
+}
+
 impl GrpcChannel {
     /// Connect to any of the provided gRPC endpoints
+    ///
+    /// The endpoints are kept in a pool, the channel sends its requests to one of those serving the expected chain.
+    /// When the current endpoint fails to answer a request, the request is sent again to the next endpoint.
+    /// The endpoints are also checked in the background: the current one is replaced when it lags behind,
+    /// and the endpoints that couldn't be reached are verified again.
+    ///
+    /// The channel is recorded to or replayed from a [`Fixture`] when the [`GRPC_RECORD_ENV_NAME`](crate::env::GRPC_RECORD_ENV_NAME) or [`GRPC_REPLAY_ENV_NAME`](crate::env::GRPC_REPLAY_ENV_NAME) env variables are set.
     pub async fn connect(grpc: &[String], chain_id: &str) -> Result<Channel, DaemonError> {
-        if grpc.is_empty() {
-            return Err(DaemonError::GRPCListIsEmpty);
+        Self::connect_with_rpc(grpc, &[], chain_id).await
+    }
+
+    /// Connect to any of the provided gRPC endpoints, like [`GrpcChannel::connect`].
+    /// The Tendermint RPC endpoints are used when none of the gRPC endpoints can be reached, see [`RpcTransport`]
+    pub async fn connect_with_rpc(
+        grpc: &[String],
+        rpc: &[String],
+        chain_id: &str,
+    ) -> Result<Channel, DaemonError> {
+        if let Some(path) = DaemonEnvVars::grpc_replay() {
+            return Ok(Fixture::shared(path, false)?.replaying_channel(chain_id));
+        }
+        let channel = Self::connect_endpoints(grpc, rpc, chain_id).await?;
+        match DaemonEnvVars::grpc_record() {
+            Some(path) => Ok(Fixture::shared(path, true)?.recording_channel(chain_id, channel)),
+            None => Ok(channel),
         }
+    }
 
-        let mut successful_connections = vec![];
+    async fn connect_endpoints(
+        grpc: &[String],
+        rpc: &[String],
+        chain_id: &str,
+    ) -> Result<Channel, DaemonError> {
+        if rpc.is_empty() || !grpc.is_empty() {
+            match Self::connect_pool(grpc, chain_id, HealthCheck::from_env()).await {
+                Err(DaemonError::CannotConnectGRPC) if !rpc.is_empty() => {
+                    log::warn!("Cannot connect to any gRPC endpoint, trying the RPC endpoints")
+                }
+                connection => return connection,
+            }
+        }
+        RpcTransport::connect(rpc, chain_id).await
+    }
 
-        for address in grpc.iter() {
-            log::debug!(target: &connectivity_target(), "Trying to connect to endpoint: {}", address);
+    pub(crate) async fn connect_pool(
+        grpc: &[String],
+        chain_id: &str,
+        health_check: HealthCheck,
+    ) -> Result<Channel, DaemonError> {
+        let pool = EndpointPool::connect(grpc, chain_id).await?;
+        if grpc.len() > 1 {
+            tokio::spawn(monitor_endpoints(Arc::downgrade(&pool), health_check));
+        }
+        Ok(pool.channel())
+    }
 
-            // get grpc endpoint
-            let endpoint = Channel::builder(address.clone().try_into().unwrap());
+    /// Verifies that the endpoint can be reached and serves the expected chain
+    async fn verified_endpoint(address: &str, chain_id: &str) -> Verification {
+        log::debug!(target: &connectivity_target(), "Trying to connect to endpoint: {}", address);
 
-            // try to connect to grpc endpoint
-            let maybe_client = ServiceClient::connect(endpoint.clone()).await;
+        // get grpc endpoint
+        let endpoint = match Channel::from_shared(address.to_string()) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                log::warn!("Invalid gRPC endpoint: {}, {:?}", address, e);
+                return Verification::Rejected;
+            }
+        };
 
-            // connection succeeded
-            let mut client = if maybe_client.is_ok() {
-                maybe_client?
-            } else {
-                log::warn!(
-                    "Cannot connect to gRPC endpoint: {}, {:?}",
-                    address,
-                    maybe_client.unwrap_err()
-                );
+        // try to connect to grpc endpoint
+        let maybe_client = ServiceClient::connect(endpoint.clone()).await;
+
+        // connection succeeded
+        let (mut client, endpoint) = match maybe_client {
+            Ok(client) => (client, endpoint),
+            Err(e) => {
+                log::warn!("Cannot connect to gRPC endpoint: {}, {:?}", address, e);
 
                 // try HTTPS approach
                 // https://github.com/hyperium/tonic/issues/363#issuecomment-638545965
                 if !(address.contains("https") || address.contains("443")) {
-                    continue;
+                    return Verification::Unreachable;
                 };
 
                 log::debug!(target: &connectivity_target(), "Attempting to connect with TLS");
 
                 // re attempt to connect
-                let endpoint = endpoint.clone().tls_config(ClientTlsConfig::new())?;
-                let maybe_client = ServiceClient::connect(endpoint.clone()).await;
-
-                // connection still fails
-                if maybe_client.is_err() {
-                    log::warn!(
-                        "Cannot connect to gRPC endpoint: {}, {:?}",
-                        address,
-                        maybe_client.unwrap_err()
-                    );
-                    continue;
+                let Ok(endpoint) = endpoint.tls_config(ClientTlsConfig::new()) else {
+                    return Verification::Rejected;
                 };
+                match ServiceClient::connect(endpoint.clone()).await {
+                    Ok(client) => (client, endpoint),
+                    // connection still fails
+                    Err(e) => {
+                        log::warn!("Cannot connect to gRPC endpoint: {}, {:?}", address, e);
+                        return Verification::Unreachable;
+                    }
+                }
+            }
+        };
 
-                maybe_client?
-            };
-
-            // get client information for verification down below
-            let node_info = client
-                .get_node_info(GetNodeInfoRequest {})
-                .await?
-                .into_inner();
-
-            // local juno does not return a proper ChainId with epoch format
-            // verify we are connected to the expected network
-            if node_info.default_node_info.as_ref().unwrap().network != chain_id {
-                log::error!(
-                    "Network mismatch: connection:{} != config:{}",
-                    node_info.default_node_info.as_ref().unwrap().network,
-                    chain_id
+        // get client information for verification down below
+        let node_info = match client.get_node_info(GetNodeInfoRequest {}).await {
+            Ok(node_info) => node_info.into_inner(),
+            Err(e) => {
+                log::warn!(
+                    "Cannot get node info of gRPC endpoint: {}, {:?}",
+                    address,
+                    e
                 );
-                continue;
+                return Verification::Unreachable;
+            }
+        };
+        let Some(network) = node_info.default_node_info.map(|info| info.network) else {
+            return Verification::Unreachable;
+        };
+
+        // local juno does not return a proper ChainId with epoch format
+        // verify we are connected to the expected network
+        if network != chain_id {
+            log::error!(
+                "Network mismatch: connection:{} != config:{}",
+                network,
+                chain_id
+            );
+            return Verification::Rejected;
+        }
+
+        Verification::Verified(Box::new(endpoint))
+    }
+
+    /// Create a gRPC channel from the chain info
+    pub async fn from_chain_info(chain_info: &ChainInfoOwned) -> Result<Channel, DaemonError> {
+        GrpcChannel::connect_with_rpc(
+            &chain_info.grpc_urls,
+            &chain_info.rpc_urls,
+            &chain_info.chain_id,
+        )
+        .await
+    }
+
+    /// Channel querying the state of the chain at `height`.
+    /// Every request sent through `channel` carries the height in its [`BLOCK_HEIGHT_HEADER`] header,
+    /// the node answers the module queries from its state at that height.
+    /// The queries of the node itself (blocks, transactions) aren't affected.
+    pub fn at_height(channel: Channel, height: u64) -> Channel {
+        in_process_channel(move |mut request: Request<Body>| {
+            let mut channel = channel.clone();
+            async move {
+                request
+                    .headers_mut()
+                    .insert(BLOCK_HEIGHT_HEADER, HeaderValue::from(height));
+                let request = request.map(|body| {
+                    body.map_err(|e| Status::from_error(Box::new(e)))
+                        .boxed_unsync()
+                });
+                let response = match channel.ready().await {
+                    Ok(channel) => channel.call(request).await,
+                    Err(e) => Err(e),
+                };
+                Ok::<_, Infallible>(match response {
+                    Ok(response) => response.map(|body| {
+                        body.map_err(|e| Status::from_error(Box::new(e)))
+                            .boxed_unsync()
+                    }),
+                    Err(e) => Status::unavailable(e.to_string()).to_http(),
+                })
             }
+        })
+    }
+}
+
+/// Reads the response of an endpoint, errors with the reason when the endpoint failed to answer it
+async fn read_response(response: Response<Body>) -> Result<Response<UnaryBody>, String> {
+    if response.status() != StatusCode::OK {
+        return Err(format!("HTTP status {}", response.status()));
+    }
+    // The status of the responses without a message can be in their headers
+    unavailable(response.headers())?;
+
+    let (parts, mut body) = response.into_parts();
+    let mut data = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| format!("response failed: {e}"))?;
+        data.extend_from_slice(chunk.chunk());
+    }
+    let trailers = body
+        .trailers()
+        .await
+        .map_err(|e| format!("response failed: {e}"))?;
+    if let Some(trailers) = &trailers {
+        unavailable(trailers)?;
+    }
+    Ok(Response::from_parts(
+        parts,
+        UnaryBody::new(Bytes::from(data), trailers),
+    ))
+}
 
-            // add endpoint to succesful connections
-            successful_connections.push(endpoint.connect().await?)
+/// Errors when the gRPC status of the headers is `Unavailable`
+fn unavailable(headers: &HeaderMap) -> Result<(), String> {
+    match Status::from_header_map(headers) {
+        Some(status) if status.code() == Code::Unavailable => {
+            Err(format!("endpoint unavailable: {}", status.message()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Outcome of the verification of an endpoint
+enum Verification {
+    Verified(Box<Endpoint>),
+    /// The endpoint can't be reached for now
+    Unreachable,
+    /// The endpoint is invalid or serves another chain
+    Rejected,
+}
+
+/// Endpoints of a gRPC channel, the requests of the channel are sent to the current endpoint.
+///
+/// An endpoint that fails to answer a request is replaced by the next verified endpoint, and the request is sent again:
+/// the transport errors, the HTTP errors (e.g. of a gateway in front of a node that is down) and the `Unavailable` gRPC statuses are failures of the endpoint.
+/// The health checks replace the endpoints that lag behind and verify again the endpoints that couldn't be reached until then.
+pub(crate) struct EndpointPool {
+    chain_id: String,
+    state: Mutex<PoolState>,
+}
+
+struct PoolState {
+    endpoints: Vec<PoolEndpoint>,
+    current: usize,
+}
+
+struct PoolEndpoint {
+    address: String,
+    /// Channel of the endpoint, once it's verified
+    channel: Option<Channel>,
+    rejected: bool,
+}
+
+impl EndpointPool {
+    /// Verifies the endpoints, at least one of them must serve the chain
+    pub(crate) async fn connect(grpc: &[String], chain_id: &str) -> Result<Arc<Self>, DaemonError> {
+        if grpc.is_empty() {
+            return Err(DaemonError::GRPCListIsEmpty);
+        }
+
+        let mut endpoints = vec![];
+        for address in grpc.iter() {
+            let verification = GrpcChannel::verified_endpoint(address, chain_id).await;
+            endpoints.push(PoolEndpoint {
+                address: address.clone(),
+                rejected: matches!(verification, Verification::Rejected),
+                channel: match verification {
+                    Verification::Verified(endpoint) => Some(endpoint.connect_lazy()),
+                    _ => None,
+                },
+            });
         }
 
         // we could not get any succesful connections
-        if successful_connections.is_empty() {
+        let Some(current) = endpoints
+            .iter()
+            .position(|endpoint| endpoint.channel.is_some())
+        else {
             return Err(DaemonError::CannotConnectGRPC);
+        };
+
+        Ok(Arc::new(Self {
+            chain_id: chain_id.to_string(),
+            state: Mutex::new(PoolState { endpoints, current }),
+        }))
+    }
+
+    /// Channel sending its requests to the current endpoint of the pool
+    pub(crate) fn channel(self: &Arc<Self>) -> Channel {
+        let pool = self.clone();
+        in_process_channel(move |request: Request<Body>| {
+            let pool = pool.clone();
+            async move { Ok::<_, Infallible>(pool.send(request).await) }
+        })
+    }
+
+    /// Sends the request to the current endpoint, then to the next ones while they fail to answer.
+    /// The responses are read entirely to check their gRPC status, the requests of the pool are unary.
+    async fn send(&self, request: Request<Body>) -> Response<BoxBody> {
+        let (parts, body) = request.into_parts();
+        let body = match hyper::body::to_bytes(body).await {
+            Ok(body) => body,
+            Err(e) => return Status::internal(e.to_string()).to_http(),
+        };
+
+        let attempts = self.state.lock().unwrap().endpoints.len();
+        let mut error = None;
+        for _ in 0..attempts {
+            let Some((index, mut channel)) = self.current() else {
+                break;
+            };
+            let mut request = Request::new(
+                Body::from(body.clone())
+                    .map_err(|e| Status::from_error(Box::new(e)))
+                    .boxed_unsync(),
+            );
+            *request.method_mut() = parts.method.clone();
+            *request.uri_mut() = parts.uri.clone();
+            *request.version_mut() = parts.version;
+            *request.headers_mut() = parts.headers.clone();
+
+            let response = match channel.ready().await {
+                Ok(channel) => channel.call(request).await,
+                Err(e) => Err(e),
+            };
+            // The gRPC errors other than `Unavailable` are answers of the endpoint, they're returned to the caller
+            let response = match response {
+                Ok(response) => read_response(response).await,
+                Err(e) => Err(format!("request failed: {e}")),
+            };
+            match response {
+                Ok(response) => {
+                    return response.map(|body| {
+                        body.map_err(|e| Status::from_error(Box::new(e)))
+                            .boxed_unsync()
+                    })
+                }
+                Err(e) => {
+                    self.switch_from(index, &e);
+                    error = Some(e);
+                }
+            }
         }
+        let error = error.unwrap_or_else(|| "no gRPC endpoint is verified".to_string());
+        Status::unavailable(error).to_http()
+    }
 
-        Ok(successful_connections.pop().unwrap())
+    /// Current endpoint and its channel
+    fn current(&self) -> Option<(usize, Channel)> {
+        let state = self.state.lock().unwrap();
+        let channel = state.endpoints[state.current].channel.clone()?;
+        Some((state.current, channel))
     }
 
-    /// Create a gRPC channel from the chain info
-    pub async fn from_chain_info(chain_info: &ChainInfoOwned) -> Result<Channel, DaemonError> {
-        GrpcChannel::connect(&chain_info.grpc_urls, &chain_info.chain_id).await
+    /// Replaces the endpoint at `index` by the next endpoint accepted by `healthy`, if it's still the current one
+    fn switch_to_next(&self, index: usize, reason: &str, healthy: impl Fn(usize) -> bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.current != index {
+            return;
+        }
+        let count = state.endpoints.len();
+        // Next healthy endpoint, in the order of the endpoint list
+        let Some(next) = (1..count)
+            .map(|offset| (index + offset) % count)
+            .find(|&next| state.endpoints[next].channel.is_some() && healthy(next))
+        else {
+            return;
+        };
+        log::warn!(
+            target: &connectivity_target(),
+            "gRPC endpoint {} is unhealthy ({}), switching to {}",
+            state.endpoints[index].address,
+            reason,
+            state.endpoints[next].address
+        );
+        state.current = next;
+    }
+
+    /// Replaces the endpoint at `index` by the next verified endpoint
+    fn switch_from(&self, index: usize, reason: &str) {
+        self.switch_to_next(index, reason, |_| true)
+    }
+
+    /// Verifies the endpoints that couldn't be reached until now, then replaces the current endpoint when it fails or lags behind
+    pub(crate) async fn check(&self, health_check: HealthCheck) {
+        let unverified = {
+            let state = self.state.lock().unwrap();
+            state
+                .endpoints
+                .iter()
+                .enumerate()
+                .filter(|(_, endpoint)| endpoint.channel.is_none() && !endpoint.rejected)
+                .map(|(index, endpoint)| (index, endpoint.address.clone()))
+                .collect::<Vec<_>>()
+        };
+        for (index, address) in unverified {
+            let verification = GrpcChannel::verified_endpoint(&address, &self.chain_id).await;
+            let mut state = self.state.lock().unwrap();
+            let endpoint = &mut state.endpoints[index];
+            match verification {
+                Verification::Verified(verified) => {
+                    log::info!(target: &connectivity_target(), "gRPC endpoint {} is now reachable", address);
+                    endpoint.channel = Some(verified.connect_lazy());
+                }
+                Verification::Rejected => endpoint.rejected = true,
+                Verification::Unreachable => {}
+            }
+        }
+
+        let (current, channels) = {
+            let state = self.state.lock().unwrap();
+            let channels = state
+                .endpoints
+                .iter()
+                .map(|endpoint| endpoint.channel.clone())
+                .collect::<Vec<_>>();
+            (state.current, channels)
+        };
+        let mut heights = vec![];
+        for channel in channels {
+            heights.push(match channel {
+                Some(channel) => {
+                    block_height(ServiceClient::new(channel), health_check.interval).await
+                }
+                None => None,
+            });
+        }
+        let Some(best_height) = heights.iter().flatten().max().copied() else {
+            log::warn!(target: &connectivity_target(), "No gRPC endpoint is reachable");
+            return;
+        };
+        let healthy = |index: usize| {
+            heights[index].is_some_and(|height| height + health_check.max_block_lag >= best_height)
+        };
+        if !healthy(current) {
+            let reason = format!("height {:?}, best height {}", heights[current], best_height);
+            self.switch_to_next(current, &reason, healthy);
+        }
     }
 }
 
+/// Checks the endpoints of the pool periodically, stops once its channel is dropped
+async fn monitor_endpoints(pool: Weak<EndpointPool>, health_check: HealthCheck) {
+    loop {
+        tokio::time::sleep(health_check.interval).await;
+        let Some(pool) = pool.upgrade() else {
+            return;
+        };
+        pool.check(health_check).await;
+    }
+}
+
+/// Latest block height of an endpoint, `None` if it doesn't answer in time
+async fn block_height(mut client: ServiceClient<Channel>, timeout: Duration) -> Option<u64> {
+    let response = tokio::time::timeout(timeout, client.get_latest_block(GetLatestBlockRequest {}))
+        .await
+        .ok()?
+        .ok()?
+        .into_inner();
+    #[allow(deprecated)]
+    let height = match response.sdk_block {
+        Some(block) => block.header?.height,
+        None => response.block?.header?.height,
+    };
+    height.try_into().ok()
+}
+
 #[cfg(test)]
 mod tests {
     /*
         This test asserts breaking issues around the GRPC connection
     */
 
-    use crate::DaemonAsync;
+    use crate::{
+        queriers::Bank,
+        test_utils::MockRpc,
+        transport::{RpcTransport, Transport},
+        DaemonAsync, GrpcChannel,
+    };
     use speculoos::prelude::*;
 
+    #[tokio::test]
+    async fn queries_at_height() -> anyhow::Result<()> {
+        let node = MockRpc::default();
+        let channel = RpcTransport::new(node.serve()).channel();
+
+        let balance = Bank::new_async(GrpcChannel::at_height(channel.clone(), 5))
+            ._balance("juno1address", Some("ujuno".to_string()))
+            .await?;
+        assert_eq!(balance, vec![cosmwasm_std::coin(100, "ujuno")]);
+
+        // The original channel still queries the latest state
+        Bank::new_async(channel)
+            ._balance("juno1address", Some("ujuno".to_string()))
+            .await?;
+        assert_eq!(*node.queried_heights.lock().unwrap(), vec!["5", "0"]);
+        Ok(())
+    }
+
     #[tokio::test]
     #[serial_test::serial]
     async fn no_connection() {
         let mut chain = cw_orch_daemon::networks::LOCAL_JUNO;
         let grpcs = &["https://127.0.0.1:99999"];
         chain.grpc_urls = grpcs;
+        chain.rpc_urls = &[];
 
         let build_res = DaemonAsync::builder(chain)
             .deployment_id("v0.1.0")
@@ -131,6 +562,7 @@ mod tests {
         let mut chain = cw_orch_daemon::networks::LOCAL_JUNO;
         let grpcs = &[];
         chain.grpc_urls = grpcs;
+        chain.rpc_urls = &[];
 
         let build_res = DaemonAsync::builder(chain)
             .deployment_id("v0.1.0")
@@ -141,4 +573,323 @@ mod tests {
             .that(&build_res.err().unwrap().to_string())
             .is_equal_to(String::from("The list of grpc endpoints is empty"))
     }
+
+    mod pool {
+        use std::{
+            convert::Infallible,
+            sync::{
+                atomic::{AtomicBool, AtomicU64, Ordering},
+                Arc,
+            },
+            time::Duration,
+        };
+
+        use cosmrs::proto::{
+            cosmos::base::tendermint::v1beta1::{
+                service_client::ServiceClient,
+                service_server::{Service, ServiceServer},
+                AbciQueryRequest, AbciQueryResponse, Block, GetBlockByHeightRequest,
+                GetBlockByHeightResponse, GetLatestBlockRequest, GetLatestBlockResponse,
+                GetLatestValidatorSetRequest, GetLatestValidatorSetResponse, GetNodeInfoRequest,
+                GetNodeInfoResponse, GetSyncingRequest, GetSyncingResponse,
+                GetValidatorSetByHeightRequest, GetValidatorSetByHeightResponse, Header,
+            },
+            tendermint::v0_34::p2p::DefaultNodeInfo,
+        };
+        use hyper::{
+            body::HttpBody,
+            service::{make_service_fn, service_fn},
+            Body, StatusCode,
+        };
+        use tokio::{sync::Notify, task::JoinHandle};
+        use tokio_stream::wrappers::TcpListenerStream;
+        use tonic::{
+            transport::{Channel, Server},
+            Request, Response, Status,
+        };
+        use tower::{Service as _, ServiceExt};
+
+        use crate::{
+            channel::{block_height, EndpointPool, HealthCheck},
+            DaemonError, GrpcChannel,
+        };
+
+        /// Latest block height and availability of a node, shared with the test
+        #[derive(Clone)]
+        struct NodeState {
+            height: Arc<AtomicU64>,
+            unavailable: Arc<AtomicBool>,
+        }
+
+        impl NodeState {
+            fn at(height: u64) -> Self {
+                Self {
+                    height: Arc::new(AtomicU64::new(height)),
+                    unavailable: Arc::new(AtomicBool::new(false)),
+                }
+            }
+
+            fn check(&self) -> Result<(), Status> {
+                match self.unavailable.load(Ordering::SeqCst) {
+                    true => Err(Status::unavailable("node unavailable")),
+                    false => Ok(()),
+                }
+            }
+        }
+
+        /// Node answering its chain id and its latest block height
+        struct MockNode {
+            chain_id: &'static str,
+            state: NodeState,
+        }
+
+        #[tonic::async_trait]
+        impl Service for MockNode {
+            async fn get_node_info(
+                &self,
+                _: Request<GetNodeInfoRequest>,
+            ) -> Result<Response<GetNodeInfoResponse>, Status> {
+                self.state.check()?;
+                Ok(Response::new(GetNodeInfoResponse {
+                    default_node_info: Some(DefaultNodeInfo {
+                        network: self.chain_id.to_string(),
+                        ..Default::default()
+                    }),
+                    application_version: None,
+                }))
+            }
+
+            async fn get_latest_block(
+                &self,
+                _: Request<GetLatestBlockRequest>,
+            ) -> Result<Response<GetLatestBlockResponse>, Status> {
+                self.state.check()?;
+                #[allow(deprecated)]
+                Ok(Response::new(GetLatestBlockResponse {
+                    sdk_block: Some(Block {
+                        header: Some(Header {
+                            height: self.state.height.load(Ordering::SeqCst) as i64,
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }))
+            }
+
+            async fn get_syncing(
+                &self,
+                _: Request<GetSyncingRequest>,
+            ) -> Result<Response<GetSyncingResponse>, Status> {
+                Err(Status::unimplemented("get_syncing"))
+            }
+
+            async fn get_block_by_height(
+                &self,
+                _: Request<GetBlockByHeightRequest>,
+            ) -> Result<Response<GetBlockByHeightResponse>, Status> {
+                Err(Status::unimplemented("get_block_by_height"))
+            }
+
+            async fn get_latest_validator_set(
+                &self,
+                _: Request<GetLatestValidatorSetRequest>,
+            ) -> Result<Response<GetLatestValidatorSetResponse>, Status> {
+                Err(Status::unimplemented("get_latest_validator_set"))
+            }
+
+            async fn get_validator_set_by_height(
+                &self,
+                _: Request<GetValidatorSetByHeightRequest>,
+            ) -> Result<Response<GetValidatorSetByHeightResponse>, Status> {
+                Err(Status::unimplemented("get_validator_set_by_height"))
+            }
+
+            async fn abci_query(
+                &self,
+                _: Request<AbciQueryRequest>,
+            ) -> Result<Response<AbciQueryResponse>, Status> {
+                Err(Status::unimplemented("abci_query"))
+            }
+        }
+
+        /// Node served on a local port
+        struct MockServer {
+            address: String,
+            shutdown: Arc<Notify>,
+            task: JoinHandle<Result<(), tonic::transport::Error>>,
+        }
+
+        impl MockServer {
+            /// Stops the server once its connections are closed
+            async fn stop(self) -> anyhow::Result<()> {
+                self.shutdown.notify_one();
+                self.task.await??;
+                Ok(())
+            }
+        }
+
+        /// Serves the node on a local port
+        async fn serve(chain_id: &'static str, state: NodeState) -> anyhow::Result<MockServer> {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+            let address = format!("http://{}", listener.local_addr()?);
+            let shutdown = Arc::new(Notify::new());
+            let signal = shutdown.clone();
+            let task = tokio::spawn(
+                Server::builder()
+                    .add_service(ServiceServer::new(MockNode { chain_id, state }))
+                    .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async move {
+                        signal.notified().await
+                    }),
+            );
+            Ok(MockServer {
+                address,
+                shutdown,
+                task,
+            })
+        }
+
+        /// Serves an HTTP gateway forwarding the requests to the node at `address`, answering 503 once the node is `down`
+        fn serve_gateway(address: &str, down: Arc<AtomicBool>) -> anyhow::Result<String> {
+            let node = Channel::from_shared(address.to_string())?.connect_lazy();
+            let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into())
+                .http2_only(true)
+                .serve(make_service_fn(move |_| {
+                    let (node, down) = (node.clone(), down.clone());
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request: hyper::Request<Body>| {
+                            let (mut node, down) = (node.clone(), down.clone());
+                            async move {
+                                if down.load(Ordering::SeqCst) {
+                                    return Ok(hyper::Response::builder()
+                                        .status(StatusCode::SERVICE_UNAVAILABLE)
+                                        .body(Body::from("no healthy upstream"))
+                                        .unwrap());
+                                }
+                                let request = request.map(|body| {
+                                    body.map_err(|e| Status::from_error(Box::new(e)))
+                                        .boxed_unsync()
+                                });
+                                node.ready().await?.call(request).await
+                            }
+                        }))
+                    }
+                }));
+            let address = format!("http://{}", server.local_addr());
+            tokio::spawn(server);
+            Ok(address)
+        }
+
+        const HEALTH_CHECK: HealthCheck = HealthCheck {
+            interval: Duration::from_millis(50),
+            max_block_lag: 5,
+        };
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        #[tokio::test]
+        async fn switches_to_healthy_endpoint() -> anyhow::Result<()> {
+            let stalled = NodeState::at(100);
+            let healthy = NodeState::at(100);
+            let servers = [
+                serve("juno-1", stalled).await?,
+                serve("osmosis-1", NodeState::at(1000)).await?,
+                serve("juno-1", healthy.clone()).await?,
+            ];
+            let grpc = servers.map(|server| server.address);
+
+            let pool = EndpointPool::connect(&grpc, "juno-1").await?;
+            let client = ServiceClient::new(pool.channel());
+            assert_eq!(block_height(client.clone(), TIMEOUT).await, Some(100));
+
+            // The first node stalls, the channel switches to the other node of the chain
+            healthy.height.store(200, Ordering::SeqCst);
+            pool.check(HEALTH_CHECK).await;
+            assert_eq!(block_height(client, TIMEOUT).await, Some(200));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn fails_over_when_a_request_fails() -> anyhow::Result<()> {
+            let first = serve("juno-1", NodeState::at(100)).await?;
+            let second = serve("juno-1", NodeState::at(200)).await?;
+            let grpc = [first.address.clone(), second.address.clone()];
+
+            let pool = EndpointPool::connect(&grpc, "juno-1").await?;
+            let client = ServiceClient::new(pool.channel());
+            assert_eq!(block_height(client.clone(), TIMEOUT).await, Some(100));
+
+            // The request failing on the stopped node is sent again to the other node, without health check
+            first.stop().await?;
+            assert_eq!(block_height(client, TIMEOUT).await, Some(200));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn fails_over_when_an_endpoint_is_unavailable() -> anyhow::Result<()> {
+            let failing = NodeState::at(100);
+            let first = serve("juno-1", failing.clone()).await?;
+            let second = serve("juno-1", NodeState::at(200)).await?;
+            let grpc = [first.address.clone(), second.address.clone()];
+
+            let pool = EndpointPool::connect(&grpc, "juno-1").await?;
+            let client = ServiceClient::new(pool.channel());
+            assert_eq!(block_height(client.clone(), TIMEOUT).await, Some(100));
+
+            // The node still answers, with an `Unavailable` status
+            failing.unavailable.store(true, Ordering::SeqCst);
+            assert_eq!(block_height(client, TIMEOUT).await, Some(200));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn fails_over_when_a_gateway_answers_an_http_error() -> anyhow::Result<()> {
+            let first = serve("juno-1", NodeState::at(100)).await?;
+            let second = serve("juno-1", NodeState::at(200)).await?;
+            let down = Arc::new(AtomicBool::new(false));
+            let gateway = serve_gateway(&first.address, down.clone())?;
+            let grpc = [gateway, second.address.clone()];
+
+            let pool = EndpointPool::connect(&grpc, "juno-1").await?;
+            let client = ServiceClient::new(pool.channel());
+            assert_eq!(block_height(client.clone(), TIMEOUT).await, Some(100));
+
+            // The gateway stays reachable when its node is down, it answers the requests with an HTTP error
+            down.store(true, Ordering::SeqCst);
+            assert_eq!(block_height(client, TIMEOUT).await, Some(200));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn verifies_again_endpoints_unreachable_at_startup() -> anyhow::Result<()> {
+            let late = NodeState::at(200);
+            late.unavailable.store(true, Ordering::SeqCst);
+            let first = serve("juno-1", NodeState::at(100)).await?;
+            let second = serve("juno-1", late.clone()).await?;
+            let grpc = [first.address.clone(), second.address.clone()];
+
+            let pool = EndpointPool::connect(&grpc, "juno-1").await?;
+            let client = ServiceClient::new(pool.channel());
+            assert_eq!(block_height(client.clone(), TIMEOUT).await, Some(100));
+
+            // The unreachable node isn't used until it's verified
+            pool.check(HEALTH_CHECK).await;
+            assert_eq!(block_height(client.clone(), TIMEOUT).await, Some(100));
+
+            // Once reachable, the node is verified and replaces the lagging one
+            late.unavailable.store(false, Ordering::SeqCst);
+            pool.check(HEALTH_CHECK).await;
+            assert_eq!(block_height(client, TIMEOUT).await, Some(200));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn endpoints_of_other_chains_are_rejected() -> anyhow::Result<()> {
+            let grpc = [serve("osmosis-1", NodeState::at(1)).await?.address];
+            let error = GrpcChannel::connect_pool(&grpc, "juno-1", HEALTH_CHECK)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, DaemonError::CannotConnectGRPC));
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,245 @@
+//! How long the senders wait for a transaction after broadcasting it.
+
+use std::{
+    future::{Future, IntoFuture},
+    pin::Pin,
+    time::Duration,
+};
+
+use cosmrs::proto::cosmos::base::abci::v1beta1::TxResponse;
+use tonic::transport::Channel;
+
+use crate::{
+    env::DaemonEnvVars, queriers::Node, tx_broadcaster::assert_broadcast_code_cosm_response,
+    CosmTxResponse, DaemonError,
+};
+
+/// Confirmation awaited by the senders before returning the response of a transaction
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Confirmation {
+    /// Waits for the inclusion of the transaction in a block
+    #[default]
+    Inclusion,
+    /// Waits for the inclusion of the transaction and the given number of blocks on top of its block
+    Blocks(u64),
+    /// Returns as soon as the transaction passed the checks of the node (`CheckTx`), before its inclusion.
+    /// The response only holds the hash of the transaction, its inclusion is awaited later with a [`PendingTx`].
+    ///
+    /// The response has no events, so the uploads, instantiations and migrations of the daemon still await the inclusion
+    /// of their transaction to read the code id and contract address. Only [`TxSender::commit_tx_any`](crate::senders::tx::TxSender::commit_tx_any)
+    /// and the executions return before the inclusion.
+    CheckTx,
+}
+
+impl Confirmation {
+    /// Waits for the confirmation of a transaction accepted by the node.
+    /// The inclusion is received over the websocket of `rpc_url` when it's set, it's polled otherwise
+    pub(crate) async fn confirm(
+        &self,
+        channel: Channel,
+        rpc_url: Option<String>,
+        tx_response: TxResponse,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let pending = PendingTx::new(channel, tx_response.txhash.clone()).with_rpc_url(rpc_url);
+        match *self {
+            Confirmation::Inclusion => pending.included().await,
+            Confirmation::Blocks(blocks) => pending.confirmed(blocks).await,
+            Confirmation::CheckTx => assert_broadcast_code_cosm_response(CosmTxResponse {
+                txhash: tx_response.txhash,
+                codespace: tx_response.codespace,
+                code: tx_response.code as usize,
+                raw_log: tx_response.raw_log,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// Transaction broadcasted without waiting for its inclusion.
+/// Awaiting the handle waits for the inclusion of the transaction in a block.
+///
+/// ```rust,no_run
+/// # tokio_test::block_on(async {
+/// use cw_orch_daemon::{confirmation::{Confirmation, PendingTx}, networks, DaemonAsync};
+///
+/// let daemon: DaemonAsync = DaemonAsync::builder(networks::JUNO_1)
+///     .confirmation(Confirmation::CheckTx)
+///     .build()
+///     .await.unwrap();
+/// let response = daemon.sender().bank_send("juno1...", vec![]).await.unwrap();
+/// let pending: PendingTx = daemon.pending_tx(&response);
+/// // ...
+/// let included = pending.await.unwrap();
+/// # })
+/// ```
+#[derive(Clone, Debug)]
+pub struct PendingTx {
+    pub txhash: String,
+    channel: Channel,
+    rpc_url: Option<String>,
+}
+
+impl PendingTx {
+    pub fn new(channel: Channel, txhash: impl Into<String>) -> Self {
+        Self {
+            txhash: txhash.into(),
+            channel,
+            rpc_url: None,
+        }
+    }
+
+    /// Waits for the inclusion over the websocket of `rpc_url` instead of polling the transaction
+    pub fn with_rpc_url(mut self, rpc_url: Option<String>) -> Self {
+        self.rpc_url = rpc_url;
+        self
+    }
+
+    /// Waits for the inclusion of the transaction in a block
+    pub async fn included(&self) -> Result<CosmTxResponse, DaemonError> {
+        let tx = Node::new_async(self.channel.clone())
+            .with_rpc_url(self.rpc_url.clone())
+            ._find_tx(self.txhash.clone())
+            .await?;
+        assert_broadcast_code_cosm_response(tx)
+    }
+
+    /// Waits for the inclusion of the transaction and `blocks` blocks on top of its block.
+    /// Errors when the blocks aren't produced in `blocks` × the maximum block time × the tx query retries.
+    pub async fn confirmed(&self, blocks: u64) -> Result<CosmTxResponse, DaemonError> {
+        let node = Node::new_async(self.channel.clone());
+        let block_time = match DaemonEnvVars::max_block_time() {
+            Some(block_time) => block_time,
+            None => node._average_block_speed(None).await?,
+        };
+        let max_blocks = blocks.saturating_mul(DaemonEnvVars::max_tx_query_retries() as u64);
+        let timeout = block_time.saturating_mul(u32::try_from(max_blocks).unwrap_or(u32::MAX));
+        self.confirmed_within(blocks, timeout).await
+    }
+
+    async fn confirmed_within(
+        &self,
+        blocks: u64,
+        timeout: Duration,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let tx = self.included().await?;
+        let node = Node::new_async(self.channel.clone());
+        let confirmation_height = tx.height + blocks;
+        if node._block_height().await? < confirmation_height {
+            let block_speed = node._average_block_speed(None).await?;
+            let produced = async {
+                while node._block_height().await? < confirmation_height {
+                    tokio::time::sleep(block_speed).await;
+                }
+                Ok::<_, DaemonError>(())
+            };
+            tokio::time::timeout(timeout, produced)
+                .await
+                .map_err(|_| DaemonError::TxConfirmationTimeout {
+                    txhash: self.txhash.clone(),
+                    height: confirmation_height,
+                    timeout,
+                })??;
+        }
+        Ok(tx)
+    }
+}
+
+impl IntoFuture for PendingTx {
+    type Output = Result<CosmTxResponse, DaemonError>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move { self.included().await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cosmrs::proto::cosmos::base::abci::v1beta1::TxResponse;
+
+    use super::{Confirmation, PendingTx};
+    use crate::{
+        test_utils::{MockRpc, LATEST_HEIGHT, TX_HASH},
+        transport::{RpcTransport, Transport},
+        DaemonError,
+    };
+
+    fn check_tx_response(code: u32) -> TxResponse {
+        TxResponse {
+            txhash: TX_HASH.to_string(),
+            code,
+            raw_log: "out of gas".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn check_tx_returns_before_inclusion() -> anyhow::Result<()> {
+        let channel = RpcTransport::new(MockRpc::default().serve()).channel();
+
+        let response = Confirmation::CheckTx
+            .confirm(channel.clone(), None, check_tx_response(0))
+            .await?;
+        assert_eq!(response.txhash, TX_HASH);
+        assert_eq!(response.height, 0);
+
+        // The transactions rejected by CheckTx fail right away
+        let error = Confirmation::CheckTx
+            .confirm(channel, None, check_tx_response(11))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DaemonError::TxFailed { code: 11, .. }));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pending_tx_awaits_inclusion() -> anyhow::Result<()> {
+        let channel = RpcTransport::new(MockRpc::default().serve()).channel();
+
+        let included = PendingTx::new(channel.clone(), TX_HASH).await?;
+        assert_eq!(included.height, LATEST_HEIGHT);
+
+        let confirmed = Confirmation::Blocks(0)
+            .confirm(channel, None, check_tx_response(0))
+            .await?;
+        assert_eq!(confirmed.height, LATEST_HEIGHT);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn inclusion_is_polled_without_a_websocket() -> anyhow::Result<()> {
+        let channel = RpcTransport::new(MockRpc::default().serve()).channel();
+        // Nothing listens on the websocket address, the subscription fails and the tx is polled
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await?
+            .local_addr()?;
+
+        let included = Confirmation::Inclusion
+            .confirm(
+                channel,
+                Some(format!("http://{closed}")),
+                check_tx_response(0),
+            )
+            .await?;
+        assert_eq!(included.height, LATEST_HEIGHT);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn confirmation_times_out_when_blocks_are_not_produced() -> anyhow::Result<()> {
+        let channel = RpcTransport::new(MockRpc::default().serve()).channel();
+
+        // The mock chain stays at its latest height
+        let error = PendingTx::new(channel, TX_HASH)
+            .confirmed_within(2, Duration::from_millis(200))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DaemonError::TxConfirmationTimeout { height, .. } if height == LATEST_HEIGHT + 2
+        ));
+        Ok(())
+    }
+}
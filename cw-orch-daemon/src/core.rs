@@ -1,7 +1,10 @@
 use crate::{
+    confirmation::PendingTx,
+    journal::JournalStepKind,
     queriers::CosmWasm,
-    senders::{builder::SenderBuilder, query::QuerySender},
-    DaemonAsyncBuilder, DaemonState,
+    senders::{builder::SenderBuilder, query::QuerySender, QueryOnlySender},
+    subscription::{self, EventStream},
+    DaemonAsyncBuilder, DaemonState, GrpcChannel,
 };
 
 use super::{
@@ -26,6 +29,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use serde_json::from_str;
 use std::{
     fmt::Debug,
+    future::Future,
     io::Write,
     ops::Deref,
     str::{from_utf8, FromStr},
@@ -67,6 +71,7 @@ pub const INSTANTIATE_2_TYPE_URL: &str = "/cosmwasm.wasm.v1.MsgInstantiateContra
     This daemon is thread safe and can be used between threads.
     However, please make sure that you are not trying to broadcast multiple transactions at once when using this Daemon on different threads.
     If you do so, you will get account sequence errors and your transactions won't get broadcasted.
+    Use a [`SequencedDaemon`](crate::senders::SequencedDaemon) to broadcast transactions concurrently, it tracks the account sequence locally.
 */
 pub struct DaemonAsyncBase<Sender = Wallet> {
     /// Sender to send transactions to the chain
@@ -86,6 +91,35 @@ impl<Sender> DaemonAsyncBase<Sender> {
         self.state.chain_data.as_ref()
     }
 
+    /// Subscribes to the events of the chain matching the [query](crate::subscription), over the websocket of the first of the `rpc_urls` of the chain info.
+    ///
+    /// ```rust,no_run
+    /// # tokio_test::block_on(async {
+    /// use cw_orch_daemon::{networks, subscription::SubscriptionEvent, DaemonAsync};
+    /// use futures_util::StreamExt;
+    ///
+    /// let daemon: DaemonAsync = DaemonAsync::builder(networks::JUNO_1)
+    ///     .build()
+    ///     .await.unwrap();
+    /// let mut events = daemon
+    ///     .subscribe("tm.event='Tx' AND wasm._contract_address='juno1...'")
+    ///     .await.unwrap();
+    /// while let Some(event) = events.next().await {
+    ///     if let SubscriptionEvent::Tx(tx) = event.unwrap() {
+    ///         println!("{}", tx.txhash);
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub async fn subscribe(&self, query: &str) -> Result<EventStream, DaemonError> {
+        let rpc_url = self
+            .chain_info()
+            .rpc_urls
+            .first()
+            .ok_or(DaemonError::MissingRpcEndpoint)?;
+        subscription::subscribe(rpc_url, query).await
+    }
+
     /// Get the daemon builder
     pub fn builder(chain: impl Into<ChainInfoOwned>) -> DaemonAsyncBuilder {
         DaemonAsyncBuilder::new(chain)
@@ -132,7 +166,9 @@ impl<Sender> DaemonAsyncBase<Sender> {
             deployment_id: Some(self.state.deployment_id.clone()),
             state_path: None,
             write_on_change: None,
+            journal: None,
             mnemonic: None,
+            confirmation: None,
             // If it was test it will just use same tempfile as state
             is_test: false,
         }
@@ -145,6 +181,40 @@ impl<Sender: QuerySender> DaemonAsyncBase<Sender> {
         self.sender().channel()
     }
 
+    /// Handle awaiting the inclusion of a transaction, for the responses returned before the inclusion
+    /// with [`Confirmation::CheckTx`](crate::confirmation::Confirmation::CheckTx)
+    pub fn pending_tx(&self, response: &CosmTxResponse) -> PendingTx {
+        PendingTx::new(self.channel(), response.txhash.clone())
+            .with_rpc_url(self.chain_info().rpc_urls.first().cloned())
+    }
+
+    /// Read-only daemon querying the state of the chain at `height`.
+    /// The queriers and the queries of the returned daemon are answered from the state at that height,
+    /// provided that the node still holds it (archive node or recent enough height).
+    ///
+    /// ```rust,no_run
+    /// # tokio_test::block_on(async {
+    /// use cw_orch_daemon::{networks, queriers::Bank, DaemonAsync};
+    ///
+    /// let daemon: DaemonAsync = DaemonAsync::builder(networks::JUNO_1)
+    ///     .build()
+    ///     .await.unwrap();
+    /// let past = daemon.at_height(1_000_000);
+    /// let balance = Bank::new_async(past.channel())
+    ///     ._balance("juno1...", None)
+    ///     .await.unwrap();
+    /// # })
+    /// ```
+    pub fn at_height(&self, height: u64) -> DaemonAsyncBase<QueryOnlySender> {
+        DaemonAsyncBase::new(
+            QueryOnlySender {
+                channel: GrpcChannel::at_height(self.channel(), height),
+                chain_info: self.state.chain_data.clone(),
+            },
+            self.state.clone(),
+        )
+    }
+
     /// Query a contract.
     pub async fn query<Q: Serialize + Debug, T: Serialize + DeserializeOwned>(
         &self,
@@ -171,7 +241,17 @@ impl<Sender: QuerySender> DaemonAsyncBase<Sender> {
             ._average_block_speed(Some(0.9))
             .await?;
 
-        let wait_time = average_block_speed.mul_f64(amount as f64);
+        // The new blocks are received without polling when the chain has an RPC endpoint.
+        // They're polled when they don't arrive within twice their average time, e.g. when the websocket stalls
+        if let Some(rpc_url) = self.chain_info().rpc_urls.first() {
+            let timeout = average_block_speed.mul_f64(2.0 * (amount + 1) as f64);
+            if subscription::wait_for_height(rpc_url, end_height, timeout).await {
+                return Ok(());
+            }
+            last_height = Node::new_async(self.channel())._block_height().await?;
+        }
+
+        let wait_time = average_block_speed.mul_f64(end_height.saturating_sub(last_height) as f64);
 
         // now wait for that amount of time
         tokio::time::sleep(wait_time).await;
@@ -259,6 +339,14 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
         admin: Option<&Addr>,
         coins: &[Coin],
     ) -> Result<CosmTxResponse, DaemonError> {
+        let step = (
+            self.sender_addr(),
+            code_id,
+            label,
+            admin,
+            serde_json::to_value(init_msg)?,
+            coins,
+        );
         let init_msg = MsgInstantiateContract {
             code_id,
             label: Some(label.unwrap_or("instantiate_contract").to_string()),
@@ -269,10 +357,19 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
         };
 
         let result = self
-            .sender()
-            .commit_tx(vec![init_msg], None)
-            .await
-            .map_err(Into::into)?;
+            .journaled(
+                JournalStepKind::Instantiate,
+                &step,
+                Some(code_id),
+                None,
+                async {
+                    self.sender()
+                        .commit_tx(vec![init_msg], None)
+                        .await
+                        .map_err(Into::into)
+                },
+            )
+            .await?;
 
         log::info!(target: &transaction_target(), "Instantiation done: {:?}", result.txhash);
 
@@ -289,6 +386,15 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
         coins: &[Coin],
         salt: Binary,
     ) -> Result<CosmTxResponse, DaemonError> {
+        let step = (
+            self.sender_addr(),
+            code_id,
+            label,
+            admin,
+            serde_json::to_value(init_msg)?,
+            coins,
+            &salt,
+        );
         let init_msg = MsgInstantiateContract2 {
             code_id,
             label: label.unwrap_or("instantiate_contract").to_string(),
@@ -301,16 +407,25 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
         };
 
         let result = self
-            .sender()
-            .commit_tx_any(
-                vec![Any {
-                    type_url: INSTANTIATE_2_TYPE_URL.to_string(),
-                    value: init_msg.encode_to_vec(),
-                }],
+            .journaled(
+                JournalStepKind::Instantiate,
+                &step,
+                Some(code_id),
                 None,
+                async {
+                    self.sender()
+                        .commit_tx_any(
+                            vec![Any {
+                                type_url: INSTANTIATE_2_TYPE_URL.to_string(),
+                                value: init_msg.encode_to_vec(),
+                            }],
+                            None,
+                        )
+                        .await
+                        .map_err(Into::into)
+                },
             )
-            .await
-            .map_err(Into::into)?;
+            .await?;
 
         log::info!(target: &transaction_target(), "Instantiation done: {:?}", result.txhash);
 
@@ -330,11 +445,25 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
             msg: serde_json::to_vec(&migrate_msg)?,
             code_id: new_code_id,
         };
+        let step = (
+            contract_address,
+            new_code_id,
+            serde_json::to_value(migrate_msg)?,
+        );
         let result = self
-            .sender()
-            .commit_tx(vec![exec_msg], None)
-            .await
-            .map_err(Into::into)?;
+            .journaled(
+                JournalStepKind::Migrate,
+                &step,
+                Some(new_code_id),
+                Some(contract_address),
+                async {
+                    self.sender()
+                        .commit_tx(vec![exec_msg], None)
+                        .await
+                        .map_err(Into::into)
+                },
+            )
+            .await?;
         Ok(result)
     }
 
@@ -348,6 +477,10 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
         log::debug!(target: &transaction_target(), "Uploading file at {:?}", wasm_path);
 
         let file_contents = std::fs::read(wasm_path.path())?;
+        let step = (
+            self.sender_addr(),
+            hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&file_contents)),
+        );
         let mut e = write::GzEncoder::new(Vec::new(), Compression::default());
         e.write_all(&file_contents)?;
         let wasm_byte_code = e.finish()?;
@@ -358,10 +491,13 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
         };
 
         let result = self
-            .sender()
-            .commit_tx(vec![store_msg], None)
-            .await
-            .map_err(Into::into)?;
+            .journaled(JournalStepKind::Upload, &step, None, None, async {
+                self.sender()
+                    .commit_tx(vec![store_msg], None)
+                    .await
+                    .map_err(Into::into)
+            })
+            .await?;
 
         log::info!(target: &transaction_target(), "Uploading done: {:?}", result.txhash);
 
@@ -369,11 +505,75 @@ impl<Sender: TxSender> DaemonAsyncBase<Sender> {
 
         // wait for the node to return the contract information for this upload
         let wasm = CosmWasm::new_async(self.channel());
-        while wasm._code(code_id).await.is_err() {
+        while result.is_broadcasted() && wasm._code(code_id).await.is_err() {
             self.next_block().await?;
         }
         Ok(result)
     }
+
+    /// Awaits the inclusion of a transaction returned before its inclusion, the deployment steps need its events
+    async fn included(&self, response: CosmTxResponse) -> Result<CosmTxResponse, DaemonError> {
+        if response.height == 0 && response.is_broadcasted() {
+            self.pending_tx(&response).included().await
+        } else {
+            Ok(response)
+        }
+    }
+
+    /// Sends a deployment step, recording it in the deployment journal when journaling is enabled.
+    /// A step that a previous run already confirmed on-chain isn't sent again, its transaction is returned instead.
+    async fn journaled(
+        &self,
+        kind: JournalStepKind,
+        step: &impl Serialize,
+        code_id: Option<u64>,
+        contract_address: Option<&Addr>,
+        send: impl Future<Output = Result<CosmTxResponse, DaemonError>>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let Some(key) = self.state.journal_key(kind, step)? else {
+            return self.included(send.await?).await;
+        };
+
+        if let Some(recorded) = self.state.journal_step(&key)? {
+            match Node::new_async(self.channel())
+                ._find_tx_with_retries(recorded.tx_hash.clone(), 1)
+                .await
+            {
+                Ok(tx) if tx.code == 0 => {
+                    log::info!(target: &transaction_target(), "Skipping {} step {}, already confirmed in tx {:?}", kind, recorded.index, recorded.tx_hash);
+                    return Ok(tx);
+                }
+                _ => {
+                    log::warn!(target: &transaction_target(), "Journaled {} tx {:?} is not confirmed on-chain, sending it again", kind, recorded.tx_hash);
+                }
+            }
+        }
+
+        let result = self.included(send.await?).await?;
+        // The exported transactions are sent again by the next runs until they're broadcasted
+        if !result.is_broadcasted() {
+            return Ok(result);
+        }
+        let (code_id, contract_address) = match kind {
+            JournalStepKind::Upload => (result.uploaded_code_id().ok(), None),
+            JournalStepKind::Instantiate => (
+                code_id,
+                result
+                    .instantiated_contract_address()
+                    .ok()
+                    .map(String::from),
+            ),
+            JournalStepKind::Migrate => (code_id, contract_address.map(ToString::to_string)),
+        };
+        self.state.clone().record_journal_step(
+            &key,
+            kind,
+            &result.txhash,
+            code_id,
+            contract_address,
+        )?;
+        Ok(result)
+    }
 }
 
 impl Querier for DaemonAsync {
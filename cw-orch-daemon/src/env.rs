@@ -24,10 +24,15 @@ pub const MIN_GAS_ENV_NAME: &str = "CW_ORCH_MIN_GAS";
 pub const MAX_TX_QUERIES_RETRY_ENV_NAME: &str = "CW_ORCH_MAX_TX_QUERY_RETRIES";
 pub const WALLET_BALANCE_ASSERTION_ENV_NAME: &str = "CW_ORCH_WALLET_BALANCE_ASSERTION";
 pub const LOGS_ACTIVATION_MESSAGE_ENV_NAME: &str = "CW_ORCH_LOGS_ACTIVATION_MESSAGE";
+pub const GRPC_HEALTH_CHECK_INTERVAL_ENV_NAME: &str = "CW_ORCH_GRPC_HEALTH_CHECK_INTERVAL";
+pub const GRPC_MAX_BLOCK_LAG_ENV_NAME: &str = "CW_ORCH_GRPC_MAX_BLOCK_LAG";
+pub const GRPC_RECORD_ENV_NAME: &str = "CW_ORCH_GRPC_RECORD";
+pub const GRPC_REPLAY_ENV_NAME: &str = "CW_ORCH_GRPC_REPLAY";
 
 pub const MAIN_MNEMONIC_ENV_NAME: &str = "MAIN_MNEMONIC";
 pub const TEST_MNEMONIC_ENV_NAME: &str = "TEST_MNEMONIC";
 pub const LOCAL_MNEMONIC_ENV_NAME: &str = "LOCAL_MNEMONIC";
+pub const KEYRING_PASSPHRASE_ENV_NAME: &str = "CW_ORCH_KEYRING_PASSPHRASE";
 pub struct DaemonEnvVars {}
 impl DaemonEnvVars {
     /// Optional - Path
@@ -124,6 +129,42 @@ impl DaemonEnvVars {
         }
     }
 
+    /// Optional - Duration
+    /// Defaults to 5 seconds
+    /// Interval between the health checks of the gRPC endpoints of a chain.
+    /// The next healthy endpoint is used when the current one fails or lags behind
+    pub fn grpc_health_check_interval() -> Duration {
+        if let Ok(str_value) = env::var(GRPC_HEALTH_CHECK_INTERVAL_ENV_NAME) {
+            parse_block_time_duration(&str_value)
+        } else {
+            Duration::from_secs(5)
+        }
+    }
+
+    /// Optional - Integer
+    /// Defaults to 5
+    /// Number of blocks a gRPC endpoint can lag behind the most advanced endpoint of the chain before it's considered unhealthy
+    pub fn grpc_max_block_lag() -> u64 {
+        if let Ok(str_value) = env::var(GRPC_MAX_BLOCK_LAG_ENV_NAME) {
+            parse_with_log(str_value, GRPC_MAX_BLOCK_LAG_ENV_NAME)
+        } else {
+            5
+        }
+    }
+
+    /// Optional - Path
+    /// Fixture file recording the gRPC requests of the channels and their responses, see [`Fixture`](crate::fixture::Fixture)
+    pub fn grpc_record() -> Option<PathBuf> {
+        env::var(GRPC_RECORD_ENV_NAME).ok().map(PathBuf::from)
+    }
+
+    /// Optional - Path
+    /// Fixture file replaying the recorded responses to the gRPC requests of the channels, without reaching the nodes.
+    /// Takes precedence over [`GRPC_RECORD_ENV_NAME`]
+    pub fn grpc_replay() -> Option<PathBuf> {
+        env::var(GRPC_REPLAY_ENV_NAME).ok().map(PathBuf::from)
+    }
+
     /// Optional - String
     /// Mandatory when interacting with a daemon on mainnet
     /// Mnemonic of the address interacting with a mainnet
@@ -144,6 +185,13 @@ impl DaemonEnvVars {
     pub fn local_mnemonic() -> Option<String> {
         env::var(LOCAL_MNEMONIC_ENV_NAME).ok()
     }
+
+    /// Optional - String
+    /// Passphrase of the Cosmos-SDK `file` keyring the keys are loaded from
+    /// Prompted for when not set and manual interactions are enabled
+    pub fn keyring_passphrase() -> Option<String> {
+        env::var(KEYRING_PASSPHRASE_ENV_NAME).ok()
+    }
 }
 
 /// Fetches the default state folder.
@@ -102,6 +102,12 @@ pub enum DaemonError {
     NewNetwork(String),
     #[error("Can not connect to any grpc endpoint that was provided.")]
     CannotConnectGRPC,
+    #[error("Can not connect to any rpc endpoint that was provided.")]
+    CannotConnectRPC,
+    #[error("The chain info has no rpc endpoint, set its `rpc_urls`")]
+    MissingRpcEndpoint,
+    #[error(transparent)]
+    Websocket(#[from] ::async_tungstenite::tungstenite::Error),
     #[error("tx failed: {reason} with code {code}")]
     TxFailed { code: usize, reason: String },
     #[error("The list of grpc endpoints is empty")]
@@ -126,6 +132,18 @@ pub enum DaemonError {
     OpenFile(String, String),
     #[error("State file {0} already locked, use another state file, clone daemon which holds the lock, or use `state` method of Builder")]
     StateAlreadyLocked(String),
+    #[error("Transaction not broadcasted, its result is available once the unsigned transaction written to {0} is signed and broadcasted")]
+    TxNotBroadcasted(String),
+    #[error("Keyring error: {0}")]
+    Keyring(String),
+    #[error("Coin type {0} is not supported without the `eth` feature")]
+    UnsupportedCoinType(u32),
+    #[error("Transaction {txhash} not confirmed, block {height} wasn't reached after {timeout:?}")]
+    TxConfirmationTimeout {
+        txhash: String,
+        height: u64,
+        timeout: std::time::Duration,
+    },
 }
 
 impl DaemonError {
@@ -0,0 +1,229 @@
+//! Gas prices of chains with a dynamic fee market, where the base fee changes every block.
+
+use cw_orch_core::log::transaction_target;
+use prost::Message;
+use tonic::{
+    codec::ProstCodec, codegen::http::uri::PathAndQuery, transport::Channel, Code, Status,
+};
+
+use crate::{
+    proto::fee_market::{
+        GasPriceRequest, GasPriceResponse, QueryEipBaseFeeRequest, QueryEipBaseFeeResponse,
+        FEEMARKET_GAS_PRICE_PATH, OSMOSIS_EIP_BASE_FEE_PATH,
+    },
+    DaemonError,
+};
+
+/// Number of decimals of the `LegacyDec` amounts
+const DEC_DECIMALS: i32 = 18;
+
+/// On-chain module giving the current gas price
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeeMarketModule {
+    /// `GetEipBaseFee` query of the `osmosis.txfees` module, priced in the base denom of Osmosis
+    OsmosisTxFees,
+    /// `GasPrice` query of the Skip `feemarket` module, used by Neutron among others
+    SkipFeeMarket,
+}
+
+/// Prices the fees of the transactions from the base fee of the fee market of the chain, queried before each transaction.
+/// When the query fails, the gas price of the chain info is used.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct FeeMarket {
+    pub module: FeeMarketModule,
+    /// Multiplier applied to the base fee, so the transaction stays valid if the base fee rises before its inclusion
+    pub multiplier: f64,
+    /// Maximum gas price paid, whatever the base fee of the chain
+    pub max_gas_price: Option<f64>,
+}
+
+impl FeeMarket {
+    pub fn new(module: FeeMarketModule) -> Self {
+        Self {
+            module,
+            multiplier: 1.1,
+            max_gas_price: None,
+        }
+    }
+
+    /// Fee market of the `osmosis.txfees` module
+    pub fn osmosis() -> Self {
+        Self::new(FeeMarketModule::OsmosisTxFees)
+    }
+
+    /// Fee market of the Skip `feemarket` module
+    pub fn skip() -> Self {
+        Self::new(FeeMarketModule::SkipFeeMarket)
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn max_gas_price(mut self, max_gas_price: f64) -> Self {
+        self.max_gas_price = Some(max_gas_price);
+        self
+    }
+
+    /// Queries the base fee of the chain, in `denom`
+    pub async fn base_fee(&self, channel: Channel, denom: &str) -> Result<f64, DaemonError> {
+        let base_fee = match self.module {
+            FeeMarketModule::OsmosisTxFees => {
+                let response: QueryEipBaseFeeResponse = unary(
+                    channel,
+                    OSMOSIS_EIP_BASE_FEE_PATH,
+                    QueryEipBaseFeeRequest {},
+                )
+                .await?;
+                response.base_fee
+            }
+            FeeMarketModule::SkipFeeMarket => {
+                let response: GasPriceResponse = unary(
+                    channel,
+                    FEEMARKET_GAS_PRICE_PATH,
+                    GasPriceRequest {
+                        denom: denom.to_string(),
+                    },
+                )
+                .await?;
+                response
+                    .price
+                    .ok_or_else(|| DaemonError::StdErr(format!("No gas price for {denom}")))?
+                    .amount
+            }
+        };
+        parse_dec(&base_fee)
+    }
+
+    /// Gas price of the next transaction, the base fee with the multiplier, capped to the max gas price.
+    /// Falls back to `default_gas_price` when the fee market can't be queried.
+    pub async fn gas_price(&self, channel: Channel, denom: &str, default_gas_price: f64) -> f64 {
+        match self.base_fee(channel, denom).await {
+            Ok(base_fee) => self.price(base_fee),
+            Err(e) => {
+                log::warn!(
+                    target: &transaction_target(),
+                    "Couldn't query the fee market, using the gas price {default_gas_price}: {e}"
+                );
+                default_gas_price
+            }
+        }
+    }
+
+    fn price(&self, base_fee: f64) -> f64 {
+        let price = base_fee * self.multiplier;
+        self.max_gas_price.map_or(price, |max| price.min(max))
+    }
+}
+
+/// Sends a unary gRPC query, for modules without a generated client
+async fn unary<Req, Resp>(
+    channel: Channel,
+    path: &'static str,
+    request: Req,
+) -> Result<Resp, Status>
+where
+    Req: Message + 'static,
+    Resp: Message + Default + 'static,
+{
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| Status::new(Code::Unknown, format!("Service was not ready: {e}")))?;
+    Ok(grpc
+        .unary(
+            tonic::Request::new(request),
+            PathAndQuery::from_static(path),
+            ProstCodec::default(),
+        )
+        .await?
+        .into_inner())
+}
+
+/// Parses a `LegacyDec`, either encoded as an integer with 18 decimals or as a decimal string
+fn parse_dec(value: &str) -> Result<f64, DaemonError> {
+    if value.contains('.') {
+        Ok(value.parse()?)
+    } else {
+        Ok(value.parse::<u128>()? as f64 / 10f64.powi(DEC_DECIMALS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmrs::proto::cosmos::base::v1beta1::DecCoin;
+    use prost::Message;
+    use tonic::{transport::Channel, Status};
+
+    use super::{parse_dec, FeeMarket};
+    use crate::{
+        proto::fee_market::{
+            GasPriceRequest, GasPriceResponse, QueryEipBaseFeeResponse, FEEMARKET_GAS_PRICE_PATH,
+            OSMOSIS_EIP_BASE_FEE_PATH,
+        },
+        transport::{in_process_channel, serve_unary},
+    };
+
+    /// Node serving the base fee of osmosis and the gas price of the Skip fee market in `ujuno`
+    fn fee_market_node() -> Channel {
+        in_process_channel(|request| {
+            serve_unary(request, |path, message, _| async move {
+                match path.as_str() {
+                    OSMOSIS_EIP_BASE_FEE_PATH => Ok(QueryEipBaseFeeResponse {
+                        base_fee: "2500000000000000".to_string(),
+                    }
+                    .encode_to_vec()),
+                    FEEMARKET_GAS_PRICE_PATH => {
+                        let request = GasPriceRequest::decode(message)
+                            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+                        let price = (request.denom == "ujuno").then(|| DecCoin {
+                            denom: request.denom,
+                            amount: "0.0053".to_string(),
+                        });
+                        Ok(GasPriceResponse { price }.encode_to_vec())
+                    }
+                    _ => Err(Status::unimplemented(path)),
+                }
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn base_fee_is_queried_from_the_fee_market() -> anyhow::Result<()> {
+        let channel = fee_market_node();
+
+        let osmosis = FeeMarket::osmosis();
+        assert_eq!(osmosis.base_fee(channel.clone(), "uosmo").await?, 0.0025);
+        let skip = FeeMarket::skip();
+        assert_eq!(skip.base_fee(channel.clone(), "ujuno").await?, 0.0053);
+        assert!(skip.base_fee(channel.clone(), "untrn").await.is_err());
+
+        // The gas price of the chain info is used when the fee market has no price
+        assert_eq!(skip.gas_price(channel.clone(), "untrn", 0.1).await, 0.1);
+        assert_eq!(
+            osmosis
+                .max_gas_price(0.002)
+                .gas_price(channel, "uosmo", 0.1)
+                .await,
+            0.002
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_legacy_dec() -> anyhow::Result<()> {
+        assert_eq!(parse_dec("25000000000000000")?, 0.025);
+        assert_eq!(parse_dec("0.0053")?, 0.0053);
+        assert!(parse_dec("price").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn gas_price_is_capped() {
+        let fee_market = FeeMarket::osmosis().multiplier(2.0);
+        assert_eq!(fee_market.price(0.01), 0.02);
+        assert_eq!(fee_market.max_gas_price(0.015).price(0.01), 0.015);
+    }
+}
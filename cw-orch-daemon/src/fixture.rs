@@ -0,0 +1,366 @@
+//! Recording and replay of the gRPC exchanges of a daemon, to run tests without a node.
+//!
+//! A recording channel forwards every request to the node and saves the request and its response to a fixture file.
+//! A replaying channel serves the responses saved in the fixture, without any network access.
+//! The requests are matched by chain, method, height and body. When the same request was recorded several times,
+//! its responses are replayed in the recorded order.
+//!
+//! The fixture files are in the JSON Lines format. A recording replaces the file when its first exchange is recorded,
+//! then appends every exchange as a line as soon as it's recorded.
+//!
+//! Setting the [`CW_ORCH_GRPC_RECORD`](crate::env::GRPC_RECORD_ENV_NAME) or [`CW_ORCH_GRPC_REPLAY`](crate::env::GRPC_REPLAY_ENV_NAME)
+//! env variable to the path of a fixture records or replays every channel created by [`GrpcChannel::connect`](crate::GrpcChannel::connect),
+//! which is used by the `Daemon`, the `live_mock` querier and `CloneTesting`.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cw_orch_core::log::connectivity_target;
+use prost::bytes::{Buf, BufMut, Bytes};
+use serde::{Deserialize, Serialize};
+use tonic::{
+    codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+    codegen::http::uri::PathAndQuery,
+    metadata::MetadataValue,
+    transport::Channel,
+    Code, Status,
+};
+
+use crate::{
+    transport::{in_process_channel, serve_unary, BLOCK_HEIGHT_HEADER},
+    DaemonError,
+};
+
+lazy_static::lazy_static! {
+    /// Fixtures used by the channels of the process, by path and whether they're recording
+    static ref FIXTURES: Mutex<HashMap<(PathBuf, bool), Fixture>> = Mutex::new(HashMap::new());
+}
+
+/// Exchanges recorded in a fixture file.
+/// Cloning the fixture shares it, the exchanges of all its channels are recorded in the same file.
+#[derive(Clone)]
+pub struct Fixture {
+    path: PathBuf,
+    exchanges: Arc<Mutex<Vec<Exchange>>>,
+    /// Number of times each request was replayed
+    replayed: Arc<Mutex<HashMap<RequestKey, usize>>>,
+    /// File the recorded exchanges are appended to, replacing the fixture file when the first exchange is recorded
+    output: Arc<Mutex<Option<File>>>,
+}
+
+/// Request and response of a unary gRPC call
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Exchange {
+    chain_id: String,
+    method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    height: Option<u64>,
+    /// Base64 encoded request message
+    request: String,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RecordedResponse {
+    /// Base64 encoded response message
+    Ok(String),
+    Err {
+        code: i32,
+        message: String,
+    },
+}
+
+type RequestKey = (String, String, Option<u64>, String);
+
+impl Exchange {
+    fn key(&self) -> RequestKey {
+        (
+            self.chain_id.clone(),
+            self.method.clone(),
+            self.height,
+            self.request.clone(),
+        )
+    }
+}
+
+impl Fixture {
+    /// Fixture recording new exchanges to `path`.
+    /// An existing file is replaced when the first exchange is recorded, the next exchanges are appended to it.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::new(path.into(), vec![])
+    }
+
+    /// Fixture replaying the exchanges recorded at `path`
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, DaemonError> {
+        let path = path.into();
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            DaemonError::StdErr(format!("Cannot read the fixture {}: {e}", path.display()))
+        })?;
+        let exchanges = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Exchange>, _>>()?;
+        Ok(Self::new(path, exchanges))
+    }
+
+    fn new(path: PathBuf, exchanges: Vec<Exchange>) -> Self {
+        Self {
+            path,
+            exchanges: Arc::new(Mutex::new(exchanges)),
+            replayed: Default::default(),
+            output: Default::default(),
+        }
+    }
+
+    /// Fixture at `path` shared by all the channels of the process, recording if `record` is set or replaying otherwise.
+    /// A fixture recorded by the process is loaded again to be replayed.
+    pub(crate) fn shared(path: PathBuf, record: bool) -> Result<Self, DaemonError> {
+        let mut fixtures = FIXTURES.lock().unwrap();
+        if let Some(fixture) = fixtures.get(&(path.clone(), record)) {
+            return Ok(fixture.clone());
+        }
+        let fixture = if record {
+            Self::record(path.clone())
+        } else {
+            Self::load(path.clone())?
+        };
+        fixtures.insert((path, record), fixture.clone());
+        Ok(fixture)
+    }
+
+    /// Path of the fixture file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Channel forwarding its requests to `channel` and recording them with their responses
+    pub fn recording_channel(&self, chain_id: &str, channel: Channel) -> Channel {
+        let fixture = self.clone();
+        let chain_id = chain_id.to_string();
+        in_process_channel(move |request| {
+            let fixture = fixture.clone();
+            let chain_id = chain_id.clone();
+            let channel = channel.clone();
+            serve_unary(request, |method, message, height| async move {
+                let response = forward(channel, &method, message.clone(), height).await;
+                fixture.push(&Exchange {
+                    chain_id,
+                    method,
+                    height,
+                    request: STANDARD.encode(&message),
+                    response: match &response {
+                        Ok(message) => RecordedResponse::Ok(STANDARD.encode(message)),
+                        Err(status) => RecordedResponse::Err {
+                            code: status.code() as i32,
+                            message: status.message().to_string(),
+                        },
+                    },
+                });
+                response
+            })
+        })
+    }
+
+    /// Channel serving the responses recorded for the chain, without reaching any node
+    pub fn replaying_channel(&self, chain_id: &str) -> Channel {
+        let fixture = self.clone();
+        let chain_id = chain_id.to_string();
+        in_process_channel(move |request| {
+            let fixture = fixture.clone();
+            let chain_id = chain_id.clone();
+            serve_unary(request, |method, message, height| async move {
+                fixture.replay((chain_id, method, height, STANDARD.encode(&message)))
+            })
+        })
+    }
+
+    fn push(&self, exchange: &Exchange) {
+        if let Err(e) = self.append(exchange) {
+            log::error!(target: &connectivity_target(), "Cannot save the fixture {}: {e}", self.path.display());
+        }
+    }
+
+    /// Appends the exchange to the fixture file, so the fixture is complete whenever the process stops
+    fn append(&self, exchange: &Exchange) -> Result<(), DaemonError> {
+        let mut output = self.output.lock().unwrap();
+        if output.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            *output = Some(File::create(&self.path)?);
+        }
+        let mut line = serde_json::to_string(exchange)?;
+        line.push('\n');
+        if let Some(file) = output.as_mut() {
+            file.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn replay(&self, key: RequestKey) -> Result<Vec<u8>, Status> {
+        let exchanges = self.exchanges.lock().unwrap();
+        let recorded: Vec<_> = exchanges
+            .iter()
+            .filter(|exchange| exchange.key() == key)
+            .collect();
+        let (chain_id, method, height, request) = &key;
+        if recorded.is_empty() {
+            return Err(Status::failed_precondition(format!(
+                "No response recorded in the fixture {} for {method} on {chain_id}{} with the request {request}",
+                self.path.display(),
+                height.map(|height| format!(" at height {height}")).unwrap_or_default(),
+            )));
+        }
+
+        // The responses are replayed in order, the last one is replayed once they're exhausted
+        let mut replayed = self.replayed.lock().unwrap();
+        let count = replayed.entry(key.clone()).or_default();
+        let exchange = recorded[(*count).min(recorded.len() - 1)];
+        *count += 1;
+
+        match &exchange.response {
+            RecordedResponse::Ok(message) => STANDARD
+                .decode(message)
+                .map_err(|e| Status::data_loss(format!("Invalid recorded response: {e}"))),
+            RecordedResponse::Err { code, message } => {
+                Err(Status::new(Code::from_i32(*code), message.clone()))
+            }
+        }
+    }
+}
+
+/// Sends the unary request to `channel`
+async fn forward(
+    channel: Channel,
+    method: &str,
+    message: Bytes,
+    height: Option<u64>,
+) -> Result<Vec<u8>, Status> {
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| Status::unavailable(e.to_string()))?;
+
+    let mut request = tonic::Request::new(message);
+    if let Some(height) = height {
+        request
+            .metadata_mut()
+            .insert(BLOCK_HEIGHT_HEADER, MetadataValue::from(height));
+    }
+    let path = PathAndQuery::from_str(method).map_err(|e| Status::internal(e.to_string()))?;
+    let response = grpc.unary(request, path, RawCodec).await?;
+    Ok(response.into_inner().to_vec())
+}
+
+/// Codec of the encoded messages, forwarded without decoding them
+#[derive(Clone, Copy, Default)]
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Bytes;
+    type Decode = Bytes;
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Bytes, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put(item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Bytes>, Status> {
+        Ok(Some(src.copy_to_bytes(src.remaining())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::coin;
+
+    use super::Fixture;
+    use crate::{
+        queriers::Bank,
+        state::gen_temp_file_path,
+        test_utils::{MockRpc, CHAIN_ID},
+        transport::{RpcTransport, Transport},
+        DaemonError, GrpcChannel,
+    };
+
+    #[tokio::test]
+    async fn replays_recorded_exchanges() -> anyhow::Result<()> {
+        let path = gen_temp_file_path().with_extension("jsonl");
+        let node = MockRpc::default();
+        let channel = RpcTransport::new(node.serve()).channel();
+        std::fs::write(&path, "stale exchange\n")?;
+
+        let recording = Fixture::record(&path).recording_channel(CHAIN_ID, channel);
+        let balance = Bank::new_async(recording.clone())
+            ._balance("juno1address", Some("ujuno".to_string()))
+            .await?;
+        let error = Bank::new_async(GrpcChannel::at_height(recording, 5))
+            ._supply_of("ujuno")
+            .await
+            .unwrap_err();
+        assert_eq!(node.queried_heights.lock().unwrap().len(), 2);
+        // The stale file is replaced, then every exchange is appended as a line
+        assert_eq!(std::fs::read_to_string(&path)?.lines().count(), 2);
+
+        // The replay doesn't reach the node
+        let replaying = Fixture::load(&path)?.replaying_channel(CHAIN_ID);
+        let replayed = Bank::new_async(replaying.clone())
+            ._balance("juno1address", Some("ujuno".to_string()))
+            .await?;
+        assert_eq!(replayed, balance);
+        assert_eq!(replayed, vec![coin(100, "ujuno")]);
+        let replayed_error = Bank::new_async(GrpcChannel::at_height(replaying.clone(), 5))
+            ._supply_of("ujuno")
+            .await
+            .unwrap_err();
+        let (DaemonError::Status(error), DaemonError::Status(replayed_error)) =
+            (error, replayed_error)
+        else {
+            panic!("expected gRPC errors");
+        };
+        assert_eq!(replayed_error.code(), error.code());
+        assert_eq!(replayed_error.message(), error.message());
+        assert_eq!(node.queried_heights.lock().unwrap().len(), 2);
+
+        // The requests missing from the fixture fail
+        let missing = Bank::new_async(replaying)
+            ._balance("juno1other", Some("ujuno".to_string()))
+            .await
+            .unwrap_err();
+        assert!(missing
+            .to_string()
+            .contains("No response recorded in the fixture"));
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! Journal of the deployment steps sent through a daemon, used to resume interrupted deployments.
+//!
+//! When enabled with [`DaemonAsyncBuilder::journal`](crate::DaemonAsyncBuilder::journal), every upload, instantiation and migration
+//! is recorded in the state file with its transaction hash, under the `journal` key of the chain and the deployment id.
+//! Steps are identified by their content (code checksum, message, funds, ...) and their occurrence in the deployment,
+//! so a re-run of the same deployment skips the steps that were already confirmed on-chain.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Key of the journal in the chain state
+pub(crate) const JOURNAL_KEY: &str = "journal";
+
+/// Kind of deployment step recorded in the journal
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalStepKind {
+    /// Code upload
+    Upload,
+    /// Contract instantiation, with or without a predictable address
+    Instantiate,
+    /// Contract migration
+    Migrate,
+}
+
+impl Display for JournalStepKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            JournalStepKind::Upload => "upload",
+            JournalStepKind::Instantiate => "instantiate",
+            JournalStepKind::Migrate => "migrate",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+/// Deployment step recorded in the journal
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JournalStep {
+    /// Position of the step in the deployment
+    pub index: usize,
+    /// Kind of step
+    pub kind: JournalStepKind,
+    /// Hash of the transaction that executed the step
+    pub tx_hash: String,
+    /// Code id uploaded, instantiated or migrated to
+    pub code_id: Option<u64>,
+    /// Address of the contract instantiated or migrated
+    pub contract_address: Option<String>,
+}
+
+/// Counts the occurrences of the steps sent during the current run,
+/// to tell apart identical steps of a deployment.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct JournalCursor {
+    occurrences: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl JournalCursor {
+    /// Returns the journal key of the next step with this content in the given deployment
+    pub(crate) fn next_key(
+        &self,
+        deployment: &str,
+        kind: JournalStepKind,
+        content: &[u8],
+    ) -> String {
+        let step = format!("{kind}:{}", hex::encode(Sha256::digest(content)));
+        let mut occurrences = self.occurrences.lock().unwrap();
+        let occurrence = occurrences
+            .entry(format!("{deployment}/{step}"))
+            .or_default();
+        *occurrence += 1;
+        format!("{step}#{occurrence}")
+    }
+
+    /// Forgets the steps sent in the given deployment
+    pub(crate) fn reset(&self, deployment: &str) {
+        let prefix = format!("{deployment}/");
+        self.occurrences
+            .lock()
+            .unwrap()
+            .retain(|step, _| !step.starts_with(&prefix));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use cw_orch_core::environment::ChainInfoOwned;
+
+    use super::{JournalCursor, JournalStep, JournalStepKind};
+    use crate::{networks::JUNO_1, DaemonState};
+
+    #[test]
+    fn identical_steps_have_different_keys() {
+        let cursor = JournalCursor::default();
+        let first = cursor.next_key("default", JournalStepKind::Upload, b"code");
+        let second = cursor.next_key("default", JournalStepKind::Upload, b"code");
+        assert_ne!(first, second);
+        assert_eq!(
+            cursor.next_key("other", JournalStepKind::Upload, b"code"),
+            first
+        );
+
+        cursor.reset("default");
+        assert_eq!(
+            cursor.next_key("default", JournalStepKind::Upload, b"code"),
+            first
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn record_inspect_and_reset_journal() -> anyhow::Result<()> {
+        let chain: ChainInfoOwned = JUNO_1.into();
+        let path = crate::gen_temp_file_path()
+            .into_os_string()
+            .into_string()
+            .unwrap();
+        let mut state =
+            DaemonState::new(path, &Arc::new(chain), "default".to_string(), false, true)?;
+        state.journal = Some(JournalCursor::default());
+
+        let upload = state
+            .journal_key(JournalStepKind::Upload, &"code")?
+            .unwrap();
+        assert_eq!(state.journal_step(&upload)?, None);
+        state.record_journal_step(&upload, JournalStepKind::Upload, "hash-1", Some(1), None)?;
+        let instantiate = state
+            .journal_key(JournalStepKind::Instantiate, &(1, "msg"))?
+            .unwrap();
+        state.record_journal_step(
+            &instantiate,
+            JournalStepKind::Instantiate,
+            "hash-2",
+            Some(1),
+            Some("juno1contract".to_string()),
+        )?;
+
+        // Re-recording a step keeps its position
+        state.record_journal_step(&upload, JournalStepKind::Upload, "hash-3", Some(2), None)?;
+        assert_eq!(
+            state.journal()?,
+            vec![
+                JournalStep {
+                    index: 0,
+                    kind: JournalStepKind::Upload,
+                    tx_hash: "hash-3".to_string(),
+                    code_id: Some(2),
+                    contract_address: None,
+                },
+                JournalStep {
+                    index: 1,
+                    kind: JournalStepKind::Instantiate,
+                    tx_hash: "hash-2".to_string(),
+                    code_id: Some(1),
+                    contract_address: Some("juno1contract".to_string()),
+                }
+            ]
+        );
+        assert!(state.deployment_journal("other")?.is_empty());
+
+        // After a reset, the next run starts from the first step again
+        state.reset_journal()?;
+        assert!(state.journal()?.is_empty());
+        assert_eq!(
+            state.journal_key(JournalStepKind::Upload, &"code")?,
+            Some(upload)
+        );
+        Ok(())
+    }
+}
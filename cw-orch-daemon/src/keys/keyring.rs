@@ -0,0 +1,396 @@
+//! Encrypted keyring written by `<chain-binary> keys add <name> --keyring-backend file`.
+//!
+//! Every key is stored in a `<name>.info` file of the keyring directory (`<chain-home>/keyring-file`),
+//! encrypted as a JWE token (`PBES2-HS256+A128KW` key wrapping, `A256GCM` content encryption) with the passphrase of the keyring.
+//! The decrypted content is a JSON item holding the protobuf `Record` of the key, written by Cosmos-SDK v0.46 and later.
+
+use std::{
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use aes::{
+    cipher::{BlockDecrypt, KeyInit},
+    Aes128, Block,
+};
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use cosmrs::proto::cosmos::crypto::secp256k1::PrivKey;
+use cw_orch_core::CoreEnvVars;
+use prost::Message;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM},
+    pbkdf2,
+};
+use serde_json::Value;
+
+use crate::{env::DaemonEnvVars, DaemonError};
+
+/// Key management algorithm of the keyring items
+const KEY_ALGORITHM: &str = "PBES2-HS256+A128KW";
+/// Content encryption algorithm of the keyring items
+const CONTENT_ALGORITHM: &str = "A256GCM";
+/// Initial value of the AES key wrap (RFC 3394)
+const KEY_WRAP_IV: u64 = 0xA6A6_A6A6_A6A6_A6A6;
+/// Type of the secp256k1 private keys of the Cosmos-SDK
+const PRIVATE_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PrivKey";
+
+/// `cosmos.crypto.keyring.v1.Record`, limited to the keys stored locally
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct Record {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(message, optional, tag = "3")]
+    local: Option<LocalRecord>,
+}
+
+/// `cosmos.crypto.keyring.v1.Record.Local`
+#[derive(Clone, PartialEq, ::prost::Message)]
+struct LocalRecord {
+    #[prost(message, optional, tag = "1")]
+    priv_key: Option<prost_types::Any>,
+}
+
+/// Keyring directory of the `file` backend of the Cosmos-SDK, unlocked with its passphrase
+#[derive(Clone)]
+pub struct FileKeyring {
+    dir: PathBuf,
+    passphrase: String,
+}
+
+impl FileKeyring {
+    /// Opens the keyring in `dir`, either the keyring directory or the home of the chain binary containing `keyring-file`
+    pub fn new(dir: impl AsRef<Path>, passphrase: impl ToString) -> Self {
+        let dir = dir.as_ref();
+        let keyring_dir = dir.join("keyring-file");
+        Self {
+            dir: if keyring_dir.is_dir() {
+                keyring_dir
+            } else {
+                dir.to_path_buf()
+            },
+            passphrase: passphrase.to_string(),
+        }
+    }
+
+    /// Opens the keyring in `dir` with the passphrase of the environment, or prompts for it
+    pub fn unlock(dir: impl AsRef<Path>) -> Result<Self, DaemonError> {
+        let passphrase = match DaemonEnvVars::keyring_passphrase() {
+            Some(passphrase) => passphrase,
+            None if CoreEnvVars::manual_interaction() => dialoguer::Password::new()
+                .with_prompt(format!(
+                    "Enter the passphrase of the keyring {}",
+                    dir.as_ref().display()
+                ))
+                .interact()?,
+            None => {
+                return Err(DaemonError::Keyring(format!(
+                    "No passphrase to unlock the keyring, set the {} env variable",
+                    crate::env::KEYRING_PASSPHRASE_ENV_NAME
+                )))
+            }
+        };
+        Ok(Self::new(dir, passphrase))
+    }
+
+    /// Names of the keys of the keyring
+    pub fn key_names(&self) -> Result<Vec<String>, DaemonError> {
+        let mut names = std::fs::read_dir(&self.dir)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+            .collect::<Result<Vec<_>, DaemonError>>()?
+            .into_iter()
+            .filter_map(|file| file.strip_suffix(".info").map(ToString::to_string))
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Decrypts the secp256k1 private key with the given name
+    pub fn secret_key(&self, name: &str) -> Result<Vec<u8>, DaemonError> {
+        let path = self.dir.join(format!("{name}.info"));
+        let token = std::fs::read_to_string(&path)
+            .map_err(|e| DaemonError::OpenFile(path.display().to_string(), e.to_string()))?;
+        let item: Value = serde_json::from_slice(&self.decrypt(token.trim())?)?;
+        let record = STANDARD.decode(item["Data"].as_str().unwrap_or_default())?;
+
+        let private_key = Record::decode(record.as_slice())
+            .ok()
+            .and_then(|record| record.local)
+            .and_then(|local| local.priv_key)
+            .ok_or_else(|| {
+                DaemonError::Keyring(format!(
+                    "Key {name} isn't a local key written by Cosmos-SDK v0.46 or later, migrate it with `<chain-binary> keys migrate`"
+                ))
+            })?;
+        if private_key.type_url != PRIVATE_KEY_TYPE_URL {
+            return Err(DaemonError::Keyring(format!(
+                "Key {name} has an unsupported type {}",
+                private_key.type_url
+            )));
+        }
+        Ok(PrivKey::decode(private_key.value.as_slice())?.key)
+    }
+
+    /// Decrypts a JWE compact token with the passphrase of the keyring
+    fn decrypt(&self, token: &str) -> Result<Vec<u8>, DaemonError> {
+        let parts = token.split('.').collect::<Vec<_>>();
+        let [header, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+            return Err(DaemonError::Keyring("Invalid keyring item".to_string()));
+        };
+        let protected: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)?;
+        if protected["alg"] != KEY_ALGORITHM || protected["enc"] != CONTENT_ALGORITHM {
+            return Err(DaemonError::Keyring(format!(
+                "Unsupported keyring item encryption {} {}",
+                protected["alg"], protected["enc"]
+            )));
+        }
+        let iterations = protected["p2c"]
+            .as_u64()
+            .and_then(|iterations| NonZeroU32::new(iterations.try_into().ok()?))
+            .ok_or_else(|| DaemonError::Keyring("Invalid keyring item".to_string()))?;
+
+        // The key encryption key is derived from the passphrase, salted with the algorithm name
+        let mut salt = KEY_ALGORITHM.as_bytes().to_vec();
+        salt.push(0);
+        salt.extend(URL_SAFE_NO_PAD.decode(protected["p2s"].as_str().unwrap_or_default())?);
+        let mut key_encryption_key = [0u8; 16];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            &salt,
+            self.passphrase.as_bytes(),
+            &mut key_encryption_key,
+        );
+        let content_key =
+            aes_key_unwrap(&key_encryption_key, &URL_SAFE_NO_PAD.decode(encrypted_key)?)
+                .ok_or_else(|| DaemonError::Keyring("Wrong keyring passphrase".to_string()))?;
+
+        let key = UnboundKey::new(&AES_256_GCM, &content_key)
+            .map_err(|_| DaemonError::Keyring("Invalid keyring item".to_string()))?;
+        let nonce = Nonce::try_assume_unique_for_key(&URL_SAFE_NO_PAD.decode(iv)?)
+            .map_err(|_| DaemonError::Keyring("Invalid keyring item".to_string()))?;
+        let mut content = URL_SAFE_NO_PAD.decode(ciphertext)?;
+        content.extend(URL_SAFE_NO_PAD.decode(tag)?);
+        let plaintext = LessSafeKey::new(key)
+            .open_in_place(nonce, Aad::from(header.as_bytes()), &mut content)
+            .map_err(|_| DaemonError::Keyring("Corrupted keyring item".to_string()))?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// AES key unwrap (RFC 3394), returns `None` if the integrity check fails
+fn aes_key_unwrap(key_encryption_key: &[u8], wrapped: &[u8]) -> Option<Vec<u8>> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return None;
+    }
+    let cipher = Aes128::new_from_slice(key_encryption_key).ok()?;
+    let n = wrapped.len() / 8 - 1;
+    let mut a = u64::from_be_bytes(wrapped[..8].try_into().unwrap());
+    let mut r = wrapped[8..]
+        .chunks(8)
+        .map(<[u8]>::to_vec)
+        .collect::<Vec<_>>();
+
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = (n * j + i + 1) as u64;
+            let mut block = Block::from(block_of(a ^ t, &r[i]));
+            cipher.decrypt_block(&mut block);
+            a = u64::from_be_bytes(block[..8].try_into().unwrap());
+            r[i] = block[8..].to_vec();
+        }
+    }
+
+    (a == KEY_WRAP_IV).then(|| r.concat())
+}
+
+/// AES block made of the integrity register and a 64-bit block of the key
+fn block_of(a: u64, r: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..8].copy_from_slice(&a.to_be_bytes());
+    block[8..].copy_from_slice(r);
+    block
+}
+
+#[cfg(test)]
+mod test {
+    use aes::{
+        cipher::{BlockEncrypt, KeyInit},
+        Aes128, Block,
+    };
+    use base64::{
+        engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+        Engine,
+    };
+    use bitcoin::secp256k1::Secp256k1;
+    use cosmrs::proto::cosmos::crypto::secp256k1::PrivKey;
+    use prost::Message;
+    use ring::{
+        aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM},
+        pbkdf2,
+    };
+    use serde_json::json;
+
+    use crate::keys::private::PrivateKey;
+
+    use super::{
+        aes_key_unwrap, block_of, FileKeyring, LocalRecord, Record, KEY_WRAP_IV,
+        PRIVATE_KEY_TYPE_URL,
+    };
+
+    /// AES key wrap (RFC 3394), the inverse of [`aes_key_unwrap`]
+    fn aes_key_wrap(key_encryption_key: &[u8], key: &[u8]) -> Vec<u8> {
+        let cipher = Aes128::new_from_slice(key_encryption_key).unwrap();
+        let n = key.len() / 8;
+        let mut a = KEY_WRAP_IV;
+        let mut r = key.chunks(8).map(<[u8]>::to_vec).collect::<Vec<_>>();
+        for j in 0..6 {
+            for (i, r_i) in r.iter_mut().enumerate() {
+                let mut block = Block::from(block_of(a, r_i));
+                cipher.encrypt_block(&mut block);
+                a = u64::from_be_bytes(block[..8].try_into().unwrap()) ^ (n * j + i + 1) as u64;
+                *r_i = block[8..].to_vec();
+            }
+        }
+        [a.to_be_bytes().to_vec(), r.concat()].concat()
+    }
+
+    /// Encrypts a keyring item like the `file` backend of the Cosmos-SDK
+    fn encrypt_item(passphrase: &str, item: &[u8]) -> String {
+        let salt = [7u8; 12];
+        let header = URL_SAFE_NO_PAD.encode(
+            json!({
+                "alg": "PBES2-HS256+A128KW",
+                "enc": "A256GCM",
+                "p2c": 8192,
+                "p2s": URL_SAFE_NO_PAD.encode(salt)
+            })
+            .to_string(),
+        );
+        let mut full_salt = b"PBES2-HS256+A128KW\0".to_vec();
+        full_salt.extend(salt);
+        let mut key_encryption_key = [0u8; 16];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            8192.try_into().unwrap(),
+            &full_salt,
+            passphrase.as_bytes(),
+            &mut key_encryption_key,
+        );
+        let content_key = [3u8; 32];
+        let iv = [5u8; 12];
+        let mut content = item.to_vec();
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &content_key).unwrap())
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(iv),
+                Aad::from(header.as_bytes()),
+                &mut content,
+            )
+            .unwrap();
+        let tag = content.split_off(content.len() - 16);
+
+        [
+            header,
+            URL_SAFE_NO_PAD.encode(aes_key_wrap(&key_encryption_key, &content_key)),
+            URL_SAFE_NO_PAD.encode(iv),
+            URL_SAFE_NO_PAD.encode(content),
+            URL_SAFE_NO_PAD.encode(tag),
+        ]
+        .join(".")
+    }
+
+    #[test]
+    fn key_unwrap_matches_rfc_3394() {
+        let key_encryption_key = hex::decode("000102030405060708090A0B0C0D0E0F").unwrap();
+        let wrapped = hex::decode("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5").unwrap();
+        assert_eq!(
+            aes_key_unwrap(&key_encryption_key, &wrapped),
+            Some(hex::decode("00112233445566778899AABBCCDDEEFF").unwrap())
+        );
+        assert_eq!(
+            aes_key_wrap(
+                &key_encryption_key,
+                &hex::decode("00112233445566778899AABBCCDDEEFF").unwrap()
+            ),
+            wrapped
+        );
+        assert_eq!(aes_key_unwrap(&[0; 16], &wrapped), None);
+    }
+
+    #[test]
+    fn decrypt_keyring_file() -> anyhow::Result<()> {
+        let dir = crate::gen_temp_file_path();
+        let keyring_dir = dir.join("keyring-file");
+        std::fs::create_dir_all(&keyring_dir)?;
+
+        let secret_key = vec![9u8; 32];
+        let record = Record {
+            name: "deployer".to_string(),
+            local: Some(LocalRecord {
+                priv_key: Some(prost_types::Any {
+                    type_url: PRIVATE_KEY_TYPE_URL.to_string(),
+                    value: PrivKey {
+                        key: secret_key.clone(),
+                    }
+                    .encode_to_vec(),
+                }),
+            }),
+        };
+        let item = json!({
+            "Key": "deployer.info",
+            "Data": STANDARD.encode(record.encode_to_vec()),
+            "Label": "",
+            "Description": "",
+            "KeychainNotTrustApplication": false,
+            "KeychainNotSynchronizable": false
+        });
+        std::fs::write(
+            keyring_dir.join("deployer.info"),
+            encrypt_item("passphrase", item.to_string().as_bytes()),
+        )?;
+        std::fs::write(keyring_dir.join("keyhash"), "")?;
+
+        let keyring = FileKeyring::new(&dir, "passphrase");
+        assert_eq!(keyring.key_names()?, vec!["deployer".to_string()]);
+        assert_eq!(keyring.secret_key("deployer")?, secret_key);
+        assert_eq!(
+            PrivateKey::from_secret_key(&secret_key, 118)?
+                .raw_key()
+                .to_vec(),
+            secret_key
+        );
+
+        let error = FileKeyring::new(&dir, "wrong")
+            .secret_key("deployer")
+            .unwrap_err();
+        assert!(error.to_string().contains("Wrong keyring passphrase"));
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_keyring_of_the_chain_binary() -> anyhow::Result<()> {
+        // Key of the local juno validator, as written by `junod keys add validator --recover --keyring-backend file`
+        let mnemonic = "clip hire initial neck maid actor venue client foam budget lock catalog sweet steak waste crater broccoli pipe steak sister coyote moment obvious choose";
+        let keyring = FileKeyring::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"),
+            "cw-orch-keyring",
+        );
+        assert_eq!(keyring.key_names()?, vec!["validator".to_string()]);
+
+        let secp = Secp256k1::new();
+        let private_key = PrivateKey::from_secret_key(&keyring.secret_key("validator")?, 118)?;
+        assert_eq!(
+            private_key.public_key(&secp).account("juno")?,
+            "juno16g2rahf5846rxzp3fwlswy08fz8ccuwk03k57y"
+        );
+        assert_eq!(
+            private_key.raw_key(),
+            PrivateKey::from_words(&secp, mnemonic, 0, 0, 118)?.raw_key()
+        );
+        Ok(())
+    }
+}
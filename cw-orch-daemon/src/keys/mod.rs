@@ -1,4 +1,5 @@
 #![allow(unused)]
+pub mod keyring;
 pub mod private;
 pub mod public;
 pub mod signature;
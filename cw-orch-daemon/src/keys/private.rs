@@ -6,7 +6,7 @@ use ::ethers_core::k256::ecdsa::SigningKey;
 use base64::Engine;
 use bitcoin::secp256k1::{self, Secp256k1};
 use bitcoin::{
-    bip32::{ExtendedPrivKey, IntoDerivationPath},
+    bip32::{ChainCode, ChildNumber, ExtendedPrivKey, IntoDerivationPath},
     Network,
 };
 use cosmrs::tx::SignerPublicKey;
@@ -98,6 +98,26 @@ impl PrivateKey {
         Self::gen_private_key_raw(secp, raw_key, account, index, coin_type)
     }
 
+    /// for an already derived secp256k1 secret key, like the keys of a Cosmos-SDK keyring
+    pub fn from_secret_key(secret_key: &[u8], coin_type: u32) -> Result<PrivateKey, DaemonError> {
+        let private_key = ExtendedPrivKey {
+            network: Network::Bitcoin,
+            depth: 0,
+            parent_fingerprint: Default::default(),
+            child_number: ChildNumber::Normal { index: 0 },
+            private_key: secp256k1::SecretKey::from_slice(secret_key)?,
+            chain_code: ChainCode::from([0u8; 32]),
+        };
+        Ok(PrivateKey {
+            account: 0,
+            index: 0,
+            coin_type,
+            mnemonic: None,
+            root_private_key: private_key,
+            private_key,
+        })
+    }
+
     /// generate the public key for this private key
     pub fn public_key<C: secp256k1::Signing + secp256k1::Context>(
         &self,
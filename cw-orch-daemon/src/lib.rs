@@ -7,18 +7,27 @@ pub mod json_lock;
 /// Proto types for different blockchains
 pub mod proto;
 // expose these as mods as they can grow
+pub mod confirmation;
 pub mod env;
+pub mod fee_market;
+pub mod fixture;
 pub mod keys;
 pub mod live_mock;
 pub mod queriers;
 pub mod senders;
+pub mod subscription;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod transport;
 pub mod tx_broadcaster;
 pub mod tx_builder;
+pub mod tx_json;
 
 mod builder;
 mod channel;
 mod core;
 mod error;
+mod journal;
 mod log;
 mod state;
 mod sync;
@@ -26,6 +35,7 @@ mod tx_resp;
 
 pub use self::{builder::*, channel::*, core::*, error::*, state::*, sync::*, tx_resp::*};
 pub use cw_orch_networks::networks;
+pub use journal::{JournalStep, JournalStepKind};
 pub use senders::{query::QuerySender, tx::TxSender, CosmosOptions, Wallet};
 pub use tx_builder::TxBuilder;
 mod cosmos_proto_patches;
@@ -37,6 +47,7 @@ pub(crate) mod cosmos_modules {
             authz::v1beta1 as authz,
             bank::v1beta1 as bank,
             base::{abci::v1beta1 as abci, tendermint::v1beta1 as tendermint},
+            distribution::v1beta1 as distribution,
             feegrant::v1beta1 as feegrant,
             gov::v1beta1 as gov,
             staking::v1beta1 as staking,
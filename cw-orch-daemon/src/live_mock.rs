@@ -1,67 +1,122 @@
 //! Live mock is a mock that uses a live chain to query for data.
 //! It can be used to do chain-backed unit-testing. It can't be used for state-changing operations.
+//!
+//! The responses can be kept in a [`QueryCache`] so that repeated queries don't hit the network,
+//! and the `Custom` queries of a chain are answered by a handler set with [`WasmMockQuerier::with_custom_handler`].
 
-use crate::queriers::Bank;
-use crate::queriers::CosmWasm;
-use crate::queriers::Staking;
-use crate::RUNTIME;
+use crate::cosmos_modules::ibc_channel;
+use crate::queriers::{
+    cosmrs_to_cosmwasm_delegation, Bank, CosmWasm, Ibc, Staking, StakingBondStatus,
+};
+use crate::{DaemonError, RUNTIME};
 use cosmwasm_std::testing::{MockApi, MockStorage};
-use cosmwasm_std::Addr;
-use cosmwasm_std::AllBalanceResponse;
-use cosmwasm_std::BalanceResponse;
-use cosmwasm_std::BankQuery;
-use cosmwasm_std::Binary;
-use cosmwasm_std::Delegation;
-use cosmwasm_std::Empty;
-use cosmwasm_std::StakingQuery;
 use cosmwasm_std::{
-    from_json, to_json_binary, Coin, ContractResult, OwnedDeps, Querier, QuerierResult,
-    QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+    from_json, to_json_binary, Addr, AllBalanceResponse, AllDelegationsResponse,
+    AllValidatorsResponse, BalanceResponse, BankQuery, Binary, BondedDenomResponse, Coin,
+    ContractResult, CustomQuery, DelegationResponse, DenomMetadata, DenomMetadataResponse,
+    DenomUnit, Empty, FullDelegation, IbcChannel, IbcEndpoint, IbcOrder, IbcQuery,
+    ListChannelsResponse, OwnedDeps, PortIdResponse, Querier, QuerierResult, QueryRequest,
+    StakingQuery, StdError, SupplyResponse, SystemError, SystemResult, Uint128, Uint256,
+    ValidatorResponse, WasmQuery,
 };
-use cosmwasm_std::{AllDelegationsResponse, BondedDenomResponse};
+use cosmwasm_std::{ChannelResponse, ConversionOverflowError};
 use cw_orch_core::environment::BankQuerier;
 use cw_orch_core::environment::ChainInfoOwned;
 use cw_orch_core::environment::WasmQuerier;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use tonic::transport::Channel;
+use tonic::Code;
 
 use crate::channel::GrpcChannel;
 
-fn to_cosmwasm_coin(c: cosmrs::proto::cosmos::base::v1beta1::Coin) -> Coin {
-    Coin {
-        amount: Uint128::from_str(&c.amount).unwrap(),
-        denom: c.denom,
-    }
-}
+const QUERIER_ERROR: &str = "Only Bank, Wasm, Staking, Ibc and Custom queries are covered for now";
 
-const QUERIER_ERROR: &str =
-    "Only Bank balances and Wasm (raw + smart) and Some staking queries are covered for now";
+/// Precision of the decimal amounts of the Cosmos SDK
+const DEC_PRECISION: u128 = 1_000_000_000_000_000_000;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
 pub fn mock_dependencies(
     chain_info: ChainInfoOwned,
 ) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
-    let custom_querier: WasmMockQuerier = WasmMockQuerier::new(chain_info);
+    mock_dependencies_with_querier(WasmMockQuerier::new(chain_info))
+}
 
+/// Same as [`mock_dependencies`] with a configured querier, e.g. one sharing a [`QueryCache`] or handling custom queries.
+pub fn mock_dependencies_with_querier<C: CustomQuery + DeserializeOwned>(
+    querier: WasmMockQuerier<C>,
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier<C>, C> {
     OwnedDeps {
         storage: MockStorage::default(),
         api: MockApi::default(),
-        querier: custom_querier,
+        querier,
         custom_query_type: PhantomData,
     }
 }
 
+/// Responses of the successful queries, by chain and request, and by contract under test for the IBC queries without a port.
+/// Cloning the cache shares it, the queriers using the same cache only query the chain once per request.
+#[derive(Clone, Debug, Default)]
+pub struct QueryCache {
+    responses: Arc<Mutex<HashMap<CacheKey, Binary>>>,
+}
+
+/// The IBC queries without a port are answered for the contract under test, the requests alone don't identify their responses.
+/// The address of the contract is only set for those queries, the other responses are shared by the contracts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    chain_id: String,
+    contract_address: Option<String>,
+    request: Vec<u8>,
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all the cached responses, the next queries will hit the chain again
+    pub fn clear(&self) {
+        self.responses.lock().unwrap().clear()
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<Binary> {
+        self.responses.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: CacheKey, response: Binary) {
+        self.responses.lock().unwrap().insert(key, response);
+    }
+}
+
+type CustomHandler<C> = Box<dyn Fn(&C) -> QuerierResult>;
+
 /// Querier struct that fetches queries on-chain directly
-pub struct WasmMockQuerier {
+pub struct WasmMockQuerier<C = Empty> {
     channel: Channel,
+    chain_id: String,
+    cache: Option<QueryCache>,
+    contract_address: Option<String>,
+    custom_handler: Option<CustomHandler<C>>,
 }
 
-impl Querier for WasmMockQuerier {
+impl<C: CustomQuery + DeserializeOwned> Querier for WasmMockQuerier<C> {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
-        // MockQuerier doesn't support Custom, so we ignore it completely here
-        let request: QueryRequest<Empty> = match from_json(bin_request) {
+        let request: QueryRequest<C> = match from_json(bin_request) {
             Ok(v) => v,
             Err(e) => {
                 return SystemResult::Err(SystemError::InvalidRequest {
@@ -70,116 +125,280 @@ impl Querier for WasmMockQuerier {
                 })
             }
         };
-        self.handle_query(&request)
+        let key = CacheKey {
+            chain_id: self.chain_id.clone(),
+            contract_address: self
+                .contract_address
+                .clone()
+                .filter(|_| is_portless(&request)),
+            request: bin_request.to_vec(),
+        };
+        if let Some(response) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            return SystemResult::Ok(ContractResult::Ok(response));
+        }
+
+        let result = self.handle_query(&request);
+
+        // Only the successful responses are cached, the failures may come from the connection
+        if let (Some(cache), SystemResult::Ok(ContractResult::Ok(response))) =
+            (&self.cache, &result)
+        {
+            cache.insert(key, response.clone());
+        }
+        result
     }
 }
 
-impl WasmMockQuerier {
+impl<C: CustomQuery> WasmMockQuerier<C> {
     /// Function used to handle a query and customize the query behavior
     /// This implements some queries by querying an actual node for the responses
-    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
-        let handle = RUNTIME.handle();
+    pub fn handle_query(&self, request: &QueryRequest<C>) -> QuerierResult {
         match &request {
-            QueryRequest::Wasm(x) => {
-                let querier = CosmWasm::new_sync(self.channel.clone(), handle);
-                match x {
-                    WasmQuery::Smart { contract_addr, msg } => {
-                        // We forward the request to the cosmwasm querier
-
-                        let query_result: Result<Binary, _> = handle
-                            .block_on(
-                                querier._contract_state(contract_addr.to_string(), msg.to_vec()),
-                            )
-                            .map(|query_result| query_result.into());
-                        SystemResult::Ok(ContractResult::from(query_result))
-                    }
-                    WasmQuery::Raw { contract_addr, key } => {
-                        // We forward the request to the cosmwasm querie
-                        let query_result = querier
-                            .raw_query(contract_addr.to_string(), key.to_vec())
-                            .map(|query_result| query_result.into());
+            QueryRequest::Wasm(x) => self.handle_wasm_query(x),
+            QueryRequest::Bank(x) => self.handle_bank_query(x),
+            QueryRequest::Staking(x) => self.handle_staking_query(x),
+            QueryRequest::Ibc(x) => self.handle_ibc_query(x),
+            QueryRequest::Custom(x) => match &self.custom_handler {
+                Some(handler) => handler(x),
+                None => unsupported(request),
+            },
+            _ => unsupported(request),
+        }
+    }
 
-                        SystemResult::Ok(ContractResult::from(query_result))
-                    }
-                    _ => SystemResult::Err(SystemError::InvalidRequest {
-                        error: QUERIER_ERROR.to_string(),
-                        request: to_json_binary(&request).unwrap(),
-                    }),
-                }
+    fn handle_wasm_query(&self, request: &WasmQuery) -> QuerierResult {
+        let handle = RUNTIME.handle();
+        let querier = CosmWasm::new_sync(self.channel.clone(), handle);
+        match request {
+            WasmQuery::Smart { contract_addr, msg } => {
+                // We forward the request to the cosmwasm querier
+                respond(
+                    handle
+                        .block_on(querier._contract_state(contract_addr.to_string(), msg.to_vec()))
+                        .map(Into::into),
+                )
             }
-            QueryRequest::Bank(x) => {
-                let querier = Bank {
-                    channel: self.channel.clone(),
-                    rt_handle: Some(handle.clone()),
-                };
-                match x {
-                    BankQuery::Balance { address, denom } => {
-                        let query_result =
-                            querier.balance(address, Some(denom.clone())).map(|result| {
-                                to_json_binary(&BalanceResponse {
-                                    amount: result[0].clone(),
-                                })
-                                .unwrap()
-                            });
-                        SystemResult::Ok(ContractResult::from(query_result))
-                    }
-                    BankQuery::AllBalances { address } => {
-                        let query_result = querier
-                            .balance(address, None)
-                            .map(|result| AllBalanceResponse { amount: result })
-                            .map(|query_result| to_json_binary(&query_result))
-                            .unwrap();
-                        SystemResult::Ok(ContractResult::from(query_result))
+            WasmQuery::Raw { contract_addr, key } => {
+                // We forward the request to the cosmwasm querier
+                respond(
+                    querier
+                        .raw_query(contract_addr.to_string(), key.to_vec())
+                        .map(Into::into),
+                )
+            }
+            WasmQuery::ContractInfo { contract_addr } => {
+                respond_json(handle.block_on(querier._contract_info(contract_addr)))
+            }
+            WasmQuery::CodeInfo { code_id } => {
+                respond_json(handle.block_on(querier._code(*code_id)))
+            }
+            _ => unsupported(request),
+        }
+    }
+
+    fn handle_bank_query(&self, request: &BankQuery) -> QuerierResult {
+        let handle = RUNTIME.handle();
+        let querier = Bank {
+            channel: self.channel.clone(),
+            rt_handle: Some(handle.clone()),
+        };
+        match request {
+            BankQuery::Balance { address, denom } => {
+                respond_json(querier.balance(address, Some(denom.clone())).map(|result| {
+                    BalanceResponse {
+                        amount: result[0].clone(),
                     }
-                    _ => SystemResult::Err(SystemError::InvalidRequest {
-                        error: QUERIER_ERROR.to_string(),
-                        request: to_json_binary(&request).unwrap(),
+                }))
+            }
+            BankQuery::AllBalances { address } => respond_json(
+                querier
+                    .balance(address, None)
+                    .map(|result| AllBalanceResponse { amount: result }),
+            ),
+            BankQuery::Supply { denom } => {
+                respond_json(querier.supply_of(denom).map(SupplyResponse::new))
+            }
+            BankQuery::DenomMetadata { denom } => respond_json(
+                handle
+                    .block_on(querier._denom_metadata(denom))
+                    .map(|metadata| DenomMetadataResponse::new(to_cosmwasm_metadata(metadata))),
+            ),
+            _ => unsupported(request),
+        }
+    }
+
+    fn handle_staking_query(&self, request: &StakingQuery) -> QuerierResult {
+        let handle = RUNTIME.handle();
+        let querier = Staking::new_async(self.channel.clone());
+        match request {
+            StakingQuery::BondedDenom {} => respond_json(handle.block_on(querier._params()).map(
+                |result| BondedDenomResponse {
+                    denom: result.params.unwrap().bond_denom,
+                },
+            )),
+            // This query is not perfect. I guess that on_chain you should be able to get ALL delegations and not a paginated result
+            // TODO, do better here
+            StakingQuery::AllDelegations { delegator } => respond_json(
+                handle
+                    .block_on(querier._delegator_delegations(delegator, None))
+                    .and_then(|result| {
+                        Ok(AllDelegationsResponse {
+                            delegations: result
+                                .delegation_responses
+                                .into_iter()
+                                .map(cosmrs_to_cosmwasm_delegation)
+                                .collect::<Result<_, _>>()?,
+                        })
                     }),
-                }
+            ),
+            StakingQuery::Delegation {
+                delegator,
+                validator,
+            } => respond_json(handle.block_on(self.full_delegation(delegator, validator))),
+            StakingQuery::AllValidators {} => respond_json(
+                handle
+                    .block_on(querier._validators(StakingBondStatus::Bonded))
+                    .map(AllValidatorsResponse::new),
+            ),
+            StakingQuery::Validator { address } => respond_json(
+                not_found_as_none(handle.block_on(querier._validator(address)))
+                    .map(ValidatorResponse::new),
+            ),
+            _ => unsupported(request),
+        }
+    }
+
+    fn handle_ibc_query(&self, request: &IbcQuery) -> QuerierResult {
+        let handle = RUNTIME.handle();
+        let querier = Ibc::new_async(self.channel.clone());
+        match request {
+            IbcQuery::PortId {} => {
+                respond_json(handle.block_on(self.port_id(None)).map(PortIdResponse::new))
             }
-            QueryRequest::Staking(x) => {
-                let querier = Staking::new_async(self.channel.clone());
-                match x {
-                    StakingQuery::BondedDenom {} => {
-                        let query_result = handle
-                            .block_on(querier._params())
-                            .map(|result| BondedDenomResponse {
-                                denom: result.params.unwrap().bond_denom,
-                            })
-                            .map(|query_result| to_json_binary(&query_result))
-                            .unwrap();
-                        SystemResult::Ok(ContractResult::from(query_result))
-                    }
-                    // This query is not perfect. I guess that on_chain you should be able to get ALL delegations and not a paginated result
-                    // TODO, do better here
-                    StakingQuery::AllDelegations { delegator } => {
-                        let query_result = handle
-                            .block_on(querier._delegator_delegations(delegator, None))
-                            .map(|result| AllDelegationsResponse {
-                                delegations: result
-                                    .delegation_responses
-                                    .into_iter()
-                                    .filter_map(|delegation| {
-                                        delegation.delegation.map(|d| Delegation {
-                                            delegator: Addr::unchecked(d.delegator_address),
-                                            validator: d.validator_address,
-                                            amount: to_cosmwasm_coin(delegation.balance.unwrap()),
-                                        })
-                                    })
-                                    .collect(),
-                            })
-                            .map(|query_result| to_json_binary(&query_result))
-                            .unwrap();
-                        SystemResult::Ok(ContractResult::from(query_result))
-                    }
-                    _ => todo!(),
-                }
+            IbcQuery::ListChannels { port_id } => respond_json(handle.block_on(async {
+                let port_id = self.port_id(port_id.clone()).await?;
+                let channels = querier
+                    ._channels()
+                    .await?
+                    .into_iter()
+                    .filter(|channel| channel.port_id == port_id)
+                    .filter_map(|channel| {
+                        to_cosmwasm_channel(
+                            channel.port_id,
+                            channel.channel_id,
+                            ibc_channel::Channel {
+                                state: channel.state,
+                                ordering: channel.ordering,
+                                counterparty: channel.counterparty,
+                                connection_hops: channel.connection_hops,
+                                version: channel.version,
+                            },
+                        )
+                    })
+                    .collect();
+                Ok(ListChannelsResponse::new(channels))
+            })),
+            IbcQuery::Channel {
+                channel_id,
+                port_id,
+            } => respond_json(handle.block_on(async {
+                let port_id = self.port_id(port_id.clone()).await?;
+                let channel = not_found_as_none(querier._channel(&port_id, channel_id).await)?;
+                Ok(ChannelResponse::new(channel.and_then(|channel| {
+                    to_cosmwasm_channel(port_id, channel_id.clone(), channel)
+                })))
+            })),
+            _ => unsupported(request),
+        }
+    }
+
+    /// Delegation with its redelegation ability and its rewards, as computed by wasmd
+    async fn full_delegation(
+        &self,
+        delegator: &str,
+        validator: &str,
+    ) -> Result<DelegationResponse, DaemonError> {
+        let querier = Staking::new_async(self.channel.clone());
+        let Some(delegation) = not_found_as_none(querier._delegation(validator, delegator).await)?
+        else {
+            return Ok(DelegationResponse::new(None));
+        };
+
+        // The delegations receiving a redelegation can't be redelegated until it completes
+        let redelegations = querier._redelegations(delegator, "", "", None).await?;
+        let can_redelegate = if redelegations
+            .redelegation_responses
+            .iter()
+            .filter_map(|response| response.redelegation.as_ref())
+            .any(|redelegation| redelegation.validator_dst_address == validator)
+        {
+            Coin::new(0, delegation.amount.denom.clone())
+        } else {
+            delegation.amount.clone()
+        };
+
+        let rewards: crate::cosmos_modules::distribution::QueryDelegationRewardsResponse = crate::cosmos_query!(
+            self,
+            distribution,
+            delegation_rewards,
+            QueryDelegationRewardsRequest {
+                delegator_address: delegator.to_string(),
+                validator_address: validator.to_string(),
             }
-            _ => SystemResult::Err(SystemError::InvalidRequest {
-                error: QUERIER_ERROR.to_string(),
-                request: to_json_binary(&request).unwrap(),
-            }),
+        );
+        // The decimal amounts are truncated like the rewards withdrawn by the delegator
+        let accumulated_rewards = rewards
+            .rewards
+            .into_iter()
+            .map(|reward| {
+                let amount = Uint256::from_str(&reward.amount)? / Uint256::from(DEC_PRECISION);
+                Ok(Coin {
+                    amount: Uint128::try_from(amount)
+                        .map_err(|e: ConversionOverflowError| StdError::from(e))?,
+                    denom: reward.denom,
+                })
+            })
+            .filter(|coin| !matches!(coin, Ok(coin) if coin.amount.is_zero()))
+            .collect::<Result<_, StdError>>()?;
+
+        Ok(DelegationResponse::new(Some(FullDelegation {
+            delegator: Addr::unchecked(delegator),
+            validator: delegation.validator,
+            amount: delegation.amount,
+            can_redelegate,
+            accumulated_rewards,
+        })))
+    }
+
+    /// Given port or the IBC port of the contract under test
+    async fn port_id(&self, port_id: Option<String>) -> Result<String, DaemonError> {
+        if let Some(port_id) = port_id {
+            return Ok(port_id);
         }
+        let contract_address = self.contract_address.as_ref().ok_or_else(|| {
+            DaemonError::StdErr(
+                "The IBC port of the contract needs its address, set with `WasmMockQuerier::with_contract_address`".to_string(),
+            )
+        })?;
+        CosmWasm::new_async(self.channel.clone())
+            ._contract_info(contract_address)
+            .await?
+            .ibc_port
+            .ok_or_else(|| {
+                DaemonError::StdErr(format!("Contract {contract_address} has no IBC port"))
+            })
+    }
+
+    /// Shares `cache` with this querier, the cached responses are returned without querying the chain
+    pub fn with_cache(mut self, cache: QueryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Address of the contract under test, the IBC queries without a port use the IBC port of this contract
+    pub fn with_contract_address(mut self, address: impl Into<String>) -> Self {
+        self.contract_address = Some(address.into());
+        self
     }
 }
 
@@ -187,38 +406,163 @@ impl WasmMockQuerier {
     /// Creates a querier from chain information
     pub fn new(chain: ChainInfoOwned) -> Self {
         let channel = RUNTIME
-            .block_on(GrpcChannel::connect(
-                &chain.grpc_urls,
-                chain.chain_id.as_str(),
-            ))
+            .block_on(GrpcChannel::from_chain_info(&chain))
             .unwrap();
 
-        WasmMockQuerier { channel }
+        WasmMockQuerier {
+            channel,
+            chain_id: chain.chain_id,
+            cache: None,
+            contract_address: None,
+            custom_handler: None,
+        }
+    }
+
+    /// Answers the `Custom` queries of the chain with `handler`, e.g. with fixed responses or by querying the chain with a stargate query
+    pub fn with_custom_handler<C: CustomQuery>(
+        self,
+        handler: impl Fn(&C) -> QuerierResult + 'static,
+    ) -> WasmMockQuerier<C> {
+        WasmMockQuerier {
+            channel: self.channel,
+            chain_id: self.chain_id,
+            cache: self.cache,
+            contract_address: self.contract_address,
+            custom_handler: Some(Box::new(handler)),
+        }
+    }
+}
+
+fn respond(result: Result<Binary, DaemonError>) -> QuerierResult {
+    SystemResult::Ok(result.map_err(|e| e.to_string()).into())
+}
+
+fn respond_json<T: Serialize>(result: Result<T, DaemonError>) -> QuerierResult {
+    respond(result.and_then(|response| Ok(to_json_binary(&response)?)))
+}
+
+fn unsupported(request: &impl Serialize) -> QuerierResult {
+    SystemResult::Err(SystemError::InvalidRequest {
+        error: QUERIER_ERROR.to_string(),
+        request: to_json_binary(request).unwrap(),
+    })
+}
+
+/// Whether the request is an IBC query answered for the port of the contract under test
+fn is_portless<C>(request: &QueryRequest<C>) -> bool {
+    matches!(
+        request,
+        QueryRequest::Ibc(
+            IbcQuery::PortId {}
+                | IbcQuery::ListChannels { port_id: None }
+                | IbcQuery::Channel { port_id: None, .. }
+        )
+    )
+}
+
+/// Maps the not found errors of the node to `None`, for the queries with an optional response
+fn not_found_as_none<T>(result: Result<T, DaemonError>) -> Result<Option<T>, DaemonError> {
+    match result {
+        Ok(response) => Ok(Some(response)),
+        Err(DaemonError::Status(status)) if status.code() == Code::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn to_cosmwasm_metadata(metadata: crate::cosmos_modules::bank::Metadata) -> DenomMetadata {
+    DenomMetadata {
+        description: metadata.description,
+        denom_units: metadata
+            .denom_units
+            .into_iter()
+            .map(|unit| DenomUnit {
+                denom: unit.denom,
+                exponent: unit.exponent,
+                aliases: unit.aliases,
+            })
+            .collect(),
+        base: metadata.base,
+        display: metadata.display,
+        name: metadata.name,
+        symbol: metadata.symbol,
+        uri: metadata.uri,
+        uri_hash: metadata.uri_hash,
+    }
+}
+
+/// Channel as seen by the contracts, only the open channels are visible
+fn to_cosmwasm_channel(
+    port_id: String,
+    channel_id: String,
+    channel: ibc_channel::Channel,
+) -> Option<IbcChannel> {
+    if channel.state != ibc_channel::State::Open as i32 {
+        return None;
     }
+    let counterparty = channel.counterparty?;
+    let order = if channel.ordering == ibc_channel::Order::Ordered as i32 {
+        IbcOrder::Ordered
+    } else {
+        IbcOrder::Unordered
+    };
+    Some(IbcChannel::new(
+        IbcEndpoint {
+            port_id,
+            channel_id,
+        },
+        IbcEndpoint {
+            port_id: counterparty.port_id,
+            channel_id: counterparty.channel_id,
+        },
+        order,
+        channel.version,
+        channel
+            .connection_hops
+            .into_iter()
+            .next()
+            .unwrap_or_default(),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::fixture::Fixture;
     use crate::networks::JUNO_1;
+    use crate::test_utils::{MockRpc, CHAIN_ID};
 
-    use super::mock_dependencies;
+    /// Querier replaying the responses of `juno-1` in the hand-written fixture
+    fn replayed_querier() -> WasmMockQuerier {
+        let _runtime = RUNTIME.enter();
+        let fixture = Fixture::load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/grpc/juno-1.jsonl"
+        ))
+        .unwrap();
+        WasmMockQuerier {
+            channel: fixture.replaying_channel(JUNO_1.chain_id),
+            chain_id: JUNO_1.chain_id.to_string(),
+            cache: None,
+            contract_address: None,
+            custom_handler: None,
+        }
+    }
 
     #[test]
     fn bank_balance_querier() -> Result<(), anyhow::Error> {
         let address = "juno1rkhrfuq7k2k68k0hctrmv8efyxul6tgn8hny6y";
 
-        let deps = mock_dependencies(JUNO_1.into());
+        let deps = mock_dependencies_with_querier(replayed_querier());
         let deps_ref = deps.as_ref();
-        let _response: BalanceResponse =
+        let response: BalanceResponse =
             deps_ref
                 .querier
                 .query(&QueryRequest::Bank(BankQuery::Balance {
                     address: address.to_string(),
                     denom: "ujuno".to_string(),
                 }))?;
-        // We can't really test that response, but it has to unwrap at least !
+        assert_eq!(response.amount, Coin::new(1520365, "ujuno"));
 
         Ok(())
     }
@@ -227,15 +571,125 @@ mod tests {
     fn bank_all_balances_querier() -> Result<(), anyhow::Error> {
         let address = "juno1rkhrfuq7k2k68k0hctrmv8efyxul6tgn8hny6y";
 
-        let deps = mock_dependencies(JUNO_1.into());
+        let deps = mock_dependencies_with_querier(replayed_querier());
         let deps_ref = deps.as_ref();
-        let _response: AllBalanceResponse =
+        let response: AllBalanceResponse =
             deps_ref
                 .querier
                 .query(&QueryRequest::Bank(BankQuery::AllBalances {
                     address: address.to_string(),
                 }))?;
-        // We can't really test that response, but it has to unwrap at least !
+        assert_eq!(response.amount, vec![Coin::new(1520365, "ujuno")]);
+        Ok(())
+    }
+
+    /// Querier of a mock RPC node
+    fn mock_querier(node: &MockRpc) -> WasmMockQuerier {
+        let url = {
+            let _runtime = RUNTIME.enter();
+            node.serve()
+        };
+        let mut chain: ChainInfoOwned = JUNO_1.into();
+        chain.chain_id = CHAIN_ID.to_string();
+        chain.grpc_urls = vec![];
+        chain.rpc_urls = vec![url];
+        WasmMockQuerier::new(chain)
+    }
+
+    #[test]
+    fn cached_queries_hit_the_chain_once() -> anyhow::Result<()> {
+        let node = MockRpc::default();
+        let cache = QueryCache::new();
+        let deps = mock_dependencies_with_querier(mock_querier(&node).with_cache(cache.clone()));
+
+        let balance = QueryRequest::Bank(BankQuery::Balance {
+            address: "juno1address".to_string(),
+            denom: "ujuno".to_string(),
+        });
+        for _ in 0..2 {
+            let response: BalanceResponse = deps.as_ref().querier.query(&balance)?;
+            assert_eq!(response.amount, Coin::new(100, "ujuno"));
+        }
+        assert_eq!(node.queried_heights.lock().unwrap().len(), 1);
+        assert_eq!(cache.len(), 1);
+
+        // The failed queries aren't cached
+        let supply = QueryRequest::Bank(BankQuery::Supply {
+            denom: "ujuno".to_string(),
+        });
+        deps.as_ref()
+            .querier
+            .query::<SupplyResponse>(&supply)
+            .unwrap_err();
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        let _: BalanceResponse = deps.as_ref().querier.query(&balance)?;
+        assert_eq!(node.queried_heights.lock().unwrap().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn cached_queries_are_shared_by_contracts_under_test() -> anyhow::Result<()> {
+        let node = MockRpc::default();
+        let cache = QueryCache::new();
+        let balance = QueryRequest::<Empty>::Bank(BankQuery::Balance {
+            address: "juno1address".to_string(),
+            denom: "ujuno".to_string(),
+        });
+
+        // Only the port-less IBC queries are answered for the contract under test, the other responses are shared
+        for contract_address in ["juno1contract", "juno1other", "juno1contract"] {
+            let deps = mock_dependencies_with_querier(
+                mock_querier(&node)
+                    .with_cache(cache.clone())
+                    .with_contract_address(contract_address),
+            );
+            let _: BalanceResponse = deps.as_ref().querier.query(&balance)?;
+        }
+        assert_eq!(cache.len(), 1);
+        assert_eq!(node.queried_heights.lock().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[derive(
+        Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+    )]
+    #[serde(rename_all = "snake_case")]
+    enum ChainQuery {
+        FullDenom { subdenom: String },
+    }
+
+    impl CustomQuery for ChainQuery {}
+
+    #[test]
+    fn custom_queries_are_handled() -> anyhow::Result<()> {
+        let querier = mock_querier(&MockRpc::default()).with_custom_handler(
+            |ChainQuery::FullDenom { subdenom }| {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&format!("factory/juno1creator/{subdenom}")).unwrap(),
+                ))
+            },
+        );
+        let deps = mock_dependencies_with_querier(querier);
+
+        let denom: String =
+            deps.as_ref()
+                .querier
+                .query(&QueryRequest::Custom(ChainQuery::FullDenom {
+                    subdenom: "token".to_string(),
+                }))?;
+        assert_eq!(denom, "factory/juno1creator/token");
+
+        // The other queries are still answered by the chain
+        let response: BalanceResponse =
+            deps.as_ref()
+                .querier
+                .query(&QueryRequest::Bank(BankQuery::Balance {
+                    address: "juno1address".to_string(),
+                    denom: "ujuno".to_string(),
+                }))?;
+        assert_eq!(response.amount, Coin::new(100, "ujuno"));
         Ok(())
     }
 }
@@ -0,0 +1,34 @@
+#![allow(missing_docs)]
+//! Queries of the fee market modules, pricing the gas of the transactions from an on-chain base fee.
+//! Decimal amounts are `LegacyDec` values, encoded as integers with 18 decimals.
+
+use cosmrs::proto::cosmos::base::v1beta1::DecCoin;
+
+/// `osmosis.txfees.v1beta1.QueryEipBaseFeeRequest`
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryEipBaseFeeRequest {}
+
+/// `osmosis.txfees.v1beta1.QueryEipBaseFeeResponse`
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryEipBaseFeeResponse {
+    #[prost(string, tag = "1")]
+    pub base_fee: String,
+}
+
+pub const OSMOSIS_EIP_BASE_FEE_PATH: &str = "/osmosis.txfees.v1beta1.Query/GetEipBaseFee";
+
+/// `feemarket.feemarket.v1.GasPriceRequest`
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GasPriceRequest {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+}
+
+/// `feemarket.feemarket.v1.GasPriceResponse`
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GasPriceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub price: Option<DecCoin>,
+}
+
+pub const FEEMARKET_GAS_PRICE_PATH: &str = "/feemarket.feemarket.v1.Query/GasPrice";
@@ -1 +1,2 @@
+pub mod fee_market;
 pub mod injective;
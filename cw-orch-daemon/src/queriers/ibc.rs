@@ -285,6 +285,20 @@ impl Ibc {
         )))
     }
 
+    /// Get all the channels of the chain
+    pub async fn _channels(&self) -> Result<Vec<ibc_channel::IdentifiedChannel>, DaemonError> {
+        use cosmos_modules::ibc_channel::QueryChannelsResponse;
+
+        let ibc_channels: QueryChannelsResponse = cosmos_query!(
+            self,
+            ibc_channel,
+            channels,
+            QueryChannelsRequest { pagination: None }
+        );
+
+        Ok(ibc_channels.channels)
+    }
+
     /// Get all the channels for a specific connection
     pub async fn _connection_channels(
         &self,
@@ -2,7 +2,7 @@ use std::{cmp::min, time::Duration};
 
 use crate::{
     cosmos_modules, env::DaemonEnvVars, error::DaemonError, senders::query::QuerySender,
-    tx_resp::CosmTxResponse, DaemonBase,
+    subscription, tx_resp::CosmTxResponse, DaemonBase,
 };
 
 use cosmrs::{
@@ -26,6 +26,8 @@ use tonic::transport::Channel;
 pub struct Node {
     pub channel: Channel,
     pub rt_handle: Option<Handle>,
+    /// Tendermint RPC endpoint whose websocket notifies the inclusion of the transactions, they're polled without it
+    pub rpc_url: Option<String>,
 }
 
 impl Node {
@@ -33,14 +35,22 @@ impl Node {
         Self {
             channel: daemon.channel(),
             rt_handle: Some(daemon.rt_handle.clone()),
+            rpc_url: daemon.chain_info().rpc_urls.first().cloned(),
         }
     }
     pub fn new_async(channel: Channel) -> Self {
         Self {
             channel,
             rt_handle: None,
+            rpc_url: None,
         }
     }
+
+    /// Waits for the transactions over the websocket of `rpc_url` instead of polling them
+    pub fn with_rpc_url(mut self, rpc_url: Option<String>) -> Self {
+        self.rpc_url = rpc_url;
+        self
+    }
 }
 
 impl<Sender: QuerySender> QuerierGetter<Node> for DaemonBase<Sender> {
@@ -205,6 +215,13 @@ impl Node {
 
     /// Simulate TX
     pub async fn _simulate_tx(&self, tx_bytes: Vec<u8>) -> Result<u64, DaemonError> {
+        let resp = self._simulate(tx_bytes).await?;
+        let gas_used = resp.gas_info.unwrap().gas_used;
+        Ok(gas_used)
+    }
+
+    /// Simulate TX, returns the gas it used and the events it emitted
+    pub async fn _simulate(&self, tx_bytes: Vec<u8>) -> Result<SimulateResponse, DaemonError> {
         let mut client =
             cosmos_modules::tx::service_client::ServiceClient::new(self.channel.clone());
         #[allow(deprecated)]
@@ -212,8 +229,7 @@ impl Node {
             .simulate(cosmos_modules::tx::SimulateRequest { tx: None, tx_bytes })
             .await?
             .into_inner();
-        let gas_used = resp.gas_info.unwrap().gas_used;
-        Ok(gas_used)
+        Ok(resp)
     }
 
     /// Returns all the block info
@@ -225,8 +241,29 @@ impl Node {
 
     /// Find TX by hash
     pub async fn _find_tx(&self, hash: String) -> Result<CosmTxResponse, DaemonError> {
-        self._find_tx_with_retries(hash, DaemonEnvVars::max_tx_query_retries())
-            .await
+        let retries = DaemonEnvVars::max_tx_query_retries();
+        // The inclusion is received without polling when the node has an RPC endpoint.
+        // The tx is polled when the subscription fails or the tx doesn't arrive in time, e.g. when the websocket stalls
+        if let Some(rpc_url) = &self.rpc_url {
+            let timeout = self
+                ._average_block_speed(None)
+                .await?
+                .mul_f64(retries as f64);
+            let included = async { self.get_tx(&hash).await.is_ok() };
+            subscription::wait_for_tx(rpc_url, &hash, timeout, included).await;
+        }
+        self._find_tx_with_retries(hash, retries).await
+    }
+
+    async fn get_tx(&self, hash: &str) -> Result<CosmTxResponse, tonic::Status> {
+        let mut client =
+            cosmos_modules::tx::service_client::ServiceClient::new(self.channel.clone());
+        let tx = client
+            .get_tx(cosmos_modules::tx::GetTxRequest {
+                hash: hash.to_string(),
+            })
+            .await?;
+        Ok(tx.into_inner().tx_response.unwrap().into())
     }
 
     /// Find TX by hash with a given amount of retries
@@ -235,10 +272,6 @@ impl Node {
         hash: String,
         retries: usize,
     ) -> Result<CosmTxResponse, DaemonError> {
-        let mut client =
-            cosmos_modules::tx::service_client::ServiceClient::new(self.channel.clone());
-
-        let request = cosmos_modules::tx::GetTxRequest { hash: hash.clone() };
         let mut block_speed = self._average_block_speed(Some(0.7)).await?;
         let max_block_time = DaemonEnvVars::max_block_time();
         if let Some(max_time) = max_block_time {
@@ -249,9 +282,8 @@ impl Node {
         }
 
         for _ in 0..retries {
-            match client.get_tx(request.clone()).await {
-                Ok(tx) => {
-                    let resp = tx.into_inner().tx_response.unwrap().into();
+            match self.get_tx(&hash).await {
+                Ok(resp) => {
                     log::debug!(target: &query_target(), "TX found: {:?}", resp);
                     return Ok(resp);
                 }
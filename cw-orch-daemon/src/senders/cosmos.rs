@@ -1,13 +1,4 @@
-use crate::{
-    env::DaemonEnvVars,
-    proto::injective::ETHEREUM_COIN_TYPE,
-    queriers::Bank,
-    tx_broadcaster::{
-        account_sequence_strategy, assert_broadcast_code_cosm_response, insufficient_fee_strategy,
-        TxBroadcaster,
-    },
-    CosmosOptions, GrpcChannel,
-};
+use crate::{env::DaemonEnvVars, queriers::Bank, CosmosOptions, GrpcChannel};
 
 use crate::proto::injective::InjectiveEthAccount;
 use crate::{
@@ -18,117 +9,74 @@ use crate::{
     tx_resp::CosmTxResponse,
 };
 
-#[cfg(feature = "eth")]
-use crate::proto::injective::InjectiveSigner;
-
-use crate::{core::parse_cw_coins, keys::private::PrivateKey};
+use crate::core::parse_cw_coins;
 use cosmrs::{
     bank::MsgSend,
-    crypto::secp256k1::SigningKey,
-    proto::{cosmos::authz::v1beta1::MsgExec, traits::Message},
+    proto::{
+        cosmos::{authz::v1beta1::MsgExec, tx::v1beta1::TxRaw},
+        traits::Message,
+    },
     tendermint::chain::Id,
     tx::{self, ModeInfo, Msg, Raw, SignDoc, SignMode, SignerInfo},
     AccountId, Any,
 };
 use cosmwasm_std::{coin, Coin};
-use cw_orch_core::{
-    environment::{ChainInfoOwned, ChainKind},
-    CoreEnvVars, CwEnvError,
-};
+use cw_orch_core::{environment::ChainInfoOwned, CoreEnvVars};
 
-use crate::env::{LOCAL_MNEMONIC_ENV_NAME, MAIN_MNEMONIC_ENV_NAME, TEST_MNEMONIC_ENV_NAME};
-use bitcoin::secp256k1::{All, Secp256k1, Signing};
 use std::{str::FromStr, sync::Arc};
 
 use cosmos_modules::vesting::PeriodicVestingAccount;
 use tonic::transport::Channel;
 
-use super::{cosmos_options::CosmosWalletKey, query::QuerySender, tx::TxSender};
+use super::{
+    query::QuerySender,
+    signer::{PrivateKeySigner, Signer, SignerBuilder},
+    tx::{BroadcastSender, TxSender},
+};
 
 const GAS_BUFFER: f64 = 1.3;
 const BUFFER_THRESHOLD: u64 = 200_000;
 const SMALL_GAS_BUFFER: f64 = 1.4;
 
 /// A wallet is a sender of transactions, can be safely cloned and shared within the same thread.
-pub type Wallet = CosmosSender<All>;
+pub type Wallet = CosmosSender<PrivateKeySigner>;
 
 /// Signer of the transactions and helper for address derivation
 /// This is the main interface for simulating and signing transactions
 #[derive(Clone)]
-pub struct CosmosSender<C: Signing + Clone> {
-    pub private_key: PrivateKey,
+pub struct CosmosSender<S: Signer = PrivateKeySigner> {
+    /// Signer of the transactions
+    pub signer: S,
     /// gRPC channel
     pub grpc_channel: Channel,
     /// Information about the chain
     pub chain_info: Arc<ChainInfoOwned>,
-    pub(crate) options: CosmosOptions,
-    pub secp: Secp256k1<C>,
+    pub(crate) options: CosmosOptions<S>,
 }
 
-impl Wallet {
+impl<S: Signer> CosmosSender<S> {
     pub async fn new(
         chain_info: &Arc<ChainInfoOwned>,
-        options: CosmosOptions,
-    ) -> Result<Wallet, DaemonError> {
-        let secp = Secp256k1::new();
-
-        let pk_from_mnemonic = |mnemonic: &str| -> Result<PrivateKey, DaemonError> {
-            PrivateKey::from_words(
-                &secp,
-                mnemonic,
-                0,
-                options.hd_index.unwrap_or(0),
-                chain_info.network_info.coin_type,
-            )
-        };
-
-        let pk: PrivateKey = match &options.key {
-            CosmosWalletKey::Mnemonic(mnemonic) => pk_from_mnemonic(mnemonic)?,
-            CosmosWalletKey::Env => {
-                let mnemonic = get_mnemonic_env(&chain_info.kind)?;
-                pk_from_mnemonic(&mnemonic)?
-            }
-            CosmosWalletKey::RawKey(bytes) => PrivateKey::from_raw_key(
-                &secp,
-                bytes,
-                0,
-                options.hd_index.unwrap_or(0),
-                chain_info.network_info.coin_type,
-            )?,
-        };
+        options: CosmosOptions<S>,
+    ) -> Result<CosmosSender<S>, DaemonError> {
+        let signer = options.key.build(chain_info, options.hd_index).await?;
 
         // ensure address is valid
-        AccountId::new(
-            &chain_info.network_info.pub_address_prefix,
-            &pk.public_key(&secp).raw_address.unwrap(),
-        )?;
+        signer.account_id(&chain_info.network_info.pub_address_prefix)?;
 
         Ok(Self {
             chain_info: chain_info.clone(),
             grpc_channel: GrpcChannel::from_chain_info(chain_info.as_ref()).await?,
-            private_key: pk,
-            secp,
+            signer,
             options,
         })
     }
 
-    /// Construct a new Sender from a mnemonic
-    pub async fn from_mnemonic(
-        chain_info: &Arc<ChainInfoOwned>,
-        mnemonic: &str,
-    ) -> Result<Wallet, DaemonError> {
-        let options = CosmosOptions {
-            key: CosmosWalletKey::Mnemonic(mnemonic.to_string()),
-            ..Default::default()
-        };
-        Self::new(chain_info, options).await
-    }
-
     pub fn channel(&self) -> Channel {
         self.grpc_channel.clone()
     }
 
-    pub fn options(&self) -> CosmosOptions {
+    pub fn options(&self) -> CosmosOptions<S> {
         self.options.clone()
     }
 
@@ -195,7 +143,7 @@ impl Wallet {
         )?;
 
         let auth_info = SignerInfo {
-            public_key: self.private_key.get_signer_public_key(&self.secp),
+            public_key: self.signer.public_key(),
             mode_info: ModeInfo::single(SignMode::Direct),
             sequence,
         }
@@ -208,7 +156,7 @@ impl Wallet {
             account_number,
         )?;
 
-        let tx_raw = self.sign(sign_doc)?;
+        let tx_raw = self.sign(sign_doc).await?;
 
         Node::new_async(self.channel())
             ._simulate_tx(tx_raw.to_bytes()?)
@@ -230,7 +178,7 @@ impl Wallet {
 
         let gas_needed = tx_builder.simulate(self).await?;
 
-        let (gas_for_submission, fee_amount) = self.get_fee_from_gas(gas_needed)?;
+        let (gas_for_submission, fee_amount) = self.get_fee_from_gas(gas_needed).await?;
         let expected_fee = coin(fee_amount, self.get_fee_token());
         // During simulation, we also make sure the account has enough balance to submit the transaction
         // This is disabled by an env variable
@@ -255,52 +203,25 @@ impl Wallet {
         self.commit_tx_any(msgs, memo).await
     }
 
-    pub fn sign(&self, sign_doc: SignDoc) -> Result<Raw, DaemonError> {
-        let tx_raw = if self.private_key.coin_type == ETHEREUM_COIN_TYPE {
-            #[cfg(not(feature = "eth"))]
-            panic!(
-                "Coin Type {} not supported without eth feature",
-                ETHEREUM_COIN_TYPE
-            );
-            #[cfg(feature = "eth")]
-            self.private_key.sign_injective(sign_doc)?
-        } else {
-            sign_doc.sign(&self.cosmos_private_key())?
-        };
-        Ok(tx_raw)
+    pub async fn sign(&self, sign_doc: SignDoc) -> Result<Raw, DaemonError> {
+        let body_bytes = sign_doc.body_bytes.clone();
+        let auth_info_bytes = sign_doc.auth_info_bytes.clone();
+        let signature = self.signer.sign(sign_doc).await?;
+        Ok(TxRaw {
+            body_bytes,
+            auth_info_bytes,
+            signatures: vec![signature],
+        }
+        .into())
     }
 
     pub async fn base_account(&self) -> Result<BaseAccount, DaemonError> {
-        let addr = self.address().to_string();
-
-        let mut client = cosmos_modules::auth::query_client::QueryClient::new(self.channel());
-
-        let resp = client
-            .account(cosmos_modules::auth::QueryAccountRequest { address: addr })
-            .await?
-            .into_inner();
-
-        let account = resp.account.unwrap().value;
-
-        let acc = if let Ok(acc) = BaseAccount::decode(account.as_ref()) {
-            acc
-        } else if let Ok(acc) = PeriodicVestingAccount::decode(account.as_ref()) {
-            // try vesting account, (used by Terra2)
-            acc.base_vesting_account.unwrap().base_account.unwrap()
-        } else if let Ok(acc) = InjectiveEthAccount::decode(account.as_ref()) {
-            acc.base_account.unwrap()
-        } else {
-            return Err(DaemonError::StdErr(
-                "Unknown account type returned from QueryAccountRequest".into(),
-            ));
-        };
-
-        Ok(acc)
+        query_base_account(self.channel(), self.address().to_string()).await
     }
 
     /// Allows for checking wether the sender is able to broadcast a transaction that necessitates the provided `gas`
     pub async fn has_enough_balance_for_gas(&self, gas: u64) -> Result<(), DaemonError> {
-        let (_gas_expected, fee_amount) = self.get_fee_from_gas(gas)?;
+        let (_gas_expected, fee_amount) = self.get_fee_from_gas(gas).await?;
         let fee_denom = self.get_fee_token();
 
         self.assert_wallet_balance(&coin(fee_amount, fee_denom))
@@ -365,44 +286,71 @@ impl Wallet {
         }
     }
 
+    /// Wraps the messages in a `MsgExec` when an authz granter is set
+    pub(crate) fn authz_msgs(&self, msgs: Vec<Any>) -> Vec<Any> {
+        if self.options.authz_granter.is_some() {
+            // We wrap authz messages
+            vec![Any {
+                type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(),
+                value: MsgExec {
+                    grantee: self.pub_addr_str(),
+                    msgs,
+                }
+                .encode_to_vec(),
+            }]
+        } else {
+            msgs
+        }
+    }
+
     pub(crate) fn get_fee_token(&self) -> String {
         self.chain_info.gas_denom.to_string()
     }
 
-    fn cosmos_private_key(&self) -> SigningKey {
-        SigningKey::from_slice(&self.private_key.raw_key()).unwrap()
+    /// Gas price of the next transaction, queried from the fee market of the chain if set in the options
+    pub async fn gas_price(&self) -> f64 {
+        match &self.options.fee_market {
+            Some(fee_market) => {
+                fee_market
+                    .gas_price(
+                        self.channel(),
+                        &self.chain_info.gas_denom,
+                        self.chain_info.gas_price,
+                    )
+                    .await
+            }
+            None => self.chain_info.gas_price,
+        }
     }
 
     /// Compute the gas fee from the expected gas in the transaction
     /// Applies a Gas Buffer for including signature verification
-    pub(crate) fn get_fee_from_gas(&self, gas: u64) -> Result<(u64, u128), DaemonError> {
-        let mut gas_expected = if let Some(gas_buffer) = DaemonEnvVars::gas_buffer() {
-            gas as f64 * gas_buffer
-        } else if gas < BUFFER_THRESHOLD {
-            gas as f64 * SMALL_GAS_BUFFER
-        } else {
-            gas as f64 * GAS_BUFFER
-        };
-
-        let min_gas = DaemonEnvVars::min_gas();
-        gas_expected = (min_gas as f64).max(gas_expected);
-
-        let fee_amount = gas_expected * (self.chain_info.gas_price + 0.00001);
+    pub(crate) async fn get_fee_from_gas(&self, gas: u64) -> Result<(u64, u128), DaemonError> {
+        Ok(fee_from_gas(gas, self.gas_price().await))
+    }
+}
 
-        Ok((gas_expected as u64, fee_amount as u128))
+impl Wallet {
+    /// Construct a new Sender from a mnemonic
+    pub async fn from_mnemonic(
+        chain_info: &Arc<ChainInfoOwned>,
+        mnemonic: &str,
+    ) -> Result<Wallet, DaemonError> {
+        let options = CosmosOptions::default().mnemonic(mnemonic);
+        Self::new(chain_info, options).await
     }
 }
 
-impl QuerySender for Wallet {
+impl<S: Signer> QuerySender for CosmosSender<S> {
     type Error = DaemonError;
-    type Options = CosmosOptions;
+    type Options = CosmosOptions<S>;
 
     fn channel(&self) -> Channel {
         self.channel()
     }
 }
 
-impl TxSender for Wallet {
+impl<S: Signer> TxSender for CosmosSender<S> {
     async fn commit_tx_any(
         &self,
         msgs: Vec<Any>,
@@ -410,66 +358,96 @@ impl TxSender for Wallet {
     ) -> Result<CosmTxResponse, DaemonError> {
         let timeout_height = Node::new_async(self.channel())._block_height().await? + 10u64;
 
-        let msgs = if self.options.authz_granter.is_some() {
-            // We wrap authz messages
-            vec![Any {
-                type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(),
-                value: MsgExec {
-                    grantee: self.pub_addr_str(),
-                    msgs,
-                }
-                .encode_to_vec(),
-            }]
-        } else {
-            msgs
-        };
-
-        let tx_body = TxBuilder::build_body(msgs, memo, timeout_height);
+        let tx_body = TxBuilder::build_body(self.authz_msgs(msgs), memo, timeout_height);
 
         let tx_builder = TxBuilder::new(tx_body);
 
-        // We retry broadcasting the tx, with the following strategies
-        // 1. In case there is an `incorrect account sequence` error, we can retry as much as possible (doesn't cost anything to the user)
-        // 2. In case there is an insufficient_fee error, we retry once (costs fee to the user everytime we submit this kind of tx)
-        // 3. In case there is an other error, we fail
-        let tx_response = TxBroadcaster::default()
-            .add_strategy(insufficient_fee_strategy())
-            .add_strategy(account_sequence_strategy())
+        // We retry broadcasting the tx with the strategies of the options, if there is an other error, we fail
+        let tx_response = self
+            .options
+            .tx_broadcaster(true)
             .broadcast(tx_builder, self)
             .await?;
 
-        let resp = Node::new_async(self.channel())
-            ._find_tx(tx_response.txhash)
-            .await?;
-
-        assert_broadcast_code_cosm_response(resp)
+        self.options
+            .confirmation
+            .confirm(
+                self.channel(),
+                self.chain_info.rpc_urls.first().cloned(),
+                tx_response,
+            )
+            .await
     }
 
     fn account_id(&self) -> AccountId {
-        AccountId::new(
-            &self.chain_info.network_info.pub_address_prefix,
-            &self.private_key.public_key(&self.secp).raw_address.unwrap(),
-        )
-        // unwrap as address is validated on construction
-        .unwrap()
+        self.signer
+            .account_id(&self.chain_info.network_info.pub_address_prefix)
+            // unwrap as address is validated on construction
+            .unwrap()
     }
 }
 
-fn get_mnemonic_env(chain_kind: &ChainKind) -> Result<String, CwEnvError> {
-    match chain_kind {
-        ChainKind::Local => DaemonEnvVars::local_mnemonic(),
-        ChainKind::Testnet => DaemonEnvVars::test_mnemonic(),
-        ChainKind::Mainnet => DaemonEnvVars::main_mnemonic(),
+impl<S: Signer> BroadcastSender for CosmosSender<S> {
+    async fn broadcast_tx_builder(
+        &self,
+        tx_builder: &mut TxBuilder,
+    ) -> Result<cosmrs::proto::cosmos::base::abci::v1beta1::TxResponse, DaemonError> {
+        let tx = tx_builder.build(self).await?;
+        self.broadcast_tx(tx).await
     }
-    .ok_or(CwEnvError::EnvVarNotPresentNamed(
-        get_mnemonic_env_name(chain_kind).to_string(),
-    ))
 }
 
-fn get_mnemonic_env_name(chain_kind: &ChainKind) -> &str {
-    match chain_kind {
-        ChainKind::Local => LOCAL_MNEMONIC_ENV_NAME,
-        ChainKind::Testnet => TEST_MNEMONIC_ENV_NAME,
-        ChainKind::Mainnet => MAIN_MNEMONIC_ENV_NAME,
-    }
+/// Queries the base account of an address, used to sign its transactions
+pub(crate) async fn query_base_account(
+    channel: Channel,
+    addr: String,
+) -> Result<BaseAccount, DaemonError> {
+    let mut client = cosmos_modules::auth::query_client::QueryClient::new(channel);
+
+    let resp = client
+        .account(cosmos_modules::auth::QueryAccountRequest { address: addr })
+        .await?
+        .into_inner();
+
+    let account = resp.account.unwrap().value;
+
+    let acc = if let Ok(acc) = BaseAccount::decode(account.as_ref()) {
+        acc
+    } else if let Ok(acc) = PeriodicVestingAccount::decode(account.as_ref()) {
+        // try vesting account, (used by Terra2)
+        acc.base_vesting_account.unwrap().base_account.unwrap()
+    } else if let Ok(acc) = InjectiveEthAccount::decode(account.as_ref()) {
+        acc.base_account.unwrap()
+    } else {
+        return Err(DaemonError::StdErr(
+            "Unknown account type returned from QueryAccountRequest".into(),
+        ));
+    };
+
+    Ok(acc)
+}
+
+/// Compute the gas fee from the expected gas in the transaction and the gas price
+/// Applies a Gas Buffer for including signature verification
+pub(crate) fn fee_from_gas(gas: u64, gas_price: f64) -> (u64, u128) {
+    let mut gas_expected = if let Some(gas_buffer) = DaemonEnvVars::gas_buffer() {
+        gas as f64 * gas_buffer
+    } else if gas < BUFFER_THRESHOLD {
+        gas as f64 * SMALL_GAS_BUFFER
+    } else {
+        gas as f64 * GAS_BUFFER
+    };
+
+    let min_gas = DaemonEnvVars::min_gas();
+    gas_expected = (min_gas as f64).max(gas_expected);
+
+    (
+        gas_expected as u64,
+        fee_for_gas_limit(gas_expected as u64, gas_price),
+    )
+}
+
+/// Compute the fee of a transaction with a fixed gas limit
+pub(crate) fn fee_for_gas_limit(gas_limit: u64, gas_price: f64) -> u128 {
+    (gas_limit as f64 * (gas_price + 0.00001)) as u128
 }
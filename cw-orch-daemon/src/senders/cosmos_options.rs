@@ -1,32 +1,58 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::PathBuf, str::FromStr, sync::Arc};
 
 use cosmrs::AccountId;
 use cw_orch_core::environment::ChainInfoOwned;
 
-use crate::{DaemonError, Wallet};
-
-use super::{builder::SenderBuilder, CosmosSender};
+use crate::{
+    confirmation::Confirmation,
+    fee_market::FeeMarket,
+    tx_broadcaster::{
+        account_sequence_strategy, insufficient_fee_strategy, RetryStrategy, TxBroadcaster,
+    },
+    DaemonError,
+};
+
+use super::{
+    builder::SenderBuilder,
+    signer::{PrivateKeyOptions, PrivateKeySigner, Signer, SignerBuilder},
+    CosmosSender,
+};
 
 /// Options for how txs should be constructed for this sender.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 #[non_exhaustive]
-pub struct CosmosOptions {
+pub struct CosmosOptions<S: Signer = PrivateKeySigner> {
     pub authz_granter: Option<String>,
     pub fee_granter: Option<String>,
     pub hd_index: Option<u32>,
-    /// Used to derive the private key
-    pub(crate) key: CosmosWalletKey,
+    /// Fee market the gas price of the transactions is queried from, instead of using the gas price of the chain info
+    pub fee_market: Option<FeeMarket>,
+    /// Confirmation awaited after broadcasting a transaction, defaults to its inclusion in a block
+    pub confirmation: Confirmation,
+    /// Used to build the signer
+    pub(crate) key: S::Options,
+    /// Strategies tried, in order, after the default ones when broadcasting a transaction fails
+    pub(crate) retry_strategies: Vec<RetryStrategy>,
+    /// Whether the insufficient fee and account sequence strategies are used
+    pub(crate) default_retry_strategies: bool,
 }
 
-#[derive(Default, Clone)]
-pub enum CosmosWalletKey {
-    Mnemonic(String),
-    RawKey(Vec<u8>),
-    #[default]
-    Env,
+impl Default for CosmosOptions {
+    fn default() -> Self {
+        Self {
+            authz_granter: None,
+            fee_granter: None,
+            hd_index: None,
+            fee_market: None,
+            confirmation: Confirmation::default(),
+            key: PrivateKeyOptions::default(),
+            retry_strategies: vec![],
+            default_retry_strategies: true,
+        }
+    }
 }
 
-impl CosmosOptions {
+impl<S: Signer> CosmosOptions<S> {
     pub fn check(&self) -> Result<(), DaemonError> {
         if let Some(addr) = &self.authz_granter {
             AccountId::from_str(addr)?;
@@ -54,8 +80,41 @@ impl CosmosOptions {
         self
     }
 
-    pub fn mnemonic(mut self, mnemonic: impl ToString) -> Self {
-        self.key = CosmosWalletKey::Mnemonic(mnemonic.to_string());
+    /// Price the fees of the transactions from the base fee of the fee market of the chain
+    pub fn fee_market(mut self, fee_market: FeeMarket) -> Self {
+        self.fee_market = Some(fee_market);
+        self
+    }
+
+    /// Confirmation awaited after broadcasting a transaction
+    pub fn confirmation(mut self, confirmation: Confirmation) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// Sign the transactions with another [`Signer`], built from its options
+    pub fn signer<K: SignerBuilder>(self, key: K) -> CosmosOptions<K::Signer> {
+        CosmosOptions {
+            authz_granter: self.authz_granter,
+            fee_granter: self.fee_granter,
+            hd_index: self.hd_index,
+            fee_market: self.fee_market,
+            confirmation: self.confirmation,
+            key,
+            retry_strategies: self.retry_strategies,
+            default_retry_strategies: self.default_retry_strategies,
+        }
+    }
+
+    /// Retry broadcasting the transactions with this strategy, on top of the default ones
+    pub fn retry_strategy(mut self, strategy: RetryStrategy) -> Self {
+        self.add_retry_strategy(strategy);
+        self
+    }
+
+    /// Replace all the retry strategies, including the default ones, by these strategies
+    pub fn retry_strategies(mut self, strategies: Vec<RetryStrategy>) -> Self {
+        self.set_retry_strategies(strategies);
         self
     }
 
@@ -71,14 +130,69 @@ impl CosmosOptions {
         self.hd_index = Some(index);
     }
 
+    pub fn set_fee_market(&mut self, fee_market: FeeMarket) {
+        self.fee_market = Some(fee_market);
+    }
+
+    pub fn set_confirmation(&mut self, confirmation: Confirmation) {
+        self.confirmation = confirmation;
+    }
+
+    pub fn add_retry_strategy(&mut self, strategy: RetryStrategy) {
+        self.retry_strategies.push(strategy);
+    }
+
+    pub fn set_retry_strategies(&mut self, strategies: Vec<RetryStrategy>) {
+        self.retry_strategies = strategies;
+        self.default_retry_strategies = false;
+    }
+
+    /// Broadcaster retrying the transactions with the strategies of the options.
+    /// The account sequence strategy is left out when the sender manages the sequence itself.
+    pub(crate) fn tx_broadcaster(&self, account_sequence: bool) -> TxBroadcaster {
+        let mut broadcaster = TxBroadcaster::default();
+        if self.default_retry_strategies {
+            // 1. In case there is an insufficient_fee error, we retry once (costs fee to the user everytime we submit this kind of tx)
+            // 2. In case there is an `incorrect account sequence` error, we can retry as much as possible (doesn't cost anything to the user)
+            broadcaster = broadcaster.add_strategy(insufficient_fee_strategy());
+            if account_sequence {
+                broadcaster = broadcaster.add_strategy(account_sequence_strategy());
+            }
+        }
+        self.retry_strategies
+            .iter()
+            .cloned()
+            .fold(broadcaster, TxBroadcaster::add_strategy)
+    }
+}
+
+impl CosmosOptions {
+    pub fn mnemonic(mut self, mnemonic: impl ToString) -> Self {
+        self.key = PrivateKeyOptions::Mnemonic(mnemonic.to_string());
+        self
+    }
+
     pub fn set_mnemonic(&mut self, mnemonic: impl ToString) {
-        self.key = CosmosWalletKey::Mnemonic(mnemonic.to_string());
+        self.key = PrivateKeyOptions::Mnemonic(mnemonic.to_string());
+    }
+
+    /// Use the key `name` of a Cosmos-SDK `file` keyring, unlocked with the passphrase of the environment or a prompt
+    pub fn keyring(mut self, dir: impl Into<PathBuf>, name: impl ToString) -> Self {
+        self.set_keyring(dir, name);
+        self
+    }
+
+    pub fn set_keyring(&mut self, dir: impl Into<PathBuf>, name: impl ToString) {
+        self.key = PrivateKeyOptions::Keyring {
+            dir: dir.into(),
+            name: name.to_string(),
+        };
     }
 }
 
-impl SenderBuilder for CosmosOptions {
+impl<S: Signer> SenderBuilder for CosmosOptions<S> {
     type Error = DaemonError;
-    type Sender = Wallet;
+    type Sender = CosmosSender<S>;
 
     async fn build(&self, chain_info: &Arc<ChainInfoOwned>) -> Result<Self::Sender, Self::Error> {
         CosmosSender::new(chain_info, self.clone()).await
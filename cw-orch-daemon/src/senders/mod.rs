@@ -7,11 +7,23 @@ pub mod tx;
 mod cosmos;
 mod cosmos_batch;
 mod cosmos_options;
+mod multisig;
+mod offline;
 mod query_only;
+mod remote_signer;
+mod sequenced;
+mod signer;
 
 pub use {
     cosmos::{CosmosSender, Wallet},
     cosmos_batch::{options::CosmosBatchOptions, BatchDaemon, CosmosBatchSender},
-    cosmos_options::{CosmosOptions, CosmosWalletKey},
-    query_only::{QueryOnlyDaemon, QueryOnlySender},
+    cosmos_options::CosmosOptions,
+    multisig::{multisig_account_id, MultisigDaemon, MultisigOptions, MultisigSender, MultisigTx},
+    offline::{OfflineDaemon, OfflineOptions, OfflineSender, UnsignedTx},
+    query_only::{DryRunBatch, QueryOnlyDaemon, QueryOnlySender},
+    remote_signer::{RemoteSigner, RemoteSignerOptions},
+    sequenced::{SequencedDaemon, SequencedOptions, SequencedSender},
+    signer::{PrivateKeyOptions, PrivateKeySigner, Signer, SignerBuilder},
 };
+
+pub(crate) use cosmos::fee_for_gas_limit;
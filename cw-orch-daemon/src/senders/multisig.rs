@@ -0,0 +1,621 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bitcoin::secp256k1::{
+    ecdsa::Signature, Message as SecpMessage, PublicKey as SecpPublicKey, Secp256k1,
+};
+use cosmrs::{
+    crypto::{secp256k1::SigningKey, CompactBitArray, LegacyAminoMultisig, PublicKey},
+    proto::cosmos::{
+        crypto::multisig::v1beta1::MultiSignature,
+        tx::{signing::v1beta1::SignMode, v1beta1::TxRaw},
+    },
+    tx::{mode_info::Multi, ModeInfo, SignerInfo},
+    AccountId, Any,
+};
+use cw_orch_core::{environment::ChainInfoOwned, log::transaction_target};
+use prost::Message;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tonic::transport::Channel;
+
+use crate::{
+    keys::private::PrivateKey,
+    tx_json::{legacy_amino_sign_bytes, tx_to_json},
+    CosmTxResponse, DaemonBase, DaemonError,
+};
+
+use super::{
+    builder::SenderBuilder,
+    offline::{OfflineOptions, OfflineSender, UnsignedTx},
+    query::QuerySender,
+    tx::TxSender,
+};
+
+/// Prefix of the Amino encoding of a `LegacyAminoPubKey`
+const MULTISIG_AMINO_PREFIX: [u8; 4] = [0x22, 0xc1, 0xf7, 0xe2];
+/// Prefix of the Amino encoding of a secp256k1 public key
+const SECP256K1_AMINO_PREFIX: [u8; 4] = [0xeb, 0x5a, 0xe9, 0x87];
+
+/// Daemon that sends its transactions from a multisig account, see [`MultisigSender`].
+pub type MultisigDaemon = DaemonBase<MultisigSender>;
+
+/// Options for the multisig account of the [`MultisigSender`] and the members that sign locally.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct MultisigOptions {
+    /// Number of signatures needed to broadcast a transaction
+    pub threshold: u32,
+    /// Public keys of the members of the multisig, in the order of the multisig account
+    pub public_keys: Vec<PublicKey>,
+    /// Keys of the members that sign the transactions locally
+    pub(crate) signers: Vec<PrivateKey>,
+    /// Folder the partially signed transactions are written to.
+    /// Defaults to the current directory
+    pub output_dir: Option<PathBuf>,
+    /// Gas limit of the transactions.
+    /// Defaults to a simulation of the transaction on the node
+    pub gas_limit: Option<u64>,
+}
+
+impl MultisigOptions {
+    pub fn new(threshold: u32, public_keys: Vec<PublicKey>) -> Self {
+        Self {
+            threshold,
+            public_keys,
+            signers: vec![],
+            output_dir: None,
+            gas_limit: None,
+        }
+    }
+
+    /// Adds a member that signs the transactions locally
+    pub fn signer(mut self, private_key: PrivateKey) -> Self {
+        self.signers.push(private_key);
+        self
+    }
+
+    pub fn output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Returns the multisig key of the account
+    pub fn multisig(&self) -> LegacyAminoMultisig {
+        LegacyAminoMultisig {
+            threshold: self.threshold,
+            public_keys: self.public_keys.clone(),
+        }
+    }
+}
+
+impl SenderBuilder for MultisigOptions {
+    type Error = DaemonError;
+    type Sender = MultisigSender;
+
+    async fn build(&self, chain_info: &Arc<ChainInfoOwned>) -> Result<Self::Sender, Self::Error> {
+        let multisig = self.multisig();
+        if multisig.threshold == 0 || multisig.threshold as usize > multisig.public_keys.len() {
+            return Err(DaemonError::StdErr(format!(
+                "Invalid multisig threshold {} for {} members",
+                multisig.threshold,
+                multisig.public_keys.len()
+            )));
+        }
+        for signer in &self.signers {
+            member_index(&multisig, &signer_public_key(signer)?)?;
+        }
+        let address = multisig_account_id(&multisig, &chain_info.network_info.pub_address_prefix)?;
+
+        let mut offline_options = OfflineOptions::new(address);
+        offline_options.output_dir = self.output_dir.clone();
+        offline_options.gas_limit = self.gas_limit;
+
+        Ok(MultisigSender {
+            offline: offline_options.build(chain_info).await?,
+            options: self.clone(),
+            multisig,
+            pending_txs: Default::default(),
+        })
+    }
+}
+
+/// Transaction of a multisig account, collecting the signatures of its members
+#[derive(Clone, Debug)]
+pub struct MultisigTx {
+    /// Transaction to sign
+    pub unsigned_tx: UnsignedTx,
+    /// Multisig key of the account
+    pub multisig: LegacyAminoMultisig,
+    /// Signatures, by index of the member in the multisig
+    signatures: BTreeMap<usize, Vec<u8>>,
+}
+
+impl MultisigTx {
+    pub fn new(unsigned_tx: UnsignedTx, multisig: LegacyAminoMultisig) -> Self {
+        Self {
+            unsigned_tx,
+            multisig,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the bytes the members sign, with `SIGN_MODE_LEGACY_AMINO_JSON`
+    pub fn sign_bytes(&self) -> Result<Vec<u8>, DaemonError> {
+        legacy_amino_sign_bytes(
+            &self.unsigned_tx.body.clone().into(),
+            &self.unsigned_tx.fee.clone().into(),
+            &self.unsigned_tx.chain_id,
+            self.unsigned_tx.account_number,
+            self.unsigned_tx.sequence,
+        )
+    }
+
+    /// Signs the transaction with the key of a member
+    pub fn sign(&mut self, private_key: &PrivateKey) -> Result<(), DaemonError> {
+        let signing_key = SigningKey::from_slice(&private_key.raw_key())?;
+        let signature = signing_key.sign(&self.sign_bytes()?)?;
+        self.add_signature(&signing_key.public_key(), signature.to_vec())
+    }
+
+    /// Adds the signature of a member, after verifying it
+    pub fn add_signature(
+        &mut self,
+        public_key: &PublicKey,
+        signature: Vec<u8>,
+    ) -> Result<(), DaemonError> {
+        let index = member_index(&self.multisig, public_key)?;
+
+        let secp = Secp256k1::verification_only();
+        let message = SecpMessage::from_slice(&Sha256::digest(self.sign_bytes()?))?;
+        secp.verify_ecdsa(
+            &message,
+            &Signature::from_compact(&signature)?,
+            &SecpPublicKey::from_slice(&public_key.to_bytes())?,
+        )
+        .map_err(|_| {
+            DaemonError::StdErr(format!(
+                "Invalid signature of multisig member {}",
+                public_key.to_string()
+            ))
+        })?;
+
+        self.signatures.insert(index, signature);
+        Ok(())
+    }
+
+    /// Imports the signatures of a file written by `<chain-binary> tx sign <file> --multisig <address> --signature-only`
+    pub fn import_signatures(&mut self, path: impl AsRef<Path>) -> Result<(), DaemonError> {
+        let descriptors: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+        let descriptors = descriptors["signatures"]
+            .as_array()
+            .ok_or_else(|| DaemonError::StdErr("No signatures found in file".to_string()))?;
+        for descriptor in descriptors {
+            let mode = &descriptor["data"]["single"]["mode"];
+            if mode != SignMode::LegacyAminoJson.as_str_name() {
+                return Err(DaemonError::StdErr(format!(
+                    "Multisig members need to sign with {}, got {mode}",
+                    SignMode::LegacyAminoJson.as_str_name()
+                )));
+            }
+            let public_key = PublicKey::from_json(&descriptor["public_key"].to_string())?;
+            let signature = descriptor["data"]["single"]["signature"]
+                .as_str()
+                .unwrap_or_default();
+            self.add_signature(&public_key, STANDARD.decode(signature)?)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes the signatures collected so far like `<chain-binary> tx sign --signature-only`, to be merged with `<chain-binary> tx multisign`
+    pub fn signatures_to_json(&self) -> Result<Value, DaemonError> {
+        let signatures = self
+            .signatures
+            .iter()
+            .map(|(index, signature)| {
+                let public_key: Value =
+                    serde_json::from_str(&self.multisig.public_keys[*index].to_json())?;
+                Ok(json!({
+                    "public_key": public_key,
+                    "data": {
+                        "single": {
+                            "mode": SignMode::LegacyAminoJson.as_str_name(),
+                            "signature": STANDARD.encode(signature)
+                        }
+                    },
+                    "sequence": self.unsigned_tx.sequence.to_string()
+                }))
+            })
+            .collect::<Result<Vec<_>, DaemonError>>()?;
+        Ok(json!({ "signatures": signatures }))
+    }
+
+    /// Public keys of the members that signed the transaction
+    pub fn signers(&self) -> Vec<PublicKey> {
+        self.signatures
+            .keys()
+            .map(|index| self.multisig.public_keys[*index])
+            .collect()
+    }
+
+    /// Whether the transaction has enough signatures to be broadcasted
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.multisig.threshold as usize
+    }
+
+    /// Returns the signed transaction, with the signatures collected so far
+    pub fn to_raw(&self) -> Result<TxRaw, DaemonError> {
+        let signer_info = multisig_signer_info(
+            &self.multisig,
+            self.signatures.keys().copied(),
+            self.unsigned_tx.sequence,
+        );
+        let signature = MultiSignature {
+            signatures: self.signatures.values().cloned().collect(),
+        };
+        Ok(TxRaw {
+            body_bytes: self.unsigned_tx.body.clone().into_bytes()?,
+            auth_info_bytes: signer_info
+                .auth_info(self.unsigned_tx.fee.clone())
+                .into_bytes()?,
+            signatures: vec![signature.encode_to_vec()],
+        })
+    }
+
+    /// Returns the Cosmos-SDK JSON encoding of the signed transaction, as written by `<chain-binary> tx multisign`
+    pub fn to_json(&self) -> Result<Value, DaemonError> {
+        let tx = self.to_raw()?;
+        tx_to_json(
+            &Message::decode(tx.body_bytes.as_slice())?,
+            &Message::decode(tx.auth_info_bytes.as_slice())?,
+            &tx.signatures,
+        )
+    }
+}
+
+/// Sender of a legacy Amino multisig account, the kind created with `<chain-binary> keys add <name> --multisig <keys>`.
+///
+/// Transactions are signed with the keys of the members set with [`MultisigOptions::signer`] and broadcasted once the threshold is met.
+/// Otherwise they are kept as pending [`MultisigTx`] and written to files to be signed by the other members
+/// with `<chain-binary> tx sign <file> --multisig <address> --signature-only`.
+/// Their signatures are imported with [`MultisigTx::import_signatures`] and the transaction broadcasted with [`MultisigSender::broadcast`].
+///
+/// Transactions that aren't broadcasted return a [`DaemonError::TxNotBroadcasted`] error.
+#[derive(Clone)]
+pub struct MultisigSender {
+    offline: OfflineSender,
+    pub(crate) options: MultisigOptions,
+    multisig: LegacyAminoMultisig,
+    pending_txs: Arc<Mutex<Vec<MultisigTx>>>,
+}
+
+impl MultisigSender {
+    pub fn options(&self) -> MultisigOptions {
+        self.options.clone()
+    }
+
+    /// Information about the chain
+    pub fn chain_info(&self) -> &Arc<ChainInfoOwned> {
+        &self.offline.chain_info
+    }
+
+    /// Transactions waiting for the signatures of other members, in order
+    pub fn pending_txs(&self) -> Vec<MultisigTx> {
+        self.pending_txs.lock().unwrap().clone()
+    }
+
+    /// Broadcasts a multisig transaction that meets the threshold, and removes it from the pending transactions
+    pub async fn broadcast(&self, tx: &MultisigTx) -> Result<CosmTxResponse, DaemonError> {
+        if !tx.is_complete() {
+            return Err(DaemonError::StdErr(format!(
+                "Multisig transaction signed by {} members, {} signatures are needed",
+                tx.signatures.len(),
+                tx.multisig.threshold
+            )));
+        }
+        let resp = self
+            .offline
+            .broadcast_tx_bytes(tx.to_raw()?.encode_to_vec())
+            .await?;
+        self.pending_txs
+            .lock()
+            .unwrap()
+            .retain(|pending| pending.unsigned_tx.sequence != tx.unsigned_tx.sequence);
+        Ok(resp)
+    }
+}
+
+impl QuerySender for MultisigSender {
+    type Error = DaemonError;
+    type Options = MultisigOptions;
+
+    fn channel(&self) -> Channel {
+        self.offline.channel()
+    }
+}
+
+impl TxSender for MultisigSender {
+    async fn commit_tx_any(
+        &self,
+        msgs: Vec<Any>,
+        memo: Option<&str>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let unsigned_tx = self
+            .offline
+            .unsigned_tx(msgs, memo, |sequence| {
+                // Simulated with placeholder signatures of the first members
+                let threshold = self.multisig.threshold as usize;
+                let signature = MultiSignature {
+                    signatures: vec![vec![0; 64]; threshold],
+                };
+                Ok((
+                    multisig_signer_info(&self.multisig, 0..threshold, sequence),
+                    signature.encode_to_vec(),
+                ))
+            })
+            .await?;
+
+        let mut tx = MultisigTx::new(unsigned_tx, self.multisig.clone());
+        for signer in &self.options.signers {
+            tx.sign(signer)?;
+        }
+        if tx.is_complete() {
+            return self.broadcast(&tx).await;
+        }
+
+        let path = tx.unsigned_tx.path.clone();
+        std::fs::write(&path, serde_json::to_string(&tx.unsigned_tx.to_json()?)?)?;
+        if !tx.signatures.is_empty() {
+            std::fs::write(
+                path.with_extension("signatures.json"),
+                serde_json::to_string(&tx.signatures_to_json()?)?,
+            )?;
+        }
+        log::info!(
+            target: &transaction_target(),
+            "Multisig transaction signed by {} of the {} required members, unsigned transaction written to {:?}, sign it with account number {} and sequence {}",
+            tx.signatures.len(),
+            self.multisig.threshold,
+            path,
+            tx.unsigned_tx.account_number,
+            tx.unsigned_tx.sequence
+        );
+        self.pending_txs.lock().unwrap().push(tx);
+
+        Err(DaemonError::TxNotBroadcasted(
+            path.to_string_lossy().to_string(),
+        ))
+    }
+
+    fn account_id(&self) -> AccountId {
+        self.offline.account_id()
+    }
+}
+
+/// Returns the address of a legacy Amino multisig account: the hash of the Amino encoding of its key
+pub fn multisig_account_id(
+    multisig: &LegacyAminoMultisig,
+    prefix: &str,
+) -> Result<AccountId, DaemonError> {
+    let mut amino = MULTISIG_AMINO_PREFIX.to_vec();
+    amino.push(0x08);
+    encode_varint(multisig.threshold as u64, &mut amino);
+    for public_key in &multisig.public_keys {
+        if public_key.type_url() != PublicKey::SECP256K1_TYPE_URL {
+            return Err(DaemonError::StdErr(format!(
+                "Multisig members with {} keys are not supported",
+                public_key.type_url()
+            )));
+        }
+        let key = public_key.to_bytes();
+        amino.push(0x12);
+        encode_varint(
+            (SECP256K1_AMINO_PREFIX.len() + 1 + key.len()) as u64,
+            &mut amino,
+        );
+        amino.extend(SECP256K1_AMINO_PREFIX);
+        encode_varint(key.len() as u64, &mut amino);
+        amino.extend(key);
+    }
+    Ok(AccountId::new(prefix, &Sha256::digest(amino)[..20])?)
+}
+
+fn encode_varint(value: u64, buf: &mut Vec<u8>) {
+    prost::encoding::encode_varint(value, buf)
+}
+
+/// Signer info of a multisig account, with the members at the given indices signing with `SIGN_MODE_LEGACY_AMINO_JSON`
+fn multisig_signer_info(
+    multisig: &LegacyAminoMultisig,
+    signer_indices: impl IntoIterator<Item = usize>,
+    sequence: u64,
+) -> SignerInfo {
+    let members = multisig.public_keys.len();
+    let mut elems = vec![0; members.div_ceil(8)];
+    let mut mode_infos = vec![];
+    for index in signer_indices {
+        elems[index / 8] |= 1 << (7 - index % 8);
+        mode_infos.push(ModeInfo::single(SignMode::LegacyAminoJson));
+    }
+
+    SignerInfo {
+        public_key: Some(multisig.clone().into()),
+        mode_info: ModeInfo::Multi(Multi {
+            bitarray: CompactBitArray::new((members % 8) as u32, elems),
+            mode_infos,
+        }),
+        sequence,
+    }
+}
+
+fn member_index(
+    multisig: &LegacyAminoMultisig,
+    public_key: &PublicKey,
+) -> Result<usize, DaemonError> {
+    multisig
+        .public_keys
+        .iter()
+        .position(|member| member == public_key)
+        .ok_or_else(|| {
+            DaemonError::StdErr(format!(
+                "Key {} is not a member of the multisig",
+                public_key.to_string()
+            ))
+        })
+}
+
+fn signer_public_key(private_key: &PrivateKey) -> Result<PublicKey, DaemonError> {
+    Ok(SigningKey::from_slice(&private_key.raw_key())?.public_key())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::Secp256k1;
+    use cosmrs::{
+        crypto::LegacyAminoMultisig,
+        proto::cosmos::{
+            bank::v1beta1::MsgSend,
+            crypto::multisig::v1beta1::MultiSignature,
+            tx::v1beta1::{mode_info, AuthInfo},
+        },
+        Any,
+    };
+    use prost::{Message, Name};
+
+    use super::{multisig_account_id, signer_public_key, MultisigTx};
+    use crate::{keys::private::PrivateKey, senders::UnsignedTx, tx_json::tx_from_json, TxBuilder};
+
+    fn members() -> anyhow::Result<(Vec<PrivateKey>, LegacyAminoMultisig)> {
+        let secp = Secp256k1::new();
+        let keys = (0..3)
+            .map(|_| PrivateKey::new(&secp, 118))
+            .collect::<Result<Vec<_>, _>>()?;
+        let public_keys = keys
+            .iter()
+            .map(signer_public_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((
+            keys,
+            LegacyAminoMultisig {
+                threshold: 2,
+                public_keys,
+            },
+        ))
+    }
+
+    fn unsigned_tx() -> anyhow::Result<UnsignedTx> {
+        let msg = MsgSend {
+            from_address: "juno1multisig".to_string(),
+            to_address: "juno1recipient".to_string(),
+            amount: vec![],
+        };
+        Ok(UnsignedTx {
+            chain_id: "juno-1".to_string(),
+            account_number: 12,
+            sequence: 3,
+            body: TxBuilder::build_body(
+                vec![Any {
+                    type_url: MsgSend::type_url(),
+                    value: msg.encode_to_vec(),
+                }],
+                None,
+                0,
+            ),
+            fee: TxBuilder::build_fee(5000u128, "ujuno", 200_000, None)?,
+            path: Default::default(),
+        })
+    }
+
+    #[test]
+    fn multisig_address_depends_on_members_and_threshold() -> anyhow::Result<()> {
+        let (_, multisig) = members()?;
+        let address = multisig_account_id(&multisig, "juno")?;
+        assert_eq!(address, multisig_account_id(&multisig, "juno")?);
+        assert_eq!(address.to_bytes().len(), 20);
+
+        let mut other_threshold = multisig.clone();
+        other_threshold.threshold = 3;
+        assert_ne!(address, multisig_account_id(&other_threshold, "juno")?);
+
+        let mut other_order = multisig.clone();
+        other_order.public_keys.reverse();
+        assert_ne!(address, multisig_account_id(&other_order, "juno")?);
+        Ok(())
+    }
+
+    #[test]
+    fn signatures_are_merged_until_threshold() -> anyhow::Result<()> {
+        let (keys, multisig) = members()?;
+        let mut tx = MultisigTx::new(unsigned_tx()?, multisig.clone());
+
+        tx.sign(&keys[2])?;
+        assert!(!tx.is_complete());
+
+        // The second member signs on another machine and shares a signature file
+        let mut remote_tx = MultisigTx::new(unsigned_tx()?, multisig.clone());
+        remote_tx.sign(&keys[0])?;
+        let path = crate::gen_temp_file_path();
+        std::fs::write(&path, serde_json::to_vec(&remote_tx.signatures_to_json()?)?)?;
+        tx.import_signatures(&path)?;
+        std::fs::remove_file(path)?;
+
+        assert!(tx.is_complete());
+        assert_eq!(
+            tx.signers(),
+            vec![multisig.public_keys[0], multisig.public_keys[2]]
+        );
+
+        let raw = tx.to_raw()?;
+        let auth_info = AuthInfo::decode(raw.auth_info_bytes.as_slice())?;
+        let Some(mode_info::Sum::Multi(multi)) = auth_info.signer_infos[0]
+            .mode_info
+            .clone()
+            .and_then(|mode_info| mode_info.sum)
+        else {
+            panic!("Expected a multisig mode info");
+        };
+        let bitarray = multi.bitarray.unwrap();
+        assert_eq!(bitarray.extra_bits_stored, 3);
+        assert_eq!(bitarray.elems, vec![0b1010_0000]);
+        assert_eq!(multi.mode_infos.len(), 2);
+        assert_eq!(
+            MultiSignature::decode(raw.signatures[0].as_slice())?
+                .signatures
+                .len(),
+            2
+        );
+
+        // The JSON encoding is the one broadcasted after `<chain-binary> tx multisign`
+        assert_eq!(tx_from_json(&tx.to_json()?)?, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_signatures_are_rejected() -> anyhow::Result<()> {
+        let (keys, multisig) = members()?;
+        let mut tx = MultisigTx::new(unsigned_tx()?, multisig.clone());
+
+        let outsider = PrivateKey::new(&Secp256k1::new(), 118)?;
+        assert!(tx.sign(&outsider).is_err());
+
+        let mut other_tx = unsigned_tx()?;
+        other_tx.sequence += 1;
+        let mut other_tx = MultisigTx::new(other_tx, multisig.clone());
+        other_tx.sign(&keys[1])?;
+        let signature = other_tx.signatures[&1].clone();
+        assert!(tx
+            .add_signature(&multisig.public_keys[1], signature)
+            .is_err());
+        assert!(tx.signers().is_empty());
+        Ok(())
+    }
+}
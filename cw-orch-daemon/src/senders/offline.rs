@@ -0,0 +1,448 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use cosmrs::{
+    crypto::PublicKey,
+    proto::{
+        cosmos::tx::v1beta1::{AuthInfo, TxRaw},
+        cosmwasm::wasm::v1::{MsgInstantiateContract, MsgInstantiateContract2, MsgStoreCode},
+        traits::Name,
+    },
+    tendermint::chain::Id,
+    tx::{self, Fee, SignDoc, SignerInfo},
+    AccountId, Any,
+};
+use cosmwasm_std::{instantiate2_address, CanonicalAddr};
+use cw_orch_core::{environment::ChainInfoOwned, log::transaction_target};
+use prost::Message;
+use serde_json::Value;
+use tonic::transport::Channel;
+
+use crate::{
+    cosmos_modules::{
+        self,
+        auth::BaseAccount,
+        tendermint_abci::{Event, EventAttribute},
+    },
+    queriers::{CosmWasm, Node},
+    tx_broadcaster::{assert_broadcast_code_cosm_response, assert_broadcast_code_response},
+    tx_json::{msg_from_json, msg_to_json, tx_from_json, tx_to_json},
+    CosmTxResponse, DaemonBase, DaemonError, GrpcChannel, TxBuilder, INSTANTIATE_2_TYPE_URL,
+};
+
+use super::{
+    builder::SenderBuilder,
+    cosmos::{fee_for_gas_limit, fee_from_gas, query_base_account},
+    query::QuerySender,
+    tx::TxSender,
+};
+
+/// Daemon that writes its transactions to files to be signed offline, see [`OfflineSender`].
+pub type OfflineDaemon = DaemonBase<OfflineSender>;
+
+/// Options for how txs should be exported by the [`OfflineSender`].
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub struct OfflineOptions {
+    /// Address of the account that signs the transactions
+    pub address: String,
+    /// Folder the unsigned transactions are written to.
+    /// Defaults to the current directory
+    pub output_dir: Option<PathBuf>,
+    /// Gas limit of the transactions.
+    /// Defaults to a simulation of the transaction on the node
+    pub gas_limit: Option<u64>,
+}
+
+impl OfflineOptions {
+    pub fn new(address: impl ToString) -> Self {
+        Self {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+}
+
+impl SenderBuilder for OfflineOptions {
+    type Error = DaemonError;
+    type Sender = OfflineSender;
+
+    async fn build(&self, chain_info: &Arc<ChainInfoOwned>) -> Result<Self::Sender, Self::Error> {
+        let account_id = AccountId::from_str(&self.address)?;
+        if account_id.prefix() != chain_info.network_info.pub_address_prefix {
+            return Err(DaemonError::StdErr(format!(
+                "Address {} doesn't belong to chain {}",
+                self.address, chain_info.chain_id
+            )));
+        }
+
+        Ok(OfflineSender {
+            channel: GrpcChannel::from_chain_info(chain_info.as_ref()).await?,
+            chain_info: chain_info.clone(),
+            account_id,
+            options: self.clone(),
+            next_sequence: Default::default(),
+            unsigned_txs: Default::default(),
+        })
+    }
+}
+
+/// Transaction waiting to be signed offline
+#[derive(Clone, Debug)]
+pub struct UnsignedTx {
+    /// Chain id the transaction is sent to
+    pub chain_id: String,
+    /// Account number of the signer
+    pub account_number: u64,
+    /// Sequence of the signer for this transaction
+    pub sequence: u64,
+    /// Body of the transaction
+    pub body: tx::Body,
+    /// Fee of the transaction
+    pub fee: Fee,
+    /// File the transaction was written to
+    pub path: PathBuf,
+}
+
+impl UnsignedTx {
+    /// Returns the [`SignDoc`] of the transaction, for a signer using `SIGN_MODE_DIRECT` with the given public key
+    pub fn sign_doc(&self, public_key: PublicKey) -> Result<SignDoc, DaemonError> {
+        let auth_info =
+            SignerInfo::single_direct(Some(public_key), self.sequence).auth_info(self.fee.clone());
+        Ok(SignDoc::new(
+            &self.body,
+            &auth_info,
+            &Id::try_from(self.chain_id.clone())?,
+            self.account_number,
+        )?)
+    }
+
+    /// Returns the Cosmos-SDK JSON encoding of the unsigned transaction, as expected by `<chain-binary> tx sign`
+    pub fn to_json(&self) -> Result<Value, DaemonError> {
+        let auth_info = AuthInfo {
+            signer_infos: vec![],
+            fee: Some(self.fee.clone().into()),
+            tip: None,
+        };
+        tx_to_json(&self.body.clone().into(), &auth_info, &[])
+    }
+}
+
+/// Sender that doesn't hold any key: its transactions are written to files as unsigned Cosmos-SDK transactions.
+///
+/// The files can be signed on another machine with `<chain-binary> tx sign <file> --offline --account-number <n> --sequence <n>`,
+/// or with the [`SignDoc`] returned by [`UnsignedTx::sign_doc`].
+/// Signed transactions are then broadcasted with [`OfflineSender::broadcast_signed_tx`].
+///
+/// Committing a transaction returns a response without hash, see [`CosmTxResponse::is_broadcasted`], so a whole deployment can be exported.
+/// The response holds the addresses of the `instantiate2` instantiations, that the deployments read once the transaction is broadcasted.
+/// The code id of an upload and the address of a classic instantiation are unknown until the transaction is broadcasted,
+/// those transactions return a [`DaemonError::TxNotBroadcasted`] error with the path of the exported file.
+#[derive(Clone)]
+pub struct OfflineSender {
+    /// gRPC channel
+    pub channel: Channel,
+    /// Information about the chain
+    pub chain_info: Arc<ChainInfoOwned>,
+    pub(crate) options: OfflineOptions,
+    account_id: AccountId,
+    /// Sequence of the next transaction, accounting for the transactions that are not broadcasted yet
+    next_sequence: Arc<Mutex<Option<u64>>>,
+    unsigned_txs: Arc<Mutex<Vec<UnsignedTx>>>,
+}
+
+impl OfflineSender {
+    pub fn options(&self) -> OfflineOptions {
+        self.options.clone()
+    }
+
+    /// Transactions written by this sender, in order
+    pub fn unsigned_txs(&self) -> Vec<UnsignedTx> {
+        self.unsigned_txs.lock().unwrap().clone()
+    }
+
+    /// Broadcasts a transaction signed offline, read from a Cosmos-SDK JSON file written by `<chain-binary> tx sign`
+    pub async fn broadcast_signed_tx(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let tx: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+        self.broadcast_tx_bytes(tx_from_json(&tx)?.encode_to_vec())
+            .await
+    }
+
+    /// Broadcasts the bytes of a signed transaction, as written by `<chain-binary> tx encode`
+    pub async fn broadcast_tx_bytes(
+        &self,
+        tx_bytes: Vec<u8>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let mut client = cosmos_modules::tx::service_client::ServiceClient::new(self.channel());
+        let commit = client
+            .broadcast_tx(cosmos_modules::tx::BroadcastTxRequest {
+                tx_bytes,
+                mode: cosmos_modules::tx::BroadcastMode::Sync.into(),
+            })
+            .await?
+            .into_inner()
+            .tx_response
+            .unwrap();
+        let commit = assert_broadcast_code_response(commit)?;
+
+        let resp = Node::new_async(self.channel())
+            ._find_tx(commit.txhash)
+            .await?;
+
+        assert_broadcast_code_cosm_response(resp)
+    }
+
+    /// Returns the sequence of the next transaction, given the sequence of the account on-chain
+    fn next_sequence(&self, sequence: u64) -> u64 {
+        let mut next_sequence = self.next_sequence.lock().unwrap();
+        let sequence = next_sequence.map_or(sequence, |next| next.max(sequence));
+        *next_sequence = Some(sequence + 1);
+        sequence
+    }
+
+    /// Builds the unsigned transaction of the messages, for the next sequence of the account.
+    ///
+    /// Unless a gas limit is set in the options, the gas limit is simulated with the signer info and signature returned by `simulation_signer` for the sequence.
+    pub(crate) async fn unsigned_tx(
+        &self,
+        msgs: Vec<Any>,
+        memo: Option<&str>,
+        simulation_signer: impl FnOnce(u64) -> Result<(SignerInfo, Vec<u8>), DaemonError>,
+    ) -> Result<UnsignedTx, DaemonError> {
+        // Messages are normalized through their JSON encoding, so the exported transaction is the one that gets signed
+        let msgs = msgs
+            .iter()
+            .map(|msg| msg_from_json(&msg_to_json(msg)?))
+            .collect::<Result<Vec<_>, DaemonError>>()?;
+
+        let BaseAccount {
+            account_number,
+            sequence,
+            ..
+        } = query_base_account(self.channel(), self.account_id.to_string()).await?;
+        let sequence = self.next_sequence(sequence);
+
+        // The transaction is signed later on, it doesn't time out
+        let body = TxBuilder::build_body(msgs, memo, 0);
+        let gas_limit = match self.options.gas_limit {
+            Some(gas_limit) => gas_limit,
+            None => {
+                let (signer_info, signature) = simulation_signer(sequence)?;
+                self.simulate_gas_limit(&body, signer_info, signature)
+                    .await?
+            }
+        };
+        let fee_amount = fee_for_gas_limit(gas_limit, self.chain_info.gas_price);
+        let fee = TxBuilder::build_fee(fee_amount, &self.chain_info.gas_denom, gas_limit, None)?;
+
+        let path = self
+            .options
+            .output_dir
+            .clone()
+            .unwrap_or_default()
+            .join(format!(
+                "unsigned-{}-{}-{}.json",
+                self.chain_info.chain_id, self.account_id, sequence
+            ));
+        Ok(UnsignedTx {
+            chain_id: self.chain_info.chain_id.clone(),
+            account_number,
+            sequence,
+            body,
+            fee,
+            path,
+        })
+    }
+
+    /// Events emitted by the messages once they're broadcasted, that are read by the deployments
+    async fn expected_events(&self, msgs: &[Any]) -> Result<Vec<Event>, DaemonError> {
+        let wasm = CosmWasm::new_async(self.channel());
+        let mut events = vec![];
+        for msg in msgs {
+            if msg.type_url == INSTANTIATE_2_TYPE_URL {
+                let msg = MsgInstantiateContract2::decode(msg.value.as_slice())?;
+                let checksum = wasm._code_id_hash(msg.code_id).await?;
+                let creator = AccountId::from_str(&msg.sender)?;
+                let address = instantiate2_address(
+                    &checksum,
+                    &CanonicalAddr::from(creator.to_bytes()),
+                    &msg.salt,
+                )?;
+                events.push(event(
+                    "instantiate",
+                    &[
+                        (
+                            "_contract_address",
+                            AccountId::new(creator.prefix(), address.as_slice())?.to_string(),
+                        ),
+                        ("code_id", msg.code_id.to_string()),
+                    ],
+                ));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Simulates the transaction with a placeholder signature and returns the gas limit to use
+    async fn simulate_gas_limit(
+        &self,
+        body: &tx::Body,
+        signer_info: SignerInfo,
+        signature: Vec<u8>,
+    ) -> Result<u64, DaemonError> {
+        let fee = TxBuilder::build_fee(0u8, &self.chain_info.gas_denom, 0, None)?;
+        let tx = TxRaw {
+            body_bytes: body.clone().into_bytes()?,
+            auth_info_bytes: signer_info.auth_info(fee).into_bytes()?,
+            signatures: vec![signature],
+        };
+        let gas = Node::new_async(self.channel())
+            ._simulate_tx(tx.encode_to_vec())
+            .await?;
+
+        Ok(fee_from_gas(gas, self.chain_info.gas_price).0)
+    }
+}
+
+impl QuerySender for OfflineSender {
+    type Error = DaemonError;
+    type Options = OfflineOptions;
+
+    fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+}
+
+impl TxSender for OfflineSender {
+    async fn commit_tx_any(
+        &self,
+        msgs: Vec<Any>,
+        memo: Option<&str>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let unsigned_tx = self
+            .unsigned_tx(msgs, memo, |sequence| {
+                Ok((SignerInfo::single_direct(None, sequence), vec![]))
+            })
+            .await?;
+        let path = unsigned_tx.path.clone();
+        std::fs::write(&path, serde_json::to_string(&unsigned_tx.to_json()?)?)?;
+        log::info!(
+            target: &transaction_target(),
+            "Unsigned transaction written to {:?}, sign it with account number {} and sequence {}",
+            path,
+            unsigned_tx.account_number,
+            unsigned_tx.sequence
+        );
+        let msgs = unsigned_tx.body.messages.clone();
+        self.unsigned_txs.lock().unwrap().push(unsigned_tx);
+
+        if msgs.iter().any(|msg| {
+            msg.type_url == MsgStoreCode::type_url()
+                || msg.type_url == MsgInstantiateContract::type_url()
+        }) {
+            return Err(DaemonError::TxNotBroadcasted(
+                path.to_string_lossy().to_string(),
+            ));
+        }
+        Ok(CosmTxResponse {
+            raw_log: format!("Unsigned transaction written to {}", path.display()),
+            events: self.expected_events(&msgs).await?,
+            ..Default::default()
+        })
+    }
+
+    fn account_id(&self) -> AccountId {
+        self.account_id.clone()
+    }
+}
+
+fn event(r#type: &str, attributes: &[(&str, String)]) -> Event {
+    Event {
+        r#type: r#type.to_string(),
+        attributes: attributes
+            .iter()
+            .map(|(key, value)| EventAttribute {
+                key: key.to_string().into(),
+                value: value.clone().into(),
+                index: true,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use cosmrs::{
+        crypto::secp256k1::SigningKey,
+        proto::cosmos::{bank::v1beta1::MsgSend, tx::v1beta1::TxRaw},
+        Any,
+    };
+    use prost::{Message, Name};
+    use serde_json::json;
+
+    use super::UnsignedTx;
+    use crate::{tx_json::tx_from_json, DaemonError, TxBuilder};
+
+    #[test]
+    fn signed_tx_matches_sign_doc() -> anyhow::Result<()> {
+        let msg = MsgSend {
+            from_address: "juno1sender".to_string(),
+            to_address: "juno1recipient".to_string(),
+            amount: vec![],
+        };
+        let unsigned_tx = UnsignedTx {
+            chain_id: "juno-1".to_string(),
+            account_number: 12,
+            sequence: 3,
+            body: TxBuilder::build_body(
+                vec![Any {
+                    type_url: MsgSend::type_url(),
+                    value: msg.encode_to_vec(),
+                }],
+                None,
+                0,
+            ),
+            fee: TxBuilder::build_fee(5000u128, "ujuno", 200_000, None)?,
+            path: Default::default(),
+        };
+        let key = SigningKey::random();
+        let signed = TxRaw::from(
+            unsigned_tx
+                .sign_doc(key.public_key())?
+                .sign(&key)
+                .map_err(DaemonError::from)?,
+        );
+
+        // Fill in the unsigned transaction like `<chain-binary> tx sign` does
+        let mut tx = unsigned_tx.to_json()?;
+        tx["auth_info"]["signer_infos"] = json!([{
+            "public_key": {
+                "@type": "/cosmos.crypto.secp256k1.PubKey",
+                "key": STANDARD.encode(key.public_key().to_bytes())
+            },
+            "mode_info": { "single": { "mode": "SIGN_MODE_DIRECT" } },
+            "sequence": "3"
+        }]);
+        tx["signatures"] = json!([STANDARD.encode(&signed.signatures[0])]);
+
+        assert_eq!(tx_from_json(&tx)?, signed);
+        Ok(())
+    }
+}
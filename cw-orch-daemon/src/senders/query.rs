@@ -10,6 +10,7 @@ pub trait QuerySender: Clone {
     /// Options for this sender
     type Options: SenderBuilder<Sender = Self>;
 
-    /// Get the channel for the sender
+    /// Get the channel for the sender.
+    /// It's connected to a gRPC endpoint or served by a [`Transport`](crate::transport::Transport) like Tendermint RPC.
     fn channel(&self) -> Channel;
 }
@@ -1,12 +1,31 @@
-use std::sync::Arc;
+use std::{fmt::Debug, io::Write, str::FromStr, sync::Arc};
 
-use crate::{error::DaemonError, DaemonBase, GrpcChannel};
-
-use cw_orch_core::environment::ChainInfoOwned;
+use crate::{
+    core::{parse_cw_coins, proto_parse_cw_coins},
+    cosmos_modules::{auth::BaseAccount, tendermint_abci::Event},
+    error::DaemonError,
+    queriers::Node,
+    CosmTxResponse, DaemonBase, GrpcChannel, TxBuilder, INSTANTIATE_2_TYPE_URL,
+};
 
+use cosmrs::{
+    cosmwasm::{MsgExecuteContract, MsgInstantiateContract, MsgMigrateContract, MsgStoreCode},
+    proto::{cosmos::tx::v1beta1::TxRaw, cosmwasm::wasm::v1::MsgInstantiateContract2},
+    tx::{AuthInfo, Msg, SignerInfo},
+    AccountId, Any,
+};
+use cosmwasm_std::{Addr, Binary, Coin};
+use cw_orch_core::{
+    contract::{interface_traits::Uploadable, WasmPath},
+    environment::{ChainInfoOwned, DryRunEnv, DryRunResponse},
+    CwEnvError,
+};
+use flate2::{write, Compression};
+use prost::Message;
+use serde::Serialize;
 use tonic::transport::Channel;
 
-use super::{builder::SenderBuilder, query::QuerySender};
+use super::{builder::SenderBuilder, cosmos::query_base_account, query::QuerySender};
 
 /// Daemon that does not support signing.
 /// Will err on any attempt to sign a transaction or retrieve a sender address.
@@ -44,6 +63,256 @@ impl QuerySender for QueryOnlySender {
     }
 }
 
+/// Size of the messages simulated in a single transaction by a `DryRun` of a [`QueryOnlyDaemon`], in bytes
+const MAX_BATCH_BYTES: usize = 2_000_000;
+
+/// Transactions simulated by a `DryRun` of a [`QueryOnlyDaemon`].
+///
+/// Every transaction is simulated after the ones before it, in a single transaction,
+/// so it's run against the codes and contracts of the deployment that are not on-chain.
+/// Once the messages exceed [`MAX_BATCH_BYTES`], the next transactions are simulated in a new batch,
+/// without the codes and contracts of the previous ones.
+#[derive(Clone, Debug, Default)]
+pub struct DryRunBatch {
+    /// Account the next transactions are simulated for
+    sender: Option<Addr>,
+    /// Messages of the transactions simulated so far
+    msgs: Vec<Any>,
+    /// Signers of the messages with their on-chain sequence, in order of appearance
+    signers: Vec<(AccountId, u64)>,
+    /// Gas used by the simulation of the messages
+    gas_used: u64,
+    /// Number of events emitted by the messages
+    events: usize,
+}
+
+impl DryRunBatch {
+    fn sender(&self) -> Result<AccountId, DaemonError> {
+        let sender = self.sender.as_ref().ok_or_else(|| {
+            DaemonError::StdErr(
+                "The query-only daemon has no sender, build its dry run with `DryRun::with_sender`"
+                    .into(),
+            )
+        })?;
+        Ok(AccountId::from_str(sender.as_str())?)
+    }
+}
+
+impl QueryOnlyDaemon {
+    /// Simulates the transactions of the batch followed by `msg`, returns the events and the gas used by `msg`
+    async fn simulate_after(
+        &self,
+        batch: &mut DryRunBatch,
+        msg: Any,
+    ) -> Result<DryRunResponse<CosmTxResponse>, DaemonError> {
+        let sender = batch.sender()?;
+        let batch_bytes: usize = batch.msgs.iter().map(|msg| msg.value.len()).sum();
+        if !batch.msgs.is_empty() && batch_bytes + msg.value.len() > MAX_BATCH_BYTES {
+            log::warn!(
+                "The simulated messages exceed {MAX_BATCH_BYTES} bytes, the next transactions are simulated without the codes and contracts of the previous ones"
+            );
+            *batch = DryRunBatch {
+                sender: batch.sender.take(),
+                ..Default::default()
+            };
+        }
+
+        let mut msgs = batch.msgs.clone();
+        msgs.push(msg);
+        // The sequences of the signers are only queried once, the simulations don't increment them
+        let mut signers = batch.signers.clone();
+        if !signers.iter().any(|(signer, _)| *signer == sender) {
+            let BaseAccount { sequence, .. } =
+                query_base_account(self.channel(), sender.to_string()).await?;
+            signers.push((sender, sequence));
+        }
+
+        // The signatures are not verified during the simulation, the public keys are replaced by placeholders
+        let signer_infos = signers
+            .iter()
+            .map(|(_, sequence)| SignerInfo::single_direct(None, *sequence))
+            .collect();
+        let fee = TxBuilder::build_fee(0u8, &self.chain_info().gas_denom, 0, None)?;
+        let tx = TxRaw {
+            body_bytes: TxBuilder::build_body(msgs.clone(), None, 0).into_bytes()?,
+            auth_info_bytes: AuthInfo { signer_infos, fee }.into_bytes()?,
+            signatures: vec![vec![]; signers.len()],
+        };
+        let simulation = Node::new_async(self.channel())
+            ._simulate(tx.encode_to_vec())
+            .await?;
+
+        let gas_used = simulation
+            .gas_info
+            .map(|gas_info| gas_info.gas_used)
+            .unwrap_or_default();
+        // The `tx` events are emitted by the ante handler, for the whole transaction
+        let msg_events: Vec<Event> = simulation
+            .result
+            .map(|result| result.events)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|event| event.r#type != "tx")
+            .collect();
+        let events = msg_events
+            .get(batch.events..)
+            .map(<[Event]>::to_vec)
+            .unwrap_or_default();
+        let gas_estimate = gas_used.saturating_sub(batch.gas_used);
+
+        batch.msgs = msgs;
+        batch.signers = signers;
+        batch.gas_used = gas_used;
+        batch.events = msg_events.len();
+
+        Ok(DryRunResponse {
+            response: CosmTxResponse {
+                raw_log: "Transaction simulated, not broadcasted".to_string(),
+                gas_used: gas_estimate,
+                events,
+                ..Default::default()
+            },
+            gas_estimate: Some(gas_estimate),
+        })
+    }
+
+    fn dry_run_msg(
+        &self,
+        batch: &mut DryRunBatch,
+        msg: impl Msg,
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        let msg = msg.into_any().map_err(DaemonError::from)?;
+        self.dry_run_any(batch, msg)
+    }
+
+    fn dry_run_any(
+        &self,
+        batch: &mut DryRunBatch,
+        msg: Any,
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        self.rt_handle
+            .block_on(self.simulate_after(batch, msg))
+            .map_err(Into::into)
+    }
+}
+
+// The transactions are simulated on the node, the query-only daemon can't sign them
+impl DryRunEnv for QueryOnlyDaemon {
+    type Response = CosmTxResponse;
+    type ContractSource = WasmPath;
+    type Sender = Addr;
+    type Context = DryRunBatch;
+
+    fn dry_run_sender(&self, context: &DryRunBatch) -> Result<Addr, CwEnvError> {
+        Ok(Addr::unchecked(context.sender()?.to_string()))
+    }
+
+    fn set_dry_run_sender(&mut self, context: &mut DryRunBatch, sender: Addr) {
+        context.sender = Some(sender);
+    }
+
+    fn dry_run_upload<T: Uploadable>(
+        &self,
+        context: &mut DryRunBatch,
+        _contract_source: &T,
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        let wasm_path = <T as Uploadable>::wasm(self.chain_info());
+        let file_contents = std::fs::read(wasm_path.path())?;
+        let mut e = write::GzEncoder::new(Vec::new(), Compression::default());
+        e.write_all(&file_contents)?;
+        let store_msg = MsgStoreCode {
+            sender: context.sender()?,
+            wasm_byte_code: e.finish()?,
+            instantiate_permission: None,
+        };
+        self.dry_run_msg(context, store_msg)
+    }
+
+    fn dry_run_instantiate<I: Serialize + Debug>(
+        &self,
+        context: &mut DryRunBatch,
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        let init_msg = MsgInstantiateContract {
+            code_id,
+            label: Some(label.unwrap_or("instantiate_contract").to_string()),
+            admin: admin
+                .map(|a| AccountId::from_str(a.as_str()))
+                .transpose()
+                .map_err(DaemonError::from)?,
+            sender: context.sender()?,
+            msg: serde_json::to_vec(&init_msg)?,
+            funds: parse_cw_coins(coins)?,
+        };
+        self.dry_run_msg(context, init_msg)
+    }
+
+    fn dry_run_instantiate2<I: Serialize + Debug>(
+        &self,
+        context: &mut DryRunBatch,
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+        salt: Binary,
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        let init_msg = MsgInstantiateContract2 {
+            code_id,
+            label: label.unwrap_or("instantiate_contract").to_string(),
+            admin: admin.map(Into::into).unwrap_or_default(),
+            sender: context.sender()?.to_string(),
+            msg: serde_json::to_vec(&init_msg)?,
+            funds: proto_parse_cw_coins(coins)?,
+            salt: salt.to_vec(),
+            fix_msg: false,
+        };
+        self.dry_run_any(
+            context,
+            Any {
+                type_url: INSTANTIATE_2_TYPE_URL.to_string(),
+                value: init_msg.encode_to_vec(),
+            },
+        )
+    }
+
+    fn dry_run_execute<E: Serialize + Debug>(
+        &self,
+        context: &mut DryRunBatch,
+        exec_msg: &E,
+        coins: &[Coin],
+        contract_address: &Addr,
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        let exec_msg = MsgExecuteContract {
+            sender: context.sender()?,
+            contract: AccountId::from_str(contract_address.as_str()).map_err(DaemonError::from)?,
+            msg: serde_json::to_vec(&exec_msg)?,
+            funds: parse_cw_coins(coins)?,
+        };
+        self.dry_run_msg(context, exec_msg)
+    }
+
+    fn dry_run_migrate<M: Serialize + Debug>(
+        &self,
+        context: &mut DryRunBatch,
+        migrate_msg: &M,
+        new_code_id: u64,
+        contract_address: &Addr,
+    ) -> Result<DryRunResponse<CosmTxResponse>, CwEnvError> {
+        let migrate_msg = MsgMigrateContract {
+            sender: context.sender()?,
+            contract: AccountId::from_str(contract_address.as_str()).map_err(DaemonError::from)?,
+            msg: serde_json::to_vec(&migrate_msg)?,
+            code_id: new_code_id,
+        };
+        self.dry_run_msg(context, migrate_msg)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use cw_orch_networks::networks::JUNO_1;
@@ -0,0 +1,278 @@
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{process::Stdio, sync::Arc};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cosmrs::{
+    crypto::PublicKey,
+    tx::{SignDoc, SignerPublicKey},
+    AccountId,
+};
+use cw_orch_core::environment::ChainInfoOwned;
+use serde_json::{json, Value};
+#[cfg(unix)]
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::DaemonError;
+
+use super::signer::{Signer, SignerBuilder};
+
+/// How to reach the process that holds the key of a [`RemoteSigner`].
+#[derive(Clone, Debug)]
+pub enum RemoteSignerOptions {
+    /// Program started for every request, reading the request on its standard input and writing the response on its standard output
+    Command { program: String, args: Vec<String> },
+    /// Unix socket of a signing service, answering one request per connection
+    #[cfg(unix)]
+    Socket(PathBuf),
+}
+
+impl RemoteSignerOptions {
+    pub fn command(program: impl ToString, args: Vec<String>) -> Self {
+        Self::Command {
+            program: program.to_string(),
+            args,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn socket(path: impl Into<PathBuf>) -> Self {
+        Self::Socket(path.into())
+    }
+
+    /// Sends a request and returns the response, erroring if the signer answered with an error
+    async fn request(&self, request: Value) -> Result<Value, DaemonError> {
+        let mut request = serde_json::to_vec(&request)?;
+        request.push(b'\n');
+
+        let response = match self {
+            RemoteSignerOptions::Command { program, args } => {
+                let mut child = tokio::process::Command::new(program)
+                    .args(args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                let mut stdin = child.stdin.take().unwrap();
+                stdin.write_all(&request).await?;
+                drop(stdin);
+
+                let mut response = vec![];
+                child
+                    .stdout
+                    .take()
+                    .unwrap()
+                    .read_to_end(&mut response)
+                    .await?;
+                let status = child.wait().await?;
+                if !status.success() {
+                    return Err(DaemonError::StdErr(format!(
+                        "Remote signer {program} exited with {status}"
+                    )));
+                }
+                response
+            }
+            #[cfg(unix)]
+            RemoteSignerOptions::Socket(path) => {
+                let mut stream = tokio::net::UnixStream::connect(path).await?;
+                stream.write_all(&request).await?;
+                let mut response = String::new();
+                BufReader::new(stream).read_line(&mut response).await?;
+                response.into_bytes()
+            }
+        };
+
+        let response: Value = serde_json::from_slice(&response)?;
+        if let Some(error) = response.get("error") {
+            return Err(DaemonError::StdErr(format!("Remote signer error: {error}")));
+        }
+        Ok(response)
+    }
+}
+
+impl SignerBuilder for RemoteSignerOptions {
+    type Signer = RemoteSigner;
+
+    async fn build(
+        &self,
+        chain_info: &Arc<ChainInfoOwned>,
+        hd_index: Option<u32>,
+    ) -> Result<Self::Signer, DaemonError> {
+        let response = self
+            .request(json!({
+                "method": "public_key",
+                "chain_id": chain_info.chain_id,
+                "hd_index": hd_index
+            }))
+            .await?;
+        let public_key = PublicKey::from_json(&response["public_key"].to_string())?;
+
+        Ok(RemoteSigner {
+            options: self.clone(),
+            chain_id: chain_info.chain_id.clone(),
+            hd_index,
+            public_key,
+        })
+    }
+}
+
+/// Signer that delegates signing to another process, so the key never enters the memory of the script.
+///
+/// Requests and responses are JSON objects on a single line:
+/// - `{"method": "public_key", "chain_id": "<chain-id>", "hd_index": <index or null>}` is answered with
+///   `{"public_key": {"@type": "/cosmos.crypto.secp256k1.PubKey", "key": "<base64>"}}`.
+/// - `{"method": "sign", "chain_id": "<chain-id>", "hd_index": <index or null>, "sign_doc": "<base64>"}` is answered with
+///   `{"signature": "<base64>"}`, the signature of the protobuf encoded `SignDoc` (`SIGN_MODE_DIRECT`).
+///
+/// Failures are answered with `{"error": "<message>"}`.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    options: RemoteSignerOptions,
+    chain_id: String,
+    hd_index: Option<u32>,
+    public_key: PublicKey,
+}
+
+impl Signer for RemoteSigner {
+    type Options = RemoteSignerOptions;
+
+    fn account_id(&self, prefix: &str) -> Result<AccountId, DaemonError> {
+        Ok(self.public_key.account_id(prefix)?)
+    }
+
+    fn public_key(&self) -> Option<SignerPublicKey> {
+        Some(self.public_key.into())
+    }
+
+    async fn sign(&self, sign_doc: SignDoc) -> Result<Vec<u8>, DaemonError> {
+        let response = self
+            .options
+            .request(json!({
+                "method": "sign",
+                "chain_id": self.chain_id,
+                "hd_index": self.hd_index,
+                "sign_doc": STANDARD.encode(sign_doc.into_bytes()?)
+            }))
+            .await?;
+        let signature = response["signature"].as_str().ok_or_else(|| {
+            DaemonError::StdErr("Remote signer response has no signature".to_string())
+        })?;
+        Ok(STANDARD.decode(signature)?)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::sync::Arc;
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bitcoin::secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1};
+    use cosmrs::{
+        crypto::secp256k1::SigningKey,
+        tendermint::chain::Id,
+        tx::{SignDoc, SignerInfo},
+    };
+    use cw_orch_core::environment::ChainInfoOwned;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::UnixListener,
+    };
+
+    use super::RemoteSignerOptions;
+    use crate::{
+        networks::JUNO_1,
+        senders::signer::{Signer, SignerBuilder},
+        DaemonError, TxBuilder,
+    };
+
+    /// Signing service holding a key, answering the requests of the socket
+    fn serve(listener: UnixListener, key: SigningKey) {
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (reader, mut writer) = stream.into_split();
+                let mut request = String::new();
+                BufReader::new(reader)
+                    .read_line(&mut request)
+                    .await
+                    .unwrap();
+                let request: Value = serde_json::from_str(&request).unwrap();
+                let response = match request["method"].as_str().unwrap() {
+                    "public_key" => {
+                        json!({ "public_key": serde_json::from_str::<Value>(&key.public_key().to_json()).unwrap() })
+                    }
+                    "sign" => {
+                        let sign_doc = STANDARD
+                            .decode(request["sign_doc"].as_str().unwrap())
+                            .unwrap();
+                        json!({ "signature": STANDARD.encode(key.sign(&sign_doc).unwrap().to_vec()) })
+                    }
+                    _ => json!({ "error": "unknown method" }),
+                };
+                writer
+                    .write_all(format!("{response}\n").as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn remote_signer_signs_through_socket() -> anyhow::Result<()> {
+        let path = crate::gen_temp_file_path();
+        let key = SigningKey::random();
+        let public_key = key.public_key();
+        serve(UnixListener::bind(&path)?, key);
+
+        let chain_info = Arc::new(ChainInfoOwned::from(JUNO_1));
+        let signer = RemoteSignerOptions::socket(&path)
+            .build(&chain_info, None)
+            .await?;
+        assert_eq!(
+            signer.account_id("juno")?,
+            public_key.account_id("juno").map_err(DaemonError::from)?
+        );
+
+        let auth_info = SignerInfo::single_direct(Some(public_key), 0)
+            .auth_info(TxBuilder::build_fee(5000u128, "ujuno", 200_000, None)?);
+        let sign_doc = SignDoc::new(
+            &TxBuilder::build_body(vec![], None, 0),
+            &auth_info,
+            &Id::try_from("juno-1")?,
+            1,
+        )
+        .map_err(DaemonError::from)?;
+        let signature = signer.sign(sign_doc.clone()).await?;
+
+        let secp = Secp256k1::verification_only();
+        secp.verify_ecdsa(
+            &Message::from_slice(&Sha256::digest(
+                sign_doc.into_bytes().map_err(DaemonError::from)?,
+            ))?,
+            &Signature::from_compact(&signature)?,
+            &PublicKey::from_slice(&public_key.to_bytes())?,
+        )?;
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn remote_signer_errors_are_returned() {
+        let chain_info = Arc::new(ChainInfoOwned::from(JUNO_1));
+        let error = RemoteSignerOptions::command(
+            "sh",
+            vec![
+                "-c".to_string(),
+                r#"cat > /dev/null; echo '{"error": "keyring locked"}'"#.to_string(),
+            ],
+        )
+        .build(&chain_info, None)
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("keyring locked"));
+    }
+}
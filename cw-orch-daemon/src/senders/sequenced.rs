@@ -0,0 +1,330 @@
+use std::{future::Future, sync::Arc};
+
+use cosmrs::{proto::cosmos::base::abci::v1beta1::TxResponse, tx::Body, AccountId, Any};
+use cw_orch_core::{environment::ChainInfoOwned, log::transaction_target};
+use tokio::sync::Mutex;
+use tonic::transport::Channel;
+
+use crate::{
+    cosmos_modules::auth::BaseAccount, queriers::Node, tx_broadcaster::has_account_sequence_error,
+    CosmTxResponse, DaemonBase, DaemonError, TxBuilder,
+};
+
+use super::{
+    builder::SenderBuilder,
+    query::QuerySender,
+    signer::PrivateKeySigner,
+    tx::{BroadcastSender, TxSender},
+    CosmosOptions, CosmosSender, Signer,
+};
+
+/// Daemon that can broadcast transactions from several tasks at once, see [`SequencedSender`].
+pub type SequencedDaemon = DaemonBase<SequencedSender>;
+
+/// Options of the [`SequencedSender`], the options of the wrapped [`CosmosSender`].
+#[derive(Clone)]
+pub struct SequencedOptions<S: Signer = PrivateKeySigner>(pub(crate) CosmosOptions<S>);
+
+impl Default for SequencedOptions {
+    fn default() -> Self {
+        Self(CosmosOptions::default())
+    }
+}
+
+impl<S: Signer> From<CosmosOptions<S>> for SequencedOptions<S> {
+    fn from(options: CosmosOptions<S>) -> Self {
+        Self(options)
+    }
+}
+
+impl<S: Signer> SequencedOptions<S> {
+    pub fn new(options: CosmosOptions<S>) -> Self {
+        Self(options)
+    }
+}
+
+/// Account number and sequence of the next transaction of the account
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct AccountSequence {
+    account_number: u64,
+    sequence: u64,
+}
+
+/// Sequence of the next transaction of the account, shared by the clones of a [`SequencedSender`]
+#[derive(Clone, Default)]
+struct SequenceTracker(Arc<Mutex<Option<AccountSequence>>>);
+
+impl SequenceTracker {
+    /// Broadcasts a transaction with the next sequence, the sequence is held until the broadcast returns.
+    /// The sequence is queried with `query_account` when it isn't known, and resynced once when the node rejects it.
+    async fn broadcast<T, Q, B>(
+        &self,
+        address: &str,
+        query_account: impl Fn() -> Q,
+        broadcast: impl Fn(AccountSequence) -> B,
+    ) -> Result<T, DaemonError>
+    where
+        Q: Future<Output = Result<AccountSequence, DaemonError>>,
+        B: Future<Output = Result<T, DaemonError>>,
+    {
+        let mut account = self.0.lock().await;
+        let mut resynced = false;
+        loop {
+            let current = match *account {
+                Some(current) => current,
+                None => query_account().await?,
+            };
+
+            match broadcast(current).await {
+                Ok(response) => {
+                    *account = Some(AccountSequence {
+                        sequence: current.sequence + 1,
+                        ..current
+                    });
+                    return Ok(response);
+                }
+                // Transactions of the account were sent from somewhere else, we resync once
+                Err(e) if !resynced && has_account_sequence_error(&e.to_string()) => {
+                    *account = expected_sequence(&e.to_string()).map(|sequence| AccountSequence {
+                        sequence,
+                        ..current
+                    });
+                    log::warn!(
+                        target: &transaction_target(),
+                        "Account sequence {} rejected, resyncing the sequence of {}",
+                        current.sequence,
+                        address
+                    );
+                    resynced = true;
+                }
+                // The sequence wasn't used, it's kept for the next tx
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Sender that tracks the sequence of its account locally, so transactions can be committed concurrently.
+///
+/// Each transaction is signed with the next sequence and broadcasted while holding the sequence,
+/// it's released as soon as the node accepted the transaction in its mempool.
+/// Waiting for the inclusion of the transactions happens concurrently, so several transactions are included in the same block.
+///
+/// The sequence is queried from the node once, then only after the node rejected a transaction because of an account sequence mismatch.
+#[derive(Clone)]
+pub struct SequencedSender<S: Signer = PrivateKeySigner> {
+    pub sender: CosmosSender<S>,
+    account: SequenceTracker,
+}
+
+impl<S: Signer> SequencedSender<S> {
+    /// Sequence of the next transaction, if it was already queried from the node
+    pub async fn next_sequence(&self) -> Option<u64> {
+        self.account.0.lock().await.map(|account| account.sequence)
+    }
+
+    /// Forgets the local sequence, the next transaction queries it from the node
+    pub async fn reset_sequence(&self) {
+        *self.account.0.lock().await = None;
+    }
+
+    /// Signs the transaction with the next sequence and broadcasts it, returning once the node accepted it in its mempool
+    async fn broadcast_body(&self, body: Body) -> Result<TxResponse, DaemonError> {
+        let query_account = || async {
+            let BaseAccount {
+                account_number,
+                sequence,
+                ..
+            } = self.sender.base_account().await?;
+            Ok::<_, DaemonError>(AccountSequence {
+                account_number,
+                sequence,
+            })
+        };
+        let broadcast = |current: AccountSequence| {
+            let mut tx_builder = TxBuilder::new(body.clone());
+            tx_builder
+                .account_number(current.account_number)
+                .sequence(current.sequence);
+            // The account sequence errors are handled by the tracker, by resyncing the sequence
+            async move {
+                self.sender
+                    .options
+                    .tx_broadcaster(false)
+                    .broadcast(tx_builder, &self.sender)
+                    .await
+            }
+        };
+        self.account
+            .broadcast(&self.sender.pub_addr_str(), query_account, broadcast)
+            .await
+    }
+}
+
+/// Sequence expected by the node, from an error like `account sequence mismatch, expected 12, got 10: incorrect account sequence`
+fn expected_sequence(error: &str) -> Option<u64> {
+    let (_, expected) = error.split_once("expected ")?;
+    let end = expected
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(expected.len());
+    expected[..end].parse().ok()
+}
+
+impl<S: Signer> SenderBuilder for SequencedOptions<S> {
+    type Error = DaemonError;
+    type Sender = SequencedSender<S>;
+
+    async fn build(&self, chain_info: &Arc<ChainInfoOwned>) -> Result<Self::Sender, Self::Error> {
+        Ok(SequencedSender {
+            sender: self.0.build(chain_info).await?,
+            account: Default::default(),
+        })
+    }
+}
+
+impl<S: Signer> QuerySender for SequencedSender<S> {
+    type Error = DaemonError;
+    type Options = SequencedOptions<S>;
+
+    fn channel(&self) -> Channel {
+        self.sender.channel()
+    }
+}
+
+impl<S: Signer> TxSender for SequencedSender<S> {
+    async fn commit_tx_any(
+        &self,
+        msgs: Vec<Any>,
+        memo: Option<&str>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        let timeout_height = Node::new_async(self.channel())._block_height().await? + 10u64;
+        let body = TxBuilder::build_body(self.sender.authz_msgs(msgs), memo, timeout_height);
+
+        let tx_response = self.broadcast_body(body).await?;
+
+        // Waiting for the confirmation happens outside of the sequence lock
+        self.sender
+            .options
+            .confirmation
+            .confirm(
+                self.channel(),
+                self.sender.chain_info.rpc_urls.first().cloned(),
+                tx_response,
+            )
+            .await
+    }
+
+    fn account_id(&self) -> AccountId {
+        self.sender.account_id()
+    }
+}
+
+impl<S: Signer> BroadcastSender for SequencedSender<S> {
+    async fn broadcast_tx_builder(
+        &self,
+        tx_builder: &mut TxBuilder,
+    ) -> Result<TxResponse, DaemonError> {
+        self.sender.broadcast_tx_builder(tx_builder).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
+    use tokio::sync::Mutex;
+
+    use super::{expected_sequence, AccountSequence, SequenceTracker};
+    use crate::DaemonError;
+
+    #[test]
+    fn parse_expected_sequence() {
+        assert_eq!(
+            expected_sequence(
+                "tx failed: account sequence mismatch, expected 12, got 10: incorrect account sequence with code 32"
+            ),
+            Some(12)
+        );
+        assert_eq!(expected_sequence("incorrect account sequence"), None);
+    }
+
+    /// Node accepting the transactions signed with the sequence of the account
+    #[derive(Default)]
+    struct MockNode {
+        sequence: Mutex<u64>,
+        accepted: Mutex<Vec<u64>>,
+        account_queries: AtomicU64,
+        rejections: AtomicU64,
+    }
+
+    impl MockNode {
+        async fn query_account(&self) -> Result<AccountSequence, DaemonError> {
+            self.account_queries.fetch_add(1, Ordering::SeqCst);
+            Ok(AccountSequence {
+                account_number: 1,
+                sequence: *self.sequence.lock().await,
+            })
+        }
+
+        async fn broadcast(&self, account: AccountSequence) -> Result<u64, DaemonError> {
+            // Lets the other commits run between the signature and the broadcast
+            tokio::task::yield_now().await;
+            let mut sequence = self.sequence.lock().await;
+            if account.sequence != *sequence {
+                self.rejections.fetch_add(1, Ordering::SeqCst);
+                return Err(DaemonError::TxFailed {
+                    code: 32,
+                    reason: format!(
+                        "account sequence mismatch, expected {}, got {}: incorrect account sequence",
+                        *sequence, account.sequence
+                    ),
+                });
+            }
+            *sequence += 1;
+            self.accepted.lock().await.push(account.sequence);
+            Ok(account.sequence)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_commits_use_consecutive_sequences() -> anyhow::Result<()> {
+        let node = Arc::new(MockNode::default());
+        let tracker = SequenceTracker::default();
+
+        let commit = |tracker: SequenceTracker, node: Arc<MockNode>| async move {
+            tracker
+                .broadcast(
+                    "juno1sender",
+                    || node.query_account(),
+                    |account| node.broadcast(account),
+                )
+                .await
+        };
+        assert_eq!(commit(tracker.clone(), node.clone()).await?, 0);
+
+        // A transaction of the account is sent from somewhere else
+        *node.sequence.lock().await += 1;
+
+        let commits = (0..10)
+            .map(|_| tokio::spawn(commit(tracker.clone(), node.clone())))
+            .collect::<Vec<_>>();
+        let mut sequences = vec![];
+        for commit in commits {
+            sequences.push(commit.await??);
+        }
+        sequences.sort();
+        assert_eq!(sequences, (2..12).collect::<Vec<_>>());
+        assert_eq!(
+            *node.accepted.lock().await,
+            [vec![0], (2..12).collect()].concat()
+        );
+
+        // The sequence is queried once, and resynced once from the rejection of the node
+        assert_eq!(node.account_queries.load(Ordering::SeqCst), 1);
+        assert_eq!(node.rejections.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+}
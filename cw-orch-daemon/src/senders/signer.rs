@@ -0,0 +1,205 @@
+use std::{path::PathBuf, sync::Arc};
+
+use bitcoin::secp256k1::{All, Secp256k1};
+use cosmrs::{
+    crypto::secp256k1::SigningKey,
+    tx::{SignDoc, SignerPublicKey},
+    AccountId,
+};
+use cw_orch_core::{
+    environment::{ChainInfoOwned, ChainKind},
+    CwEnvError,
+};
+
+use crate::{
+    env::{DaemonEnvVars, LOCAL_MNEMONIC_ENV_NAME, MAIN_MNEMONIC_ENV_NAME, TEST_MNEMONIC_ENV_NAME},
+    keys::{keyring::FileKeyring, private::PrivateKey},
+    proto::injective::ETHEREUM_COIN_TYPE,
+    DaemonError,
+};
+#[cfg(feature = "eth")]
+use {crate::proto::injective::InjectiveSigner, cosmrs::proto::cosmos::tx::v1beta1::TxRaw};
+
+/// Signs the transactions of a [`CosmosSender`](super::CosmosSender).
+///
+/// The signer doesn't need to hold the key: signing can be delegated to another process, see [`RemoteSigner`](super::RemoteSigner).
+pub trait Signer: Clone + Send + Sync {
+    /// Options used to build this signer
+    type Options: SignerBuilder<Signer = Self>;
+
+    /// Returns the `AccountId` of the signer on a chain with the given address prefix.
+    fn account_id(&self, prefix: &str) -> Result<AccountId, DaemonError>;
+
+    /// Returns the public key set in the signer info of the transactions.
+    fn public_key(&self) -> Option<SignerPublicKey>;
+
+    /// Signs the transaction and returns the signature.
+    fn sign(
+        &self,
+        sign_doc: SignDoc,
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, DaemonError>> + Send;
+}
+
+/// Allows building a [`Signer`] from its options, when the sender is built.
+pub trait SignerBuilder: Clone + Send + Sync {
+    type Signer: Signer<Options = Self>;
+
+    /// Build a new `Signer` for the chain, with the optional HD index of the key.
+    fn build(
+        &self,
+        chain_info: &Arc<ChainInfoOwned>,
+        hd_index: Option<u32>,
+    ) -> impl std::future::Future<Output = Result<Self::Signer, DaemonError>> + Send;
+}
+
+/// Source of the private key of the default [`PrivateKeySigner`].
+#[derive(Default, Clone)]
+pub enum PrivateKeyOptions {
+    Mnemonic(String),
+    RawKey(Vec<u8>),
+    /// Key `name` of a Cosmos-SDK keyring directory written with `--keyring-backend file`, see [`FileKeyring`].
+    /// The keyring only holds the derived private key, so the key can't be used with an HD index.
+    Keyring {
+        dir: PathBuf,
+        name: String,
+    },
+    /// Mnemonic read from the environment variable of the chain kind
+    #[default]
+    Env,
+}
+
+impl SignerBuilder for PrivateKeyOptions {
+    type Signer = PrivateKeySigner;
+
+    async fn build(
+        &self,
+        chain_info: &Arc<ChainInfoOwned>,
+        hd_index: Option<u32>,
+    ) -> Result<Self::Signer, DaemonError> {
+        if let (PrivateKeyOptions::Keyring { name, .. }, Some(index)) = (self, hd_index) {
+            return Err(DaemonError::Keyring(format!(
+                "The HD index {index} can't be set for the keyring key {name}, the keyring only holds the derived private key"
+            )));
+        }
+
+        let secp = Secp256k1::new();
+        let hd_index = hd_index.unwrap_or(0);
+        let coin_type = chain_info.network_info.coin_type;
+
+        let private_key = match self {
+            PrivateKeyOptions::Mnemonic(mnemonic) => {
+                PrivateKey::from_words(&secp, mnemonic, 0, hd_index, coin_type)?
+            }
+            PrivateKeyOptions::Env => {
+                let mnemonic = get_mnemonic_env(&chain_info.kind)?;
+                PrivateKey::from_words(&secp, &mnemonic, 0, hd_index, coin_type)?
+            }
+            PrivateKeyOptions::RawKey(bytes) => {
+                PrivateKey::from_raw_key(&secp, bytes, 0, hd_index, coin_type)?
+            }
+            PrivateKeyOptions::Keyring { dir, name } => {
+                let secret_key = FileKeyring::unlock(dir)?.secret_key(name)?;
+                PrivateKey::from_secret_key(&secret_key, coin_type)?
+            }
+        };
+
+        Ok(PrivateKeySigner { private_key, secp })
+    }
+}
+
+/// Default signer, holding the private key in memory.
+#[derive(Clone)]
+pub struct PrivateKeySigner {
+    pub private_key: PrivateKey,
+    pub secp: Secp256k1<All>,
+}
+
+impl Signer for PrivateKeySigner {
+    type Options = PrivateKeyOptions;
+
+    fn account_id(&self, prefix: &str) -> Result<AccountId, DaemonError> {
+        Ok(AccountId::new(
+            prefix,
+            &self.private_key.public_key(&self.secp).raw_address.unwrap(),
+        )?)
+    }
+
+    fn public_key(&self) -> Option<SignerPublicKey> {
+        self.private_key.get_signer_public_key(&self.secp)
+    }
+
+    async fn sign(&self, sign_doc: SignDoc) -> Result<Vec<u8>, DaemonError> {
+        if self.private_key.coin_type == ETHEREUM_COIN_TYPE {
+            #[cfg(not(feature = "eth"))]
+            return Err(DaemonError::UnsupportedCoinType(ETHEREUM_COIN_TYPE));
+            #[cfg(feature = "eth")]
+            return Ok(TxRaw::from(self.private_key.sign_injective(sign_doc)?)
+                .signatures
+                .remove(0));
+        }
+        let signing_key = SigningKey::from_slice(&self.private_key.raw_key())?;
+        Ok(signing_key.sign(&sign_doc.into_bytes()?)?.to_vec())
+    }
+}
+
+fn get_mnemonic_env(chain_kind: &ChainKind) -> Result<String, CwEnvError> {
+    match chain_kind {
+        ChainKind::Local => DaemonEnvVars::local_mnemonic(),
+        ChainKind::Testnet => DaemonEnvVars::test_mnemonic(),
+        ChainKind::Mainnet => DaemonEnvVars::main_mnemonic(),
+    }
+    .ok_or(CwEnvError::EnvVarNotPresentNamed(
+        get_mnemonic_env_name(chain_kind).to_string(),
+    ))
+}
+
+fn get_mnemonic_env_name(chain_kind: &ChainKind) -> &str {
+    match chain_kind {
+        ChainKind::Local => LOCAL_MNEMONIC_ENV_NAME,
+        ChainKind::Testnet => TEST_MNEMONIC_ENV_NAME,
+        ChainKind::Mainnet => MAIN_MNEMONIC_ENV_NAME,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::networks::LOCAL_JUNO;
+
+    use super::*;
+
+    const LOCAL_MNEMONIC: &str = "clip hire initial neck maid actor venue client foam budget lock catalog sweet steak waste crater broccoli pipe steak sister coyote moment obvious choose";
+
+    #[tokio::test]
+    async fn keyring_keys_reject_hd_index() {
+        let key = PrivateKeyOptions::Keyring {
+            dir: concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures").into(),
+            name: "validator".to_string(),
+        };
+        let error = key
+            .build(&Arc::new(LOCAL_JUNO.into()), Some(1))
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("The HD index 1 can't be set"));
+    }
+
+    #[cfg(not(feature = "eth"))]
+    #[tokio::test]
+    async fn ethereum_keys_need_the_eth_feature() {
+        let secp = Secp256k1::new();
+        let private_key =
+            PrivateKey::from_words(&secp, LOCAL_MNEMONIC, 0, 0, ETHEREUM_COIN_TYPE).unwrap();
+        let signer = PrivateKeySigner { private_key, secp };
+        let sign_doc = SignDoc {
+            body_bytes: vec![],
+            auth_info_bytes: vec![],
+            chain_id: LOCAL_JUNO.chain_id.to_string(),
+            account_number: 1,
+        };
+        let error = signer.sign(sign_doc).await.unwrap_err();
+        assert!(matches!(
+            error,
+            DaemonError::UnsupportedCoinType(ETHEREUM_COIN_TYPE)
+        ));
+    }
+}
@@ -1,7 +1,7 @@
-use cosmrs::{tx::Msg, AccountId, Any};
+use cosmrs::{proto::cosmos::base::abci::v1beta1::TxResponse, tx::Msg, AccountId, Any};
 use cosmwasm_std::Addr;
 
-use crate::CosmTxResponse;
+use crate::{CosmTxResponse, DaemonError, TxBuilder};
 
 use super::query::QuerySender;
 
@@ -36,3 +36,14 @@ pub trait TxSender: QuerySender {
         self.commit_tx_any(msgs, memo)
     }
 }
+
+/// A sender that signs and broadcasts the transactions of a [`TxBuilder`].
+/// Allows retrying its transactions with the strategies of a [`TxBroadcaster`](crate::tx_broadcaster::TxBroadcaster).
+/// Custom senders implement it to reuse the retry strategies in their [`TxSender::commit_tx_any`].
+pub trait BroadcastSender: TxSender {
+    /// Builds and signs the transaction, then broadcasts it without waiting for its inclusion in a block.
+    fn broadcast_tx_builder(
+        &self,
+        tx_builder: &mut TxBuilder,
+    ) -> impl std::future::Future<Output = Result<TxResponse, DaemonError>> + Send;
+}
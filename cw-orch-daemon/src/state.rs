@@ -1,5 +1,6 @@
 use super::error::DaemonError;
 use crate::env::{default_state_folder, DaemonEnvVars};
+use crate::journal::{JournalCursor, JournalStep, JournalStepKind, JOURNAL_KEY};
 use crate::{json_lock::JsonLockedState, networks::ChainKind};
 
 use cosmwasm_std::Addr;
@@ -31,6 +32,8 @@ pub struct DaemonState {
     pub chain_data: Arc<ChainInfoOwned>,
     /// Whether to write on every change of the state
     pub write_on_change: bool,
+    /// Deployment journal of the current run, if journaling is enabled
+    pub(crate) journal: Option<JournalCursor>,
 }
 
 impl Drop for DaemonState {
@@ -117,6 +120,7 @@ impl DaemonState {
             deployment_id,
             chain_data: chain_data.clone(),
             write_on_change,
+            journal: None,
         })
     }
 
@@ -238,6 +242,103 @@ impl DaemonState {
         Ok(())
     }
 
+    /// Whether the uploads, instantiations and migrations sent with this state are recorded in the deployment journal
+    pub fn is_journaled(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Steps recorded in the journal of the current deployment on this chain, in execution order
+    pub fn journal(&self) -> Result<Vec<JournalStep>, DaemonError> {
+        self.deployment_journal(&self.deployment_id)
+    }
+
+    /// Steps recorded in the journal of the given deployment on this chain, in execution order
+    pub fn deployment_journal(&self, deployment_id: &str) -> Result<Vec<JournalStep>, DaemonError> {
+        let steps = self.get(JOURNAL_KEY)?[deployment_id]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+        let mut steps = steps
+            .into_values()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<JournalStep>, _>>()?;
+        steps.sort_by_key(|step| step.index);
+        Ok(steps)
+    }
+
+    /// Removes the journal of the current deployment on this chain.
+    /// The next run of the deployment sends every step again.
+    pub fn reset_journal(&mut self) -> Result<(), DaemonError> {
+        let deployment_id = self.deployment_id.clone();
+        self.reset_deployment_journal(&deployment_id)
+    }
+
+    /// Removes the journal of the given deployment on this chain
+    pub fn reset_deployment_journal(&mut self, deployment_id: &str) -> Result<(), DaemonError> {
+        self.remove(JOURNAL_KEY, deployment_id)?;
+        if let Some(journal) = &self.journal {
+            journal.reset(&self.journal_scope(deployment_id));
+        }
+        Ok(())
+    }
+
+    /// Returns the journal key of the next step with this content, if journaling is enabled
+    pub(crate) fn journal_key(
+        &self,
+        kind: JournalStepKind,
+        content: &impl Serialize,
+    ) -> Result<Option<String>, DaemonError> {
+        let Some(journal) = &self.journal else {
+            return Ok(None);
+        };
+        Ok(Some(journal.next_key(
+            &self.journal_scope(&self.deployment_id),
+            kind,
+            &serde_json::to_vec(content)?,
+        )))
+    }
+
+    /// Step of the current deployment recorded under this key
+    pub(crate) fn journal_step(&self, key: &str) -> Result<Option<JournalStep>, DaemonError> {
+        let step = self.get(JOURNAL_KEY)?[&self.deployment_id][key].clone();
+        if step.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(step)?))
+    }
+
+    /// Records a step of the current deployment under this key.
+    /// A step that was already recorded keeps its position in the deployment.
+    pub(crate) fn record_journal_step(
+        &mut self,
+        key: &str,
+        kind: JournalStepKind,
+        tx_hash: &str,
+        code_id: Option<u64>,
+        contract_address: Option<String>,
+    ) -> Result<(), DaemonError> {
+        let index = match self.journal_step(key)? {
+            Some(step) => step.index,
+            None => self.journal()?.len(),
+        };
+        let step = JournalStep {
+            index,
+            kind,
+            tx_hash: tx_hash.to_string(),
+            code_id,
+            contract_address,
+        };
+        let mut steps = self.get(JOURNAL_KEY)?[&self.deployment_id].clone();
+        steps[key] = json!(step);
+        let deployment_id = self.deployment_id.clone();
+        self.set(JOURNAL_KEY, &deployment_id, steps)
+    }
+
+    /// Scope of the journal steps of a deployment on this chain
+    fn journal_scope(&self, deployment_id: &str) -> String {
+        format!("{}/{deployment_id}", self.chain_data.chain_id)
+    }
+
     /// Flushes all the state related to the current chain
     /// Only works on Local networks
     pub fn flush(&mut self) -> Result<(), DaemonError> {
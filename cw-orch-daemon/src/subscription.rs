@@ -0,0 +1,438 @@
+//! Subscriptions to the events of a chain, over the websocket of its Tendermint RPC endpoint.
+//!
+//! The [queries](https://docs.cometbft.com/v0.38/core/subscription) select the events with conditions on their attributes,
+//! e.g. `tm.event='Tx' AND wasm._contract_address='juno1...'` for the transactions executing a contract.
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+use async_tungstenite::{
+    tokio::{connect_async, ConnectStream},
+    tungstenite::Message,
+    WebSocketStream,
+};
+use cosmrs::tendermint::Block;
+use futures_util::{stream, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    transport::{decode_base64, ExecTxResult},
+    CosmTxResponse, DaemonError,
+};
+
+/// Query of the new blocks of the chain
+pub const NEW_BLOCK_QUERY: &str = "tm.event='NewBlock'";
+/// Query of the transactions of the chain, conditions on their events can be added
+pub const TX_QUERY: &str = "tm.event='Tx'";
+
+const TX_EVENT: &str = "tendermint/event/Tx";
+const NEW_BLOCK_EVENT: &str = "tendermint/event/NewBlock";
+
+/// Query of the transaction with the given hash, to wait for its inclusion
+pub fn tx_hash_query(hash: &str) -> String {
+    format!("{TX_QUERY} AND tx.hash='{}'", hash.to_uppercase())
+}
+
+/// Event received from a subscription
+#[derive(Debug, Clone)]
+pub enum SubscriptionEvent {
+    /// Transaction included in a block.
+    /// The timestamp of the block isn't part of the event, it's left to its default value.
+    Tx(CosmTxResponse),
+    /// New block of the chain
+    NewBlock(Box<Block>),
+    /// Other event, with its type and value as sent by the node
+    Other(Value),
+}
+
+/// Stream of the events of a subscription, the subscription ends when the stream is dropped
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<SubscriptionEvent, DaemonError>> + Send>>;
+
+/// Websocket url of a Tendermint RPC endpoint
+pub fn websocket_url(rpc_url: &str) -> String {
+    let url = rpc_url.trim_end_matches('/');
+    let url = if let Some(host) = url.strip_prefix("https://") {
+        format!("wss://{host}")
+    } else if let Some(host) = url.strip_prefix("http://") {
+        format!("ws://{host}")
+    } else {
+        url.to_string()
+    };
+    if url.ends_with("/websocket") {
+        url
+    } else {
+        format!("{url}/websocket")
+    }
+}
+
+/// Subscribes to the events matching `query` on the Tendermint RPC endpoint at `rpc_url`
+pub async fn subscribe(rpc_url: &str, query: &str) -> Result<EventStream, DaemonError> {
+    let (mut socket, _) = connect_async(websocket_url(rpc_url)).await?;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "subscribe",
+        "params": { "query": query },
+    });
+    socket.send(Message::Text(request.to_string())).await?;
+
+    // The node confirms the subscription before sending its events
+    match next_response(&mut socket).await? {
+        Some(RpcMessage {
+            error: Some(error), ..
+        }) => return Err(DaemonError::StdErr(format!("Subscription failed: {error}"))),
+        Some(_) => {}
+        None => {
+            return Err(DaemonError::StdErr(
+                "Subscription closed by the node".to_string(),
+            ))
+        }
+    }
+
+    Ok(Box::pin(stream::unfold(
+        Some(socket),
+        |socket| async move {
+            let mut socket = socket?;
+            match next_event(&mut socket).await {
+                Ok(Some(event)) => Some((Ok(event), Some(socket))),
+                Ok(None) => None,
+                // The subscription ends after an error
+                Err(e) => Some((Err(e), None)),
+            }
+        },
+    )))
+}
+
+/// Waits for a block at `height` or later over the websocket of the Tendermint RPC endpoint at `rpc_url`.
+/// Returns `false` when the subscription fails or ends, or when the block isn't received within `timeout`
+pub(crate) async fn wait_for_height(rpc_url: &str, height: u64, timeout: Duration) -> bool {
+    let wait = async {
+        let Ok(mut blocks) = subscribe(rpc_url, NEW_BLOCK_QUERY).await else {
+            return false;
+        };
+        while let Some(Ok(event)) = blocks.next().await {
+            if let SubscriptionEvent::NewBlock(block) = event {
+                if block.header.height.value() >= height {
+                    return true;
+                }
+            }
+        }
+        false
+    };
+    tokio::time::timeout(timeout, wait).await.unwrap_or(false)
+}
+
+/// Waits for the inclusion of the transaction `hash` over the websocket of the Tendermint RPC endpoint at `rpc_url`.
+/// `included` is awaited once subscribed, for the transactions included before the subscription.
+/// Returns `false` when the subscription fails or ends, or when the transaction isn't received within `timeout`
+pub(crate) async fn wait_for_tx(
+    rpc_url: &str,
+    hash: &str,
+    timeout: Duration,
+    included: impl Future<Output = bool>,
+) -> bool {
+    let wait = async {
+        let Ok(mut txs) = subscribe(rpc_url, &tx_hash_query(hash)).await else {
+            return false;
+        };
+        if included.await {
+            return true;
+        }
+        while let Some(Ok(event)) = txs.next().await {
+            if let SubscriptionEvent::Tx(tx) = event {
+                if tx.txhash.eq_ignore_ascii_case(hash) {
+                    return true;
+                }
+            }
+        }
+        false
+    };
+    tokio::time::timeout(timeout, wait).await.unwrap_or(false)
+}
+
+#[derive(Deserialize)]
+struct RpcMessage {
+    result: Option<Value>,
+    error: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct TxEvent {
+    #[serde(rename = "TxResult")]
+    tx_result: TxEventResult,
+}
+
+#[derive(Deserialize)]
+struct TxEventResult {
+    height: String,
+    tx: String,
+    result: ExecTxResult,
+}
+
+#[derive(Deserialize)]
+struct NewBlockEvent {
+    block: Block,
+}
+
+/// Next JSON-RPC message of the websocket, `None` once it's closed
+async fn next_response(
+    socket: &mut WebSocketStream<ConnectStream>,
+) -> Result<Option<RpcMessage>, DaemonError> {
+    while let Some(message) = socket.next().await {
+        match message? {
+            Message::Text(text) => return Ok(Some(serde_json::from_str(&text)?)),
+            Message::Close(_) => return Ok(None),
+            // The pings are answered by the socket
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+async fn next_event(
+    socket: &mut WebSocketStream<ConnectStream>,
+) -> Result<Option<SubscriptionEvent>, DaemonError> {
+    while let Some(message) = next_response(socket).await? {
+        if let Some(error) = message.error {
+            return Err(DaemonError::StdErr(format!("Subscription error: {error}")));
+        }
+        if let Some(data) = message
+            .result
+            .and_then(|mut result| result.get_mut("data").map(Value::take))
+        {
+            return decode_event(data).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn decode_event(data: Value) -> Result<SubscriptionEvent, DaemonError> {
+    let event = match data["type"].as_str() {
+        Some(TX_EVENT) => {
+            let event: TxEvent = serde_json::from_value(data["value"].clone())?;
+            SubscriptionEvent::Tx(tx_response(event.tx_result)?)
+        }
+        Some(NEW_BLOCK_EVENT) => {
+            let event: NewBlockEvent = serde_json::from_value(data["value"].clone())?;
+            SubscriptionEvent::NewBlock(Box::new(event.block))
+        }
+        _ => SubscriptionEvent::Other(data),
+    };
+    Ok(event)
+}
+
+fn tx_response(tx_result: TxEventResult) -> Result<CosmTxResponse, DaemonError> {
+    let tx = decode_base64(&tx_result.tx)?;
+    let result = tx_result.result;
+    Ok(CosmTxResponse {
+        height: tx_result.height.parse()?,
+        txhash: hex::encode_upper(Sha256::digest(tx)),
+        codespace: result.codespace,
+        code: result.code as usize,
+        data: hex::encode_upper(decode_base64(&result.data.unwrap_or_default())?),
+        raw_log: result.log,
+        info: result.info,
+        gas_wanted: result.gas_wanted as u64,
+        gas_used: result.gas_used as u64,
+        events: result.events.into_iter().map(Into::into).collect(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_tungstenite::tungstenite::Message;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use cw_orch_core::environment::IndexResponse;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+
+    use std::time::Duration;
+
+    use super::{
+        subscribe, tx_hash_query, wait_for_height, wait_for_tx, websocket_url, SubscriptionEvent,
+    };
+
+    const EMPTY_HASH: &str = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+
+    /// Serves a websocket accepting a single subscription and sending its events, returns the RPC url of the node.
+    /// The websocket is closed after the events unless it `stalls`
+    async fn serve(events: Vec<Value>, stalls: bool) -> anyhow::Result<String> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = format!("http://{}", listener.local_addr()?);
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = async_tungstenite::tokio::accept_async(stream)
+                .await
+                .unwrap();
+            let request = socket.next().await.unwrap().unwrap().into_text().unwrap();
+            let request: Value = serde_json::from_str(&request).unwrap();
+            assert_eq!(request["method"], "subscribe");
+
+            let query = request["params"]["query"].clone();
+            let mut messages = vec![json!({ "jsonrpc": "2.0", "id": 0, "result": {} })];
+            messages.extend(events.into_iter().map(|data| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 0,
+                    "result": { "query": query, "data": data, "events": {} }
+                })
+            }));
+            for message in messages {
+                socket
+                    .send(Message::Text(message.to_string()))
+                    .await
+                    .unwrap();
+            }
+            if stalls {
+                std::future::pending::<()>().await;
+            }
+            socket.close(None).await.unwrap();
+        });
+        Ok(address)
+    }
+
+    fn tx_event(tx: &[u8]) -> Value {
+        json!({
+            "type": "tendermint/event/Tx",
+            "value": {
+                "TxResult": {
+                    "height": "7",
+                    "index": 0,
+                    "tx": STANDARD.encode(tx),
+                    "result": {
+                        "gas_wanted": "200000",
+                        "gas_used": "120000",
+                        "events": [{
+                            "type": "wasm",
+                            "attributes": [{ "key": "_contract_address", "value": "juno1contract", "index": true }]
+                        }]
+                    }
+                }
+            }
+        })
+    }
+
+    fn new_block_event() -> Value {
+        let block_id = json!({ "hash": EMPTY_HASH, "parts": { "total": 1, "hash": EMPTY_HASH } });
+        json!({
+            "type": "tendermint/event/NewBlock",
+            "value": {
+                "block": {
+                    "header": {
+                        "version": { "block": "11", "app": "0" },
+                        "chain_id": "juno-1",
+                        "height": "1",
+                        "time": "2024-01-01T00:00:00Z",
+                        "last_block_id": null,
+                        "last_commit_hash": EMPTY_HASH,
+                        "data_hash": EMPTY_HASH,
+                        "validators_hash": EMPTY_HASH,
+                        "next_validators_hash": EMPTY_HASH,
+                        "consensus_hash": EMPTY_HASH,
+                        "app_hash": EMPTY_HASH,
+                        "last_results_hash": EMPTY_HASH,
+                        "evidence_hash": EMPTY_HASH,
+                        "proposer_address": "0000000000000000000000000000000000000000"
+                    },
+                    "data": { "txs": [] },
+                    "evidence": { "evidence": [] },
+                    "last_commit": null
+                },
+                "block_id": block_id
+            }
+        })
+    }
+
+    #[test]
+    fn websocket_urls() {
+        assert_eq!(
+            websocket_url("https://rpc.juno.example:443/"),
+            "wss://rpc.juno.example:443/websocket"
+        );
+        assert_eq!(
+            websocket_url("http://localhost:26657"),
+            "ws://localhost:26657/websocket"
+        );
+        assert_eq!(
+            websocket_url("ws://localhost:26657/websocket"),
+            "ws://localhost:26657/websocket"
+        );
+        assert_eq!(tx_hash_query("abcd"), "tm.event='Tx' AND tx.hash='ABCD'");
+    }
+
+    #[tokio::test]
+    async fn receives_decoded_events() -> anyhow::Result<()> {
+        let tx = b"tx bytes";
+        let other = json!({ "type": "tendermint/event/ValidatorSetUpdates", "value": {} });
+        let address = serve(vec![tx_event(tx), new_block_event(), other.clone()], false).await?;
+
+        let mut events = subscribe(&address, "tm.event='Tx'").await?;
+
+        let Some(Ok(SubscriptionEvent::Tx(tx_response))) = events.next().await else {
+            panic!("expected a tx event");
+        };
+        assert_eq!(tx_response.txhash, hex::encode_upper(Sha256::digest(tx)));
+        assert_eq!(tx_response.height, 7);
+        assert_eq!(tx_response.gas_used, 120000);
+        assert_eq!(
+            tx_response.event_attr_value("wasm", "_contract_address")?,
+            "juno1contract"
+        );
+
+        let Some(Ok(SubscriptionEvent::NewBlock(block))) = events.next().await else {
+            panic!("expected a new block event");
+        };
+        assert_eq!(block.header.height.value(), 1);
+
+        let Some(Ok(SubscriptionEvent::Other(event))) = events.next().await else {
+            panic!("expected another event");
+        };
+        assert_eq!(event, other);
+
+        // The stream ends once the node closes the websocket
+        assert!(events.next().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn waits_for_heights_until_the_timeout() -> anyhow::Result<()> {
+        let timeout = Duration::from_secs(5);
+        let address = serve(vec![new_block_event()], true).await?;
+        assert!(wait_for_height(&address, 1, timeout).await);
+
+        // The stalled websocket doesn't send the next block
+        let address = serve(vec![new_block_event()], true).await?;
+        let start = tokio::time::Instant::now();
+        assert!(!wait_for_height(&address, 2, Duration::from_millis(200)).await);
+        assert!(start.elapsed() < timeout);
+
+        // Neither does the closed one
+        let address = serve(vec![new_block_event()], false).await?;
+        assert!(!wait_for_height(&address, 2, timeout).await);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn waits_for_txs_until_the_timeout() -> anyhow::Result<()> {
+        let timeout = Duration::from_secs(5);
+        let tx = b"tx bytes";
+        let hash = hex::encode(Sha256::digest(tx));
+
+        let address = serve(vec![tx_event(tx)], true).await?;
+        assert!(wait_for_tx(&address, &hash, timeout, async { false }).await);
+
+        // The tx included before the subscription is found by `included`
+        let address = serve(vec![], true).await?;
+        assert!(wait_for_tx(&address, &hash, timeout, async { true }).await);
+
+        // The stalled websocket doesn't send the tx
+        let address = serve(vec![tx_event(b"other tx")], true).await?;
+        let start = tokio::time::Instant::now();
+        assert!(!wait_for_tx(&address, &hash, Duration::from_millis(200), async { false }).await);
+        assert!(start.elapsed() < timeout);
+        Ok(())
+    }
+}
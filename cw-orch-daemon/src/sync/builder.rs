@@ -1,6 +1,8 @@
 use crate::senders::builder::SenderBuilder;
 
-use crate::{DaemonAsyncBuilder, DaemonBase, DaemonState, Wallet, RUNTIME};
+use crate::{
+    confirmation::Confirmation, DaemonAsyncBuilder, DaemonBase, DaemonState, Wallet, RUNTIME,
+};
 use cw_orch_core::environment::ChainInfoOwned;
 
 use super::super::error::DaemonError;
@@ -27,10 +29,12 @@ pub struct DaemonBuilder {
     // State from rebuild or existing daemon
     pub(crate) state: Option<DaemonState>,
     pub(crate) write_on_change: Option<bool>,
+    pub(crate) journal: Option<bool>,
     // # Use tempfile as state
     pub(crate) is_test: bool,
 
     pub(crate) mnemonic: Option<String>,
+    pub(crate) confirmation: Option<Confirmation>,
 }
 
 impl DaemonBuilder {
@@ -42,7 +46,9 @@ impl DaemonBuilder {
             state_path: None,
             state: None,
             write_on_change: None,
+            journal: None,
             mnemonic: None,
+            confirmation: None,
             is_test: false,
         }
     }
@@ -78,6 +84,14 @@ impl DaemonBuilder {
         self
     }
 
+    /// Overwrites the endpoints used to interact with the chain with a Tendermint RPC url.
+    /// The node is then reached without gRPC, see [`RpcTransport`](crate::transport::RpcTransport)
+    pub fn rpc_url(&mut self, url: impl Into<String>) -> &mut Self {
+        self.chain.grpc_urls = vec![];
+        self.chain.rpc_urls = vec![url.into()];
+        self
+    }
+
     /// Set the mnemonic used for the default Cosmos wallet
     pub fn mnemonic(&mut self, mnemonic: impl Into<String>) -> &mut Self {
         self.mnemonic = Some(mnemonic.into());
@@ -115,6 +129,21 @@ impl DaemonBuilder {
         self
     }
 
+    /// Whether to record the uploads, instantiations and migrations in a deployment journal stored in the state file.
+    /// A re-run of an interrupted deployment skips the steps that are already confirmed on-chain.
+    /// Defaults to `false`
+    pub fn journal(&mut self, journal: bool) -> &mut Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Set the confirmation awaited after broadcasting a transaction with the default Cosmos wallet.
+    /// Defaults to the inclusion of the transaction in a block
+    pub fn confirmation(&mut self, confirmation: Confirmation) -> &mut Self {
+        self.confirmation = Some(confirmation);
+        self
+    }
+
     /// Overwrite the chain info
     pub fn chain(&mut self, chain: impl Into<ChainInfoOwned>) -> &mut Self {
         self.chain = chain.into();
@@ -1,9 +1,12 @@
-use std::{fmt::Debug, ops::DerefMut};
+use std::{fmt::Debug, ops::DerefMut, path::Path};
 
 use super::super::senders::Wallet;
 use crate::{
     queriers::{Bank, CosmWasmBase, Node},
-    senders::{builder::SenderBuilder, query::QuerySender},
+    senders::{
+        builder::SenderBuilder, query::QuerySender, MultisigDaemon, MultisigTx, OfflineDaemon,
+        QueryOnlyDaemon, UnsignedTx,
+    },
     CosmTxResponse, DaemonAsyncBase, DaemonBuilder, DaemonError, DaemonState,
 };
 use cosmwasm_std::{Addr, Coin};
@@ -100,6 +103,23 @@ impl<Sender: QuerySender> DaemonBase<Sender> {
         self.daemon.sender().channel()
     }
 
+    /// Waits for the inclusion of a transaction returned before its inclusion,
+    /// with [`Confirmation::CheckTx`](crate::confirmation::Confirmation::CheckTx)
+    pub fn wait_tx(&self, response: &CosmTxResponse) -> Result<CosmTxResponse, DaemonError> {
+        self.rt_handle
+            .block_on(self.daemon.pending_tx(response).included())
+    }
+
+    /// Read-only daemon querying the state of the chain at `height`, see [`DaemonAsyncBase::at_height`].
+    /// The [`QueryHandler`] queriers of the returned daemon (e.g. [`WasmQuerier`](cw_orch_core::environment::WasmQuerier),
+    /// [`BankQuerier`](cw_orch_core::environment::BankQuerier)) query the chain at that height.
+    pub fn at_height(&self, height: u64) -> QueryOnlyDaemon {
+        DaemonBase {
+            daemon: self.daemon.at_height(height),
+            rt_handle: self.rt_handle.clone(),
+        }
+    }
+
     /// Returns a new [`DaemonBuilder`] with the current configuration.
     /// **Does not copy the `Sender`**
     /// Does not consume the original [`Daemon`].
@@ -110,8 +130,10 @@ impl<Sender: QuerySender> DaemonBase<Sender> {
             deployment_id: Some(self.daemon.state.deployment_id.clone()),
             state_path: None,
             write_on_change: None,
+            journal: None,
             handle: Some(self.rt_handle.clone()),
             mnemonic: None,
+            confirmation: None,
             // If it was test it will just use same tempfile as state
             is_test: false,
         }
@@ -133,6 +155,35 @@ impl Daemon {
     }
 }
 
+// Helpers for Daemon with [`OfflineSender`](crate::senders::OfflineSender) sender.
+impl OfflineDaemon {
+    /// Transactions written to be signed offline, in order
+    pub fn unsigned_txs(&self) -> Vec<UnsignedTx> {
+        self.sender().unsigned_txs()
+    }
+
+    /// Broadcasts a transaction signed offline, read from a Cosmos-SDK JSON file written by `<chain-binary> tx sign`
+    pub fn broadcast_signed_tx(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<CosmTxResponse, DaemonError> {
+        self.rt_handle
+            .block_on(self.sender().broadcast_signed_tx(path))
+    }
+}
+
+impl MultisigDaemon {
+    /// Multisig transactions waiting for the signatures of other members, in order
+    pub fn pending_txs(&self) -> Vec<MultisigTx> {
+        self.sender().pending_txs()
+    }
+
+    /// Broadcasts a multisig transaction that meets the threshold
+    pub fn broadcast_multisig_tx(&self, tx: &MultisigTx) -> Result<CosmTxResponse, DaemonError> {
+        self.rt_handle.block_on(self.sender().broadcast(tx))
+    }
+}
+
 impl<Sender> ChainState for DaemonBase<Sender> {
     type Out = DaemonState;
 
@@ -0,0 +1,302 @@
+//! Mock node serving the Tendermint JSON-RPC methods used by the daemon, to test it without a chain.
+//!
+//! Enabled in the tests of the crate and with the `test-utils` feature.
+
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cosmrs::proto::{
+    cosmos::{
+        auth::v1beta1::{BaseAccount, QueryAccountRequest, QueryAccountResponse},
+        bank::v1beta1::QueryBalanceResponse,
+        base::{
+            abci::v1beta1::{GasInfo, Result as TxResult},
+            v1beta1::Coin,
+        },
+        tx::v1beta1::{SimulateRequest, SimulateResponse, Tx, TxBody, TxRaw},
+    },
+    tendermint::v0_34::abci::{Event, EventAttribute},
+    Any,
+};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use prost::Message;
+use serde_json::{json, Value};
+
+pub const CHAIN_ID: &str = "juno-1";
+pub const LATEST_HEIGHT: u64 = 12;
+pub const TX_HASH: &str = "5F2D2E8F19A9F2C8A3A3E9B7C3F0D1B2A4C5D6E7F8091A2B3C4D5E6F708192A3";
+/// Address of the instantiated contracts
+pub const CONTRACT: &str = "juno14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9skjuwg8";
+/// Sequence of the accounts
+pub const SEQUENCE: u64 = 3;
+/// Gas used by each message of a simulated transaction
+pub const MSG_GAS: u64 = 100_000;
+const EMPTY_HASH: &str = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+
+/// JSON-RPC node of a chain producing a block every second, stopped at its latest height.
+/// Every address owns the same balance, and its account is at `SEQUENCE`.
+/// The transactions are included as `TX_HASH` at the latest height, the simulated transactions use `MSG_GAS` per message
+/// and instantiate their contracts at `CONTRACT`.
+///
+/// The default node is `juno-1` at height 12, every address owns 100ujuno.
+#[derive(Clone)]
+pub struct MockRpc {
+    chain_id: String,
+    latest_height: u64,
+    balance: Coin,
+    /// Methods of the requests received by the node
+    pub requests: Arc<Mutex<Vec<String>>>,
+    /// Heights of the ABCI queries, `0` for the latest height
+    pub queried_heights: Arc<Mutex<Vec<String>>>,
+    /// Heights of the requested blocks, the latest block isn't recorded
+    pub queried_blocks: Arc<Mutex<Vec<u64>>>,
+    /// Queries of the `tx_search` requests
+    pub tx_searches: Arc<Mutex<Vec<String>>>,
+    /// Base64 encoded transactions broadcasted to the node
+    txs: Arc<Mutex<Vec<String>>>,
+}
+
+impl Default for MockRpc {
+    fn default() -> Self {
+        Self::new(CHAIN_ID, LATEST_HEIGHT, cosmwasm_std::coin(100, "ujuno"))
+    }
+}
+
+impl MockRpc {
+    pub fn new(chain_id: &str, latest_height: u64, balance: cosmwasm_std::Coin) -> Self {
+        Self {
+            chain_id: chain_id.to_string(),
+            latest_height,
+            balance: Coin {
+                denom: balance.denom,
+                amount: balance.amount.to_string(),
+            },
+            requests: Default::default(),
+            queried_heights: Default::default(),
+            queried_blocks: Default::default(),
+            tx_searches: Default::default(),
+            txs: Default::default(),
+        }
+    }
+
+    fn result(&self, method: &str, params: &Value) -> Result<Value, String> {
+        self.requests.lock().unwrap().push(method.to_string());
+        let result = match method {
+            "status" => json!({
+                "node_info": { "id": "mock", "network": self.chain_id, "version": "0.38.0", "moniker": "mock" }
+            }),
+            "block" => {
+                let height = params["height"]
+                    .as_str()
+                    .map(|height| height.parse().unwrap());
+                self.queried_blocks.lock().unwrap().extend(height);
+                self.block(height.unwrap_or(self.latest_height))
+            }
+            "abci_query" => {
+                self.queried_heights
+                    .lock()
+                    .unwrap()
+                    .push(params["height"].as_str().unwrap().to_string());
+                let data = hex::decode(params["data"].as_str().unwrap()).unwrap();
+                self.abci_query(params["path"].as_str().unwrap(), &data)
+            }
+            "broadcast_tx_sync" => {
+                let tx = params["tx"].as_str().unwrap().to_string();
+                self.txs.lock().unwrap().push(tx);
+                json!({ "code": 0, "data": "", "log": "", "codespace": "", "hash": TX_HASH })
+            }
+            "tx" => {
+                if params["hash"] != STANDARD.encode(hex::decode(TX_HASH).unwrap()) {
+                    return Err("tx not found".to_string());
+                }
+                self.tx()
+            }
+            "tx_search" => {
+                self.tx_searches
+                    .lock()
+                    .unwrap()
+                    .push(params["query"].as_str().unwrap().to_string());
+                json!({ "txs": [self.tx()], "total_count": "1" })
+            }
+            _ => return Err(format!("unknown method {method}")),
+        };
+        Ok(result)
+    }
+
+    fn abci_query(&self, path: &str, data: &[u8]) -> Value {
+        let value = match path {
+            "/cosmos.bank.v1beta1.Query/Balance" => QueryBalanceResponse {
+                balance: Some(self.balance.clone()),
+            }
+            .encode_to_vec(),
+            "/cosmos.auth.v1beta1.Query/Account" => {
+                let request = QueryAccountRequest::decode(data).unwrap();
+                let account = BaseAccount {
+                    address: request.address,
+                    pub_key: None,
+                    account_number: 1,
+                    sequence: SEQUENCE,
+                };
+                QueryAccountResponse {
+                    account: Some(Any {
+                        type_url: "/cosmos.auth.v1beta1.BaseAccount".to_string(),
+                        value: account.encode_to_vec(),
+                    }),
+                }
+                .encode_to_vec()
+            }
+            "/cosmos.tx.v1beta1.Service/Simulate" => {
+                let request = SimulateRequest::decode(data).unwrap();
+                let tx = TxRaw::decode(request.tx_bytes.as_slice()).unwrap();
+                let body = TxBody::decode(tx.body_bytes.as_slice()).unwrap();
+                SimulateResponse {
+                    gas_info: Some(GasInfo {
+                        gas_wanted: 0,
+                        gas_used: MSG_GAS * body.messages.len() as u64,
+                    }),
+                    result: Some(TxResult {
+                        events: body.messages.iter().flat_map(msg_events).collect(),
+                        ..Default::default()
+                    }),
+                }
+                .encode_to_vec()
+            }
+            "/cosmos.staking.v1beta1.Query/Validator" => {
+                return json!({
+                    "response": { "code": 22, "log": "validator not found", "codespace": "sdk" }
+                })
+            }
+            _ => {
+                return json!({
+                    "response": { "code": 6, "log": "unknown query path", "codespace": "sdk" }
+                })
+            }
+        };
+        json!({ "response": { "code": 0, "value": STANDARD.encode(value) } })
+    }
+
+    async fn handle(self, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+        let request: Value = serde_json::from_slice(&body).unwrap();
+        let response = match self.result(request["method"].as_str().unwrap(), &request["params"]) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+            Err(data) => json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32603, "message": "Internal error", "data": data }
+            }),
+        };
+        Ok(Response::new(Body::from(response.to_string())))
+    }
+
+    /// Serves the node on a local port, in the current tokio runtime, and returns its address
+    pub fn serve(&self) -> String {
+        let node = self.clone();
+        let server =
+            Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service_fn(move |_| {
+                let node = node.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request| node.clone().handle(request)))
+                }
+            }));
+        let address = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        address
+    }
+
+    /// Transaction `TX_HASH` included at the latest height, the last one broadcasted if any
+    fn tx(&self) -> Value {
+        let tx = self
+            .txs
+            .lock()
+            .unwrap()
+            .last()
+            .cloned()
+            .unwrap_or_else(|| STANDARD.encode(Tx::default().encode_to_vec()));
+        json!({
+            "hash": TX_HASH,
+            "height": self.latest_height.to_string(),
+            "index": 0,
+            "tx_result": {
+                "code": 0,
+                "data": null,
+                "log": "",
+                "info": "",
+                "gas_wanted": "200000",
+                "gas_used": "120000",
+                "events": [{
+                    "type": "transfer",
+                    "attributes": [{ "key": "amount", "value": "10ujuno", "index": true }]
+                }],
+                "codespace": ""
+            },
+            "tx": tx
+        })
+    }
+
+    /// Block at `height`, produced `height` seconds after 2024-01-01
+    fn block(&self, height: u64) -> Value {
+        let block_id = json!({ "hash": EMPTY_HASH, "parts": { "total": 1, "hash": EMPTY_HASH } });
+        // The first block has no previous block
+        let last_block_id = (height > 1).then(|| block_id.clone());
+        let last_commit = (height > 1).then(|| {
+            json!({ "height": (height - 1).to_string(), "round": 0, "block_id": block_id, "signatures": [] })
+        });
+        json!({
+            "block_id": block_id,
+            "block": {
+                "header": {
+                    "version": { "block": "11", "app": "0" },
+                    "chain_id": self.chain_id,
+                    "height": height.to_string(),
+                    "time": format!(
+                        "2024-01-01T{:02}:{:02}:{:02}Z",
+                        height / 3600 % 24,
+                        height / 60 % 60,
+                        height % 60
+                    ),
+                    "last_block_id": last_block_id,
+                    "last_commit_hash": EMPTY_HASH,
+                    "data_hash": EMPTY_HASH,
+                    "validators_hash": EMPTY_HASH,
+                    "next_validators_hash": EMPTY_HASH,
+                    "consensus_hash": EMPTY_HASH,
+                    "app_hash": EMPTY_HASH,
+                    "last_results_hash": EMPTY_HASH,
+                    "evidence_hash": EMPTY_HASH,
+                    "proposer_address": "0000000000000000000000000000000000000000"
+                },
+                "data": { "txs": [] },
+                "evidence": { "evidence": [] },
+                "last_commit": last_commit
+            }
+        })
+    }
+}
+
+/// Events emitted by a simulated message
+fn msg_events(msg: &Any) -> Vec<Event> {
+    let event = |r#type: &str, key: &'static str, value: String| Event {
+        r#type: r#type.to_string(),
+        attributes: vec![EventAttribute {
+            key: key.into(),
+            value: value.into(),
+            index: true,
+        }],
+    };
+    let mut events = vec![event("message", "action", msg.type_url.clone())];
+    if msg.type_url == "/cosmwasm.wasm.v1.MsgInstantiateContract" {
+        events.push(event(
+            "instantiate",
+            "_contract_address",
+            CONTRACT.to_string(),
+        ));
+    }
+    events
+}
@@ -0,0 +1,855 @@
+//! Transports reaching a node over another protocol than gRPC.
+//!
+//! The queriers and senders of the daemon send their requests over a gRPC [`Channel`].
+//! The channel of a [`Transport`] is served in-process: every gRPC request is translated to the protocol of the transport.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cosmrs::{
+    proto::{
+        cosmos::{
+            base::{
+                abci::v1beta1::TxResponse,
+                tendermint::v1beta1::{
+                    GetBlockByHeightRequest, GetBlockByHeightResponse, GetLatestBlockResponse,
+                    GetNodeInfoResponse,
+                },
+            },
+            tx::v1beta1::{
+                BroadcastTxRequest, BroadcastTxResponse, GetTxRequest, GetTxResponse,
+                GetTxsEventResponse, OrderBy, Tx,
+            },
+        },
+        tendermint::v0_34::{
+            abci::{Event, EventAttribute},
+            p2p::DefaultNodeInfo,
+            types::{Block, BlockId},
+        },
+    },
+    tendermint, Any,
+};
+use cw_orch_core::log::connectivity_target;
+use hyper::{
+    body::{Bytes, HttpBody},
+    header::CONTENT_TYPE,
+    server::conn::Http,
+    service::service_fn,
+    Body, HeaderMap, Request, Response,
+};
+use prost::Message;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::json;
+use tonic::{
+    codegen::http::Uri,
+    transport::{Channel, Endpoint},
+    Code, Status,
+};
+
+use crate::{cosmos_proto_patches::v0_50::tx::GetTxsEventRequest, DaemonError};
+
+/// Header of the gRPC requests setting the height of the queried state
+pub const BLOCK_HEIGHT_HEADER: &str = "x-cosmos-block-height";
+
+const BROADCAST_TX_PATH: &str = "/cosmos.tx.v1beta1.Service/BroadcastTx";
+const GET_TX_PATH: &str = "/cosmos.tx.v1beta1.Service/GetTx";
+const GET_TXS_EVENT_PATH: &str = "/cosmos.tx.v1beta1.Service/GetTxsEvent";
+const GET_LATEST_BLOCK_PATH: &str = "/cosmos.base.tendermint.v1beta1.Service/GetLatestBlock";
+const GET_BLOCK_BY_HEIGHT_PATH: &str = "/cosmos.base.tendermint.v1beta1.Service/GetBlockByHeight";
+const GET_NODE_INFO_PATH: &str = "/cosmos.base.tendermint.v1beta1.Service/GetNodeInfo";
+const TX_TYPE_URL: &str = "/cosmos.tx.v1beta1.Tx";
+
+/// Protocol used to reach a node instead of its gRPC endpoint.
+///
+/// The module queries are sent to the node as ABCI queries, the gRPC path of the query being the ABCI path.
+/// The transactions and the `cosmos.base.tendermint` queries, which are not served by the application, are implemented by the transport.
+pub trait Transport: Clone + Send + Sync + 'static {
+    /// Queries the application at `path` with the encoded request, at the latest height if `height` is `None`
+    fn abci_query(
+        &self,
+        path: &str,
+        data: Vec<u8>,
+        height: Option<u64>,
+    ) -> impl Future<Output = Result<Vec<u8>, Status>> + Send;
+
+    /// Broadcasts the encoded transaction, returning once it passed the checks of the node
+    fn broadcast_tx_sync(
+        &self,
+        tx: Vec<u8>,
+    ) -> impl Future<Output = Result<TxResponse, Status>> + Send;
+
+    /// Transaction included on chain with the given hash
+    fn tx(&self, hash: &str) -> impl Future<Output = Result<GetTxResponse, Status>> + Send;
+
+    /// Transactions included on chain matching the events `query`, on the 1-based `page` of `limit` transactions
+    fn tx_search(
+        &self,
+        query: &str,
+        page: u64,
+        limit: u64,
+        order_by: OrderBy,
+    ) -> impl Future<Output = Result<GetTxsEventResponse, Status>> + Send;
+
+    /// Block at `height`, the latest block if `height` is `None`
+    fn block(
+        &self,
+        height: Option<u64>,
+    ) -> impl Future<Output = Result<(BlockId, Block), Status>> + Send;
+
+    /// Information about the node, including the chain id it serves
+    fn node_info(&self) -> impl Future<Output = Result<GetNodeInfoResponse, Status>> + Send;
+
+    /// gRPC channel whose requests are served by the transport
+    fn channel(self) -> Channel {
+        in_process_channel(move |request| serve(self.clone(), request))
+    }
+}
+
+/// gRPC channel whose requests are served in-process by `service`, over an in-memory HTTP/2 connection
+pub(crate) fn in_process_channel<F, R, B>(service: F) -> Channel
+where
+    F: Fn(Request<Body>) -> R + Clone + Send + Sync + 'static,
+    R: Future<Output = Result<Response<B>, Infallible>> + Send + 'static,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Endpoint::from_static("http://in-process.local").connect_with_connector_lazy(
+        tower::service_fn(move |_: Uri| {
+            let service = service.clone();
+            async move {
+                let (client, server) = tokio::io::duplex(1 << 16);
+                tokio::spawn(async move {
+                    if let Err(e) = Http::new()
+                        .http2_only(true)
+                        .serve_connection(server, service_fn(service))
+                        .await
+                    {
+                        log::debug!(target: &connectivity_target(), "In-process connection closed: {e}");
+                    }
+                });
+                Ok::<_, std::io::Error>(client)
+            }
+        }),
+    )
+}
+
+/// Serves a unary gRPC request with the transport
+async fn serve<T: Transport>(
+    transport: T,
+    request: Request<Body>,
+) -> Result<Response<UnaryBody>, Infallible> {
+    serve_unary(request, |path, message, height| async move {
+        handle(&transport, &path, message, height).await
+    })
+    .await
+}
+
+/// Serves a unary gRPC request with `handler`, called with the path, the message and the height of the request
+pub(crate) async fn serve_unary<F, R>(
+    request: Request<Body>,
+    handler: F,
+) -> Result<Response<UnaryBody>, Infallible>
+where
+    F: FnOnce(String, Bytes, Option<u64>) -> R,
+    R: Future<Output = Result<Vec<u8>, Status>>,
+{
+    let path = request.uri().path().to_string();
+    let height = request
+        .headers()
+        .get(BLOCK_HEIGHT_HEADER)
+        .and_then(|height| height.to_str().ok()?.parse().ok());
+
+    let response = match hyper::body::to_bytes(request.into_body()).await {
+        Ok(body) => match decode_frame(body) {
+            Ok(message) => handler(path, message, height).await,
+            Err(status) => Err(status),
+        },
+        Err(e) => Err(Status::internal(e.to_string())),
+    };
+    Ok(unary_response(response))
+}
+
+async fn handle<T: Transport>(
+    transport: &T,
+    path: &str,
+    message: Bytes,
+    height: Option<u64>,
+) -> Result<Vec<u8>, Status> {
+    let response = match path {
+        BROADCAST_TX_PATH => {
+            let request = BroadcastTxRequest::decode(message).map_err(invalid_request)?;
+            BroadcastTxResponse {
+                tx_response: Some(transport.broadcast_tx_sync(request.tx_bytes).await?),
+            }
+            .encode_to_vec()
+        }
+        GET_TX_PATH => {
+            let request = GetTxRequest::decode(message).map_err(invalid_request)?;
+            transport.tx(&request.hash).await?.encode_to_vec()
+        }
+        GET_TXS_EVENT_PATH => {
+            let request = GetTxsEventRequest::decode(message).map_err(invalid_request)?;
+            // The nodes before Cosmos-SDK 0.50 are queried with the events only
+            let query = if request.query.is_empty() {
+                request.events.join(" AND ")
+            } else {
+                request.query
+            };
+            let limit = if request.limit == 0 {
+                100
+            } else {
+                request.limit
+            };
+            let order_by = OrderBy::try_from(request.order_by).unwrap_or_default();
+            transport
+                .tx_search(&query, request.page.max(1), limit, order_by)
+                .await?
+                .encode_to_vec()
+        }
+        GET_LATEST_BLOCK_PATH => {
+            let (block_id, block) = transport.block(None).await?;
+            #[allow(deprecated)]
+            GetLatestBlockResponse {
+                block_id: Some(block_id),
+                block: Some(block),
+                sdk_block: None,
+            }
+            .encode_to_vec()
+        }
+        GET_BLOCK_BY_HEIGHT_PATH => {
+            let request = GetBlockByHeightRequest::decode(message).map_err(invalid_request)?;
+            let (block_id, block) = transport.block(Some(request.height as u64)).await?;
+            #[allow(deprecated)]
+            GetBlockByHeightResponse {
+                block_id: Some(block_id),
+                block: Some(block),
+                sdk_block: None,
+            }
+            .encode_to_vec()
+        }
+        GET_NODE_INFO_PATH => transport.node_info().await?.encode_to_vec(),
+        _ => transport.abci_query(path, message.to_vec(), height).await?,
+    };
+    Ok(response)
+}
+
+fn invalid_request(e: prost::DecodeError) -> Status {
+    Status::invalid_argument(format!("Invalid request: {e}"))
+}
+
+/// Message of a unary gRPC request, without its compression flag and length
+fn decode_frame(body: Bytes) -> Result<Bytes, Status> {
+    if body.len() < 5 || body[0] != 0 {
+        return Err(Status::unimplemented(
+            "Only uncompressed unary requests are supported",
+        ));
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    if body.len() < 5 + len {
+        return Err(Status::invalid_argument("Truncated request"));
+    }
+    Ok(body.slice(5..5 + len))
+}
+
+fn unary_response(response: Result<Vec<u8>, Status>) -> Response<UnaryBody> {
+    let (data, status) = match response {
+        Ok(message) => {
+            let mut frame = Vec::with_capacity(5 + message.len());
+            frame.push(0);
+            frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+            frame.extend_from_slice(&message);
+            (Some(frame.into()), Status::ok(""))
+        }
+        Err(status) => (None, status),
+    };
+    let mut trailers = HeaderMap::new();
+    // The message of the status is percent-encoded, this can't fail
+    let _ = status.add_header(&mut trailers);
+
+    Response::builder()
+        .header(CONTENT_TYPE, "application/grpc")
+        .body(UnaryBody {
+            data,
+            trailers: Some(trailers),
+        })
+        .unwrap()
+}
+
+/// Body of a unary gRPC response, a single message followed by the status in the trailers
+pub(crate) struct UnaryBody {
+    data: Option<Bytes>,
+    trailers: Option<HeaderMap>,
+}
+
+impl UnaryBody {
+    pub(crate) fn new(data: Bytes, trailers: Option<HeaderMap>) -> Self {
+        Self {
+            data: (!data.is_empty()).then_some(data),
+            trailers,
+        }
+    }
+}
+
+impl HttpBody for UnaryBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Poll::Ready(self.get_mut().data.take().map(Ok))
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(self.get_mut().trailers.take()))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data.is_none() && self.trailers.is_none()
+    }
+}
+
+/// Transport over the Tendermint (CometBFT) JSON-RPC endpoint of a node, usually on port 26657.
+///
+/// The events of the transactions are expected in the format of CometBFT 0.37 and later, with plain text attributes.
+/// The `cosmos.tx.v1beta1.Service/GetTxsEvent` queries are sent as `tx_search` requests, their `pagination` is ignored.
+#[derive(Clone, Debug)]
+pub struct RpcTransport {
+    client: reqwest::Client,
+    url: String,
+    /// Timestamps of the blocks of the returned transactions, by height
+    block_times: Arc<Mutex<HashMap<i64, String>>>,
+}
+
+impl RpcTransport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            block_times: Default::default(),
+        }
+    }
+
+    /// Connect to the first of the provided RPC endpoints that serves the expected chain
+    pub async fn connect(rpc: &[String], chain_id: &str) -> Result<Channel, DaemonError> {
+        for address in rpc {
+            log::debug!(target: &connectivity_target(), "Trying to connect to RPC endpoint: {}", address);
+            let transport = RpcTransport::new(address.as_str());
+            let network = match transport.node_info().await {
+                Ok(node_info) => node_info.default_node_info.unwrap_or_default().network,
+                Err(e) => {
+                    log::warn!("Cannot connect to RPC endpoint: {}, {:?}", address, e);
+                    continue;
+                }
+            };
+            if network != chain_id {
+                log::error!(
+                    "Network mismatch: connection:{} != config:{}",
+                    network,
+                    chain_id
+                );
+                continue;
+            }
+            return Ok(transport.channel());
+        }
+        Err(DaemonError::CannotConnectRPC)
+    }
+
+    /// Calls a JSON-RPC method of the node
+    async fn call<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<R, Status> {
+        let response: RpcResponse<R> = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await
+            .map_err(|e| Status::unavailable(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| Status::internal(format!("Invalid {method} response: {e}")))?;
+
+        match response {
+            RpcResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            RpcResponse {
+                error: Some(error), ..
+            } => Err(Status::unknown(format!(
+                "{}: {}",
+                error.message, error.data
+            ))),
+            _ => Err(Status::internal(format!("Empty {method} response"))),
+        }
+    }
+
+    async fn tendermint_block(
+        &self,
+        height: Option<u64>,
+    ) -> Result<(tendermint::block::Id, tendermint::Block), Status> {
+        let params = match height {
+            Some(height) => json!({ "height": height.to_string() }),
+            None => json!({}),
+        };
+        let result: BlockResult = self.call("block", params).await?;
+        Ok((result.block_id, result.block))
+    }
+
+    /// Timestamp of the block at `height`, the blocks are only fetched once
+    async fn block_time(&self, height: i64) -> Result<String, Status> {
+        if let Some(time) = self.block_times.lock().unwrap().get(&height) {
+            return Ok(time.clone());
+        }
+        let (_, block) = self.tendermint_block(Some(height as u64)).await?;
+        let time = block.header.time.to_rfc3339();
+        self.block_times
+            .lock()
+            .unwrap()
+            .insert(height, time.clone());
+        Ok(time)
+    }
+
+    /// Decoded transaction and response of a transaction returned by the node, with the timestamp of its block
+    async fn tx_response(&self, result: TxResult) -> Result<(Tx, TxResponse), Status> {
+        let height = result.height;
+        let timestamp = self.block_time(height).await?;
+
+        let tx_bytes = decode_base64(&result.tx)?;
+        let tx = Tx::decode(tx_bytes.as_slice()).map_err(|e| Status::internal(e.to_string()))?;
+        let tx_result = result.tx_result;
+        let tx_response = TxResponse {
+            height,
+            txhash: result.hash,
+            codespace: tx_result.codespace,
+            code: tx_result.code,
+            data: hex::encode_upper(decode_base64(&tx_result.data.unwrap_or_default())?),
+            raw_log: tx_result.log,
+            logs: vec![],
+            info: tx_result.info,
+            gas_wanted: tx_result.gas_wanted,
+            gas_used: tx_result.gas_used,
+            tx: Some(Any {
+                type_url: TX_TYPE_URL.to_string(),
+                value: tx_bytes,
+            }),
+            timestamp,
+            events: tx_result.events.into_iter().map(Event::from).collect(),
+        };
+        Ok((tx, tx_response))
+    }
+}
+
+impl Transport for RpcTransport {
+    async fn abci_query(
+        &self,
+        path: &str,
+        data: Vec<u8>,
+        height: Option<u64>,
+    ) -> Result<Vec<u8>, Status> {
+        let result: AbciQueryResult = self
+            .call(
+                "abci_query",
+                json!({
+                    "path": path,
+                    "data": hex::encode(data),
+                    "height": height.unwrap_or_default().to_string(),
+                    "prove": false,
+                }),
+            )
+            .await?;
+        let response = result.response;
+        if response.code != 0 {
+            return Err(Status::new(
+                abci_error_code(&response.codespace, response.code),
+                format!(
+                    "{} (codespace: {}, code: {})",
+                    response.log, response.codespace, response.code
+                ),
+            ));
+        }
+        decode_base64(&response.value.unwrap_or_default())
+    }
+
+    async fn broadcast_tx_sync(&self, tx: Vec<u8>) -> Result<TxResponse, Status> {
+        let result: BroadcastTxResult = self
+            .call("broadcast_tx_sync", json!({ "tx": STANDARD.encode(tx) }))
+            .await?;
+        Ok(TxResponse {
+            txhash: result.hash,
+            codespace: result.codespace,
+            code: result.code,
+            data: result.data,
+            raw_log: result.log,
+            ..Default::default()
+        })
+    }
+
+    async fn tx(&self, hash: &str) -> Result<GetTxResponse, Status> {
+        let hash = hex::decode(hash)
+            .map_err(|e| Status::invalid_argument(format!("Invalid tx hash: {e}")))?;
+        let result: TxResult = self
+            .call(
+                "tx",
+                json!({ "hash": STANDARD.encode(hash), "prove": false }),
+            )
+            .await?;
+        let (tx, tx_response) = self.tx_response(result).await?;
+        Ok(GetTxResponse {
+            tx: Some(tx),
+            tx_response: Some(tx_response),
+        })
+    }
+
+    async fn tx_search(
+        &self,
+        query: &str,
+        page: u64,
+        limit: u64,
+        order_by: OrderBy,
+    ) -> Result<GetTxsEventResponse, Status> {
+        let order_by = match order_by {
+            OrderBy::Desc => "desc",
+            OrderBy::Asc | OrderBy::Unspecified => "asc",
+        };
+        let result: TxSearchResult = self
+            .call(
+                "tx_search",
+                json!({
+                    "query": query,
+                    "prove": false,
+                    "page": page.to_string(),
+                    "per_page": limit.to_string(),
+                    "order_by": order_by,
+                }),
+            )
+            .await?;
+        let mut response = GetTxsEventResponse {
+            total: result.total_count as u64,
+            ..Default::default()
+        };
+        for result in result.txs {
+            let (tx, tx_response) = self.tx_response(result).await?;
+            response.txs.push(tx);
+            response.tx_responses.push(tx_response);
+        }
+        Ok(response)
+    }
+
+    async fn block(&self, height: Option<u64>) -> Result<(BlockId, Block), Status> {
+        let (block_id, block) = self.tendermint_block(height).await?;
+        Ok((block_id.into(), block.into()))
+    }
+
+    async fn node_info(&self) -> Result<GetNodeInfoResponse, Status> {
+        let status: StatusResult = self.call("status", json!({})).await?;
+        let node_info = status.node_info;
+        Ok(GetNodeInfoResponse {
+            default_node_info: Some(DefaultNodeInfo {
+                default_node_id: node_info.id,
+                listen_addr: node_info.listen_addr,
+                network: node_info.network,
+                version: node_info.version,
+                moniker: node_info.moniker,
+                ..Default::default()
+            }),
+            application_version: None,
+        })
+    }
+}
+
+/// gRPC code of an error of the application, the reverse of the mapping of the gRPC errors of the queries by the Cosmos SDK
+fn abci_error_code(codespace: &str, code: u32) -> Code {
+    match (codespace, code) {
+        // ErrUnauthorized
+        ("sdk", 4) => Code::Unauthenticated,
+        // ErrInvalidRequest
+        ("sdk", 18) => Code::InvalidArgument,
+        // ErrKeyNotFound, ErrNotFound
+        ("sdk", 22 | 38) => Code::NotFound,
+        _ => Code::Unknown,
+    }
+}
+
+pub(crate) fn decode_base64(value: &str) -> Result<Vec<u8>, Status> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| Status::internal(format!("Invalid base64 value: {e}")))
+}
+
+/// Deserializes an integer encoded as a string, like the 64 bits integers of the JSON-RPC responses
+fn int_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<R> {
+    result: Option<R>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+    #[serde(default)]
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct AbciQueryResult {
+    response: AbciQueryResponse,
+}
+
+#[derive(Deserialize)]
+struct AbciQueryResponse {
+    #[serde(default)]
+    code: u32,
+    #[serde(default)]
+    log: String,
+    #[serde(default)]
+    codespace: String,
+    value: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BroadcastTxResult {
+    code: u32,
+    #[serde(default)]
+    data: String,
+    #[serde(default)]
+    log: String,
+    #[serde(default)]
+    codespace: String,
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct TxSearchResult {
+    txs: Vec<TxResult>,
+    #[serde(deserialize_with = "int_from_str")]
+    total_count: i64,
+}
+
+#[derive(Deserialize)]
+struct TxResult {
+    hash: String,
+    #[serde(deserialize_with = "int_from_str")]
+    height: i64,
+    tx_result: ExecTxResult,
+    tx: String,
+}
+
+/// Result of the execution of a transaction, the fields set to their default value are omitted
+#[derive(Deserialize)]
+pub(crate) struct ExecTxResult {
+    #[serde(default)]
+    pub code: u32,
+    pub data: Option<String>,
+    #[serde(default)]
+    pub log: String,
+    #[serde(default)]
+    pub info: String,
+    #[serde(default, deserialize_with = "int_from_str")]
+    pub gas_wanted: i64,
+    #[serde(default, deserialize_with = "int_from_str")]
+    pub gas_used: i64,
+    #[serde(default)]
+    pub events: Vec<RpcEvent>,
+    #[serde(default)]
+    pub codespace: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RpcEvent {
+    r#type: String,
+    #[serde(default)]
+    attributes: Vec<RpcEventAttribute>,
+}
+
+#[derive(Deserialize)]
+struct RpcEventAttribute {
+    key: String,
+    value: Option<String>,
+    #[serde(default)]
+    index: bool,
+}
+
+impl From<RpcEvent> for Event {
+    fn from(event: RpcEvent) -> Self {
+        Event {
+            r#type: event.r#type,
+            attributes: event
+                .attributes
+                .into_iter()
+                .map(|attribute| EventAttribute {
+                    key: attribute.key.into(),
+                    value: attribute.value.unwrap_or_default().into(),
+                    index: attribute.index,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BlockResult {
+    block_id: tendermint::block::Id,
+    block: tendermint::Block,
+}
+
+#[derive(Deserialize)]
+struct StatusResult {
+    node_info: RpcNodeInfo,
+}
+
+#[derive(Deserialize)]
+struct RpcNodeInfo {
+    id: String,
+    #[serde(default)]
+    listen_addr: String,
+    network: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    moniker: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmrs::proto::cosmos::tx::v1beta1::{
+        service_client::ServiceClient, BroadcastTxRequest, Tx,
+    };
+    use cw_orch_core::environment::IndexResponse;
+    use prost::Message;
+    use tonic::Code;
+
+    use super::{RpcTransport, Transport};
+    use crate::{
+        queriers::{Bank, Node, Staking},
+        test_utils::{MockRpc, CHAIN_ID, LATEST_HEIGHT, TX_HASH},
+        DaemonError, GrpcChannel,
+    };
+
+    #[tokio::test]
+    async fn module_queries_are_abci_queries() -> anyhow::Result<()> {
+        let node = MockRpc::default();
+        let channel = RpcTransport::new(node.serve()).channel();
+
+        let balance = Bank::new_async(channel.clone())
+            ._balance("juno1address", Some("ujuno".to_string()))
+            .await?;
+        assert_eq!(balance, vec![cosmwasm_std::coin(100, "ujuno")]);
+
+        // The errors of the application are returned as gRPC errors
+        let error = Bank::new_async(channel.clone())
+            ._supply_of("ujuno")
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("unknown query path"));
+        let error = Staking::new_async(channel)
+            ._validator("junovaloper1validator")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DaemonError::Status(status) if status.code() == Code::NotFound));
+        assert_eq!(*node.queried_heights.lock().unwrap(), vec!["0", "0", "0"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn node_queries_and_txs_are_served_by_rpc() -> anyhow::Result<()> {
+        let node = MockRpc::default();
+        let channel = RpcTransport::new(node.serve()).channel();
+
+        let querier = Node::new_async(channel.clone());
+        assert_eq!(querier._block_height().await?, LATEST_HEIGHT);
+        assert_eq!(
+            querier._info().await?.default_node_info.unwrap().network,
+            CHAIN_ID
+        );
+
+        let broadcast = ServiceClient::new(channel)
+            .broadcast_tx(BroadcastTxRequest {
+                tx_bytes: Tx::default().encode_to_vec(),
+                mode: 2,
+            })
+            .await?
+            .into_inner()
+            .tx_response
+            .unwrap();
+        assert_eq!(broadcast.txhash, TX_HASH);
+
+        let tx = querier._find_tx_with_retries(broadcast.txhash, 1).await?;
+        assert_eq!(tx.height, LATEST_HEIGHT);
+        assert_eq!(tx.gas_used, 120000);
+        assert_eq!(tx.event_attr_value("transfer", "amount")?, "10ujuno");
+
+        // The block of the transactions is only fetched once
+        querier._find_tx(TX_HASH.to_string()).await?;
+        let queried_blocks = node.queried_blocks.lock().unwrap();
+        assert_eq!(
+            queried_blocks
+                .iter()
+                .filter(|height| **height == LATEST_HEIGHT)
+                .count(),
+            1
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn txs_by_events_are_searched_by_rpc() -> anyhow::Result<()> {
+        let node = MockRpc::default();
+        let channel = RpcTransport::new(node.serve()).channel();
+
+        let txs = Node::new_async(channel)
+            ._find_tx_by_events(vec!["transfer.amount='10ujuno'".to_string()], None, None)
+            .await?;
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].txhash, TX_HASH);
+        assert_eq!(txs[0].event_attr_value("transfer", "amount")?, "10ujuno");
+        assert_eq!(
+            *node.tx_searches.lock().unwrap(),
+            vec!["transfer.amount='10ujuno'"]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rpc_endpoints_of_the_chain_are_connected() -> anyhow::Result<()> {
+        let address = MockRpc::default().serve();
+
+        let error = RpcTransport::connect(std::slice::from_ref(&address), "osmosis-1")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DaemonError::CannotConnectRPC));
+
+        let channel = GrpcChannel::connect_with_rpc(&[], &[address], CHAIN_ID).await?;
+        assert_eq!(
+            Node::new_async(channel)._block_height().await?,
+            LATEST_HEIGHT
+        );
+        Ok(())
+    }
+}
@@ -1,11 +1,16 @@
+use std::time::Duration;
+
 use cosmrs::proto::cosmos::base::abci::v1beta1::TxResponse;
 use cw_orch_core::log::transaction_target;
 
-use crate::{queriers::Node, CosmTxResponse, DaemonError, TxBuilder, Wallet};
+use tonic::Code;
+
+use crate::{queriers::Node, senders::tx::BroadcastSender, CosmTxResponse, DaemonError, TxBuilder};
 
 pub type StrategyAction =
     fn(&mut TxBuilder, &Result<TxResponse, DaemonError>) -> Result<(), DaemonError>;
 
+#[derive(Clone)]
 pub struct RetryStrategy {
     /// This function is called right after a transaction has been submitted to a node.
     /// It is used to check if the transaction has been submitted successfully or if an error occurred
@@ -43,11 +48,24 @@ impl RetryStrategy {
     }
 }
 
-#[derive(Default)]
+/// Delay between retries when the average block speed can't be queried from the node
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(6);
+
 pub struct TxBroadcaster {
     strategies: Vec<RetryStrategy>,
+    retry_delay: Duration,
+}
+
+impl Default for TxBroadcaster {
+    fn default() -> Self {
+        Self {
+            strategies: vec![],
+            retry_delay: DEFAULT_RETRY_DELAY,
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub enum BroadcastRetry {
     Infinite,
     Finite(u64),
@@ -62,12 +80,19 @@ impl TxBroadcaster {
         self
     }
 
+    /// Sets the delay between retries used when the node can't be queried for its average block speed.
+    /// Defaults to [`DEFAULT_RETRY_DELAY`]
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
     // We can't make async recursions easily because wallet is not `Sync`
     // Thus we use a `while` loop structure here
-    pub async fn broadcast(
+    pub async fn broadcast<T: BroadcastSender>(
         mut self,
         mut tx_builder: TxBuilder,
-        wallet: &Wallet,
+        wallet: &T,
     ) -> Result<TxResponse, DaemonError> {
         let mut tx_retry = true;
 
@@ -90,9 +115,11 @@ impl TxBroadcaster {
                     tx_retry = true;
 
                     // We still await for the next block, to avoid spamming retry when an error occurs
+                    // The node may be the reason of the retry, so we don't fail if it can't be queried
                     let block_speed = Node::new_async(wallet.channel())
                         ._average_block_speed(None)
-                        .await?;
+                        .await
+                        .unwrap_or(self.retry_delay);
                     log::warn!(
                         target: &transaction_target(),
                         "Retrying broadcasting TX in {:?} milliseconds because of {}",
@@ -110,6 +137,14 @@ impl TxBroadcaster {
     }
 }
 
+/// Raw log of the broadcasted tx or the error that occurred
+fn raw_log(tx_response: &Result<TxResponse, DaemonError>) -> String {
+    match tx_response {
+        Ok(r) => r.raw_log.clone(),
+        Err(e) => e.to_string(),
+    }
+}
+
 fn strategy_condition_met(
     s: &RetryStrategy,
     tx_response: &Result<TxResponse, DaemonError>,
@@ -120,12 +155,11 @@ fn strategy_condition_met(
     }
 }
 
-async fn broadcast_helper(
+async fn broadcast_helper<T: BroadcastSender>(
     tx_builder: &mut TxBuilder,
-    wallet: &Wallet,
+    wallet: &T,
 ) -> Result<TxResponse, DaemonError> {
-    let tx = tx_builder.build(wallet).await?;
-    let tx_response = wallet.broadcast_tx(tx).await?;
+    let tx_response = wallet.broadcast_tx_builder(tx_builder).await?;
     log::debug!(target: &transaction_target(), "TX broadcast response: {:?}", tx_response);
 
     assert_broadcast_code_response(tx_response)
@@ -240,7 +274,7 @@ pub fn insufficient_fee_strategy() -> RetryStrategy {
     )
 }
 
-fn has_account_sequence_error(raw_log: &str) -> bool {
+pub(crate) fn has_account_sequence_error(raw_log: &str) -> bool {
     raw_log.contains("incorrect account sequence")
 }
 
@@ -254,9 +288,83 @@ pub fn account_sequence_strategy() -> RetryStrategy {
     )
 }
 
+fn has_mempool_full_error(raw_log: &str) -> bool {
+    raw_log.contains("mempool is full")
+}
+
+/// Retries broadcasting the tx while the mempool of the node is full
+pub fn mempool_full_strategy() -> RetryStrategy {
+    RetryStrategy::new(
+        |tx_response| has_mempool_full_error(&tx_response.raw_log),
+        |error| has_mempool_full_error(&error.to_string()),
+        None,
+        BroadcastRetry::Finite(10),
+        "a full mempool".to_string(),
+    )
+}
+
+fn is_node_timeout(error: &DaemonError) -> bool {
+    match error {
+        DaemonError::Status(status) => {
+            matches!(status.code(), Code::DeadlineExceeded | Code::Unavailable)
+        }
+        DaemonError::TransportError(_) => true,
+        // Broadcasted txs with a non-zero code are returned as errors
+        DaemonError::TxFailed { reason, .. } => reason.contains("timed out waiting for tx"),
+        _ => false,
+    }
+}
+
+/// Retries the tx when the node didn't answer in time, when simulating or broadcasting it
+pub fn node_timeout_strategy() -> RetryStrategy {
+    RetryStrategy::new(
+        |tx_response| tx_response.raw_log.contains("timed out waiting for tx"),
+        is_node_timeout,
+        None,
+        BroadcastRetry::Finite(3),
+        "a node timeout".to_string(),
+    )
+}
+
+fn has_out_of_gas_error(raw_log: &str) -> bool {
+    raw_log.contains("out of gas")
+}
+
+/// Gas limit multiplier applied when the node runs out of gas while checking the tx
+const OUT_OF_GAS_MULTIPLIER: f64 = 1.5;
+
+/// Bumps the gas limit of the tx when the node runs out of gas while checking it.
+/// The fee of the tx is increased accordingly.
+pub fn out_of_gas_strategy() -> RetryStrategy {
+    RetryStrategy::new(
+        |tx_response| has_out_of_gas_error(&tx_response.raw_log),
+        // Broadcasted txs with a non-zero code are returned as errors
+        |error| matches!(error, DaemonError::TxFailed { reason, .. } if has_out_of_gas_error(reason)),
+        Some(|tx_builder, tx_response| {
+            let Some(gas_limit) = tx_builder.gas_limit else {
+                return Err(DaemonError::StdErr(raw_log(tx_response)));
+            };
+            tx_builder.gas_limit((gas_limit as f64 * OUT_OF_GAS_MULTIPLIER) as u64);
+            if let Some(fee_amount) = tx_builder.fee_amount {
+                tx_builder.fee_amount((fee_amount as f64 * OUT_OF_GAS_MULTIPLIER) as u128);
+            }
+            Ok(())
+        }),
+        BroadcastRetry::Finite(3),
+        "an out of gas error".to_string(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use cosmrs::{AccountId, Any};
+    use cw_orch_core::environment::ChainInfoOwned;
+    use tonic::transport::Channel;
+
     use super::*;
+    use crate::senders::{builder::SenderBuilder, query::QuerySender, tx::TxSender};
 
     #[test]
     fn test_parse_suggested_fee() {
@@ -264,4 +372,137 @@ mod tests {
         let fee = parse_suggested_fee(log).unwrap();
         assert_eq!(fee, 444255);
     }
+
+    /// Sender answering the broadcasts with predefined responses, recording the gas limit of each broadcast
+    #[derive(Clone)]
+    struct MockSender {
+        responses: Arc<Mutex<Vec<TxResponse>>>,
+        gas_limits: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl MockSender {
+        fn new(mut responses: Vec<TxResponse>) -> Self {
+            responses.reverse();
+            Self {
+                responses: Arc::new(Mutex::new(responses)),
+                gas_limits: Default::default(),
+            }
+        }
+    }
+
+    struct MockOptions;
+
+    impl SenderBuilder for MockOptions {
+        type Error = DaemonError;
+        type Sender = MockSender;
+
+        async fn build(&self, _: &Arc<ChainInfoOwned>) -> Result<MockSender, DaemonError> {
+            unimplemented!()
+        }
+    }
+
+    impl QuerySender for MockSender {
+        type Error = DaemonError;
+        type Options = MockOptions;
+
+        fn channel(&self) -> Channel {
+            // Nothing listens there, block speed queries fail
+            Channel::from_static("http://127.0.0.1:1").connect_lazy()
+        }
+    }
+
+    impl TxSender for MockSender {
+        async fn commit_tx_any(
+            &self,
+            _: Vec<Any>,
+            _: Option<&str>,
+        ) -> Result<CosmTxResponse, DaemonError> {
+            unimplemented!()
+        }
+
+        fn account_id(&self) -> AccountId {
+            AccountId::new("cosmos", &[0; 20]).unwrap()
+        }
+    }
+
+    impl BroadcastSender for MockSender {
+        async fn broadcast_tx_builder(
+            &self,
+            tx_builder: &mut TxBuilder,
+        ) -> Result<TxResponse, DaemonError> {
+            // Building the tx sets the simulated gas limit
+            let gas_limit = *tx_builder.gas_limit.get_or_insert(100_000);
+            self.gas_limits.lock().unwrap().push(gas_limit);
+            Ok(self.responses.lock().unwrap().pop().unwrap())
+        }
+    }
+
+    fn failed_tx(code: u32, raw_log: &str) -> TxResponse {
+        TxResponse {
+            code,
+            raw_log: raw_log.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn broadcaster(strategy: RetryStrategy) -> TxBroadcaster {
+        TxBroadcaster::default()
+            .add_strategy(strategy)
+            .retry_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn out_of_gas_bumps_gas_and_fee() {
+        let sender = MockSender::new(vec![
+            failed_tx(
+                11,
+                "out of gas in location: txSize; gasWanted: 100000, gasUsed: 120000: out of gas",
+            ),
+            TxResponse::default(),
+        ]);
+
+        let tx_builder = TxBuilder::new(TxBuilder::build_body(vec![], None, 0));
+        broadcaster(out_of_gas_strategy())
+            .broadcast(tx_builder, &sender)
+            .await
+            .unwrap();
+        assert_eq!(*sender.gas_limits.lock().unwrap(), vec![100_000, 150_000]);
+    }
+
+    #[tokio::test]
+    async fn retries_are_limited() {
+        let timeout = failed_tx(5, "timed out waiting for tx to be included in a block");
+        let sender = MockSender::new(vec![timeout; 4]);
+
+        let tx_builder = TxBuilder::new(TxBuilder::build_body(vec![], None, 0));
+        let res = broadcaster(node_timeout_strategy())
+            .broadcast(tx_builder, &sender)
+            .await;
+        assert!(matches!(res, Err(DaemonError::TxFailed { code: 5, .. })));
+        // The tx is retried 3 times
+        assert!(sender.responses.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn node_errors_match_strategies() {
+        let timeout = Err(DaemonError::Status(tonic::Status::deadline_exceeded(
+            "request timed out",
+        )));
+        assert!(strategy_condition_met(&node_timeout_strategy(), &timeout));
+        assert!(!strategy_condition_met(&mempool_full_strategy(), &timeout));
+
+        let mempool_full = Ok(TxResponse {
+            code: 20,
+            raw_log: "mempool is full: number of txs 5000 (max: 5000)".to_string(),
+            ..Default::default()
+        });
+        assert!(strategy_condition_met(
+            &mempool_full_strategy(),
+            &mempool_full
+        ));
+        assert!(!strategy_condition_met(
+            &node_timeout_strategy(),
+            &mempool_full
+        ));
+    }
 }
@@ -10,7 +10,7 @@ use cosmrs::{
 };
 use cw_orch_core::log::transaction_target;
 
-use crate::Wallet;
+use crate::senders::{fee_for_gas_limit, CosmosSender, Signer};
 
 use super::DaemonError;
 
@@ -24,6 +24,8 @@ pub struct TxBuilder {
     pub(crate) gas_limit: Option<u64>,
     // if defined, use this sequence, else get it from the node
     pub(crate) sequence: Option<SequenceNumber>,
+    // if defined, use this account number, else get it from the node
+    pub(crate) account_number: Option<u64>,
 }
 
 impl TxBuilder {
@@ -34,6 +36,7 @@ impl TxBuilder {
             fee_amount: None,
             gas_limit: None,
             sequence: None,
+            account_number: None,
         }
     }
     /// Set a fixed fee amount for the tx
@@ -51,6 +54,11 @@ impl TxBuilder {
         self.sequence = Some(sequence);
         self
     }
+    /// Set the account number of the signer of the tx
+    pub fn account_number(&mut self, account_number: u64) -> &mut Self {
+        self.account_number = Some(account_number);
+        self
+    }
 
     /// Builds the body of the tx with a given memo and timeout.
     pub fn build_body(msgs: Vec<Any>, memo: Option<&str>, timeout: u64) -> tx::Body {
@@ -73,8 +81,14 @@ impl TxBuilder {
         Ok(fee)
     }
 
-    /// Simulates the transaction and returns the necessary gas fee returned by the simulation on a node
-    pub async fn simulate(&self, wallet: &Wallet) -> Result<u64, DaemonError> {
+    /// Returns the account number and sequence of the tx, queried from the node when they are not set
+    async fn account<S: Signer>(
+        &self,
+        wallet: &CosmosSender<S>,
+    ) -> Result<(u64, u64), DaemonError> {
+        if let (Some(account_number), Some(sequence)) = (self.account_number, self.sequence) {
+            return Ok((account_number, sequence));
+        }
         // get the account number of the wallet
         let BaseAccount {
             account_number,
@@ -83,7 +97,15 @@ impl TxBuilder {
         } = wallet.base_account().await?;
 
         // overwrite sequence if set (can be used for concurrent txs)
-        let sequence = self.sequence.unwrap_or(sequence);
+        Ok((
+            self.account_number.unwrap_or(account_number),
+            self.sequence.unwrap_or(sequence),
+        ))
+    }
+
+    /// Simulates the transaction and returns the necessary gas fee returned by the simulation on a node
+    pub async fn simulate<S: Signer>(&self, wallet: &CosmosSender<S>) -> Result<u64, DaemonError> {
+        let (account_number, sequence) = self.account(wallet).await?;
 
         wallet
             .calculate_gas(&self.body, sequence, account_number)
@@ -92,16 +114,8 @@ impl TxBuilder {
 
     /// Builds the raw tx with a given body and fee and signs it.
     /// Sets the TxBuilder's gas limit to its simulated amount for later use.
-    pub async fn build(&mut self, wallet: &Wallet) -> Result<Raw, DaemonError> {
-        // get the account number of the wallet
-        let BaseAccount {
-            account_number,
-            sequence,
-            ..
-        } = wallet.base_account().await?;
-
-        // overwrite sequence if set (can be used for concurrent txs)
-        let sequence = self.sequence.unwrap_or(sequence);
+    pub async fn build<S: Signer>(&mut self, wallet: &CosmosSender<S>) -> Result<Raw, DaemonError> {
+        let (account_number, sequence) = self.account(wallet).await?;
 
         //
         let (tx_fee, gas_limit) = if let (Some(fee), Some(gas_limit)) =
@@ -114,13 +128,23 @@ impl TxBuilder {
                 gas_limit
             );
             (fee, gas_limit)
+        } else if let Some(gas_limit) = self.gas_limit {
+            // the fee is paid for the whole gas limit, the gas limit can be bumped by retry strategies
+            let fee = fee_for_gas_limit(gas_limit, wallet.gas_price().await);
+            log::debug!(
+                target: &transaction_target(),
+                "Using pre-defined gas limit: {}, calculated fee: {}",
+                gas_limit,
+                fee
+            );
+            (fee, gas_limit)
         } else {
             let sim_gas_used = wallet
                 .calculate_gas(&self.body, sequence, account_number)
                 .await?;
             log::debug!(target: &transaction_target(), "Simulated gas needed {:?}", sim_gas_used);
 
-            let (gas_expected, fee_amount) = wallet.get_fee_from_gas(sim_gas_used)?;
+            let (gas_expected, fee_amount) = wallet.get_fee_from_gas(sim_gas_used).await?;
 
             log::debug!(target: &transaction_target(), "Calculated fee needed: {:?}", fee_amount);
             // set the gas limit of self for future txs
@@ -146,7 +170,7 @@ impl TxBuilder {
         );
 
         let auth_info = SignerInfo {
-            public_key: wallet.private_key.get_signer_public_key(&wallet.secp),
+            public_key: wallet.signer.public_key(),
             mode_info: ModeInfo::single(SignMode::Direct),
             sequence,
         }
@@ -158,6 +182,6 @@ impl TxBuilder {
             &Id::try_from(wallet.chain_info.chain_id.to_string())?,
             account_number,
         )?;
-        wallet.sign(sign_doc).map_err(Into::into)
+        wallet.sign(sign_doc).await
     }
 }
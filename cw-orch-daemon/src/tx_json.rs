@@ -0,0 +1,697 @@
+//! Cosmos-SDK JSON encoding of transactions.
+//!
+//! Unsigned transactions are encoded like the output of `<chain-binary> tx ... --generate-only`, so they can be signed offline with `<chain-binary> tx sign`.
+//! Transactions signed by `<chain-binary> tx sign` are decoded back into raw transactions, ready to be broadcasted.
+//!
+//! The messages sent by cw-orchestrator are encoded field by field: CosmWasm messages, bank sends and authz executions.
+//! Other messages are encoded as a protobuf `Any`, `{ "@type": <type url>, "value": <base64 bytes> }`, that cw-orchestrator decodes back
+//! but that the chain binaries can't read.
+//! The legacy Amino JSON encoding of the CosmWasm messages and bank sends is used to sign for multisig accounts with `SIGN_MODE_LEGACY_AMINO_JSON`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cosmrs::{
+    crypto::LegacyAminoMultisig,
+    proto::{
+        cosmos::{
+            authz::v1beta1::MsgExec,
+            bank::v1beta1::MsgSend,
+            base::v1beta1::Coin,
+            crypto::{
+                multisig::{v1beta1::CompactBitArray, LegacyAminoPubKey},
+                secp256k1::PubKey,
+            },
+            tx::{
+                signing::v1beta1::SignMode,
+                v1beta1::{mode_info, AuthInfo, Fee, ModeInfo, SignerInfo, TxBody, TxRaw},
+            },
+        },
+        cosmwasm::wasm::v1::{
+            MsgExecuteContract, MsgInstantiateContract, MsgInstantiateContract2,
+            MsgMigrateContract, MsgStoreCode,
+        },
+        traits::{Message, Name},
+        Any,
+    },
+};
+use serde_json::{json, Value};
+
+use crate::{DaemonError, INSTANTIATE_2_TYPE_URL};
+
+const MSG_EXEC_TYPE_URL: &str = "/cosmos.authz.v1beta1.MsgExec";
+
+/// Encodes a transaction in the Cosmos-SDK JSON format
+pub fn tx_to_json(
+    body: &TxBody,
+    auth_info: &AuthInfo,
+    signatures: &[Vec<u8>],
+) -> Result<Value, DaemonError> {
+    if !body.extension_options.is_empty()
+        || !body.non_critical_extension_options.is_empty()
+        || auth_info.tip.is_some()
+    {
+        return Err(unsupported("transaction extensions and tips"));
+    }
+    let fee = auth_info.fee.clone().unwrap_or_default();
+
+    Ok(json!({
+        "body": {
+            "messages": body.messages.iter().map(msg_to_json).collect::<Result<Vec<_>, _>>()?,
+            "memo": body.memo,
+            "timeout_height": body.timeout_height.to_string(),
+            "extension_options": [],
+            "non_critical_extension_options": []
+        },
+        "auth_info": {
+            "signer_infos": auth_info.signer_infos.iter().map(signer_info_to_json).collect::<Result<Vec<_>, _>>()?,
+            "fee": {
+                "amount": coins_to_json(&fee.amount),
+                "gas_limit": fee.gas_limit.to_string(),
+                "payer": fee.payer,
+                "granter": fee.granter
+            },
+            "tip": null
+        },
+        "signatures": signatures.iter().map(|signature| STANDARD.encode(signature)).collect::<Vec<_>>()
+    }))
+}
+
+/// Decodes a transaction in the Cosmos-SDK JSON format into a raw transaction
+pub fn tx_from_json(tx: &Value) -> Result<TxRaw, DaemonError> {
+    let body = &tx["body"];
+    let auth_info = &tx["auth_info"];
+    if !array(&body["extension_options"])?.is_empty()
+        || !array(&body["non_critical_extension_options"])?.is_empty()
+        || !auth_info["tip"].is_null()
+    {
+        return Err(unsupported("transaction extensions and tips"));
+    }
+
+    let body = TxBody {
+        messages: array(&body["messages"])?
+            .iter()
+            .map(msg_from_json)
+            .collect::<Result<_, _>>()?,
+        memo: string(&body["memo"])?,
+        timeout_height: uint(&body["timeout_height"])?,
+        extension_options: vec![],
+        non_critical_extension_options: vec![],
+    };
+    let fee = &auth_info["fee"];
+    let auth_info = AuthInfo {
+        signer_infos: array(&auth_info["signer_infos"])?
+            .iter()
+            .map(signer_info_from_json)
+            .collect::<Result<_, _>>()?,
+        fee: Some(Fee {
+            amount: coins_from_json(&fee["amount"])?,
+            gas_limit: uint(&fee["gas_limit"])?,
+            payer: string(&fee["payer"])?,
+            granter: string(&fee["granter"])?,
+        }),
+        tip: None,
+    };
+    let signatures = array(&tx["signatures"])?
+        .iter()
+        .map(|signature| Ok(STANDARD.decode(string(signature)?)?))
+        .collect::<Result<_, DaemonError>>()?;
+
+    Ok(TxRaw {
+        body_bytes: body.encode_to_vec(),
+        auth_info_bytes: auth_info.encode_to_vec(),
+        signatures,
+    })
+}
+
+/// Encodes a transaction message in the Cosmos-SDK JSON format
+pub fn msg_to_json(msg: &Any) -> Result<Value, DaemonError> {
+    let bytes = msg.value.as_slice();
+    let mut value = if msg.type_url == MsgStoreCode::type_url() {
+        let msg = MsgStoreCode::decode(bytes)?;
+        if msg.instantiate_permission.is_some() {
+            return Err(unsupported("instantiate permissions"));
+        }
+        json!({
+            "sender": msg.sender,
+            "wasm_byte_code": STANDARD.encode(msg.wasm_byte_code),
+            "instantiate_permission": null
+        })
+    } else if msg.type_url == MsgInstantiateContract::type_url() {
+        let msg = MsgInstantiateContract::decode(bytes)?;
+        json!({
+            "sender": msg.sender,
+            "admin": msg.admin,
+            "code_id": msg.code_id.to_string(),
+            "label": msg.label,
+            "msg": contract_msg_to_json(&msg.msg)?,
+            "funds": coins_to_json(&msg.funds)
+        })
+    } else if msg.type_url == INSTANTIATE_2_TYPE_URL {
+        let msg = MsgInstantiateContract2::decode(bytes)?;
+        json!({
+            "sender": msg.sender,
+            "admin": msg.admin,
+            "code_id": msg.code_id.to_string(),
+            "label": msg.label,
+            "msg": contract_msg_to_json(&msg.msg)?,
+            "funds": coins_to_json(&msg.funds),
+            "salt": STANDARD.encode(msg.salt),
+            "fix_msg": msg.fix_msg
+        })
+    } else if msg.type_url == MsgExecuteContract::type_url() {
+        let msg = MsgExecuteContract::decode(bytes)?;
+        json!({
+            "sender": msg.sender,
+            "contract": msg.contract,
+            "msg": contract_msg_to_json(&msg.msg)?,
+            "funds": coins_to_json(&msg.funds)
+        })
+    } else if msg.type_url == MsgMigrateContract::type_url() {
+        let msg = MsgMigrateContract::decode(bytes)?;
+        json!({
+            "sender": msg.sender,
+            "contract": msg.contract,
+            "code_id": msg.code_id.to_string(),
+            "msg": contract_msg_to_json(&msg.msg)?
+        })
+    } else if msg.type_url == MsgSend::type_url() {
+        let msg = MsgSend::decode(bytes)?;
+        json!({
+            "from_address": msg.from_address,
+            "to_address": msg.to_address,
+            "amount": coins_to_json(&msg.amount)
+        })
+    } else if msg.type_url == MSG_EXEC_TYPE_URL {
+        let msg = MsgExec::decode(bytes)?;
+        json!({
+            "grantee": msg.grantee,
+            "msgs": msg.msgs.iter().map(msg_to_json).collect::<Result<Vec<_>, _>>()?
+        })
+    } else {
+        json!({ "value": STANDARD.encode(bytes) })
+    };
+    value["@type"] = json!(msg.type_url);
+    Ok(value)
+}
+
+/// Decodes a transaction message in the Cosmos-SDK JSON format
+pub fn msg_from_json(msg: &Value) -> Result<Any, DaemonError> {
+    let type_url = string(&msg["@type"])?;
+    let value = if type_url == MsgStoreCode::type_url() {
+        if !msg["instantiate_permission"].is_null() {
+            return Err(unsupported("instantiate permissions"));
+        }
+        MsgStoreCode {
+            sender: string(&msg["sender"])?,
+            wasm_byte_code: STANDARD.decode(string(&msg["wasm_byte_code"])?)?,
+            instantiate_permission: None,
+        }
+        .encode_to_vec()
+    } else if type_url == MsgInstantiateContract::type_url() {
+        MsgInstantiateContract {
+            sender: string(&msg["sender"])?,
+            admin: string(&msg["admin"])?,
+            code_id: uint(&msg["code_id"])?,
+            label: string(&msg["label"])?,
+            msg: serde_json::to_vec(&msg["msg"])?,
+            funds: coins_from_json(&msg["funds"])?,
+        }
+        .encode_to_vec()
+    } else if type_url == INSTANTIATE_2_TYPE_URL {
+        MsgInstantiateContract2 {
+            sender: string(&msg["sender"])?,
+            admin: string(&msg["admin"])?,
+            code_id: uint(&msg["code_id"])?,
+            label: string(&msg["label"])?,
+            msg: serde_json::to_vec(&msg["msg"])?,
+            funds: coins_from_json(&msg["funds"])?,
+            salt: STANDARD.decode(string(&msg["salt"])?)?,
+            fix_msg: msg["fix_msg"].as_bool().unwrap_or_default(),
+        }
+        .encode_to_vec()
+    } else if type_url == MsgExecuteContract::type_url() {
+        MsgExecuteContract {
+            sender: string(&msg["sender"])?,
+            contract: string(&msg["contract"])?,
+            msg: serde_json::to_vec(&msg["msg"])?,
+            funds: coins_from_json(&msg["funds"])?,
+        }
+        .encode_to_vec()
+    } else if type_url == MsgMigrateContract::type_url() {
+        MsgMigrateContract {
+            sender: string(&msg["sender"])?,
+            contract: string(&msg["contract"])?,
+            code_id: uint(&msg["code_id"])?,
+            msg: serde_json::to_vec(&msg["msg"])?,
+        }
+        .encode_to_vec()
+    } else if type_url == MsgSend::type_url() {
+        MsgSend {
+            from_address: string(&msg["from_address"])?,
+            to_address: string(&msg["to_address"])?,
+            amount: coins_from_json(&msg["amount"])?,
+        }
+        .encode_to_vec()
+    } else if type_url == MSG_EXEC_TYPE_URL {
+        MsgExec {
+            grantee: string(&msg["grantee"])?,
+            msgs: array(&msg["msgs"])?
+                .iter()
+                .map(msg_from_json)
+                .collect::<Result<_, _>>()?,
+        }
+        .encode_to_vec()
+    } else {
+        STANDARD.decode(string(&msg["value"])?)?
+    };
+    Ok(Any { type_url, value })
+}
+
+/// Returns the bytes signed with `SIGN_MODE_LEGACY_AMINO_JSON`: the sorted JSON encoding of the legacy `StdSignDoc`
+pub fn legacy_amino_sign_bytes(
+    body: &TxBody,
+    fee: &Fee,
+    chain_id: &str,
+    account_number: u64,
+    sequence: u64,
+) -> Result<Vec<u8>, DaemonError> {
+    if !body.extension_options.is_empty() || !body.non_critical_extension_options.is_empty() {
+        return Err(unsupported("transaction extensions"));
+    }
+    let mut amino_fee = json!({
+        "amount": coins_to_json(&fee.amount),
+        "gas": fee.gas_limit.to_string()
+    });
+    if !fee.payer.is_empty() {
+        amino_fee["payer"] = json!(fee.payer);
+    }
+    if !fee.granter.is_empty() {
+        amino_fee["granter"] = json!(fee.granter);
+    }
+    let mut sign_doc = json!({
+        "account_number": account_number.to_string(),
+        "chain_id": chain_id,
+        "fee": amino_fee,
+        "memo": body.memo,
+        "msgs": body.messages.iter().map(msg_to_amino_json).collect::<Result<Vec<_>, _>>()?,
+        "sequence": sequence.to_string()
+    });
+    if body.timeout_height != 0 {
+        sign_doc["timeout_height"] = json!(body.timeout_height.to_string());
+    }
+
+    // The Cosmos-SDK encodes JSON with Go, which escapes HTML characters and line separators
+    let sign_doc = serde_json::to_string(&sorted_json(sign_doc))?
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029");
+    Ok(sign_doc.into_bytes())
+}
+
+/// Encodes a transaction message in the legacy Amino JSON format
+pub fn msg_to_amino_json(msg: &Any) -> Result<Value, DaemonError> {
+    let (_, amino_type) = AMINO_TYPES
+        .into_iter()
+        .find(|(type_url, _)| msg.type_url == *type_url)
+        .ok_or_else(|| unsupported(&format!("Amino message type {}", msg.type_url)))?;
+    let mut value = msg_to_json(msg)?;
+    let fields = value.as_object_mut().unwrap();
+    fields.remove("@type");
+    // Empty fields are omitted by Amino, except for coins
+    fields.retain(|_, field| !matches!(field, Value::Null | Value::Bool(false)) && field != "");
+
+    Ok(json!({ "type": amino_type, "value": value }))
+}
+
+const AMINO_TYPES: [(&str, &str); 6] = [
+    ("/cosmwasm.wasm.v1.MsgStoreCode", "wasm/MsgStoreCode"),
+    (
+        "/cosmwasm.wasm.v1.MsgInstantiateContract",
+        "wasm/MsgInstantiateContract",
+    ),
+    (INSTANTIATE_2_TYPE_URL, "wasm/MsgInstantiateContract2"),
+    (
+        "/cosmwasm.wasm.v1.MsgExecuteContract",
+        "wasm/MsgExecuteContract",
+    ),
+    (
+        "/cosmwasm.wasm.v1.MsgMigrateContract",
+        "wasm/MsgMigrateContract",
+    ),
+    ("/cosmos.bank.v1beta1.MsgSend", "cosmos-sdk/MsgSend"),
+];
+
+/// Sorts the keys of the JSON objects, recursively
+fn sorted_json(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => {
+            let mut fields = fields.into_iter().collect::<Vec<_>>();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, field)| (key, sorted_json(field)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sorted_json).collect()),
+        value => value,
+    }
+}
+
+fn signer_info_to_json(signer_info: &SignerInfo) -> Result<Value, DaemonError> {
+    Ok(json!({
+        "public_key": signer_info.public_key.as_ref().map(public_key_to_json).transpose()?,
+        "mode_info": mode_info_to_json(signer_info.mode_info.as_ref())?,
+        "sequence": signer_info.sequence.to_string()
+    }))
+}
+
+fn signer_info_from_json(signer_info: &Value) -> Result<SignerInfo, DaemonError> {
+    let public_key = &signer_info["public_key"];
+    Ok(SignerInfo {
+        public_key: if public_key.is_null() {
+            None
+        } else {
+            Some(public_key_from_json(public_key)?)
+        },
+        mode_info: Some(mode_info_from_json(&signer_info["mode_info"])?),
+        sequence: uint(&signer_info["sequence"])?,
+    })
+}
+
+fn public_key_to_json(public_key: &Any) -> Result<Value, DaemonError> {
+    if public_key.type_url == LegacyAminoMultisig::TYPE_URL {
+        let multisig = LegacyAminoPubKey::decode(public_key.value.as_slice())?;
+        Ok(json!({
+            "@type": public_key.type_url,
+            "threshold": multisig.threshold,
+            "public_keys": multisig.public_keys.iter().map(public_key_to_json).collect::<Result<Vec<_>, _>>()?
+        }))
+    } else {
+        Ok(json!({
+            "@type": public_key.type_url,
+            "key": STANDARD.encode(PubKey::decode(public_key.value.as_slice())?.key)
+        }))
+    }
+}
+
+fn public_key_from_json(public_key: &Value) -> Result<Any, DaemonError> {
+    let type_url = string(&public_key["@type"])?;
+    let value = if type_url == LegacyAminoMultisig::TYPE_URL {
+        LegacyAminoPubKey {
+            threshold: uint(&public_key["threshold"])? as u32,
+            public_keys: array(&public_key["public_keys"])?
+                .iter()
+                .map(public_key_from_json)
+                .collect::<Result<_, _>>()?,
+        }
+        .encode_to_vec()
+    } else {
+        PubKey {
+            key: STANDARD.decode(string(&public_key["key"])?)?,
+        }
+        .encode_to_vec()
+    };
+    Ok(Any { type_url, value })
+}
+
+fn mode_info_to_json(mode_info: Option<&ModeInfo>) -> Result<Value, DaemonError> {
+    match mode_info.and_then(|mode| mode.sum.as_ref()) {
+        Some(mode_info::Sum::Single(single)) => {
+            let mode = SignMode::try_from(single.mode)
+                .map_err(|_| unsupported(&format!("sign mode {}", single.mode)))?;
+            Ok(json!({ "single": { "mode": mode.as_str_name() } }))
+        }
+        Some(mode_info::Sum::Multi(multi)) => {
+            let bitarray = multi.bitarray.clone().unwrap_or_default();
+            Ok(json!({
+                "multi": {
+                    "bitarray": {
+                        "extra_bits_stored": bitarray.extra_bits_stored,
+                        "elems": STANDARD.encode(bitarray.elems)
+                    },
+                    "mode_infos": multi
+                        .mode_infos
+                        .iter()
+                        .map(|mode_info| mode_info_to_json(Some(mode_info)))
+                        .collect::<Result<Vec<_>, _>>()?
+                }
+            }))
+        }
+        None => Err(invalid(&Value::Null, "a mode info")),
+    }
+}
+
+fn mode_info_from_json(mode_info: &Value) -> Result<ModeInfo, DaemonError> {
+    let sum = if let Some(single) = mode_info.get("single") {
+        let mode = string(&single["mode"])?;
+        let mode = SIGN_MODES
+            .into_iter()
+            .find(|sign_mode| sign_mode.as_str_name() == mode)
+            .ok_or_else(|| unsupported(&format!("sign mode {mode}")))?;
+        mode_info::Sum::Single(mode_info::Single { mode: mode as i32 })
+    } else if let Some(multi) = mode_info.get("multi") {
+        let bitarray = &multi["bitarray"];
+        mode_info::Sum::Multi(mode_info::Multi {
+            bitarray: Some(CompactBitArray {
+                extra_bits_stored: uint(&bitarray["extra_bits_stored"])? as u32,
+                elems: STANDARD.decode(string(&bitarray["elems"])?)?,
+            }),
+            mode_infos: array(&multi["mode_infos"])?
+                .iter()
+                .map(mode_info_from_json)
+                .collect::<Result<_, _>>()?,
+        })
+    } else {
+        return Err(invalid(mode_info, "a mode info"));
+    };
+    Ok(ModeInfo { sum: Some(sum) })
+}
+
+const SIGN_MODES: [SignMode; 5] = [
+    SignMode::Direct,
+    SignMode::Textual,
+    SignMode::DirectAux,
+    SignMode::LegacyAminoJson,
+    SignMode::Eip191,
+];
+
+fn contract_msg_to_json(msg: &[u8]) -> Result<Value, DaemonError> {
+    Ok(serde_json::from_slice(msg)?)
+}
+
+fn coins_to_json(coins: &[Coin]) -> Value {
+    coins
+        .iter()
+        .map(|coin| json!({ "denom": coin.denom, "amount": coin.amount }))
+        .collect()
+}
+
+fn coins_from_json(coins: &Value) -> Result<Vec<Coin>, DaemonError> {
+    array(coins)?
+        .iter()
+        .map(|coin| {
+            Ok(Coin {
+                denom: string(&coin["denom"])?,
+                amount: string(&coin["amount"])?,
+            })
+        })
+        .collect()
+}
+
+/// Missing arrays are decoded as empty arrays, like the Cosmos-SDK does
+fn array(value: &Value) -> Result<Vec<Value>, DaemonError> {
+    match value {
+        Value::Null => Ok(vec![]),
+        Value::Array(values) => Ok(values.clone()),
+        _ => Err(invalid(value, "an array")),
+    }
+}
+
+/// Missing strings are decoded as empty strings, like the Cosmos-SDK does
+fn string(value: &Value) -> Result<String, DaemonError> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::String(value) => Ok(value.clone()),
+        _ => Err(invalid(value, "a string")),
+    }
+}
+
+/// 64-bit integers are encoded as strings
+fn uint(value: &Value) -> Result<u64, DaemonError> {
+    match value {
+        Value::Null => Ok(0),
+        Value::String(value) => Ok(value.parse()?),
+        Value::Number(number) => number.as_u64().ok_or_else(|| invalid(value, "an integer")),
+        _ => Err(invalid(value, "an integer")),
+    }
+}
+
+fn invalid(value: &Value, expected: &str) -> DaemonError {
+    DaemonError::StdErr(format!(
+        "Invalid transaction JSON, expected {expected}, got {value}"
+    ))
+}
+
+fn unsupported(feature: &str) -> DaemonError {
+    DaemonError::StdErr(format!(
+        "The JSON encoding of {feature} is not supported by cw-orchestrator"
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use cosmrs::proto::{
+        cosmos::{
+            authz::v1beta1::MsgExec,
+            base::v1beta1::Coin,
+            crypto::secp256k1::PubKey,
+            tx::v1beta1::{mode_info, AuthInfo, Fee, ModeInfo, SignerInfo, TxBody},
+        },
+        cosmwasm::wasm::v1::MsgExecuteContract,
+        traits::{Message, Name},
+        Any,
+    };
+    use serde_json::json;
+
+    use super::{
+        legacy_amino_sign_bytes, msg_from_json, msg_to_json, tx_from_json, tx_to_json,
+        MSG_EXEC_TYPE_URL,
+    };
+
+    #[test]
+    fn signed_tx_round_trip() -> anyhow::Result<()> {
+        let body = TxBody {
+            messages: vec![Any {
+                type_url: MsgExecuteContract::type_url(),
+                value: MsgExecuteContract {
+                    sender: "juno1sender".to_string(),
+                    contract: "juno1contract".to_string(),
+                    msg: br#"{"increment":{"by":1}}"#.to_vec(),
+                    funds: vec![Coin {
+                        denom: "ujuno".to_string(),
+                        amount: "10".to_string(),
+                    }],
+                }
+                .encode_to_vec(),
+            }],
+            memo: "memo".to_string(),
+            timeout_height: 0,
+            extension_options: vec![],
+            non_critical_extension_options: vec![],
+        };
+        let auth_info = AuthInfo {
+            signer_infos: vec![SignerInfo {
+                public_key: Some(Any {
+                    type_url: PubKey::type_url(),
+                    value: PubKey { key: vec![2; 33] }.encode_to_vec(),
+                }),
+                mode_info: Some(ModeInfo {
+                    sum: Some(mode_info::Sum::Single(mode_info::Single { mode: 1 })),
+                }),
+                sequence: 4,
+            }],
+            fee: Some(Fee {
+                amount: vec![Coin {
+                    denom: "ujuno".to_string(),
+                    amount: "2500".to_string(),
+                }],
+                gas_limit: 200_000,
+                payer: String::new(),
+                granter: String::new(),
+            }),
+            tip: None,
+        };
+        let signatures = vec![vec![7; 64]];
+
+        let tx = tx_to_json(&body, &auth_info, &signatures)?;
+        assert_eq!(
+            tx["body"]["messages"][0]["msg"],
+            json!({ "increment": { "by": 1 } })
+        );
+        assert_eq!(
+            tx["auth_info"]["signer_infos"][0]["mode_info"]["single"]["mode"],
+            "SIGN_MODE_DIRECT"
+        );
+        assert_eq!(tx["auth_info"]["fee"]["gas_limit"], "200000");
+
+        let raw = tx_from_json(&tx)?;
+        assert_eq!(raw.body_bytes, body.encode_to_vec());
+        assert_eq!(raw.auth_info_bytes, auth_info.encode_to_vec());
+        assert_eq!(raw.signatures, signatures);
+        Ok(())
+    }
+
+    #[test]
+    fn legacy_amino_sign_bytes_are_sorted_and_escaped() -> anyhow::Result<()> {
+        let body = TxBody {
+            messages: vec![Any {
+                type_url: MsgExecuteContract::type_url(),
+                value: MsgExecuteContract {
+                    sender: "juno1sender".to_string(),
+                    contract: "juno1contract".to_string(),
+                    msg: br#"{"b":"<a>","a":1}"#.to_vec(),
+                    funds: vec![],
+                }
+                .encode_to_vec(),
+            }],
+            ..Default::default()
+        };
+        let fee = Fee {
+            amount: vec![Coin {
+                denom: "ujuno".to_string(),
+                amount: "2500".to_string(),
+            }],
+            gas_limit: 200_000,
+            payer: String::new(),
+            granter: String::new(),
+        };
+
+        let sign_bytes = legacy_amino_sign_bytes(&body, &fee, "juno-1", 12, 3)?;
+        assert_eq!(
+            String::from_utf8(sign_bytes)?,
+            r#"{"account_number":"12","chain_id":"juno-1","fee":{"amount":[{"amount":"2500","denom":"ujuno"}],"gas":"200000"},"memo":"","msgs":[{"type":"wasm/MsgExecuteContract","value":{"contract":"juno1contract","funds":[],"msg":{"a":1,"b":"\u003ca\u003e"},"sender":"juno1sender"}}],"sequence":"3"}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn authz_and_unknown_messages_round_trip() -> anyhow::Result<()> {
+        let execute = Any {
+            type_url: MsgExecuteContract::type_url(),
+            value: MsgExecuteContract {
+                sender: "juno1granter".to_string(),
+                contract: "juno1contract".to_string(),
+                msg: br#"{"increment":{}}"#.to_vec(),
+                funds: vec![],
+            }
+            .encode_to_vec(),
+        };
+        let exec = Any {
+            type_url: MSG_EXEC_TYPE_URL.to_string(),
+            value: MsgExec {
+                grantee: "juno1grantee".to_string(),
+                msgs: vec![execute],
+            }
+            .encode_to_vec(),
+        };
+        let json = msg_to_json(&exec)?;
+        assert_eq!(json["grantee"], "juno1grantee");
+        assert_eq!(json["msgs"][0]["msg"], json!({ "increment": {} }));
+        assert_eq!(msg_from_json(&json)?, exec);
+
+        let vote = Any {
+            type_url: "/cosmos.gov.v1beta1.MsgVote".to_string(),
+            value: vec![8, 1, 18, 4, 106, 117, 110, 111],
+        };
+        let json = msg_to_json(&vote)?;
+        assert_eq!(
+            json,
+            json!({ "@type": "/cosmos.gov.v1beta1.MsgVote", "value": "CAESBGp1bm8=" })
+        );
+        assert_eq!(msg_from_json(&json)?, vote);
+        Ok(())
+    }
+}
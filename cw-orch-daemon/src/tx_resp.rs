@@ -52,6 +52,13 @@ pub struct CosmTxResponse {
 }
 
 impl CosmTxResponse {
+    /// Whether the transaction was broadcasted.
+    /// The transactions exported by the [`OfflineSender`](crate::senders::OfflineSender) have no hash,
+    /// their response only holds the events expected once they're broadcasted.
+    pub fn is_broadcasted(&self) -> bool {
+        !self.txhash.is_empty()
+    }
+
     /// find a attribute's value from TX logs.
     /// returns: msg_index and value
     pub fn get_attribute_from_logs(
@@ -184,6 +191,10 @@ impl IndexResponse for CosmTxResponse {
         parsed_events
     }
 
+    fn gas_used(&self) -> Option<u64> {
+        Some(self.gas_used)
+    }
+
     fn data(&self) -> Option<Binary> {
         if self.data.is_empty() {
             None
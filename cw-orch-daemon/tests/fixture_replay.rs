@@ -0,0 +1,282 @@
+//! The daemon and `live_mock` replaying the responses of the local juno chain in a hand-written fixture, without any node
+
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use cosmrs::{
+    crypto::secp256k1::SigningKey,
+    proto::{
+        cosmos::{bank::v1beta1::MsgSend, base::v1beta1::Coin, tx::v1beta1::TxRaw},
+        cosmwasm::wasm::v1::MsgStoreCode,
+        traits::Name,
+    },
+    Any,
+};
+use cosmwasm_std::{coin, coins, Addr, BalanceResponse, BankQuery, QueryRequest};
+use cw_orch_core::environment::{DryRun, PlannedTxKind, QueryHandler, TxHandler};
+use cw_orch_daemon::{
+    env::{GRPC_RECORD_ENV_NAME, GRPC_REPLAY_ENV_NAME},
+    live_mock,
+    networks::LOCAL_JUNO,
+    senders::{OfflineOptions, UnsignedTx},
+    test_utils::{MockRpc, CONTRACT, MSG_GAS, SEQUENCE, TX_HASH},
+    Daemon, DaemonBuilder, DaemonError, TxSender,
+};
+use prost::Message;
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/grpc/local-juno.jsonl"
+);
+const LOCAL_MNEMONIC: &str = "clip hire initial neck maid actor venue client foam budget lock catalog sweet steak waste crater broccoli pipe steak sister coyote moment obvious choose";
+/// Address of the local validator, the account of `LOCAL_MNEMONIC`
+const VALIDATOR: &str = "juno16g2rahf5846rxzp3fwlswy08fz8ccuwk03k57y";
+/// Height of the in-process local juno node
+const LATEST_HEIGHT: u64 = 1234;
+
+/// Every channel of the process replays the fixture
+fn replay_fixture() {
+    std::env::set_var(GRPC_REPLAY_ENV_NAME, FIXTURE);
+}
+
+/// Runs `run` with a daemon builder recording the exchanges with an in-process local juno node to a new fixture,
+/// then runs it again with a builder replaying the fixture, without the node.
+/// Returns the recorded and the replayed results.
+fn record_and_replay<T>(
+    name: &str,
+    run: impl Fn(&mut DaemonBuilder) -> anyhow::Result<T>,
+) -> anyhow::Result<(T, T)> {
+    let rt = Runtime::new()?;
+    let node = MockRpc::new(
+        LOCAL_JUNO.chain_id,
+        LATEST_HEIGHT,
+        coin(999_999_000_000, "ujunox"),
+    );
+    let url = {
+        let _runtime = rt.enter();
+        node.serve()
+    };
+    let path: PathBuf = std::env::temp_dir().join(format!(
+        "cw-orch-fixture-{name}-{}.jsonl",
+        std::process::id()
+    ));
+
+    std::env::remove_var(GRPC_REPLAY_ENV_NAME);
+    std::env::set_var(GRPC_RECORD_ENV_NAME, &path);
+    let recorded = run(Daemon::builder(LOCAL_JUNO)
+        .handle(rt.handle())
+        .rpc_url(url)
+        .is_test(true));
+    std::env::remove_var(GRPC_RECORD_ENV_NAME);
+    let recorded = recorded?;
+    let requests = node.requests.lock().unwrap().len();
+
+    // Nothing listens on the url of the replay, the channels don't connect to any node
+    std::env::set_var(GRPC_REPLAY_ENV_NAME, &path);
+    let replayed = run(Daemon::builder(LOCAL_JUNO)
+        .handle(rt.handle())
+        .rpc_url("http://127.0.0.1:1")
+        .is_test(true));
+    std::env::remove_var(GRPC_REPLAY_ENV_NAME);
+    let replayed = replayed?;
+    assert_eq!(node.requests.lock().unwrap().len(), requests);
+
+    std::fs::remove_file(path)?;
+    Ok((recorded, replayed))
+}
+
+#[test]
+#[serial_test::serial]
+fn daemon_replays_the_fixture() -> anyhow::Result<()> {
+    replay_fixture();
+    let daemon = Daemon::builder(LOCAL_JUNO)
+        .mnemonic(LOCAL_MNEMONIC)
+        .is_test(true)
+        .build()?;
+    assert_eq!(daemon.sender_addr().as_str(), VALIDATOR);
+
+    let balance = daemon.balance(VALIDATOR, Some("ujunox".to_string()))?;
+    assert_eq!(balance, coins(999_999_000_000, "ujunox"));
+
+    let block = daemon.block_info()?;
+    assert_eq!(block.height, 1234);
+    assert_eq!(block.chain_id, LOCAL_JUNO.chain_id);
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn daemon_replays_the_fixture_it_recorded() -> anyhow::Result<()> {
+    let (recorded, replayed) = record_and_replay("daemon", |builder| {
+        let daemon = builder.mnemonic(LOCAL_MNEMONIC).build()?;
+        let balance = daemon.balance(VALIDATOR, Some("ujunox".to_string()))?;
+        Ok((balance, daemon.block_info()?))
+    })?;
+
+    let (balance, block) = &recorded;
+    assert_eq!(*balance, coins(999_999_000_000, "ujunox"));
+    assert_eq!(block.height, LATEST_HEIGHT);
+    assert_eq!(block.chain_id, LOCAL_JUNO.chain_id);
+    assert_eq!(replayed, recorded);
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn query_only_daemon_replays_the_dry_run_it_recorded() -> anyhow::Result<()> {
+    let (recorded, replayed) = record_and_replay("dry-run", |builder| {
+        let daemon = builder.build_sender(())?;
+
+        // The transactions are simulated for the sender given to the dry run
+        let error = DryRun::new(daemon.clone()).err().unwrap();
+        assert!(error.to_string().contains("DryRun::with_sender"));
+
+        let dry_run = DryRun::with_sender(daemon, Addr::unchecked(VALIDATOR))?;
+        dry_run.instantiate(1, &json!({}), Some("dry-run"), None, &[])?;
+        dry_run.execute(&json!({ "ping": {} }), &[], &Addr::unchecked(VALIDATOR))?;
+        Ok(dry_run.plan()?)
+    })?;
+
+    let kinds: Vec<_> = recorded.txs.iter().map(|tx| tx.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![PlannedTxKind::Instantiate, PlannedTxKind::Execute]
+    );
+    assert_eq!(
+        recorded.txs[0].contract_address,
+        Some(Addr::unchecked(CONTRACT))
+    );
+    // The gas of each transaction is the difference with the simulation of the previous ones
+    assert!(recorded
+        .txs
+        .iter()
+        .all(|tx| tx.sender.as_str() == VALIDATOR && tx.gas_estimate == Some(MSG_GAS)));
+    assert_eq!(replayed, recorded);
+    Ok(())
+}
+
+/// Signs the exported transaction like `<chain-binary> tx sign` and returns the path of the signed file
+fn sign(unsigned_tx: &UnsignedTx) -> anyhow::Result<PathBuf> {
+    // The recorded and the replayed transactions are signed by the same key, so the broadcasted bytes match
+    let key = SigningKey::from_slice(&[1; 32]).map_err(DaemonError::from)?;
+    let signed = TxRaw::from(
+        unsigned_tx
+            .sign_doc(key.public_key())?
+            .sign(&key)
+            .map_err(DaemonError::from)?,
+    );
+
+    let mut tx: serde_json::Value = serde_json::from_slice(&std::fs::read(&unsigned_tx.path)?)?;
+    tx["auth_info"]["signer_infos"] = json!([{
+        "public_key": {
+            "@type": "/cosmos.crypto.secp256k1.PubKey",
+            "key": STANDARD.encode(key.public_key().to_bytes())
+        },
+        "mode_info": { "single": { "mode": "SIGN_MODE_DIRECT" } },
+        "sequence": unsigned_tx.sequence.to_string()
+    }]);
+    tx["signatures"] = json!([STANDARD.encode(&signed.signatures[0])]);
+
+    let path = unsigned_tx.path.with_extension("signed.json");
+    std::fs::write(&path, tx.to_string())?;
+    Ok(path)
+}
+
+#[test]
+#[serial_test::serial]
+fn offline_sender_replays_the_export_and_import_it_recorded() -> anyhow::Result<()> {
+    let output_dir = std::env::temp_dir().join(format!("cw-orch-offline-{}", std::process::id()));
+    std::fs::create_dir_all(&output_dir)?;
+
+    let (recorded, replayed) = record_and_replay("offline", |builder| {
+        let daemon = builder.build_sender(
+            OfflineOptions::new(VALIDATOR)
+                .output_dir(&output_dir)
+                .gas_limit(200_000),
+        )?;
+        let sender = daemon.sender();
+
+        let send = MsgSend {
+            from_address: VALIDATOR.to_string(),
+            to_address: VALIDATOR.to_string(),
+            amount: vec![Coin {
+                denom: "ujunox".to_string(),
+                amount: "100".to_string(),
+            }],
+        };
+        let exported = daemon.rt_handle.block_on(sender.commit_tx_any(
+            vec![Any {
+                type_url: MsgSend::type_url(),
+                value: send.encode_to_vec(),
+            }],
+            None,
+        ))?;
+        assert!(!exported.is_broadcasted());
+
+        // The code id of an upload is unknown until it's broadcasted
+        let upload = MsgStoreCode {
+            sender: VALIDATOR.to_string(),
+            wasm_byte_code: b"\0asm".to_vec(),
+            instantiate_permission: None,
+        };
+        let error = daemon
+            .rt_handle
+            .block_on(sender.commit_tx_any(
+                vec![Any {
+                    type_url: MsgStoreCode::type_url(),
+                    value: upload.encode_to_vec(),
+                }],
+                None,
+            ))
+            .unwrap_err();
+        assert!(matches!(error, DaemonError::TxNotBroadcasted(_)));
+
+        let unsigned_txs = sender.unsigned_txs();
+        let broadcasted = daemon
+            .rt_handle
+            .block_on(sender.broadcast_signed_tx(sign(&unsigned_txs[0])?))?;
+        let sequences: Vec<u64> = unsigned_txs.iter().map(|tx| tx.sequence).collect();
+        Ok((sequences, broadcasted.txhash, broadcasted.height))
+    })?;
+
+    let (sequences, txhash, height) = &recorded;
+    assert_eq!(*sequences, vec![SEQUENCE, SEQUENCE + 1]);
+    assert_eq!(txhash, TX_HASH);
+    assert_eq!(*height, LATEST_HEIGHT);
+    assert_eq!(replayed, recorded);
+
+    std::fs::remove_dir_all(output_dir)?;
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn live_mock_replays_the_fixture() -> anyhow::Result<()> {
+    replay_fixture();
+    let deps = live_mock::mock_dependencies(LOCAL_JUNO.into());
+
+    let response: BalanceResponse =
+        deps.as_ref()
+            .querier
+            .query(&QueryRequest::Bank(BankQuery::Balance {
+                address: VALIDATOR.to_string(),
+                denom: "ujunox".to_string(),
+            }))?;
+    assert_eq!(response.amount, coin(999_999_000_000, "ujunox"));
+
+    // The requests missing from the fixture fail
+    let missing = deps
+        .as_ref()
+        .querier
+        .query::<BalanceResponse>(&QueryRequest::Bank(BankQuery::Balance {
+            address: VALIDATOR.to_string(),
+            denom: "ucosm".to_string(),
+        }))
+        .unwrap_err();
+    assert!(missing
+        .to_string()
+        .contains("No response recorded in the fixture"));
+    Ok(())
+}
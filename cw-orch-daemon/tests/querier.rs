@@ -1,31 +1,51 @@
 mod common;
 
-#[cfg(feature = "node-tests")]
+/// Queriers tests against the local juno node with the `node-tests` feature.
+/// Otherwise they replay the hand-written fixture of the local juno chain, without any node,
+/// and the tests of the queries missing from the fixture are skipped.
 mod queriers {
 
-    use cw_orch_core::contract::interface_traits::*;
-    use cw_orch_daemon::{queriers::Bank, GrpcChannel};
-    use cw_orch_networks::networks;
-    use mock_contract::InstantiateMsg;
-    use speculoos::{asserting, result::ResultAssertions};
-    use std::str::FromStr;
-
+    use cosmrs::proto::cosmwasm::wasm::v1::AccessType;
+    use cosmwasm_std::coin;
     use cw_orch_daemon::{
-        queriers::StakingBondStatus,
-        queriers::{CosmWasm, Gov, Ibc, Node, Staking},
-        Daemon, DaemonError,
+        fixture::Fixture,
+        queriers::{Bank, CosmWasm, Ibc, Node},
+        GrpcChannel,
     };
+    use cw_orch_networks::networks;
+    use speculoos::{asserting, result::ResultAssertions};
     use tokio::runtime::Runtime;
 
-    use cosmrs::{
-        cosmwasm::MsgExecuteContract,
-        tx::{self, Msg},
-        AccountId, Denom,
+    #[cfg(feature = "node-tests")]
+    use {
+        cosmrs::{
+            cosmwasm::MsgExecuteContract,
+            tx::{self, Msg},
+            AccountId, Denom,
+        },
+        cw_orch_core::contract::interface_traits::*,
+        cw_orch_daemon::{
+            queriers::{Gov, Staking, StakingBondStatus},
+            Daemon, DaemonError,
+        },
+        mock_contract::InstantiateMsg,
+        std::str::FromStr,
     };
 
+    const VALIDATOR: &str = "juno16g2rahf5846rxzp3fwlswy08fz8ccuwk03k57y";
+
     pub async fn build_channel() -> tonic::transport::Channel {
         let network = networks::LOCAL_JUNO;
 
+        if cfg!(not(feature = "node-tests")) {
+            return Fixture::load(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/grpc/local-juno.jsonl"
+            ))
+            .unwrap()
+            .replaying_channel(network.chain_id);
+        }
+
         let grpcs = vec![network.grpc_urls[0].into()];
 
         let channel = GrpcChannel::connect(&grpcs, network.chain_id).await;
@@ -49,11 +69,15 @@ mod queriers {
 
         let clients = rt.block_on(ibc._clients());
         asserting!("clients is ok").that(&clients).is_ok();
+        if cfg!(not(feature = "node-tests")) {
+            assert!(clients.unwrap().is_empty());
+        }
     }
 
     /*
         Querier - Staking
     */
+    #[cfg(feature = "node-tests")]
     #[test]
     fn staking() {
         let rt = Runtime::new().unwrap();
@@ -74,6 +98,7 @@ mod queriers {
     /*
         Querier - Gov
     */
+    #[cfg(feature = "node-tests")]
     #[test]
     fn gov() {
         let rt = Runtime::new().unwrap();
@@ -98,12 +123,10 @@ mod queriers {
         let params = rt.block_on(bank._params());
         asserting!("params is ok").that(&params).is_ok();
 
-        let balances =
-            rt.block_on(bank._balance("juno16g2rahf5846rxzp3fwlswy08fz8ccuwk03k57y", None));
+        let balances = rt.block_on(bank._balance(VALIDATOR, None));
         asserting!("balances is ok").that(&balances).is_ok();
 
-        let spendable_balances =
-            rt.block_on(bank._spendable_balances("juno16g2rahf5846rxzp3fwlswy08fz8ccuwk03k57y"));
+        let spendable_balances = rt.block_on(bank._spendable_balances(VALIDATOR));
         asserting!("spendable_balances is ok")
             .that(&spendable_balances)
             .is_ok();
@@ -114,6 +137,22 @@ mod queriers {
         let supply_of = rt.block_on(bank._supply_of("ujunox"));
         asserting!("supply_of is ok").that(&supply_of).is_ok();
 
+        if cfg!(not(feature = "node-tests")) {
+            assert!(params.unwrap().default_send_enabled);
+            let validator_balances = vec![
+                coin(1_000_000_000, "ucosm"),
+                coin(999_999_000_000, "ujunox"),
+            ];
+            assert_eq!(balances.unwrap(), validator_balances);
+            assert_eq!(spendable_balances.unwrap(), validator_balances);
+            let supply = coin(1_000_000_000_000, "ujunox");
+            assert_eq!(
+                total_supply.unwrap(),
+                vec![coin(1_000_000_000, "ucosm"), supply.clone()]
+            );
+            assert_eq!(supply_of.unwrap(), supply);
+        }
+
         let denom_metadata = rt.block_on(bank._denom_metadata("ucosm"));
         asserting!("denom_metadata is err, should not exists")
             .that(&denom_metadata)
@@ -123,6 +162,9 @@ mod queriers {
         asserting!("denoms_metadata is ok, but empty")
             .that(&denoms_metadata)
             .is_ok();
+        if cfg!(not(feature = "node-tests")) {
+            assert!(denoms_metadata.unwrap().is_empty());
+        }
     }
 
     /*
@@ -137,6 +179,16 @@ mod queriers {
 
         let params = rt.block_on(cw._params());
         asserting!("params is ok").that(&params).is_ok();
+        if cfg!(not(feature = "node-tests")) {
+            assert_eq!(
+                params
+                    .unwrap()
+                    .params
+                    .unwrap()
+                    .instantiate_default_permission,
+                AccessType::Everybody as i32
+            );
+        }
     }
 
     /*
@@ -157,8 +209,15 @@ mod queriers {
 
         let block_time = rt.block_on(node._block_time());
         asserting!("block_time is ok").that(&block_time).is_ok();
+
+        if cfg!(not(feature = "node-tests")) {
+            assert_eq!(block_height.unwrap(), 1234);
+            assert_eq!(latest_block.unwrap().header.chain_id.as_str(), "testing");
+            assert_eq!(block_time.unwrap(), 1_704_068_434_000_000_000);
+        }
     }
 
+    #[cfg(feature = "node-tests")]
     #[test]
     #[serial_test::serial]
     fn simulate_tx() {
@@ -203,6 +262,7 @@ mod queriers {
             .is_err();
     }
 
+    #[cfg(feature = "node-tests")]
     #[test]
     #[serial_test::serial]
     fn contract_info() {
@@ -236,6 +296,7 @@ mod queriers {
             .is_ok();
     }
 
+    #[cfg(feature = "node-tests")]
     fn parse_cw_coins(coins: &[cosmwasm_std::Coin]) -> Result<Vec<cosmrs::Coin>, DaemonError> {
         coins
             .iter()
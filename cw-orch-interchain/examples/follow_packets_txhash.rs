@@ -15,6 +15,7 @@ pub const NOBLE_1: ChainInfo = ChainInfo {
     gas_denom: "uusdc",
     gas_price: 0.1,
     grpc_urls: &["http://noble-grpc.polkachu.com:21590"],
+    rpc_urls: &[],
     lcd_url: None,
     fcd_url: None,
     network_info: NOBLE,
@@ -0,0 +1,71 @@
+use cosmos_sdk_proto::{
+    cosmos::{
+        bank::v1beta1::{MsgSend, MsgSendResponse},
+        base::v1beta1::Coin,
+    },
+    traits::{Message, Name},
+    Any,
+};
+use cw_orch::prelude::*;
+use cw_orch_interchain::{ica::InterchainAccount, prelude::*};
+use std::time::Duration;
+
+pub const JUNO: &str = "juno-1";
+pub const STARGAZE: &str = "stargaze-1";
+pub const STARGAZE_FUNDS_DENOM: &str = "ustars";
+
+fn main() -> cw_orch::anyhow::Result<()> {
+    pretty_env_logger::init();
+
+    let starship = Starship::new(None)?;
+    let interchain = starship.interchain_env();
+
+    // We register an interchain account of the juno sender on stargaze, on the connection created by starship
+    let ibc_path = starship.rt_handle.block_on(async {
+        starship
+            .client()
+            .registry()
+            .await
+            .ibc_path(JUNO, STARGAZE)
+            .await
+    })?;
+    let ica = InterchainAccount::register(
+        &interchain,
+        JUNO,
+        STARGAZE,
+        &ibc_path.chain_1.connection_id.to_string(),
+    )?;
+
+    // The account is funded on stargaze
+    let stargaze = interchain.get_chain(STARGAZE)?;
+    stargaze.commit_any::<MsgSendResponse>(
+        vec![send_msg(&stargaze.sender_addr().to_string(), &ica.address)],
+        None,
+    )?;
+
+    // And sends the funds back from juno
+    let response = ica.execute(
+        &interchain,
+        vec![send_msg(&ica.address, &stargaze.sender_addr().to_string())],
+        None,
+        Duration::from_secs(600),
+    )?;
+    let _: MsgSendResponse = response.msg_response(0)?;
+
+    Ok(())
+}
+
+fn send_msg(from_address: &str, to_address: &str) -> Any {
+    Any {
+        type_url: MsgSend::type_url(),
+        value: MsgSend {
+            from_address: from_address.to_string(),
+            to_address: to_address.to_string(),
+            amount: vec![Coin {
+                denom: STARGAZE_FUNDS_DENOM.to_string(),
+                amount: "100000".to_string(),
+            }],
+        }
+        .encode_to_vec(),
+    }
+}
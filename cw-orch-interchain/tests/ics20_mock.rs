@@ -0,0 +1,463 @@
+use cosmwasm_std::{
+    coin, to_json_vec, Addr, Binary, Deps, DepsMut, Empty, Env, IbcMsg, IbcTimeout, MessageInfo,
+    Response, StdError, StdResult,
+};
+use cw_orch::{
+    environment::{BankQuerier, DefaultQueriers, QuerierGetter, QueryHandler, TxHandler},
+    mock::cw_multi_test::{ContractWrapper, Executor},
+};
+use cw_orch_interchain_core::{types::IbcPacketOutcome, InterchainEnv};
+use cw_orch_interchain_mock::{Ics20Options, Ics20Transfer, MockIbcQuerier, MockInterchainEnv};
+use ibc_relayer_types::core::ics24_host::identifier::PortId;
+use serde_json::{json, Value};
+
+const ALL_MIDDLEWARES: Ics20Options = Ics20Options {
+    packet_forward: true,
+    ibc_hooks: true,
+};
+
+/// Interchain of juno, osmosis and stargaze running ICS-20, with channels from osmosis to juno and stargaze.
+/// Returns the channels of juno and osmosis to osmosis and stargaze
+fn interchain() -> cw_orch::anyhow::Result<(MockInterchainEnv, String, String, String)> {
+    let interchain = MockInterchainEnv::new(vec![
+        ("juno-1", "sender"),
+        ("osmosis-1", "sender"),
+        ("stargaze-1", "sender"),
+    ]);
+    for chain_id in ["juno-1", "osmosis-1", "stargaze-1"] {
+        interchain.enable_ics20(chain_id, ALL_MIDDLEWARES)?;
+    }
+
+    let channel = |src: &str, dst: &str| -> cw_orch::anyhow::Result<(String, String)> {
+        let channel = interchain
+            .create_channel(
+                src,
+                dst,
+                &PortId::transfer(),
+                &PortId::transfer(),
+                "ics20-1",
+                None,
+            )?
+            .interchain_channel
+            .get_ordered_ports_from(src)?;
+        Ok((
+            channel.0.channel.unwrap().to_string(),
+            channel.1.channel.unwrap().to_string(),
+        ))
+    };
+    let (juno_to_osmosis, osmosis_to_juno) = channel("juno-1", "osmosis-1")?;
+    let (osmosis_to_stargaze, _) = channel("osmosis-1", "stargaze-1")?;
+
+    let juno = interchain.get_chain("juno-1")?;
+    juno.add_balance(juno.sender_addr().to_string(), vec![coin(1_000, "ujuno")])?;
+    Ok((
+        interchain,
+        juno_to_osmosis,
+        osmosis_to_juno,
+        osmosis_to_stargaze,
+    ))
+}
+
+fn transfer(
+    interchain: &MockInterchainEnv,
+    channel_id: &str,
+    receiver: &str,
+    memo: Option<Value>,
+) -> Ics20Transfer {
+    let block = interchain
+        .get_chain("juno-1")
+        .unwrap()
+        .block_info()
+        .unwrap();
+    Ics20Transfer {
+        channel_id: channel_id.to_string(),
+        receiver: receiver.to_string(),
+        amount: coin(100, "ujuno"),
+        timeout: IbcTimeout::with_timestamp(block.time.plus_seconds(600)),
+        memo: memo.map(|memo| memo.to_string()),
+    }
+}
+
+fn balances(
+    interchain: &MockInterchainEnv,
+    chain_id: &str,
+    address: &str,
+) -> Vec<cosmwasm_std::Coin> {
+    interchain
+        .get_chain(chain_id)
+        .unwrap()
+        .bank_querier()
+        .balance(address, None)
+        .unwrap()
+}
+
+fn record_execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: Value) -> StdResult<Response> {
+    if msg.get("fail").is_some() {
+        return Err(StdError::generic_err("hook failed"));
+    }
+    deps.storage.set(b"funds", &to_json_vec(&info.funds)?);
+    Ok(Response::new())
+}
+
+fn record_sudo(deps: DepsMut, _env: Env, msg: Value) -> StdResult<Response> {
+    deps.storage.set(b"callback", &to_json_vec(&msg)?);
+    Ok(Response::new())
+}
+
+fn record_query(deps: Deps, _env: Env, key: String) -> StdResult<Binary> {
+    Ok(deps.storage.get(key.as_bytes()).unwrap_or_default().into())
+}
+
+fn instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+/// Instantiates a contract recording the funds it receives and its ibc-hooks callbacks
+fn recorder(interchain: &MockInterchainEnv, chain_id: &str) -> cw_orch::anyhow::Result<Addr> {
+    let chain = interchain.get_chain(chain_id)?;
+    let mut app = chain.app.borrow_mut();
+    let code_id = app.store_code(Box::new(
+        ContractWrapper::new(record_execute, instantiate, record_query).with_sudo(record_sudo),
+    ));
+    app.instantiate_contract(
+        code_id,
+        chain.sender_addr(),
+        &Empty {},
+        &[],
+        "recorder",
+        None,
+    )
+}
+
+#[test]
+fn traces_received_denoms() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, osmosis_to_juno, _) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+    let osmosis = interchain.get_chain("osmosis-1")?;
+
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(
+            &interchain,
+            &juno_to_osmosis,
+            osmosis.sender_addr().as_str(),
+            None,
+        ),
+    )?;
+    interchain.await_packets("juno-1", tx)?.into_result()?;
+
+    let received = balances(&interchain, "osmosis-1", osmosis.sender_addr().as_str());
+    assert_eq!(received.len(), 1);
+    assert!(received[0].denom.starts_with("ibc/"));
+    let trace = interchain.denom_trace("osmosis-1", &received[0].denom)?;
+    assert_eq!(trace.path, format!("transfer/{osmosis_to_juno}"));
+    assert_eq!(trace.base_denom, "ujuno");
+
+    // The tokens sent back are unescrowed
+    let back = Ics20Transfer {
+        amount: received[0].clone(),
+        ..transfer(
+            &interchain,
+            &osmosis_to_juno,
+            juno.sender_addr().as_str(),
+            None,
+        )
+    };
+    let tx = interchain.ics20_transfer("osmosis-1", &osmosis.sender_addr(), back)?;
+    interchain.await_packets("osmosis-1", tx)?.into_result()?;
+    assert!(balances(&interchain, "osmosis-1", osmosis.sender_addr().as_str()).is_empty());
+    assert_eq!(
+        balances(&interchain, "juno-1", juno.sender_addr().as_str()),
+        vec![coin(1_000, "ujuno")]
+    );
+    Ok(())
+}
+
+#[test]
+fn forwards_multi_hop_transfers() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, _, osmosis_to_stargaze) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+    let stargaze = interchain.get_chain("stargaze-1")?;
+
+    let memo = json!({
+        "forward": {
+            "receiver": stargaze.sender_addr(),
+            "port": "transfer",
+            "channel": osmosis_to_stargaze,
+            "timeout": "10m",
+        }
+    });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(&interchain, &juno_to_osmosis, "pfm", Some(memo)),
+    )?;
+    let analysis = interchain.await_packets("juno-1", tx)?;
+
+    // The forwarded packet is sent by the receive tx on osmosis
+    let IbcPacketOutcome::Success { receive_tx, .. } = &analysis.packets[0].outcome else {
+        panic!("Expected the transfer to be received on osmosis");
+    };
+    assert_eq!(receive_tx.tx_id.chain_id, "osmosis-1");
+    assert_eq!(receive_tx.packets.len(), 1);
+    let IbcPacketOutcome::Success { receive_tx, .. } = &receive_tx.packets[0].outcome else {
+        panic!("Expected the transfer to be forwarded to stargaze");
+    };
+    assert_eq!(receive_tx.tx_id.chain_id, "stargaze-1");
+    analysis.into_result()?;
+
+    let received = balances(&interchain, "stargaze-1", stargaze.sender_addr().as_str());
+    assert_eq!(received[0].amount.u128(), 100);
+    let ibc: MockIbcQuerier<_> = stargaze.querier();
+    let trace = ibc.denom_trace(&received[0].denom)?;
+    assert_eq!(trace.base_denom, "ujuno");
+    assert_eq!(trace.path.matches("transfer/").count(), 2);
+    Ok(())
+}
+
+#[test]
+fn refunds_timed_out_forwards() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, _, osmosis_to_stargaze) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+    let stargaze = interchain.get_chain("stargaze-1")?;
+
+    let memo = json!({
+        "forward": {
+            "receiver": stargaze.sender_addr(),
+            "port": "transfer",
+            "channel": osmosis_to_stargaze,
+            "timeout": 0,
+        }
+    });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(&interchain, &juno_to_osmosis, "pfm", Some(memo)),
+    )?;
+    let analysis = interchain.await_packets("juno-1", tx)?;
+
+    // The packet is acknowledged once the forwarded packet timed out
+    let IbcPacketOutcome::Success { receive_tx, .. } = &analysis.packets[0].outcome else {
+        panic!("Expected the transfer to be received on osmosis");
+    };
+    assert!(matches!(
+        receive_tx.packets[0].outcome,
+        IbcPacketOutcome::Timeout { .. }
+    ));
+    assert!(analysis.into_result().is_err());
+
+    assert!(balances(&interchain, "stargaze-1", stargaze.sender_addr().as_str()).is_empty());
+    assert_eq!(
+        balances(&interchain, "juno-1", juno.sender_addr().as_str()),
+        vec![coin(1_000, "ujuno")]
+    );
+    Ok(())
+}
+
+#[test]
+fn refunds_every_hop_of_failed_forwards() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, _, osmosis_to_stargaze) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+    let stargaze = interchain.get_chain("stargaze-1")?;
+
+    // Stargaze can't forward the transfer on to a channel that doesn't exist
+    let memo = json!({
+        "forward": {
+            "receiver": "pfm",
+            "port": "transfer",
+            "channel": osmosis_to_stargaze,
+            "next": {
+                "forward": {
+                    "receiver": juno.sender_addr(),
+                    "port": "transfer",
+                    "channel": "channel-99",
+                }
+            },
+        }
+    });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(&interchain, &juno_to_osmosis, "pfm", Some(memo)),
+    )?;
+    let analysis = interchain.await_packets("juno-1", tx)?;
+
+    // Both hops are acknowledged with an error
+    let IbcPacketOutcome::Success { receive_tx, .. } = &analysis.packets[0].outcome else {
+        panic!("Expected the transfer to be received on osmosis");
+    };
+    let IbcPacketOutcome::Success { ack, .. } = &receive_tx.packets[0].outcome else {
+        panic!("Expected the transfer to be forwarded to stargaze");
+    };
+    assert!(String::from_utf8_lossy(ack.as_slice()).contains("error"));
+    assert!(analysis.into_result().is_err());
+
+    // The forwarded tokens are refunded to the middlewares and taken back, then the sender is refunded on juno
+    for chain_id in ["osmosis-1", "stargaze-1"] {
+        assert!(balances(&interchain, chain_id, "ibc_bank_lock_module").is_empty());
+    }
+    assert!(balances(&interchain, "stargaze-1", stargaze.sender_addr().as_str()).is_empty());
+    assert_eq!(
+        balances(&interchain, "juno-1", juno.sender_addr().as_str()),
+        vec![coin(1_000, "ujuno")]
+    );
+    Ok(())
+}
+
+#[test]
+fn refunds_transfers_with_an_overflowing_forward_timeout() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, _, osmosis_to_stargaze) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+
+    let memo = json!({
+        "forward": {
+            "receiver": "pfm",
+            "port": "transfer",
+            "channel": osmosis_to_stargaze,
+            "timeout": "99999999999h",
+        }
+    });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(&interchain, &juno_to_osmosis, "pfm", Some(memo)),
+    )?;
+    let analysis = interchain.await_packets("juno-1", tx)?;
+
+    let IbcPacketOutcome::Success { ack, .. } = &analysis.packets[0].outcome else {
+        panic!("Expected the transfer to be received on osmosis");
+    };
+    assert!(String::from_utf8_lossy(ack.as_slice()).contains("Invalid forward timeout"));
+    assert_eq!(
+        balances(&interchain, "juno-1", juno.sender_addr().as_str()),
+        vec![coin(1_000, "ujuno")]
+    );
+    Ok(())
+}
+
+#[test]
+fn refunds_failed_transfers_of_chains_without_ics20() -> cw_orch::anyhow::Result<()> {
+    let interchain = MockInterchainEnv::new(vec![("juno-1", "sender"), ("osmosis-1", "sender")]);
+    interchain.enable_ics20("osmosis-1", ALL_MIDDLEWARES)?;
+    let juno_to_osmosis = interchain
+        .create_channel(
+            "juno-1",
+            "osmosis-1",
+            &PortId::transfer(),
+            &PortId::transfer(),
+            "ics20-1",
+            None,
+        )?
+        .interchain_channel
+        .get_ordered_ports_from("juno-1")?
+        .0
+        .channel
+        .unwrap()
+        .to_string();
+    let juno = interchain.get_chain("juno-1")?;
+    juno.add_balance(juno.sender_addr().to_string(), vec![coin(1_000, "ujuno")])?;
+
+    // The transfer is sent by the transfer module of cw-multi-test, osmosis can't credit an invalid receiver
+    let block = juno.block_info()?;
+    let tx = juno.app.borrow_mut().execute(
+        juno.sender_addr(),
+        IbcMsg::Transfer {
+            channel_id: juno_to_osmosis,
+            to_address: "Invalid Receiver".to_string(),
+            amount: coin(100, "ujuno"),
+            timeout: IbcTimeout::with_timestamp(block.time.plus_seconds(600)),
+        }
+        .into(),
+    )?;
+    let analysis = interchain.await_packets("juno-1", tx)?;
+    assert!(analysis.into_result().is_err());
+
+    assert!(balances(&interchain, "juno-1", "ibc_bank_lock_module").is_empty());
+    assert_eq!(
+        balances(&interchain, "juno-1", juno.sender_addr().as_str()),
+        vec![coin(1_000, "ujuno")]
+    );
+    Ok(())
+}
+
+#[test]
+fn executes_wasm_hooks() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, _, _) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+    let osmosis = interchain.get_chain("osmosis-1")?;
+    let contract = recorder(&interchain, "osmosis-1")?;
+
+    let memo = json!({ "wasm": { "contract": contract, "msg": { "record": {} } } });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(&interchain, &juno_to_osmosis, contract.as_str(), Some(memo)),
+    )?;
+    interchain.await_packets("juno-1", tx)?.into_result()?;
+
+    let funds: Vec<cosmwasm_std::Coin> = osmosis
+        .app
+        .borrow()
+        .wrap()
+        .query_wasm_smart(&contract, &"funds")?;
+    assert_eq!(funds.len(), 1);
+    assert!(funds[0].denom.starts_with("ibc/"));
+    assert_eq!(balances(&interchain, "osmosis-1", contract.as_str()), funds);
+
+    // A failing hook returns an error ack and the transfer is refunded
+    let memo = json!({ "wasm": { "contract": contract, "msg": { "fail": {} } } });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &juno.sender_addr(),
+        transfer(&interchain, &juno_to_osmosis, contract.as_str(), Some(memo)),
+    )?;
+    let analysis = interchain.await_packets("juno-1", tx)?;
+    let IbcPacketOutcome::Success { ack, .. } = &analysis.packets[0].outcome else {
+        panic!("Expected the transfer to be acknowledged");
+    };
+    assert!(String::from_utf8_lossy(ack.as_slice()).contains("error"));
+    assert_eq!(
+        balances(&interchain, "juno-1", juno.sender_addr().as_str()),
+        vec![coin(900, "ujuno")]
+    );
+    assert_eq!(balances(&interchain, "osmosis-1", contract.as_str()), funds);
+    Ok(())
+}
+
+#[test]
+fn calls_back_the_sending_contract() -> cw_orch::anyhow::Result<()> {
+    let (interchain, juno_to_osmosis, _, _) = interchain()?;
+    let juno = interchain.get_chain("juno-1")?;
+    let osmosis = interchain.get_chain("osmosis-1")?;
+    let contract = recorder(&interchain, "juno-1")?;
+    juno.add_balance(contract.to_string(), vec![coin(100, "ujuno")])?;
+
+    let memo = json!({ "ibc_callback": contract });
+    let tx = interchain.ics20_transfer(
+        "juno-1",
+        &contract,
+        transfer(
+            &interchain,
+            &juno_to_osmosis,
+            osmosis.sender_addr().as_str(),
+            Some(memo),
+        ),
+    )?;
+    interchain.await_packets("juno-1", tx)?.into_result()?;
+
+    let callback: Value = juno
+        .app
+        .borrow()
+        .wrap()
+        .query_wasm_smart(&contract, &"callback")?;
+    assert_eq!(
+        callback["ibc_lifecycle_complete"]["ibc_ack"]["success"],
+        true
+    );
+    assert_eq!(
+        callback["ibc_lifecycle_complete"]["ibc_ack"]["channel"],
+        juno_to_osmosis.as_str()
+    );
+    Ok(())
+}
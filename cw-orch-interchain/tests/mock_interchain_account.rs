@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use common::bank::BankModule;
+use cosmos_sdk_proto::{
+    cosmos::{
+        bank::v1beta1::{MsgSend, MsgSendResponse},
+        base::v1beta1::Coin,
+    },
+    ibc::applications::interchain_accounts::v1::{CosmosTx, InterchainAccountPacketData, Type},
+    traits::{Message, Name},
+    Any,
+};
+use cw_orch::prelude::*;
+use cw_orch_interchain_core::{
+    ica::{InterchainAccount, InterchainAccountEnv, MsgSendTx},
+    types::IbcPacketOutcome,
+    InterchainEnv, InterchainError,
+};
+use cw_orch_interchain_mock::MockInterchainEnv;
+mod common;
+use cosmwasm_std::coins;
+
+pub fn logger_test_init() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+pub const JUNO: &str = "juno-1";
+pub const OSMOSIS: &str = "osmosis-1";
+pub const JUNO_FUNDS_DENOM: &str = "ujuno";
+
+#[test]
+fn mock_interchain_account() -> cw_orch::anyhow::Result<()> {
+    logger_test_init();
+    let common_sender = "sender";
+    let interchain = MockInterchainEnv::new(vec![(JUNO, common_sender), (OSMOSIS, common_sender)]);
+    let juno = interchain.get_chain(JUNO)?;
+    juno.add_balance(common_sender, coins(100_000, JUNO_FUNDS_DENOM))?;
+
+    // The interchain account of the osmosis sender is registered on juno
+    let connection_id = interchain.connection_id(OSMOSIS, JUNO)?;
+    let ica = InterchainAccount::register(&interchain, OSMOSIS, JUNO, &connection_id)?;
+    assert_eq!(ica.host_chain_id(), JUNO);
+    assert_eq!(ica.connection_id(), connection_id);
+
+    // The account receives funds on juno
+    juno.send(&ica.address, coins(100, JUNO_FUNDS_DENOM))?;
+    let balance = juno
+        .bank_querier()
+        .balance(&ica.address, Some(JUNO_FUNDS_DENOM.to_string()))?;
+    assert_eq!(balance[0].amount.u128(), 100);
+
+    // The account sends them back to the sender of juno
+    let send = Any {
+        type_url: MsgSend::type_url(),
+        value: MsgSend {
+            from_address: ica.address.clone(),
+            to_address: juno.sender_addr().to_string(),
+            amount: vec![Coin {
+                denom: JUNO_FUNDS_DENOM.to_string(),
+                amount: "100".to_string(),
+            }],
+        }
+        .encode_to_vec(),
+    };
+    let response = ica.execute(&interchain, vec![send], None, Duration::from_secs(600))?;
+    let _: MsgSendResponse = response.msg_response(0)?;
+
+    let balance = juno
+        .bank_querier()
+        .balance(&ica.address, Some(JUNO_FUNDS_DENOM.to_string()))?;
+    assert_eq!(balance[0].amount.u128(), 0);
+    Ok(())
+}
+
+#[test]
+fn mock_interchain_account_failed_execution() -> cw_orch::anyhow::Result<()> {
+    logger_test_init();
+    let common_sender = "sender";
+    let interchain = MockInterchainEnv::new(vec![(JUNO, common_sender), (OSMOSIS, common_sender)]);
+    let juno = interchain.get_chain(JUNO)?;
+
+    let connection_id = interchain.connection_id(OSMOSIS, JUNO)?;
+    let ica = InterchainAccount::register(&interchain, OSMOSIS, JUNO, &connection_id)?;
+
+    // The account can't send the funds of the sender of juno
+    let send = Any {
+        type_url: MsgSend::type_url(),
+        value: MsgSend {
+            from_address: juno.sender_addr().to_string(),
+            to_address: ica.address.clone(),
+            amount: vec![Coin {
+                denom: JUNO_FUNDS_DENOM.to_string(),
+                amount: "100".to_string(),
+            }],
+        }
+        .encode_to_vec(),
+    };
+    let result = ica.execute(&interchain, vec![send], None, Duration::from_secs(600));
+    assert!(matches!(result, Err(InterchainError::FailedAckReceived(_))));
+
+    // The channel of the account is ordered, it's closed when a packet times out
+    let send_tx = interchain._internal_send_interchain_account_tx(
+        OSMOSIS,
+        MsgSendTx {
+            owner: ica.owner.clone(),
+            connection_id: ica.connection_id(),
+            packet_data: Some(InterchainAccountPacketData {
+                r#type: Type::ExecuteTx as i32,
+                data: CosmosTx { messages: vec![] }.encode_to_vec(),
+                memo: String::new(),
+            }),
+            relative_timeout: Duration::from_secs(600).as_nanos() as u64,
+        },
+    )?;
+    let analysis = interchain.hold_packets(OSMOSIS, &send_tx)?.timeout(0)?;
+    assert!(matches!(analysis.outcome, IbcPacketOutcome::Timeout { .. }));
+    assert!(ica
+        .send_msgs(&interchain, vec![], None, Duration::from_secs(600))
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{coin, CosmosMsg, IbcMsg, IbcTimeout, IbcTimeoutBlock};
+use cw_orch::{
+    environment::{BankQuerier, DefaultQueriers, QueryHandler, TxHandler},
+    mock::cw_multi_test::AppResponse,
+};
+use cw_orch_interchain_core::{types::IbcPacketOutcome, InterchainEnv};
+use cw_orch_interchain_mock::{MockInterchainEnv, PacketQueue};
+use ibc_relayer_types::core::ics24_host::identifier::PortId;
+
+/// Sends one ICS20 transfer of 100ujuno per timeout (in blocks from now) from juno to stargaze and holds the packets
+fn hold_transfers(
+    interchain: &MockInterchainEnv,
+    timeouts: Vec<u64>,
+) -> cw_orch::anyhow::Result<PacketQueue<MockApi>> {
+    let channel = interchain.create_channel(
+        "juno-1",
+        "stargaze-1",
+        &PortId::transfer(),
+        &PortId::transfer(),
+        "ics20-1",
+        None,
+    )?;
+    let juno = interchain.get_chain("juno-1")?;
+    let stargaze = interchain.get_chain("stargaze-1")?;
+    let channel = channel
+        .interchain_channel
+        .get_ordered_ports_from("juno-1")?;
+
+    juno.add_balance(juno.sender_addr().to_string(), vec![coin(1_000, "ujuno")])?;
+    let stargaze_height = stargaze.block_info()?.height;
+    let transfers = timeouts
+        .into_iter()
+        .map(|blocks| {
+            CosmosMsg::Ibc(IbcMsg::Transfer {
+                channel_id: channel.0.channel.clone().unwrap().to_string(),
+                to_address: stargaze.sender_addr().to_string(),
+                amount: coin(100, "ujuno"),
+                timeout: IbcTimeout::with_block(IbcTimeoutBlock {
+                    revision: 1,
+                    height: stargaze_height + blocks,
+                }),
+            })
+        })
+        .collect();
+    // A single tx with all the transfers
+    let responses = juno
+        .app
+        .borrow_mut()
+        .execute_multi(juno.sender_addr(), transfers)?;
+    let tx_resp = AppResponse {
+        events: responses.into_iter().flat_map(|r| r.events).collect(),
+        data: None,
+    };
+
+    Ok(interchain.hold_packets("juno-1", &tx_resp)?)
+}
+
+fn stargaze_received(interchain: &MockInterchainEnv) -> cw_orch::anyhow::Result<u128> {
+    let stargaze = interchain.get_chain("stargaze-1")?;
+    let balances = stargaze
+        .bank_querier()
+        .balance(stargaze.sender_addr(), None)?;
+    Ok(balances.iter().map(|c| c.amount.u128()).sum())
+}
+
+#[test]
+fn relays_out_of_order_and_drops() -> cw_orch::anyhow::Result<()> {
+    let interchain = MockInterchainEnv::new(vec![("juno-1", "sender"), ("stargaze-1", "sender")]);
+    let mut queue = hold_transfers(&interchain, vec![100, 100, 100])?;
+    assert_eq!(queue.len(), 3);
+    assert_eq!(stargaze_received(&interchain)?, 0);
+
+    // The last packet is relayed first
+    let last_sequence = queue.packets()[2].sequence;
+    let result = queue.relay(2)?;
+    assert!(matches!(result.outcome, IbcPacketOutcome::Success { .. }));
+    assert_eq!(stargaze_received(&interchain)?, 100);
+    assert!(queue.packets().iter().all(|p| p.sequence != last_sequence));
+
+    // The dropped packet is never received
+    queue.drop_packet(0)?;
+    assert_eq!(queue.relay_all()?.len(), 1);
+    assert!(queue.is_empty());
+    assert_eq!(stargaze_received(&interchain)?, 200);
+
+    assert!(queue.relay_next().is_err());
+    Ok(())
+}
+
+#[test]
+fn forces_timeout() -> cw_orch::anyhow::Result<()> {
+    let interchain = MockInterchainEnv::new(vec![("juno-1", "sender"), ("stargaze-1", "sender")]);
+    let stargaze = interchain.get_chain("stargaze-1")?;
+    let timeout_height = stargaze.block_info()?.height + 100;
+    let mut queue = hold_transfers(&interchain, vec![100])?;
+
+    let result = queue.timeout(0)?;
+    assert!(matches!(result.outcome, IbcPacketOutcome::Timeout { .. }));
+    assert!(stargaze.block_info()?.height >= timeout_height);
+    assert_eq!(stargaze_received(&interchain)?, 0);
+
+    // The transfer is refunded on timeout
+    let juno = interchain.get_chain("juno-1")?;
+    assert_eq!(
+        juno.bank_querier()
+            .balance(juno.sender_addr(), Some("ujuno".to_string()))?[0]
+            .amount
+            .u128(),
+        1_000
+    );
+    Ok(())
+}
+
+#[test]
+fn rejects_duplicate_delivery() -> cw_orch::anyhow::Result<()> {
+    let interchain = MockInterchainEnv::new(vec![("juno-1", "sender"), ("stargaze-1", "sender")]);
+    let mut queue = hold_transfers(&interchain, vec![100])?;
+
+    queue.relay_and_keep(0)?;
+    assert_eq!(queue.len(), 1);
+    assert!(queue.relay(0).is_err());
+    assert_eq!(stargaze_received(&interchain)?, 100);
+    Ok(())
+}
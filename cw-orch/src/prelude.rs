@@ -39,6 +39,9 @@ pub use crate::environment::{ChainInfo, ChainInfoOwned};
 // Mock for testing
 pub use crate::mock::{Mock, MockBech32};
 
+// Dry-run for deployment plans
+pub use crate::environment::DryRun;
+
 // error
 pub use crate::error::CwOrchError;
 
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, fmt::Debug, io::Read, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, io::Read, rc::Rc};
 
 use clone_cw_multi_test::{
     addons::{MockAddressGenerator, MockApiBech32},
@@ -7,31 +7,42 @@ use clone_cw_multi_test::{
         contract::{LocalWasmContract, WasmContract},
         storage::analyzer::StorageAnalyzer,
     },
-    App, AppBuilder, BankKeeper, Contract, Executor, WasmKeeper,
+    App, AppBuilder, BankKeeper, Contract, Executor, WasmKeeper, LOCAL_RUST_CODE_OFFSET,
 };
-use cosmwasm_std::{to_json_binary, WasmMsg};
+use cosmwasm_std::{to_json_binary, to_json_vec, MessageInfo, WasmMsg};
 use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Empty, Event, StdError, StdResult, Uint128};
 use cw_orch_core::contract::interface_traits::ContractInstance;
 use cw_orch_core::{
     contract::interface_traits::Uploadable,
     environment::{
         BankQuerier, BankSetter, ChainInfoOwned, ChainState, DefaultQueriers, IndexResponse,
-        StateInterface, TxHandler,
+        SimulatedEnv, StateInterface, TxHandler, GAS_USED_EVENT,
     },
     CwEnvError,
 };
-use cw_orch_daemon::{queriers::Node, RUNTIME};
+use cw_orch_daemon::{
+    queriers::{CosmWasm, Node},
+    RUNTIME,
+};
 use cw_orch_daemon::{GrpcChannel, DEFAULT_DEPLOYMENT};
+use cw_orch_mock::{ContractCall, GasMeter, GasSchedule, SimulatedMsg};
 use cw_utils::NativeBalance;
 use serde::Serialize;
 use tokio::runtime::Runtime;
 
-use crate::{contract::CloneTestingContract, queriers::bank::CloneBankQuerier};
+use crate::{
+    contract::CloneTestingContract,
+    gas::{run_metered, WasmCall},
+    queriers::bank::CloneBankQuerier,
+};
 
 use super::state::MockState;
 
 pub type CloneTestingApp = App<BankKeeper, MockApiBech32>;
 
+/// Wasm codes by code id, `None` for the codes without a wasm
+type WasmCodes = HashMap<u64, Option<Rc<Vec<u8>>>>;
+
 /// Wrapper around a cw-multi-test [`App`](cw_multi_test::App) backend.
 ///
 /// Stores a local state with a mapping of contract_id -> code_id/address
@@ -78,6 +89,12 @@ pub struct CloneTesting<S: StateInterface = MockState> {
     pub state: Rc<RefCell<S>>,
     /// Inner mutable cw-multi-test app backend
     pub app: Rc<RefCell<CloneTestingApp>>,
+    /// Optional gas meter, estimating the gas used by transactions
+    pub gas_meter: Rc<RefCell<Option<GasMeter>>>,
+    /// Wasm codes that can be run in a metered cosmwasm-vm, `None` for the codes without a wasm
+    wasm_codes: Rc<RefCell<WasmCodes>>,
+    /// Connection to the forked chain
+    remote: RemoteChannel,
 }
 
 impl CloneTesting {
@@ -155,20 +172,27 @@ impl CloneTesting {
         let mut file = std::fs::File::open(T::wasm(&self.chain).path())?;
         let mut wasm = Vec::<u8>::new();
         file.read_to_end(&mut wasm)?;
-        let code_id = self
-            .app
+        let code_size = wasm.len();
+        let code_id =
+            self.app
+                .borrow_mut()
+                .store_wasm_code(WasmContract::Local(LocalWasmContract {
+                    code: wasm.clone(),
+                }));
+        self.wasm_codes
             .borrow_mut()
-            .store_wasm_code(WasmContract::Local(LocalWasmContract { code: wasm }));
+            .insert(code_id, Some(Rc::new(wasm)));
 
         contract.set_code_id(code_id);
 
         // add contract code_id to events manually
         let mut event = Event::new("store_code");
         event = event.add_attribute("code_id", code_id.to_string());
-        let resp = AppResponse {
+        let mut resp = AppResponse {
             events: vec![event],
             ..Default::default()
         };
+        GasMeter::meter_upload(&self.gas_meter, code_id, code_size, &mut resp.events);
         Ok(resp)
     }
 }
@@ -254,12 +278,263 @@ impl<S: StateInterface> CloneTesting<S> {
             sender: sender.clone(),
             state,
             app,
+            gas_meter: Rc::new(RefCell::new(None)),
+            wasm_codes: Rc::new(RefCell::new(HashMap::new())),
+            remote: remote_channel,
         })
     }
 
     pub fn storage_analysis(&self) -> StorageAnalyzer {
         StorageAnalyzer::new(&self.app.borrow()).unwrap()
     }
+
+    /// Enables gas metering on this environment.
+    /// Transaction responses will then report the gas they used (see [`IndexResponse::gas_used`])
+    /// and the gas used by each contract can be queried with [`CloneTesting::gas_used_by`].
+    /// Uploads of wasm files are charged by code size.
+    /// Calls to contracts with a wasm (uploaded with [`CloneTesting::upload_wasm`] or deployed on the forked chain)
+    /// are run a second time in a metered cosmwasm-vm instance to get their gas usage.
+    /// Calls to `ContractWrapper` contracts use the synthetic costs of the [`GasSchedule`].
+    pub fn enable_gas_meter(&self, schedule: GasSchedule) {
+        *self.gas_meter.borrow_mut() = Some(GasMeter::new(schedule));
+    }
+
+    /// Returns the total gas used by the uploads and calls of a contract.
+    /// Returns `None` if gas metering is not enabled.
+    pub fn gas_used_by(&self, contract_id: &str) -> Option<u64> {
+        self.gas_meter
+            .borrow()
+            .as_ref()
+            .map(|gas_meter| gas_meter.gas_used_by(&*self.state.borrow(), contract_id))
+    }
+
+    /// Wasm of a code, if the environment can run it in a metered cosmwasm-vm.
+    /// Codes of the forked chain are downloaded once.
+    fn wasm_code(&self, code_id: u64) -> Option<Rc<Vec<u8>>> {
+        if let Some(code) = self.wasm_codes.borrow().get(&code_id) {
+            return code.clone();
+        }
+        let code = if code_id < LOCAL_RUST_CODE_OFFSET as u64 {
+            self.remote
+                .rt
+                .block_on(CosmWasm::new_async(self.remote.channel.clone())._code_data(code_id))
+                .ok()
+                .map(Rc::new)
+        } else {
+            None
+        };
+        self.wasm_codes.borrow_mut().insert(code_id, code.clone());
+        code
+    }
+
+    /// Gas used by a contract call in a metered cosmwasm-vm instance.
+    /// Returns `None` if gas metering is not enabled, if the contract has no wasm or if the metered call failed.
+    fn vm_gas_used(&self, contract: &Addr, code_id: Option<u64>, call: WasmCall) -> Option<u64> {
+        let schedule = self.gas_meter.borrow().as_ref()?.schedule().clone();
+        let code_id = match code_id {
+            Some(code_id) => code_id,
+            None => {
+                self.app
+                    .borrow()
+                    .wrap()
+                    .query_wasm_contract_info(contract)
+                    .ok()?
+                    .code_id
+            }
+        };
+        let code = self.wasm_code(code_id)?;
+        match run_metered(
+            &self.app,
+            &self.chain.network_info.pub_address_prefix,
+            &schedule,
+            &code,
+            contract,
+            call,
+        ) {
+            Ok((gas_used, _)) => Some(gas_used),
+            Err(err) => {
+                log::warn!(
+                    "The call to {contract} could not be metered in the cosmwasm-vm, its gas is estimated from the gas schedule: {err}"
+                );
+                None
+            }
+        }
+    }
+
+    /// Charges a contract call with the gas used in the cosmwasm-vm if available, or with the gas schedule otherwise
+    fn meter_call<M: Serialize>(
+        &self,
+        contract: &Addr,
+        code_id: Option<u64>,
+        call: ContractCall,
+        msg: &M,
+        vm_gas: Option<u64>,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        match vm_gas {
+            Some(vm_gas) => {
+                GasMeter::meter_wasm_call(&self.gas_meter, contract, vm_gas, msg, events)
+            }
+            None => GasMeter::meter_contract_call(
+                &self.gas_meter,
+                &*self.state.borrow(),
+                contract,
+                code_id,
+                call,
+                msg,
+                events,
+            ),
+        }
+    }
+
+    /// Dry-runs the messages of a transaction and returns the gas they used,
+    /// metered with the gas schedule of the environment (or the default one if gas metering is not enabled).
+    ///
+    /// Calls to contracts with a wasm run in a metered cosmwasm-vm instance on a copy of the contract storage,
+    /// so each message runs on the current state, without the changes of the previous messages of the transaction.
+    /// `ContractWrapper` contracts can't run without changing the state, their calls are charged with the synthetic costs of the schedule.
+    pub(crate) fn simulate_tx(&self, tx_bytes: &[u8]) -> Result<u64, CwEnvError> {
+        let msgs = SimulatedMsg::decode_tx(tx_bytes)?;
+        let schedule = self
+            .gas_meter
+            .borrow()
+            .as_ref()
+            .map(GasMeter::schedule)
+            .cloned()
+            .unwrap_or_default();
+
+        // The costs of the transaction are charged once, not for every message
+        let mut msg_schedule = schedule.clone();
+        msg_schedule.tx_base_cost = 0;
+        msg_schedule.tx_byte_cost = 0;
+        let gas_meter = RefCell::new(Some(GasMeter::new(msg_schedule)));
+
+        let mut events = vec![];
+        for msg in msgs {
+            match msg {
+                SimulatedMsg::StoreCode { wasm_byte_code, .. } => {
+                    GasMeter::meter_upload(&gas_meter, 0, wasm_byte_code.len(), &mut events)
+                }
+                SimulatedMsg::Instantiate {
+                    sender,
+                    code_id,
+                    msg,
+                    funds,
+                    ..
+                } => {
+                    // The new contract starts with an empty storage, its address doesn't change the gas it uses
+                    let info = MessageInfo {
+                        sender: sender.clone(),
+                        funds,
+                    };
+                    self.simulate_call(
+                        &gas_meter,
+                        &sender,
+                        code_id,
+                        ContractCall::Instantiate,
+                        WasmCall::Instantiate { info, msg: &msg },
+                        &msg,
+                        &mut events,
+                    )?
+                }
+                SimulatedMsg::Execute {
+                    sender,
+                    contract,
+                    msg,
+                    funds,
+                } => {
+                    let code_id = self
+                        .app
+                        .borrow()
+                        .wrap()
+                        .query_wasm_contract_info(&contract)?
+                        .code_id;
+                    let info = MessageInfo { sender, funds };
+                    self.simulate_call(
+                        &gas_meter,
+                        &contract,
+                        code_id,
+                        ContractCall::Execute,
+                        WasmCall::Execute { info, msg: &msg },
+                        &msg,
+                        &mut events,
+                    )?
+                }
+                SimulatedMsg::Migrate {
+                    contract,
+                    code_id,
+                    msg,
+                    ..
+                } => self.simulate_call(
+                    &gas_meter,
+                    &contract,
+                    code_id,
+                    ContractCall::Migrate,
+                    WasmCall::Migrate { msg: &msg },
+                    &msg,
+                    &mut events,
+                )?,
+                // Bank transfers only cost the transaction costs
+                SimulatedMsg::Send { .. } => {}
+            }
+        }
+
+        let messages_gas: u64 = events
+            .iter()
+            .filter(|event| event.ty == GAS_USED_EVENT.0)
+            .flat_map(|event| &event.attributes)
+            .filter(|attribute| attribute.key == GAS_USED_EVENT.1)
+            .map(|attribute| attribute.value.parse::<u64>())
+            .sum::<Result<_, _>>()?;
+        Ok(schedule.tx_cost(tx_bytes.len()) + messages_gas)
+    }
+
+    /// Charges a contract call of a simulated transaction in `gas_meter`, reporting the gas used in `events`
+    #[allow(clippy::too_many_arguments)]
+    fn simulate_call(
+        &self,
+        gas_meter: &RefCell<Option<GasMeter>>,
+        contract: &Addr,
+        code_id: u64,
+        call: ContractCall,
+        wasm_call: WasmCall,
+        msg: &[u8],
+        events: &mut Vec<Event>,
+    ) -> Result<(), CwEnvError> {
+        // The messages are charged by their JSON size, like the transactions of the environment
+        let msg: serde_json::Value = serde_json::from_slice(msg)?;
+        let Some(code) = self.wasm_code(code_id) else {
+            return Ok(GasMeter::meter_contract_call(
+                gas_meter,
+                &*self.state.borrow(),
+                contract,
+                Some(code_id),
+                call,
+                &msg,
+                events,
+            )?);
+        };
+
+        let schedule = gas_meter
+            .borrow()
+            .as_ref()
+            .map(GasMeter::schedule)
+            .cloned()
+            .unwrap_or_default();
+        let (vm_gas, response) = run_metered(
+            &self.app,
+            &self.chain.network_info.pub_address_prefix,
+            &schedule,
+            &code,
+            contract,
+            wasm_call,
+        )?;
+        let mut call_events = vec![Event::new("wasm").add_attributes(response.attributes)];
+        call_events.extend(response.events);
+        GasMeter::meter_wasm_call(gas_meter, contract, vm_gas, &msg, &mut call_events)?;
+        events.extend(call_events);
+        Ok(())
+    }
 }
 
 impl<S: StateInterface> ChainState for CloneTesting<S> {
@@ -270,6 +545,8 @@ impl<S: StateInterface> ChainState for CloneTesting<S> {
     }
 }
 
+impl<S: StateInterface> SimulatedEnv for CloneTesting<S> {}
+
 // Execute on the test chain, returns test response type
 impl<S: StateInterface> TxHandler for CloneTesting<S> {
     type Response = AppResponse;
@@ -292,13 +569,15 @@ impl<S: StateInterface> TxHandler for CloneTesting<S> {
     fn upload<T: Uploadable>(&self, _contract: &T) -> Result<Self::Response, CwEnvError> {
         let wrapper_contract = CloneTestingContract::new(T::wrapper());
         let code_id = self.app.borrow_mut().store_code(Box::new(wrapper_contract));
+        self.wasm_codes.borrow_mut().insert(code_id, None);
         // add contract code_id to events manually
         let mut event = Event::new("store_code");
         event = event.add_attribute("code_id", code_id.to_string());
-        let resp = AppResponse {
+        let mut resp = AppResponse {
             events: vec![event],
             ..Default::default()
         };
+        GasMeter::meter_upload(&self.gas_meter, code_id, 0, &mut resp.events);
         Ok(resp)
     }
 
@@ -308,16 +587,37 @@ impl<S: StateInterface> TxHandler for CloneTesting<S> {
         coins: &[cosmwasm_std::Coin],
         contract_address: &Addr,
     ) -> Result<Self::Response, CwEnvError> {
-        self.app
+        // The metered call runs on the state before the execution
+        let vm_gas = self.vm_gas_used(
+            contract_address,
+            None,
+            WasmCall::Execute {
+                info: MessageInfo {
+                    sender: self.sender.clone(),
+                    funds: coins.to_vec(),
+                },
+                msg: &to_json_vec(exec_msg)?,
+            },
+        );
+        let mut resp: AppResponse = self
+            .app
             .borrow_mut()
             .execute_contract(
                 self.sender.clone(),
                 contract_address.to_owned(),
                 exec_msg,
                 coins,
-            )
-            .map_err(From::from)
-            .map(Into::into)
+            )?
+            .into();
+        self.meter_call(
+            contract_address,
+            None,
+            ContractCall::Execute,
+            exec_msg,
+            vm_gas,
+            &mut resp.events,
+        )?;
+        Ok(resp)
     }
 
     fn instantiate<I: Serialize + Debug>(
@@ -338,11 +638,30 @@ impl<S: StateInterface> TxHandler for CloneTesting<S> {
         )?;
         // add contract address to events manually
         let mut event = Event::new("instantiate");
-        event = event.add_attribute("_contract_address", addr);
-        let resp = AppResponse {
+        event = event.add_attribute("_contract_address", addr.clone());
+        let mut resp = AppResponse {
             events: vec![event],
             ..Default::default()
         };
+        let vm_gas = self.vm_gas_used(
+            &addr,
+            Some(code_id),
+            WasmCall::Instantiate {
+                info: MessageInfo {
+                    sender: self.sender.clone(),
+                    funds: coins.to_vec(),
+                },
+                msg: &to_json_vec(init_msg)?,
+            },
+        );
+        self.meter_call(
+            &addr,
+            Some(code_id),
+            ContractCall::Instantiate,
+            init_msg,
+            vm_gas,
+            &mut resp.events,
+        )?;
         Ok(resp)
     }
 
@@ -352,16 +671,33 @@ impl<S: StateInterface> TxHandler for CloneTesting<S> {
         new_code_id: u64,
         contract_address: &Addr,
     ) -> Result<Self::Response, CwEnvError> {
-        self.app
+        // The metered call runs on the state before the migration
+        let vm_gas = self.vm_gas_used(
+            contract_address,
+            Some(new_code_id),
+            WasmCall::Migrate {
+                msg: &to_json_vec(migrate_msg)?,
+            },
+        );
+        let mut resp: AppResponse = self
+            .app
             .borrow_mut()
             .migrate_contract(
                 self.sender.clone(),
                 contract_address.clone(),
                 migrate_msg,
                 new_code_id,
-            )
-            .map_err(From::from)
-            .map(Into::into)
+            )?
+            .into();
+        self.meter_call(
+            contract_address,
+            Some(new_code_id),
+            ContractCall::Migrate,
+            migrate_msg,
+            vm_gas,
+            &mut resp.events,
+        )?;
+        Ok(resp)
     }
 
     fn instantiate2<I: Serialize + Debug>(
@@ -385,11 +721,31 @@ impl<S: StateInterface> TxHandler for CloneTesting<S> {
             }),
         )?;
 
-        let app_resp = AppResponse {
+        let mut app_resp = AppResponse {
             events: resp.events,
             data: resp.data,
         };
 
+        let contract_address = app_resp.instantiated_contract_address()?;
+        let vm_gas = self.vm_gas_used(
+            &contract_address,
+            Some(code_id),
+            WasmCall::Instantiate {
+                info: MessageInfo {
+                    sender: self.sender.clone(),
+                    funds: coins.to_vec(),
+                },
+                msg: &to_json_vec(init_msg)?,
+            },
+        );
+        self.meter_call(
+            &contract_address,
+            Some(code_id),
+            ContractCall::Instantiate,
+            init_msg,
+            vm_gas,
+            &mut app_resp.events,
+        )?;
         Ok(app_resp)
     }
 }
@@ -0,0 +1,274 @@
+//! Gas metering of wasm contracts with the cosmwasm-vm gas model
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    rc::Rc,
+    sync::Mutex,
+};
+
+use clone_cw_multi_test::{
+    addons::MockApiBech32, wasm_emulation::storage::analyzer::StorageAnalyzer,
+};
+use cosmwasm_std::{
+    Addr, Api, Binary, CanonicalAddr, ContractInfo, ContractResult, Empty, Env, MessageInfo, Order,
+    Record, Response, SystemResult, TransactionInfo,
+};
+use cosmwasm_vm::{
+    call_execute, call_instantiate, call_migrate, Backend, BackendApi, BackendError, BackendResult,
+    GasInfo, Instance, InstanceOptions, Querier, Storage,
+};
+use cw_orch_mock::GasSchedule;
+
+use crate::core::CloneTestingApp;
+
+/// Gas limit of the metered calls, in cosmwasm-vm gas points
+const VM_GAS_LIMIT: u64 = u64::MAX / 2;
+
+/// Contract call run in a metered cosmwasm-vm instance
+pub(crate) enum WasmCall<'a> {
+    /// Instantiation of a new contract, run on an empty storage
+    Instantiate { info: MessageInfo, msg: &'a [u8] },
+    /// Execution of an existing contract
+    Execute { info: MessageInfo, msg: &'a [u8] },
+    /// Migration of an existing contract
+    Migrate { msg: &'a [u8] },
+}
+
+/// Runs a contract call in a metered cosmwasm-vm instance.
+/// Returns the gas used by the call, in gas points of the [`GasSchedule`], and the response of the contract.
+///
+/// The call runs on a copy of the storage of the contract: the app state is not modified.
+/// Storage entries that weren't loaded from the forked chain yet are read from it, but they are not iterated over.
+/// Messages dispatched by the contract are not executed.
+pub(crate) fn run_metered(
+    app: &Rc<RefCell<CloneTestingApp>>,
+    address_prefix: &str,
+    schedule: &GasSchedule,
+    code: &[u8],
+    contract: &Addr,
+    call: WasmCall,
+) -> anyhow::Result<(u64, Response)> {
+    let storage = match call {
+        WasmCall::Instantiate { .. } => MeteredStorage::empty(app, contract, schedule),
+        _ => MeteredStorage::of_contract(app, contract, schedule)?,
+    };
+    let backend = Backend {
+        api: VmApi::new(address_prefix),
+        storage,
+        querier: VmQuerier(app.clone()),
+    };
+    let mut instance = Instance::from_code(
+        code,
+        backend,
+        InstanceOptions {
+            gas_limit: VM_GAS_LIMIT,
+            print_debug: false,
+        },
+        None,
+    )?;
+
+    let env = Env {
+        block: app.borrow().block_info(),
+        transaction: Some(TransactionInfo { index: 0 }),
+        contract: ContractInfo {
+            address: contract.clone(),
+        },
+    };
+    let result = match call {
+        WasmCall::Instantiate { info, msg } => {
+            call_instantiate::<_, _, _, Empty>(&mut instance, &env, &info, msg)?
+        }
+        WasmCall::Execute { info, msg } => {
+            call_execute::<_, _, _, Empty>(&mut instance, &env, &info, msg)?
+        }
+        WasmCall::Migrate { msg } => call_migrate::<_, _, _, Empty>(&mut instance, &env, msg)?,
+    };
+    let response = result.into_result().map_err(anyhow::Error::msg)?;
+
+    let report = instance.create_gas_report();
+    let gas_used = (report.used_internally + report.used_externally) / schedule.vm_gas_multiplier;
+    Ok((gas_used, response))
+}
+
+/// Address api of the metered instances, using the bech32 prefix of the forked chain
+#[derive(Clone, Copy)]
+struct VmApi {
+    address_prefix: &'static str,
+}
+
+/// Bech32 prefixes of the metered instances.
+/// The api of the instances must be `Copy`, each prefix is allocated once for the whole process.
+static ADDRESS_PREFIXES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+impl VmApi {
+    fn new(address_prefix: &str) -> Self {
+        let mut prefixes = ADDRESS_PREFIXES.lock().unwrap();
+        let prefixes = prefixes.get_or_insert_with(HashSet::new);
+        let address_prefix = match prefixes.get(address_prefix) {
+            Some(prefix) => *prefix,
+            None => {
+                let prefix: &'static str = Box::leak(address_prefix.into());
+                prefixes.insert(prefix);
+                prefix
+            }
+        };
+        Self { address_prefix }
+    }
+}
+
+impl BackendApi for VmApi {
+    fn canonical_address(&self, human: &str) -> BackendResult<Vec<u8>> {
+        let result = MockApiBech32::new(self.address_prefix)
+            .addr_canonicalize(human)
+            .map(|canonical| canonical.to_vec())
+            .map_err(|err| BackendError::user_err(err.to_string()));
+        (result, GasInfo::free())
+    }
+
+    fn human_address(&self, canonical: &[u8]) -> BackendResult<String> {
+        let result = MockApiBech32::new(self.address_prefix)
+            .addr_humanize(&CanonicalAddr::from(canonical))
+            .map(String::from)
+            .map_err(|err| BackendError::user_err(err.to_string()));
+        (result, GasInfo::free())
+    }
+}
+
+/// Querier of the metered instances, answering from the app
+struct VmQuerier(Rc<RefCell<CloneTestingApp>>);
+
+impl Querier for VmQuerier {
+    fn query_raw(
+        &self,
+        request: &[u8],
+        _gas_limit: u64,
+    ) -> BackendResult<SystemResult<ContractResult<Binary>>> {
+        (
+            Ok(self.0.borrow().wrap().raw_query(request)),
+            GasInfo::free(),
+        )
+    }
+}
+
+/// Copy of the storage of a contract, charging the storage accesses like the Cosmos SDK gas config
+struct MeteredStorage {
+    app: Rc<RefCell<CloneTestingApp>>,
+    contract: Addr,
+    /// Whether missing entries are read from the app
+    read_app: bool,
+    /// Entries of the contract, `None` for the removed ones
+    entries: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    iterators: Vec<std::vec::IntoIter<Record>>,
+    schedule: GasSchedule,
+}
+
+impl MeteredStorage {
+    fn empty(app: &Rc<RefCell<CloneTestingApp>>, contract: &Addr, schedule: &GasSchedule) -> Self {
+        Self {
+            app: app.clone(),
+            contract: contract.clone(),
+            read_app: false,
+            entries: BTreeMap::new(),
+            iterators: vec![],
+            schedule: schedule.clone(),
+        }
+    }
+
+    fn of_contract(
+        app: &Rc<RefCell<CloneTestingApp>>,
+        contract: &Addr,
+        schedule: &GasSchedule,
+    ) -> anyhow::Result<Self> {
+        let entries = StorageAnalyzer::new(&app.borrow())?
+            .get_contract_storage(contract.to_string())
+            .into_iter()
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        Ok(Self {
+            read_app: true,
+            entries,
+            ..Self::empty(app, contract, schedule)
+        })
+    }
+
+    /// Gas info of an access costing `gas` points of the schedule
+    fn gas_info(&self, gas: u64) -> GasInfo {
+        GasInfo::with_externally_used(gas * self.schedule.vm_gas_multiplier)
+    }
+}
+
+impl Storage for MeteredStorage {
+    fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        let value = match self.entries.get(key) {
+            Some(value) => Ok(value.clone()),
+            None if self.read_app => self
+                .app
+                .borrow()
+                .wrap()
+                .query_wasm_raw(self.contract.to_string(), key.to_vec())
+                .map_err(|err| BackendError::unknown(err.to_string())),
+            None => Ok(None),
+        };
+        let value_len = value
+            .as_ref()
+            .ok()
+            .and_then(Option::as_ref)
+            .map(Vec::len)
+            .unwrap_or(0);
+        let gas = self.schedule.read_cost_flat
+            + self.schedule.read_cost_per_byte * (key.len() + value_len) as u64;
+        (value, self.gas_info(gas))
+    }
+
+    fn scan(
+        &mut self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> BackendResult<u32> {
+        let mut records: Vec<Record> = self
+            .entries
+            .iter()
+            .filter(|(key, _)| {
+                start.is_none_or(|start| key.as_slice() >= start)
+                    && end.is_none_or(|end| key.as_slice() < end)
+            })
+            .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+            .collect();
+        if order == Order::Descending {
+            records.reverse();
+        }
+        self.iterators.push(records.into_iter());
+        (Ok((self.iterators.len() - 1) as u32), GasInfo::free())
+    }
+
+    fn next(&mut self, iterator_id: u32) -> BackendResult<Option<Record>> {
+        let Some(iterator) = self.iterators.get_mut(iterator_id as usize) else {
+            return (
+                Err(BackendError::iterator_does_not_exist(iterator_id)),
+                GasInfo::free(),
+            );
+        };
+        let record = iterator.next();
+        let record_len = record
+            .as_ref()
+            .map(|(key, value)| key.len() + value.len())
+            .unwrap_or(0);
+        let gas = self.schedule.iter_next_cost_flat
+            + self.schedule.read_cost_per_byte * record_len as u64;
+        (Ok(record), self.gas_info(gas))
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
+        let gas = self.schedule.write_cost_flat
+            + self.schedule.write_cost_per_byte * (key.len() + value.len()) as u64;
+        self.entries.insert(key.to_vec(), Some(value.to_vec()));
+        (Ok(()), self.gas_info(gas))
+    }
+
+    fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
+        self.entries.insert(key.to_vec(), None);
+        (Ok(()), self.gas_info(self.schedule.delete_cost))
+    }
+}
@@ -3,6 +3,7 @@
 
 mod contract_instance;
 mod core;
+mod gas;
 pub mod queriers;
 mod state;
 
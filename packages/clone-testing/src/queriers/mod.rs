@@ -39,5 +39,5 @@ impl QueryHandler for CloneTesting {
 impl DefaultQueriers for CloneTesting {
     type Bank = bank::CloneBankQuerier;
     type Wasm = wasm::CloneWasmQuerier<MockState>;
-    type Node = node::CloneNodeQuerier;
+    type Node = node::CloneNodeQuerier<MockState>;
 }
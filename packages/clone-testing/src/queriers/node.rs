@@ -1,41 +1,36 @@
-use std::{cell::RefCell, rc::Rc};
-
-use crate::{
-    core::{AppResponse, CloneTestingApp},
-    CloneTesting,
-};
+use crate::{core::AppResponse, CloneTesting};
 use cw_orch_core::{
     environment::{NodeQuerier, Querier, QuerierGetter, StateInterface},
     CwEnvError,
 };
 
-pub struct CloneNodeQuerier {
-    app: Rc<RefCell<CloneTestingApp>>,
+pub struct CloneNodeQuerier<S: StateInterface> {
+    chain: CloneTesting<S>,
 }
 
-impl CloneNodeQuerier {
-    fn new<S: StateInterface>(mock: &CloneTesting<S>) -> Self {
+impl<S: StateInterface> CloneNodeQuerier<S> {
+    fn new(chain: &CloneTesting<S>) -> Self {
         Self {
-            app: mock.app.clone(),
+            chain: chain.clone(),
         }
     }
 }
 
-impl Querier for CloneNodeQuerier {
+impl<S: StateInterface> Querier for CloneNodeQuerier<S> {
     type Error = CwEnvError;
 }
 
-impl<S: StateInterface> QuerierGetter<CloneNodeQuerier> for CloneTesting<S> {
-    fn querier(&self) -> CloneNodeQuerier {
+impl<S: StateInterface> QuerierGetter<CloneNodeQuerier<S>> for CloneTesting<S> {
+    fn querier(&self) -> CloneNodeQuerier<S> {
         CloneNodeQuerier::new(self)
     }
 }
 
-impl NodeQuerier for CloneNodeQuerier {
+impl<S: StateInterface> NodeQuerier for CloneNodeQuerier<S> {
     type Response = AppResponse;
 
     fn latest_block(&self) -> Result<cosmwasm_std::BlockInfo, Self::Error> {
-        Ok(self.app.borrow().block_info())
+        Ok(self.chain.app.borrow().block_info())
     }
 
     fn block_by_height(&self, _height: u64) -> Result<cosmwasm_std::BlockInfo, Self::Error> {
@@ -54,8 +49,11 @@ impl NodeQuerier for CloneNodeQuerier {
         Ok(block_info.time.nanos() as u128)
     }
 
-    fn simulate_tx(&self, _tx_bytes: Vec<u8>) -> Result<u64, Self::Error> {
-        unimplemented!()
+    /// Dry-runs the messages of the transaction on the current state and returns the gas they used,
+    /// with the base and size costs of the transaction, from the gas schedule of the environment.
+    /// Each message runs without the changes of the previous ones and the state of the environment is not modified.
+    fn simulate_tx(&self, tx_bytes: Vec<u8>) -> Result<u64, Self::Error> {
+        self.chain.simulate_tx(&tx_bytes)
     }
 
     fn find_tx(&self, _hash: String) -> Result<Self::Response, Self::Error> {
@@ -0,0 +1,45 @@
+use cw_orch::prelude::*;
+use cw_orch_clone_testing::CloneTesting;
+use cw_orch_daemon::{env::GRPC_REPLAY_ENV_NAME, networks::LOCAL_JUNO};
+
+/// Every channel of the process replays the hand-written fixture of the local juno chain
+fn replay_fixture() {
+    std::env::set_var(
+        GRPC_REPLAY_ENV_NAME,
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../cw-orch-daemon/tests/fixtures/grpc/local-juno.jsonl"
+        ),
+    );
+}
+
+/// The fork is created from the latest block of the hand-written fixture of the local juno chain, without any node
+#[test]
+fn clone_testing_replays_the_fixture() -> anyhow::Result<()> {
+    replay_fixture();
+    let chain = CloneTesting::new(LOCAL_JUNO)?;
+
+    let block = chain.block_info()?;
+    assert_eq!(block.height, 1234);
+    assert_eq!(block.chain_id, LOCAL_JUNO.chain_id);
+    Ok(())
+}
+
+#[test]
+fn replayed_fork_meters_wasm_calls_in_the_vm() -> anyhow::Result<()> {
+    use counter_contract::{msg::InstantiateMsg, CounterContract, CounterExecuteMsgFns};
+    use cw_orch_clone_testing::WasmUpload;
+    use cw_orch_mock::GasSchedule;
+
+    replay_fixture();
+    let chain = CloneTesting::new(LOCAL_JUNO)?;
+    let contract = CounterContract::new(chain.clone());
+    let schedule = GasSchedule::default();
+    chain.enable_gas_meter(schedule.clone());
+
+    contract.upload_wasm()?;
+    contract.instantiate(&InstantiateMsg { count: 0 }, None, None)?;
+    let gas_used = contract.increment()?.gas_used().unwrap();
+    assert!(gas_used > schedule.instance_cost + schedule.write_cost_flat);
+    Ok(())
+}
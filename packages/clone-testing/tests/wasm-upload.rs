@@ -29,3 +29,26 @@ fn multiple_upload() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn wasm_calls_are_metered_in_the_vm() -> anyhow::Result<()> {
+    use counter_contract::{msg::InstantiateMsg, CounterExecuteMsgFns};
+    use cw_orch_clone_testing::WasmUpload;
+    use cw_orch_mock::GasSchedule;
+
+    let chain = CloneTesting::new(ARCHWAY_1)?;
+    let contract = CounterContract::new(chain.clone());
+
+    // The synthetic cost of the message only applies to contracts without a wasm
+    let schedule =
+        GasSchedule::default().with_message_cost(contract.id(), "increment", 1_000_000_000);
+    chain.enable_gas_meter(schedule.clone());
+
+    contract.upload_wasm()?;
+    contract.instantiate(&InstantiateMsg { count: 0 }, None, None)?;
+    let gas_used = contract.increment()?.gas_used().unwrap();
+
+    assert!(gas_used < 1_000_000_000);
+    assert!(gas_used > schedule.instance_cost + schedule.write_cost_flat);
+    Ok(())
+}
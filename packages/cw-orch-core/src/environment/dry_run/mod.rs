@@ -0,0 +1,588 @@
+//! Dry-run environment, recording the transactions a deployment would send.
+
+mod plan;
+mod queriers;
+mod state;
+
+pub use plan::{DeploymentPlan, PlannedTx, PlannedTxKind};
+pub use queriers::{DryRunBankQuerier, DryRunNodeQuerier, DryRunWasmQuerier};
+pub use state::DryRunState;
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use cosmwasm_std::{Addr, Binary, BlockInfo, Coin};
+use serde::Serialize;
+
+use crate::{contract::interface_traits::Uploadable, CwEnvError};
+
+use super::{
+    ChainState, DefaultQueriers, EnvironmentInfo, EnvironmentQuerier, IndexResponse, Querier,
+    QueryHandler, StateInterface, TxHandler, WasmQuerier,
+};
+
+/// Environment recording every upload, instantiate, execute and migrate sent through it, to review a deployment before it's sent.
+///
+/// Transactions are run by the wrapped [`DryRunEnv`] without being broadcasted, queries are forwarded to it.
+/// - A [`SimulatedEnv`] executes the transactions locally, on the copy returned by [`SimulatedEnv::dry_run_fork`].
+///   `Mock` environments are forked, their state is not modified by the dry run.
+///   The other simulated environments share their state with the dry run, which modifies it.
+///   To plan a deployment on a live chain, wrap a `CloneTesting` fork of the chain, which executes the contracts against the chain state.
+/// - A query-only `Daemon` simulates the transactions on the node, for the account given to [`DryRun::with_sender`].
+///
+/// The code ids and addresses of the deployment are stored in a [`DryRunState`], not in the state of the wrapped environment.
+///
+/// ## Example
+/// ```ignore
+/// use cw_orch::prelude::*;
+/// use cw_orch_clone_testing::CloneTesting;
+///
+/// let fork = CloneTesting::new(PHOENIX_1)?;
+/// let dry_run = DryRun::new(fork)?;
+/// MyApplication::deploy_on(dry_run.clone(), deploy_data)?;
+///
+/// let plan = dry_run.plan()?;
+/// println!("{plan}");
+/// std::fs::write("deployment-plan.json", serde_json::to_string_pretty(&plan)?)?;
+/// ```
+pub struct DryRun<Chain: DryRunEnv> {
+    chain: Chain,
+    state: DryRunState<()>,
+    context: Arc<Mutex<Chain::Context>>,
+    txs: Arc<Mutex<Vec<PlannedTx>>>,
+}
+
+// The context is shared between the clones, derive would require it to be `Clone`
+impl<Chain: DryRunEnv> Clone for DryRun<Chain> {
+    fn clone(&self) -> Self {
+        Self {
+            chain: self.chain.clone(),
+            state: self.state.clone(),
+            context: self.context.clone(),
+            txs: self.txs.clone(),
+        }
+    }
+}
+
+/// Marker for environments executing transactions locally, without ever broadcasting them to a live chain.
+/// Those environments can be wrapped in a [`DryRun`].
+pub trait SimulatedEnv: TxHandler {
+    /// Gas used by the transaction of `response`, if the environment meters its transactions
+    fn tx_gas_used(&self, response: &Self::Response) -> Option<u64> {
+        response.gas_used()
+    }
+
+    /// Environment the transactions of a [`DryRun`] are executed on.
+    /// Defaults to a clone, sharing the state of this environment: the dry run modifies it.
+    fn dry_run_fork(&self) -> Result<Self, CwEnvError> {
+        Ok(self.clone())
+    }
+}
+
+/// Response of a transaction run by a [`DryRunEnv`]
+#[derive(Clone, Debug)]
+pub struct DryRunResponse<R> {
+    /// Response of the transaction, as it would be returned once broadcasted
+    pub response: R,
+    /// Gas estimated for the transaction, if the environment can estimate it
+    pub gas_estimate: Option<u64>,
+}
+
+/// Environment that can be wrapped in a [`DryRun`], running its transactions without broadcasting them to a live chain.
+///
+/// It's implemented for every [`SimulatedEnv`], and by the query-only `Daemon`.
+pub trait DryRunEnv: ChainState + EnvironmentQuerier + Clone {
+    /// Response type of the transactions
+    type Response: IndexResponse + Debug + Send + Clone;
+    /// Source type for uploading to the environment
+    type ContractSource;
+    /// Sender of the transactions
+    type Sender: Clone;
+    /// State of the environment kept by the dry run between its transactions
+    type Context: Default;
+
+    /// Environment the transactions of a [`DryRun`] are run on, defaults to a clone of this environment
+    fn dry_run_env(&self) -> Result<Self, CwEnvError> {
+        Ok(self.clone())
+    }
+
+    /// Address sending the transactions, errors if the environment has none
+    fn dry_run_sender(&self, context: &Self::Context) -> Result<Addr, CwEnvError>;
+
+    /// Sets the sender of the next transactions
+    fn set_dry_run_sender(&mut self, context: &mut Self::Context, sender: Self::Sender);
+
+    /// Runs the upload of a contract
+    fn dry_run_upload<T: Uploadable>(
+        &self,
+        context: &mut Self::Context,
+        contract_source: &T,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError>;
+
+    /// Runs an instantiation
+    fn dry_run_instantiate<I: Serialize + Debug>(
+        &self,
+        context: &mut Self::Context,
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError>;
+
+    /// Runs an instantiation with a predictable address
+    #[allow(clippy::too_many_arguments)]
+    fn dry_run_instantiate2<I: Serialize + Debug>(
+        &self,
+        context: &mut Self::Context,
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+        salt: Binary,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError>;
+
+    /// Runs an execution
+    fn dry_run_execute<E: Serialize + Debug>(
+        &self,
+        context: &mut Self::Context,
+        exec_msg: &E,
+        coins: &[Coin],
+        contract_address: &Addr,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError>;
+
+    /// Runs a migration
+    fn dry_run_migrate<M: Serialize + Debug>(
+        &self,
+        context: &mut Self::Context,
+        migrate_msg: &M,
+        new_code_id: u64,
+        contract_address: &Addr,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError>;
+}
+
+// The simulated environments execute the transactions, the gas they used is the estimate
+impl<Chain: SimulatedEnv + EnvironmentQuerier> DryRunEnv for Chain {
+    type Response = Chain::Response;
+    type ContractSource = Chain::ContractSource;
+    type Sender = Chain::Sender;
+    type Context = ();
+
+    fn dry_run_env(&self) -> Result<Self, CwEnvError> {
+        self.dry_run_fork()
+    }
+
+    fn dry_run_sender(&self, _context: &()) -> Result<Addr, CwEnvError> {
+        Ok(self.sender_addr())
+    }
+
+    fn set_dry_run_sender(&mut self, _context: &mut (), sender: Self::Sender) {
+        self.set_sender(sender)
+    }
+
+    fn dry_run_upload<T: Uploadable>(
+        &self,
+        _context: &mut (),
+        contract_source: &T,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError> {
+        let response = self.upload(contract_source).map_err(Into::into)?;
+        Ok(metered(self, response))
+    }
+
+    fn dry_run_instantiate<I: Serialize + Debug>(
+        &self,
+        _context: &mut (),
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError> {
+        let response = self
+            .instantiate(code_id, init_msg, label, admin, coins)
+            .map_err(Into::into)?;
+        Ok(metered(self, response))
+    }
+
+    fn dry_run_instantiate2<I: Serialize + Debug>(
+        &self,
+        _context: &mut (),
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+        salt: Binary,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError> {
+        let response = self
+            .instantiate2(code_id, init_msg, label, admin, coins, salt)
+            .map_err(Into::into)?;
+        Ok(metered(self, response))
+    }
+
+    fn dry_run_execute<E: Serialize + Debug>(
+        &self,
+        _context: &mut (),
+        exec_msg: &E,
+        coins: &[Coin],
+        contract_address: &Addr,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError> {
+        let response = self
+            .execute(exec_msg, coins, contract_address)
+            .map_err(Into::into)?;
+        Ok(metered(self, response))
+    }
+
+    fn dry_run_migrate<M: Serialize + Debug>(
+        &self,
+        _context: &mut (),
+        migrate_msg: &M,
+        new_code_id: u64,
+        contract_address: &Addr,
+    ) -> Result<DryRunResponse<Self::Response>, CwEnvError> {
+        let response = self
+            .migrate(migrate_msg, new_code_id, contract_address)
+            .map_err(Into::into)?;
+        Ok(metered(self, response))
+    }
+}
+
+/// Pairs the response of a transaction executed on a [`SimulatedEnv`] with the gas it used
+fn metered<Chain: SimulatedEnv>(
+    chain: &Chain,
+    response: Chain::Response,
+) -> DryRunResponse<Chain::Response> {
+    DryRunResponse {
+        gas_estimate: chain.tx_gas_used(&response),
+        response,
+    }
+}
+
+impl<Chain: DryRunEnv> DryRun<Chain> {
+    /// Wraps an environment to record the transactions sent to it.
+    /// The transactions are run on the environment returned by [`DryRunEnv::dry_run_env`].
+    ///
+    /// Errors if the environment has no sender, like a query-only `Daemon`: wrap it with [`DryRun::with_sender`] instead.
+    pub fn new(chain: Chain) -> Result<Self, CwEnvError> {
+        let dry_run = Self::unchecked(chain)?;
+        dry_run
+            .chain
+            .dry_run_sender(&dry_run.context.lock().unwrap())?;
+        Ok(dry_run)
+    }
+
+    /// Wraps an environment to record the transactions sent to it, simulated for `sender`.
+    pub fn with_sender(chain: Chain, sender: Chain::Sender) -> Result<Self, CwEnvError> {
+        let mut dry_run = Self::unchecked(chain)?;
+        dry_run.set_sender(sender);
+        dry_run
+            .chain
+            .dry_run_sender(&dry_run.context.lock().unwrap())?;
+        Ok(dry_run)
+    }
+
+    /// Dry run of `chain`, whose sender isn't checked
+    fn unchecked(chain: Chain) -> Result<Self, CwEnvError> {
+        Ok(Self {
+            chain: chain.dry_run_env()?,
+            state: DryRunState::new(()),
+            context: Default::default(),
+            txs: Default::default(),
+        })
+    }
+
+    /// Wrapped environment
+    pub fn inner(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Removes all the recorded transactions
+    pub fn clear_plan(&self) {
+        self.txs.lock().unwrap().clear();
+    }
+
+    /// Returns the transactions recorded since the environment was created, in execution order.
+    /// Contract ids are resolved against the state of the dry run.
+    pub fn plan(&self) -> Result<DeploymentPlan, CwEnvError> {
+        let state = self.state();
+        let addresses: HashMap<Addr, String> = state
+            .get_all_addresses()?
+            .into_iter()
+            .map(|(contract_id, address)| (address, contract_id))
+            .collect();
+        let mut code_ids: Vec<(String, u64)> = state.get_all_code_ids()?.into_iter().collect();
+        code_ids.sort();
+
+        let txs = self
+            .txs
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .map(|mut tx| {
+                tx.contract_id = tx
+                    .contract_address
+                    .as_ref()
+                    .and_then(|address| addresses.get(address).cloned())
+                    .or_else(|| {
+                        code_ids
+                            .iter()
+                            .find(|(_, code_id)| Some(*code_id) == tx.code_id)
+                            .map(|(contract_id, _)| contract_id.clone())
+                    });
+                tx
+            })
+            .collect();
+
+        Ok(DeploymentPlan {
+            chain_id: self.chain.env_info().chain_id,
+            txs,
+        })
+    }
+
+    /// Records a transaction run by the wrapped environment
+    fn record(
+        &self,
+        kind: PlannedTxKind,
+        gas_estimate: Option<u64>,
+        code_id: Option<u64>,
+        contract_address: Option<Addr>,
+        msg: Option<serde_json::Value>,
+        funds: &[Coin],
+    ) -> Result<(), CwEnvError> {
+        let sender = self.chain.dry_run_sender(&self.context.lock().unwrap())?;
+        let tx = PlannedTx {
+            kind,
+            contract_id: None,
+            code_id,
+            contract_address,
+            sender,
+            admin: None,
+            label: None,
+            msg,
+            funds: funds.to_vec(),
+            gas_estimate,
+        };
+        self.txs.lock().unwrap().push(tx);
+        Ok(())
+    }
+
+    /// Sets the instantiation details of the last recorded transaction
+    fn set_instantiation(&self, admin: Option<&Addr>, label: Option<&str>) {
+        if let Some(tx) = self.txs.lock().unwrap().last_mut() {
+            tx.admin = admin.cloned();
+            tx.label = label.map(ToString::to_string);
+        }
+    }
+}
+
+impl<Chain: DryRunEnv> ChainState for DryRun<Chain> {
+    type Out = DryRunState<Chain::Out>;
+
+    fn state(&self) -> Self::Out {
+        self.state.with_inner(self.chain.state())
+    }
+}
+
+impl<Chain: DryRunEnv> TxHandler for DryRun<Chain> {
+    type Response = Chain::Response;
+    type Error = CwEnvError;
+    type ContractSource = Chain::ContractSource;
+    type Sender = Chain::Sender;
+
+    fn sender(&self) -> Addr {
+        self.sender_addr()
+    }
+
+    fn sender_addr(&self) -> Addr {
+        // The dry run is only built with a sender, which can't be unset
+        self.chain
+            .dry_run_sender(&self.context.lock().unwrap())
+            .expect("the sender of the dry run is set when it's built")
+    }
+
+    fn set_sender(&mut self, sender: Self::Sender) {
+        self.chain
+            .set_dry_run_sender(&mut self.context.lock().unwrap(), sender)
+    }
+
+    fn upload<T: Uploadable>(&self, contract_source: &T) -> Result<Self::Response, Self::Error> {
+        let DryRunResponse {
+            response,
+            gas_estimate,
+        } = self
+            .chain
+            .dry_run_upload(&mut self.context.lock().unwrap(), contract_source)?;
+        self.record(
+            PlannedTxKind::Upload,
+            gas_estimate,
+            response.uploaded_code_id().ok(),
+            None,
+            None,
+            &[],
+        )?;
+        Ok(response)
+    }
+
+    fn instantiate<I: Serialize + Debug>(
+        &self,
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+    ) -> Result<Self::Response, Self::Error> {
+        let msg = serde_json::to_value(init_msg)?;
+        let DryRunResponse {
+            response,
+            gas_estimate,
+        } = self.chain.dry_run_instantiate(
+            &mut self.context.lock().unwrap(),
+            code_id,
+            init_msg,
+            label,
+            admin,
+            coins,
+        )?;
+        self.record(
+            PlannedTxKind::Instantiate,
+            gas_estimate,
+            Some(code_id),
+            response.instantiated_contract_address().ok(),
+            Some(msg),
+            coins,
+        )?;
+        self.set_instantiation(admin, label);
+        Ok(response)
+    }
+
+    fn instantiate2<I: Serialize + Debug>(
+        &self,
+        code_id: u64,
+        init_msg: &I,
+        label: Option<&str>,
+        admin: Option<&Addr>,
+        coins: &[Coin],
+        salt: Binary,
+    ) -> Result<Self::Response, Self::Error> {
+        let msg = serde_json::to_value(init_msg)?;
+        let DryRunResponse {
+            response,
+            gas_estimate,
+        } = self.chain.dry_run_instantiate2(
+            &mut self.context.lock().unwrap(),
+            code_id,
+            init_msg,
+            label,
+            admin,
+            coins,
+            salt,
+        )?;
+        self.record(
+            PlannedTxKind::Instantiate2,
+            gas_estimate,
+            Some(code_id),
+            response.instantiated_contract_address().ok(),
+            Some(msg),
+            coins,
+        )?;
+        self.set_instantiation(admin, label);
+        Ok(response)
+    }
+
+    fn execute<E: Serialize + Debug>(
+        &self,
+        exec_msg: &E,
+        coins: &[Coin],
+        contract_address: &Addr,
+    ) -> Result<Self::Response, Self::Error> {
+        let msg = serde_json::to_value(exec_msg)?;
+        let DryRunResponse {
+            response,
+            gas_estimate,
+        } = self.chain.dry_run_execute(
+            &mut self.context.lock().unwrap(),
+            exec_msg,
+            coins,
+            contract_address,
+        )?;
+        self.record(
+            PlannedTxKind::Execute,
+            gas_estimate,
+            None,
+            Some(contract_address.clone()),
+            Some(msg),
+            coins,
+        )?;
+        Ok(response)
+    }
+
+    fn migrate<M: Serialize + Debug>(
+        &self,
+        migrate_msg: &M,
+        new_code_id: u64,
+        contract_address: &Addr,
+    ) -> Result<Self::Response, Self::Error> {
+        let msg = serde_json::to_value(migrate_msg)?;
+        let DryRunResponse {
+            response,
+            gas_estimate,
+        } = self.chain.dry_run_migrate(
+            &mut self.context.lock().unwrap(),
+            migrate_msg,
+            new_code_id,
+            contract_address,
+        )?;
+        self.record(
+            PlannedTxKind::Migrate,
+            gas_estimate,
+            Some(new_code_id),
+            Some(contract_address.clone()),
+            Some(msg),
+            &[],
+        )?;
+        Ok(response)
+    }
+}
+
+impl<Chain: DryRunEnv> EnvironmentQuerier for DryRun<Chain> {
+    fn env_info(&self) -> EnvironmentInfo {
+        self.chain.env_info()
+    }
+}
+
+impl<Chain: DefaultQueriers + DryRunEnv> DefaultQueriers for DryRun<Chain>
+where
+    Chain::Wasm: WasmQuerier<Chain = Chain>,
+{
+    type Bank = DryRunBankQuerier<Chain>;
+    type Wasm = DryRunWasmQuerier<Chain>;
+    type Node = DryRunNodeQuerier<Chain>;
+}
+
+impl<Chain: QueryHandler + DryRunEnv> QueryHandler for DryRun<Chain>
+where
+    Chain::Wasm: WasmQuerier<Chain = Chain>,
+{
+    type Error = <Chain as QueryHandler>::Error;
+
+    fn wait_blocks(&self, amount: u64) -> Result<(), Self::Error> {
+        self.chain.wait_blocks(amount)
+    }
+
+    fn wait_seconds(&self, secs: u64) -> Result<(), Self::Error> {
+        self.chain.wait_seconds(secs)
+    }
+
+    fn next_block(&self) -> Result<(), Self::Error> {
+        self.chain.next_block()
+    }
+
+    fn block_info(&self) -> Result<BlockInfo, <Self::Node as Querier>::Error> {
+        self.chain.block_info()
+    }
+}
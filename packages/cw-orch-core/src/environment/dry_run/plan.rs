@@ -0,0 +1,109 @@
+use cosmwasm_std::{Addr, Coin};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Kind of message sent by a planned transaction
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedTxKind {
+    /// Code upload
+    Upload,
+    /// Contract instantiation
+    Instantiate,
+    /// Contract instantiation with a predictable address
+    Instantiate2,
+    /// Contract execution
+    Execute,
+    /// Contract migration
+    Migrate,
+}
+
+impl Display for PlannedTxKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            PlannedTxKind::Upload => "upload",
+            PlannedTxKind::Instantiate => "instantiate",
+            PlannedTxKind::Instantiate2 => "instantiate2",
+            PlannedTxKind::Execute => "execute",
+            PlannedTxKind::Migrate => "migrate",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+/// Transaction recorded by a [`DryRun`](super::DryRun) environment
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PlannedTx {
+    /// Kind of message sent
+    pub kind: PlannedTxKind,
+    /// Id of the contract targeted by the message, if it's registered in the state
+    pub contract_id: Option<String>,
+    /// Code id uploaded, instantiated or migrated to
+    pub code_id: Option<u64>,
+    /// Address of the contract instantiated, executed or migrated
+    pub contract_address: Option<Addr>,
+    /// Address sending the transaction
+    pub sender: Addr,
+    /// Admin of the instantiated contract
+    pub admin: Option<Addr>,
+    /// Label of the instantiated contract
+    pub label: Option<String>,
+    /// JSON payload of the message. Uploads don't have a payload.
+    pub msg: Option<serde_json::Value>,
+    /// Funds sent with the message
+    pub funds: Vec<Coin>,
+    /// Gas estimated for the transaction, if the wrapped environment reports it
+    pub gas_estimate: Option<u64>,
+}
+
+/// Ordered list of the transactions a deployment would send
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeploymentPlan {
+    /// Chain id of the environment the plan was recorded on
+    pub chain_id: String,
+    /// Planned transactions, in execution order
+    pub txs: Vec<PlannedTx>,
+}
+
+impl DeploymentPlan {
+    /// Total gas estimated for the plan.
+    /// Transactions without a gas estimate are not counted.
+    pub fn total_gas(&self) -> u64 {
+        self.txs.iter().filter_map(|tx| tx.gas_estimate).sum()
+    }
+}
+
+impl Display for DeploymentPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Deployment plan on {} ({} transactions):",
+            self.chain_id,
+            self.txs.len()
+        )?;
+        for (index, tx) in self.txs.iter().enumerate() {
+            write!(f, "{}. {}", index + 1, tx.kind)?;
+            if let Some(contract_id) = &tx.contract_id {
+                write!(f, " {contract_id}")?;
+            }
+            if let Some(code_id) = tx.code_id {
+                write!(f, " (code id {code_id})")?;
+            }
+            if let Some(address) = &tx.contract_address {
+                write!(f, " at {address}")?;
+            }
+            if let Some(msg) = &tx.msg {
+                write!(f, ": {msg}")?;
+            }
+            if !tx.funds.is_empty() {
+                let funds: Vec<String> = tx.funds.iter().map(ToString::to_string).collect();
+                write!(f, " with funds {}", funds.join(","))?;
+            }
+            if let Some(gas) = tx.gas_estimate {
+                write!(f, ", estimated gas {gas}")?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "Total estimated gas: {}", self.total_gas())
+    }
+}
@@ -0,0 +1,203 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::{BlockInfo, CodeInfoResponse, Coin, ContractInfoResponse, HexBinary};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    contract::{
+        interface_traits::{ContractInstance, Uploadable},
+        Contract, WasmPath,
+    },
+    environment::{
+        BankQuerier, ChainInfoOwned, ChainState, DefaultQueriers, NodeQuerier, Querier,
+        QuerierGetter, WasmQuerier,
+    },
+    CwEnvError,
+};
+
+use super::{DryRun, DryRunEnv};
+
+/// Bank querier of a [`DryRun`] environment, forwards the queries to the wrapped environment
+pub struct DryRunBankQuerier<Chain: DefaultQueriers> {
+    querier: Chain::Bank,
+}
+
+/// Node querier of a [`DryRun`] environment, forwards the queries to the wrapped environment
+pub struct DryRunNodeQuerier<Chain: DefaultQueriers> {
+    querier: Chain::Node,
+}
+
+/// Wasm querier of a [`DryRun`] environment, forwards the queries to the wrapped environment
+pub struct DryRunWasmQuerier<Chain: DefaultQueriers> {
+    querier: Chain::Wasm,
+}
+
+impl<Chain: DefaultQueriers> Querier for DryRunBankQuerier<Chain> {
+    type Error = <Chain::Bank as Querier>::Error;
+}
+
+impl<Chain: DefaultQueriers> Querier for DryRunNodeQuerier<Chain> {
+    type Error = <Chain::Node as Querier>::Error;
+}
+
+impl<Chain: DefaultQueriers> Querier for DryRunWasmQuerier<Chain> {
+    type Error = <Chain::Wasm as Querier>::Error;
+}
+
+impl<Chain: DefaultQueriers + DryRunEnv> QuerierGetter<DryRunBankQuerier<Chain>> for DryRun<Chain> {
+    fn querier(&self) -> DryRunBankQuerier<Chain> {
+        DryRunBankQuerier {
+            querier: self.chain.bank_querier(),
+        }
+    }
+}
+
+impl<Chain: DefaultQueriers + DryRunEnv> QuerierGetter<DryRunNodeQuerier<Chain>> for DryRun<Chain> {
+    fn querier(&self) -> DryRunNodeQuerier<Chain> {
+        DryRunNodeQuerier {
+            querier: self.chain.node_querier(),
+        }
+    }
+}
+
+impl<Chain: DefaultQueriers + DryRunEnv> QuerierGetter<DryRunWasmQuerier<Chain>> for DryRun<Chain> {
+    fn querier(&self) -> DryRunWasmQuerier<Chain> {
+        DryRunWasmQuerier {
+            querier: self.chain.wasm_querier(),
+        }
+    }
+}
+
+impl<Chain: DefaultQueriers> BankQuerier for DryRunBankQuerier<Chain> {
+    fn balance(
+        &self,
+        address: impl Into<String>,
+        denom: Option<String>,
+    ) -> Result<Vec<Coin>, Self::Error> {
+        self.querier.balance(address, denom)
+    }
+
+    fn total_supply(&self) -> Result<Vec<Coin>, Self::Error> {
+        self.querier.total_supply()
+    }
+
+    fn supply_of(&self, denom: impl Into<String>) -> Result<Coin, Self::Error> {
+        self.querier.supply_of(denom)
+    }
+}
+
+impl<Chain: DefaultQueriers> NodeQuerier for DryRunNodeQuerier<Chain> {
+    type Response = <Chain::Node as NodeQuerier>::Response;
+
+    fn latest_block(&self) -> Result<BlockInfo, Self::Error> {
+        self.querier.latest_block()
+    }
+
+    fn block_by_height(&self, height: u64) -> Result<BlockInfo, Self::Error> {
+        self.querier.block_by_height(height)
+    }
+
+    fn block_height(&self) -> Result<u64, Self::Error> {
+        self.querier.block_height()
+    }
+
+    fn block_time(&self) -> Result<u128, Self::Error> {
+        self.querier.block_time()
+    }
+
+    fn simulate_tx(&self, tx_bytes: Vec<u8>) -> Result<u64, Self::Error> {
+        self.querier.simulate_tx(tx_bytes)
+    }
+
+    fn find_tx(&self, hash: String) -> Result<Self::Response, Self::Error> {
+        self.querier.find_tx(hash)
+    }
+}
+
+impl<Chain: DefaultQueriers + DryRunEnv> WasmQuerier for DryRunWasmQuerier<Chain>
+where
+    Chain::Wasm: WasmQuerier<Chain = Chain>,
+{
+    type Chain = DryRun<Chain>;
+
+    fn code_id_hash(&self, code_id: u64) -> Result<HexBinary, Self::Error> {
+        self.querier.code_id_hash(code_id)
+    }
+
+    fn contract_info(
+        &self,
+        address: impl Into<String>,
+    ) -> Result<ContractInfoResponse, Self::Error> {
+        self.querier.contract_info(address)
+    }
+
+    fn raw_query(
+        &self,
+        address: impl Into<String>,
+        query_keys: Vec<u8>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.querier.raw_query(address, query_keys)
+    }
+
+    fn smart_query<Q: Serialize, T: DeserializeOwned>(
+        &self,
+        address: impl Into<String>,
+        query_msg: &Q,
+    ) -> Result<T, Self::Error> {
+        self.querier.smart_query(address, query_msg)
+    }
+
+    fn code(&self, code_id: u64) -> Result<CodeInfoResponse, Self::Error> {
+        self.querier.code(code_id)
+    }
+
+    fn local_hash<T: Uploadable + ContractInstance<Self::Chain>>(
+        &self,
+        contract: &T,
+    ) -> Result<HexBinary, CwEnvError> {
+        let instance = contract.as_instance();
+        let mut inner_instance = Contract::new(&instance.id, instance.environment().chain.clone());
+        inner_instance.default_code_id = instance.default_code_id;
+        inner_instance.default_address = instance.default_address.clone();
+
+        self.querier.local_hash(&InnerContract::<T, Chain> {
+            instance: inner_instance,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn instantiate2_addr(
+        &self,
+        code_id: u64,
+        creator: impl Into<String>,
+        salt: cosmwasm_std::Binary,
+    ) -> Result<String, Self::Error> {
+        self.querier.instantiate2_addr(code_id, creator, salt)
+    }
+}
+
+/// Contract of the wrapped environment, uploading the same code as the dry-run contract `T`
+struct InnerContract<T, Chain> {
+    instance: Contract<Chain>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Uploadable, Chain> Uploadable for InnerContract<T, Chain> {
+    fn wasm(chain: &ChainInfoOwned) -> WasmPath {
+        T::wasm(chain)
+    }
+
+    fn wrapper() -> Box<dyn cw_multi_test::Contract<cosmwasm_std::Empty, cosmwasm_std::Empty>> {
+        T::wrapper()
+    }
+}
+
+impl<T, Chain: ChainState> ContractInstance<Chain> for InnerContract<T, Chain> {
+    fn as_instance(&self) -> &Contract<Chain> {
+        &self.instance
+    }
+
+    fn as_instance_mut(&mut self) -> &mut Contract<Chain> {
+        &mut self.instance
+    }
+}
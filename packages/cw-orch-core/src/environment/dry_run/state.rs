@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use cosmwasm_std::Addr;
+
+use crate::{environment::StateInterface, CwEnvError};
+
+/// State of a [`DryRun`](super::DryRun) environment.
+///
+/// The code ids and addresses set during the dry run are kept in memory on top of the state of the wrapped environment,
+/// which is read but never written to. A dry run of a `Daemon` doesn't touch its state file.
+#[derive(Clone)]
+pub struct DryRunState<S> {
+    inner: S,
+    /// Code ids set during the dry run, `None` when they were removed
+    code_ids: Arc<Mutex<HashMap<String, Option<u64>>>>,
+    /// Addresses set during the dry run, `None` when they were removed
+    addresses: Arc<Mutex<HashMap<String, Option<Addr>>>>,
+}
+
+impl<S> DryRunState<S> {
+    /// Layers an empty dry-run state on top of `inner`
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            code_ids: Default::default(),
+            addresses: Default::default(),
+        }
+    }
+
+    /// State of the wrapped environment
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Shares the values set during the dry run with a newer state of the wrapped environment
+    pub(crate) fn with_inner<T>(&self, inner: T) -> DryRunState<T> {
+        DryRunState {
+            inner,
+            code_ids: self.code_ids.clone(),
+            addresses: self.addresses.clone(),
+        }
+    }
+}
+
+impl<S: StateInterface> StateInterface for DryRunState<S> {
+    fn get_address(&self, contract_id: &str) -> Result<Addr, CwEnvError> {
+        match self.addresses.lock().unwrap().get(contract_id) {
+            Some(Some(address)) => Ok(address.clone()),
+            Some(None) => Err(CwEnvError::AddrNotInStore(contract_id.to_owned())),
+            None => self.inner.get_address(contract_id),
+        }
+    }
+
+    fn set_address(&mut self, contract_id: &str, address: &Addr) {
+        self.addresses
+            .lock()
+            .unwrap()
+            .insert(contract_id.to_string(), Some(address.clone()));
+    }
+
+    fn remove_address(&mut self, contract_id: &str) {
+        self.addresses
+            .lock()
+            .unwrap()
+            .insert(contract_id.to_string(), None);
+    }
+
+    fn get_code_id(&self, contract_id: &str) -> Result<u64, CwEnvError> {
+        match self.code_ids.lock().unwrap().get(contract_id) {
+            Some(Some(code_id)) => Ok(*code_id),
+            Some(None) => Err(CwEnvError::CodeIdNotInStore(contract_id.to_owned())),
+            None => self.inner.get_code_id(contract_id),
+        }
+    }
+
+    fn set_code_id(&mut self, contract_id: &str, code_id: u64) {
+        self.code_ids
+            .lock()
+            .unwrap()
+            .insert(contract_id.to_string(), Some(code_id));
+    }
+
+    fn remove_code_id(&mut self, contract_id: &str) {
+        self.code_ids
+            .lock()
+            .unwrap()
+            .insert(contract_id.to_string(), None);
+    }
+
+    /// The addresses of the wrapped environment are not returned if it fails to list them, e.g. when it has no deployment yet
+    fn get_all_addresses(&self) -> Result<HashMap<String, Addr>, CwEnvError> {
+        let mut addresses = self.inner.get_all_addresses().unwrap_or_default();
+        for (contract_id, address) in self.addresses.lock().unwrap().iter() {
+            match address {
+                Some(address) => addresses.insert(contract_id.clone(), address.clone()),
+                None => addresses.remove(contract_id),
+            };
+        }
+        Ok(addresses)
+    }
+
+    /// The code ids of the wrapped environment are not returned if it fails to list them, e.g. when it has no deployment yet
+    fn get_all_code_ids(&self) -> Result<HashMap<String, u64>, CwEnvError> {
+        let mut code_ids = self.inner.get_all_code_ids().unwrap_or_default();
+        for (contract_id, code_id) in self.code_ids.lock().unwrap().iter() {
+            match code_id {
+                Some(code_id) => code_ids.insert(contract_id.clone(), *code_id),
+                None => code_ids.remove(contract_id),
+            };
+        }
+        Ok(code_ids)
+    }
+}
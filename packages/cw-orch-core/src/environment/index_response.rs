@@ -5,6 +5,8 @@ use snailquote::unescape;
 
 const CODE_ID_UPLOAD_EVENT: (&str, &str) = ("store_code", "code_id");
 const ADDRESS_INSTANTIATE_EVENT: (&str, &str) = ("instantiate", "_contract_address");
+/// Event attribute used by simulated environments to report the gas used by a transaction
+pub const GAS_USED_EVENT: (&str, &str) = ("tx", "gas_used");
 
 #[cfg(feature = "eth")]
 const INJECTIVE_CODE_ID_UPLOAD_EVENT: (&str, &str) =
@@ -32,6 +34,14 @@ pub trait IndexResponse {
     /// Get the data field of the response.
     fn data(&self) -> Option<Binary>;
 
+    /// Gas used by the transaction, if the environment reports it.
+    /// By default, this is read from the [`GAS_USED_EVENT`] attribute.
+    fn gas_used(&self) -> Option<u64> {
+        self.event_attr_value(GAS_USED_EVENT.0, GAS_USED_EVENT.1)
+            .ok()
+            .and_then(|gas| gas.parse().ok())
+    }
+
     /// Helper to get the contract address of a instantiate response.
     fn instantiated_contract_address(&self) -> StdResult<Addr> {
         if let Ok(code_id) = self
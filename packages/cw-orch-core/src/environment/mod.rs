@@ -1,5 +1,6 @@
 mod chain_info;
 mod cosmwasm_environment;
+pub mod dry_run;
 mod index_response;
 mod mut_env;
 mod queriers;
@@ -7,7 +8,11 @@ mod state;
 
 pub use chain_info::{ChainInfo, ChainInfoOwned, ChainKind, NetworkInfo, NetworkInfoOwned};
 pub use cosmwasm_environment::{CwEnv, TxHandler, TxResponse};
-pub use index_response::IndexResponse;
+pub use dry_run::{
+    DeploymentPlan, DryRun, DryRunEnv, DryRunResponse, DryRunState, PlannedTx, PlannedTxKind,
+    SimulatedEnv,
+};
+pub use index_response::{IndexResponse, GAS_USED_EVENT};
 pub use mut_env::{BankSetter, MutCwEnv};
 pub use queriers::{
     bank::BankQuerier,
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
 use cosmwasm_std::{Addr, Coin, Uint128};
 use cw_multi_test::{AppBuilder, MockAddressGenerator, MockApiBech32, WasmKeeper};
@@ -8,7 +8,9 @@ use cw_orch_core::{
 };
 use cw_utils::NativeBalance;
 
-use crate::{queriers::bank::MockBankQuerier, MockBase, MockBech32, MockState};
+use crate::{
+    queriers::bank::MockBankQuerier, MockAppBech32, MockBase, MockBech32, MockState, MockTxHistory,
+};
 
 impl MockBase<MockApiBech32, MockState> {
     /// Create a mock environment with the default mock state.
@@ -54,17 +56,28 @@ impl<S: StateInterface> MockBase<MockApiBech32, S> {
     /// The state is customizable by implementing the `StateInterface` trait on a custom struct and providing it on the custom constructor.
     pub fn new_custom(prefix: &'static str, custom_state: S) -> Self {
         let state = Rc::new(RefCell::new(custom_state));
-        let app = Rc::new(RefCell::new(
+        let app_factory: Rc<dyn Fn() -> MockAppBech32> = Rc::new(move || {
             AppBuilder::new_custom()
                 .with_api(MockApiBech32::new(prefix))
                 .with_wasm(WasmKeeper::default().with_address_generator(MockAddressGenerator))
-                .build(|_, _, _| {}),
-        ));
+                .build(|_, _, _| {})
+        });
+        let app = Rc::new(RefCell::new(app_factory()));
 
         // We create an address internally
         let sender = app.borrow().api().addr_make("sender");
 
-        Self { sender, state, app }
+        Self {
+            sender,
+            state,
+            app,
+            history: Rc::new(RefCell::new(MockTxHistory::new())),
+            gas_meter: Rc::new(RefCell::new(None)),
+            snapshot_codes: Rc::new(RefCell::new(BTreeMap::new())),
+            codes: Rc::new(RefCell::new(BTreeMap::new())),
+            checkpoints: Rc::new(RefCell::new(vec![])),
+            app_factory,
+        }
     }
 }
 
@@ -0,0 +1,237 @@
+use cosmwasm_std::{
+    Addr, Api, Binary, BlockInfo, Deps, DepsMut, Empty, Env, IbcBasicResponse, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcPacketAckMsg,
+    IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, MessageInfo, Order, Reply,
+    Response, Storage,
+};
+use cw_multi_test::{error::AnyResult, Contract};
+use cw_orch_core::{environment::StateInterface, CwEnvError};
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{core::MockApp, GasMeter, MockBase, MockTxHistory};
+
+/// Contract code shared between an environment and its forks.
+/// Contract wrappers can't be cloned, so they are registered behind a reference counted pointer.
+#[derive(Clone)]
+pub(crate) struct SharedCode(Rc<dyn Contract<Empty, Empty>>);
+
+impl From<Box<dyn Contract<Empty, Empty>>> for SharedCode {
+    fn from(code: Box<dyn Contract<Empty, Empty>>) -> Self {
+        Self(Rc::from(code))
+    }
+}
+
+impl Contract<Empty, Empty> for SharedCode {
+    fn execute(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Vec<u8>,
+    ) -> AnyResult<Response> {
+        self.0.execute(deps, env, info, msg)
+    }
+
+    fn instantiate(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Vec<u8>,
+    ) -> AnyResult<Response> {
+        self.0.instantiate(deps, env, info, msg)
+    }
+
+    fn query(&self, deps: Deps, env: Env, msg: Vec<u8>) -> AnyResult<Binary> {
+        self.0.query(deps, env, msg)
+    }
+
+    fn sudo(&self, deps: DepsMut, env: Env, msg: Vec<u8>) -> AnyResult<Response> {
+        self.0.sudo(deps, env, msg)
+    }
+
+    fn reply(&self, deps: DepsMut, env: Env, msg: Reply) -> AnyResult<Response> {
+        self.0.reply(deps, env, msg)
+    }
+
+    fn migrate(&self, deps: DepsMut, env: Env, msg: Vec<u8>) -> AnyResult<Response> {
+        self.0.migrate(deps, env, msg)
+    }
+
+    fn ibc_channel_open(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        msg: IbcChannelOpenMsg,
+    ) -> AnyResult<IbcChannelOpenResponse> {
+        self.0.ibc_channel_open(deps, env, msg)
+    }
+
+    fn ibc_channel_connect(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        msg: IbcChannelConnectMsg,
+    ) -> AnyResult<IbcBasicResponse> {
+        self.0.ibc_channel_connect(deps, env, msg)
+    }
+
+    fn ibc_channel_close(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        msg: IbcChannelCloseMsg,
+    ) -> AnyResult<IbcBasicResponse> {
+        self.0.ibc_channel_close(deps, env, msg)
+    }
+
+    fn ibc_packet_receive(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        msg: IbcPacketReceiveMsg,
+    ) -> AnyResult<IbcReceiveResponse> {
+        self.0.ibc_packet_receive(deps, env, msg)
+    }
+
+    fn ibc_packet_acknowledge(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        msg: IbcPacketAckMsg,
+    ) -> AnyResult<IbcBasicResponse> {
+        self.0.ibc_packet_acknowledge(deps, env, msg)
+    }
+
+    fn ibc_packet_timeout(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        msg: IbcPacketTimeoutMsg,
+    ) -> AnyResult<IbcBasicResponse> {
+        self.0.ibc_packet_timeout(deps, env, msg)
+    }
+}
+
+/// Codes uploaded through the environment, indexed by code id
+pub(crate) type CodeRegistry = BTreeMap<u64, SharedCode>;
+
+/// Returns the creators of all the codes registered in the app, indexed by code id
+pub(crate) fn code_creators<A: Api>(app: &MockApp<A>) -> BTreeMap<u64, Addr> {
+    // Code ids are sequential, we stop at the first missing one
+    let mut creators = BTreeMap::new();
+    for code_id in 1.. {
+        let Ok(code_info) = app.wrap().query_wasm_code_info(code_id) else {
+            break;
+        };
+        creators.insert(code_id, Addr::unchecked(code_info.creator));
+    }
+    creators
+}
+
+/// Identifier of a checkpoint taken with [`MockBase::checkpoint`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CheckpointId(usize);
+
+/// In-memory copy of the state of a mock environment
+pub(crate) struct Checkpoint<S> {
+    storage: Vec<(Vec<u8>, Vec<u8>)>,
+    block: BlockInfo,
+    /// Creators of the registered codes, indexed by code id
+    codes: BTreeMap<u64, Addr>,
+    state: S,
+    history: MockTxHistory,
+    gas_meter: Option<GasMeter>,
+}
+
+impl<A: Api, S: StateInterface> MockBase<A, S> {
+    /// Saves the current state of the environment in memory and returns an identifier to [`MockBase::rollback`] to it.
+    /// The checkpoint covers the app (storage, block info and code registry), the cw-orch state, the transaction history and the gas meter.
+    ///
+    /// All the codes of the environment need to have been uploaded through the environment (not directly on the app).
+    pub fn checkpoint(&self) -> Result<CheckpointId, CwEnvError> {
+        let checkpoint = self.take_checkpoint()?;
+        let mut checkpoints = self.checkpoints.borrow_mut();
+        checkpoints.push(checkpoint);
+        Ok(CheckpointId(checkpoints.len() - 1))
+    }
+
+    /// Restores the environment to the state it had when the checkpoint was taken.
+    /// The checkpoint stays available, so it's possible to roll back to it multiple times.
+    /// This affects all the clones of this environment.
+    pub fn rollback(&self, id: CheckpointId) -> Result<(), CwEnvError> {
+        let checkpoints = self.checkpoints.borrow();
+        let checkpoint = checkpoints.get(id.0).ok_or_else(|| {
+            CwEnvError::StdErr(format!("Checkpoint {} not found in environment", id.0))
+        })?;
+
+        *self.app.borrow_mut() = self.build_app(checkpoint)?;
+        *self.state.borrow_mut() = checkpoint.state.clone();
+        *self.history.borrow_mut() = checkpoint.history.clone();
+        *self.gas_meter.borrow_mut() = checkpoint.gas_meter.clone();
+        Ok(())
+    }
+
+    /// Creates an independent copy of the environment.
+    /// Unlike [`Clone`], operations on the fork don't affect the original environment and vice versa.
+    /// Only the (immutable) contract codes are shared between them.
+    pub fn fork(&self) -> Result<Self, CwEnvError> {
+        let checkpoint = self.take_checkpoint()?;
+        let app = self.build_app(&checkpoint)?;
+
+        Ok(Self {
+            sender: self.sender.clone(),
+            state: Rc::new(RefCell::new(checkpoint.state)),
+            app: Rc::new(RefCell::new(app)),
+            history: Rc::new(RefCell::new(checkpoint.history)),
+            gas_meter: Rc::new(RefCell::new(checkpoint.gas_meter)),
+            snapshot_codes: Rc::new(RefCell::new(self.snapshot_codes.borrow().clone())),
+            codes: Rc::new(RefCell::new(self.codes.borrow().clone())),
+            checkpoints: Rc::new(RefCell::new(vec![])),
+            app_factory: self.app_factory.clone(),
+        })
+    }
+
+    fn take_checkpoint(&self) -> Result<Checkpoint<S>, CwEnvError> {
+        let app = self.app.borrow();
+
+        let codes = code_creators(&app);
+        let registry = self.codes.borrow();
+        if let Some(code_id) = codes.keys().find(|code_id| !registry.contains_key(code_id)) {
+            return Err(CwEnvError::StdErr(format!(
+                "Code id {code_id} was not uploaded through the mock environment and can't be copied"
+            )));
+        }
+
+        Ok(Checkpoint {
+            storage: app.storage().range(None, None, Order::Ascending).collect(),
+            block: app.block_info(),
+            codes,
+            state: self.state.borrow().clone(),
+            history: self.history.borrow().clone(),
+            gas_meter: self.gas_meter.borrow().clone(),
+        })
+    }
+
+    /// Builds a new app from a checkpoint
+    fn build_app(&self, checkpoint: &Checkpoint<S>) -> Result<MockApp<A>, CwEnvError> {
+        let mut app = (self.app_factory)();
+
+        let registry = self.codes.borrow();
+        for (code_id, creator) in &checkpoint.codes {
+            app.store_code_with_id(
+                creator.clone(),
+                *code_id,
+                Box::new(registry[code_id].clone()),
+            )?;
+        }
+
+        let storage = app.storage_mut();
+        for (key, value) in &checkpoint.storage {
+            storage.set(key, value);
+        }
+        app.set_block(checkpoint.block.clone());
+        Ok(app)
+    }
+}
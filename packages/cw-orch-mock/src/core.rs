@@ -1,4 +1,4 @@
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, fmt::Debug, rc::Rc};
 
 use cosmwasm_std::{
     testing::{MockApi, MockStorage},
@@ -10,10 +10,15 @@ use cw_multi_test::{
 };
 use serde::Serialize;
 
-use super::state::MockState;
+use super::{
+    checkpoint::{Checkpoint, CodeRegistry, SharedCode},
+    gas::{ContractCall, GasMeter, GasSchedule},
+    history::MockTxHistory,
+    state::MockState,
+};
 use cw_orch_core::{
     contract::interface_traits::Uploadable,
-    environment::{ChainState, IndexResponse, StateInterface, TxHandler},
+    environment::{ChainState, IndexResponse, SimulatedEnv, StateInterface, TxHandler},
     CwEnvError,
 };
 
@@ -74,6 +79,18 @@ pub struct MockBase<A: Api = MockApi, S: StateInterface = MockState> {
     pub state: Rc<RefCell<S>>,
     /// Inner mutable cw-multi-test app backend
     pub app: Rc<RefCell<MockApp<A>>>,
+    /// Inner mutable history of the transactions executed on this environment
+    pub history: Rc<RefCell<MockTxHistory>>,
+    /// Optional gas meter, estimating the gas used by transactions
+    pub gas_meter: Rc<RefCell<Option<GasMeter>>>,
+    /// Creators of the codes of the last loaded snapshot, used to restore the code registry
+    pub(crate) snapshot_codes: Rc<RefCell<BTreeMap<u64, Addr>>>,
+    /// Codes uploaded through this environment, used to copy the app on checkpoints and forks
+    pub(crate) codes: Rc<RefCell<CodeRegistry>>,
+    /// Checkpoints taken on this environment
+    pub(crate) checkpoints: Rc<RefCell<Vec<Checkpoint<S>>>>,
+    /// Creates a new empty app, configured like the app of this environment
+    pub(crate) app_factory: Rc<dyn Fn() -> MockApp<A>>,
 }
 
 pub type Mock<S = MockState> = MockBase<MockApi, S>;
@@ -85,6 +102,12 @@ impl<A: Api, S: StateInterface> Clone for MockBase<A, S> {
             sender: self.sender.clone(),
             state: self.state.clone(),
             app: self.app.clone(),
+            history: self.history.clone(),
+            gas_meter: self.gas_meter.clone(),
+            snapshot_codes: self.snapshot_codes.clone(),
+            codes: self.codes.clone(),
+            checkpoints: self.checkpoints.clone(),
+            app_factory: self.app_factory.clone(),
         }
     }
 }
@@ -106,17 +129,56 @@ impl<A: Api, S: StateInterface> MockBase<A, S> {
         contract_id: &str,
         wrapper: Box<dyn Contract<Empty, Empty>>,
     ) -> Result<AppResponse, CwEnvError> {
-        let code_id = self.app.borrow_mut().store_code(wrapper);
+        let code_id = self.store_code(wrapper);
         // add contract code_id to events manually
         let mut event = Event::new("store_code");
         event = event.add_attribute("code_id", code_id.to_string());
-        let resp = AppResponse {
+        let mut resp = AppResponse {
             events: vec![event],
             ..Default::default()
         };
         let code_id = IndexResponse::uploaded_code_id(&resp)?;
         self.state.borrow_mut().set_code_id(contract_id, code_id);
-        Ok(resp)
+        GasMeter::meter_upload(&self.gas_meter, code_id, 0, &mut resp.events);
+        Ok(self.record_tx(resp))
+    }
+
+    /// Enables gas metering on this environment.
+    /// Transaction responses will then report the gas they used (see [`IndexResponse::gas_used`])
+    /// and the gas used by each contract can be queried with [`MockBase::gas_used_by`].
+    /// The gas used is synthetic, see [`GasSchedule`].
+    pub fn enable_gas_meter(&self, schedule: GasSchedule) {
+        *self.gas_meter.borrow_mut() = Some(GasMeter::new(schedule));
+    }
+
+    /// Returns the total gas used by the uploads and calls of a contract.
+    /// Returns `None` if gas metering is not enabled.
+    pub fn gas_used_by(&self, contract_id: &str) -> Option<u64> {
+        self.gas_meter
+            .borrow()
+            .as_ref()
+            .map(|gas_meter| gas_meter.gas_used_by(&*self.state.borrow(), contract_id))
+    }
+
+    /// Returns the hash of the last transaction executed on this environment.
+    /// This hash can be used with [`NodeQuerier::find_tx`](cw_orch_core::environment::NodeQuerier::find_tx)
+    pub fn last_tx_hash(&self) -> Option<String> {
+        self.history.borrow().last_tx().map(|tx| tx.hash.clone())
+    }
+
+    /// Registers a code in the app and in the code registry of the environment
+    fn store_code(&self, wrapper: Box<dyn Contract<Empty, Empty>>) -> u64 {
+        let code = SharedCode::from(wrapper);
+        let code_id = self.app.borrow_mut().store_code(Box::new(code.clone()));
+        self.codes.borrow_mut().insert(code_id, code);
+        code_id
+    }
+
+    /// Records a transaction in the history of the environment, in the current block
+    fn record_tx(&self, response: AppResponse) -> AppResponse {
+        let block = self.app.borrow().block_info();
+        self.history.borrow_mut().record_tx(block, response.clone());
+        response
     }
 }
 impl<A: Api, S: StateInterface> ChainState for MockBase<A, S> {
@@ -127,6 +189,13 @@ impl<A: Api, S: StateInterface> ChainState for MockBase<A, S> {
     }
 }
 
+impl<A: Api, S: StateInterface> SimulatedEnv for MockBase<A, S> {
+    /// Dry runs are executed on a [`MockBase::fork`], leaving this environment unmodified
+    fn dry_run_fork(&self) -> Result<Self, CwEnvError> {
+        self.fork()
+    }
+}
+
 // Execute on the test chain, returns test response type
 impl<A: Api, S: StateInterface> TxHandler for MockBase<A, S> {
     type Response = AppResponse;
@@ -147,15 +216,16 @@ impl<A: Api, S: StateInterface> TxHandler for MockBase<A, S> {
     }
 
     fn upload<T: Uploadable>(&self, _contract: &T) -> Result<Self::Response, CwEnvError> {
-        let code_id = self.app.borrow_mut().store_code(T::wrapper());
+        let code_id = self.store_code(T::wrapper());
         // add contract code_id to events manually
         let mut event = Event::new("store_code");
         event = event.add_attribute("code_id", code_id.to_string());
-        let resp = AppResponse {
+        let mut resp = AppResponse {
             events: vec![event],
             ..Default::default()
         };
-        Ok(resp)
+        GasMeter::meter_upload(&self.gas_meter, code_id, 0, &mut resp.events);
+        Ok(self.record_tx(resp))
     }
 
     fn execute<E: Serialize + Debug>(
@@ -164,15 +234,22 @@ impl<A: Api, S: StateInterface> TxHandler for MockBase<A, S> {
         coins: &[cosmwasm_std::Coin],
         contract_address: &Addr,
     ) -> Result<Self::Response, CwEnvError> {
-        self.app
-            .borrow_mut()
-            .execute_contract(
-                self.sender.clone(),
-                contract_address.to_owned(),
-                exec_msg,
-                coins,
-            )
-            .map_err(From::from)
+        let mut resp = self.app.borrow_mut().execute_contract(
+            self.sender.clone(),
+            contract_address.to_owned(),
+            exec_msg,
+            coins,
+        )?;
+        GasMeter::meter_contract_call(
+            &self.gas_meter,
+            &*self.state.borrow(),
+            contract_address,
+            None,
+            ContractCall::Execute,
+            exec_msg,
+            &mut resp.events,
+        )?;
+        Ok(self.record_tx(resp))
     }
 
     fn instantiate<I: Serialize + Debug>(
@@ -195,11 +272,20 @@ impl<A: Api, S: StateInterface> TxHandler for MockBase<A, S> {
             .borrow_mut()
             .execute(self.sender.clone(), CosmosMsg::Wasm(msg))?;
 
-        let resp = AppResponse {
+        let mut resp = AppResponse {
             events: app.events,
             data: app.data,
         };
-        Ok(resp)
+        GasMeter::meter_contract_call(
+            &self.gas_meter,
+            &*self.state.borrow(),
+            &resp.instantiated_contract_address()?,
+            Some(code_id),
+            ContractCall::Instantiate,
+            init_msg,
+            &mut resp.events,
+        )?;
+        Ok(self.record_tx(resp))
     }
 
     fn instantiate2<I: Serialize + Debug>(
@@ -225,11 +311,20 @@ impl<A: Api, S: StateInterface> TxHandler for MockBase<A, S> {
             .borrow_mut()
             .execute(self.sender.clone(), CosmosMsg::Wasm(msg))?;
 
-        let resp = AppResponse {
+        let mut resp = AppResponse {
             events: app.events,
             data: app.data,
         };
-        Ok(resp)
+        GasMeter::meter_contract_call(
+            &self.gas_meter,
+            &*self.state.borrow(),
+            &resp.instantiated_contract_address()?,
+            Some(code_id),
+            ContractCall::Instantiate,
+            init_msg,
+            &mut resp.events,
+        )?;
+        Ok(self.record_tx(resp))
     }
 
     fn migrate<M: Serialize + Debug>(
@@ -238,15 +333,22 @@ impl<A: Api, S: StateInterface> TxHandler for MockBase<A, S> {
         new_code_id: u64,
         contract_address: &Addr,
     ) -> Result<Self::Response, CwEnvError> {
-        self.app
-            .borrow_mut()
-            .migrate_contract(
-                self.sender.clone(),
-                contract_address.clone(),
-                migrate_msg,
-                new_code_id,
-            )
-            .map_err(From::from)
+        let mut resp = self.app.borrow_mut().migrate_contract(
+            self.sender.clone(),
+            contract_address.clone(),
+            migrate_msg,
+            new_code_id,
+        )?;
+        GasMeter::meter_contract_call(
+            &self.gas_meter,
+            &*self.state.borrow(),
+            contract_address,
+            Some(new_code_id),
+            ContractCall::Migrate,
+            migrate_msg,
+            &mut resp.events,
+        )?;
+        Ok(self.record_tx(resp))
     }
 }
 
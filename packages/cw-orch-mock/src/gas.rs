@@ -0,0 +1,435 @@
+use cosmwasm_std::{to_json_vec, Addr, Event, StdResult};
+use cw_orch_core::environment::{StateInterface, GAS_USED_EVENT};
+use serde::Serialize;
+
+use std::{cell::RefCell, collections::HashMap};
+
+/// Gas costs used to estimate the gas consumption of transactions on simulated environments.
+/// The default values follow the Cosmos SDK and wasmd default gas parameters.
+///
+/// A contract call costs its message size, the instance cost, the events it emitted and its execution cost.
+/// When the environment runs the wasm of the contract (e.g. codes uploaded with `upload_wasm` on `CloneTesting`),
+/// the execution cost is the gas reported by the cosmwasm-vm, storage accesses included.
+/// Otherwise (e.g. `ContractWrapper` contracts), it is the synthetic cost set with [`GasSchedule::with_message_cost`].
+#[derive(Clone, Debug)]
+pub struct GasSchedule {
+    /// Flat cost charged for every transaction (signature verification, account and fee handling)
+    pub tx_base_cost: u64,
+    /// Cost per byte of transaction payload (Cosmos SDK `TxSizeCostPerByte`)
+    pub tx_byte_cost: u64,
+    /// Cost per byte of uploaded wasm code (wasmd `CompileCost`)
+    pub upload_byte_cost: u64,
+    /// Cost of loading a contract instance for an instantiate, execute or migrate call (wasmd `InstanceCost`)
+    pub instance_cost: u64,
+    /// Cost per custom event emitted during the transaction (wasmd `CustomEventCost`)
+    pub event_cost: u64,
+    /// Cost per byte of emitted event attributes (wasmd `EventAttributeDataCost`)
+    pub event_attribute_byte_cost: u64,
+    /// Flat cost of a storage read (Cosmos SDK `ReadCostFlat`)
+    pub read_cost_flat: u64,
+    /// Cost per byte of a storage read (Cosmos SDK `ReadCostPerByte`)
+    pub read_cost_per_byte: u64,
+    /// Flat cost of a storage write (Cosmos SDK `WriteCostFlat`)
+    pub write_cost_flat: u64,
+    /// Cost per byte of a storage write (Cosmos SDK `WriteCostPerByte`)
+    pub write_cost_per_byte: u64,
+    /// Cost of a storage deletion (Cosmos SDK `DeleteCost`)
+    pub delete_cost: u64,
+    /// Flat cost of a step of a storage iterator (Cosmos SDK `IterNextCostFlat`)
+    pub iter_next_cost_flat: u64,
+    /// Number of cosmwasm-vm gas points per gas point (wasmd `DefaultGasMultiplier`)
+    pub vm_gas_multiplier: u64,
+    /// Execution costs of contract messages, indexed by contract id and message name.
+    /// They replace the execution costs a wasm VM would compute for contracts that don't have a wasm (e.g. `ContractWrapper` contracts).
+    message_costs: HashMap<(String, String), u64>,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self {
+            tx_base_cost: 40_000,
+            tx_byte_cost: 10,
+            upload_byte_cost: 3,
+            instance_cost: 60_000,
+            event_cost: 20,
+            event_attribute_byte_cost: 1,
+            read_cost_flat: 1_000,
+            read_cost_per_byte: 3,
+            write_cost_flat: 2_000,
+            write_cost_per_byte: 30,
+            delete_cost: 1_000,
+            iter_next_cost_flat: 30,
+            vm_gas_multiplier: 140_000_000,
+            message_costs: HashMap::new(),
+        }
+    }
+}
+
+impl GasSchedule {
+    /// Sets the execution cost of a message for a contract.
+    /// `message` is the name of the message as serialized in JSON (e.g. `"increment"` for `ExecuteMsg::Increment {}`).
+    /// Use `"instantiate"` and `"migrate"` to set the cost of instantiating or migrating the contract.
+    pub fn with_message_cost(
+        mut self,
+        contract_id: impl Into<String>,
+        message: impl Into<String>,
+        gas: u64,
+    ) -> Self {
+        self.message_costs
+            .insert((contract_id.into(), message.into()), gas);
+        self
+    }
+
+    /// Returns the configured execution cost of a contract message
+    pub fn message_cost(&self, contract_id: &str, message: &str) -> u64 {
+        self.message_costs
+            .get(&(contract_id.to_string(), message.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Estimates the gas needed to process a transaction of `tx_size` bytes, without executing any contract.
+    /// Only covers the base and size costs of the transaction.
+    pub fn tx_cost(&self, tx_size: usize) -> u64 {
+        self.tx_base_cost + self.tx_byte_cost * tx_size as u64
+    }
+
+    /// Gas consumed by the events emitted during a transaction
+    pub fn events_cost(&self, events: &[Event]) -> u64 {
+        events
+            .iter()
+            .map(|event| {
+                let attribute_bytes: usize = event
+                    .attributes
+                    .iter()
+                    .map(|attr| attr.key.len() + attr.value.len())
+                    .sum();
+                self.event_cost + self.event_attribute_byte_cost * attribute_bytes as u64
+            })
+            .sum()
+    }
+}
+
+/// Kind of contract call that is metered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContractCall {
+    /// Contract instantiation
+    Instantiate,
+    /// Contract execution
+    Execute,
+    /// Contract migration
+    Migrate,
+}
+
+/// Gas meter for simulated environments.
+/// Estimates the gas consumed by each transaction from a [`GasSchedule`] and keeps track of the gas used per contract.
+#[derive(Clone, Debug, Default)]
+pub struct GasMeter {
+    schedule: GasSchedule,
+    /// Gas used by code uploads, indexed by code id
+    code_gas: HashMap<u64, u64>,
+    /// Gas used by contract calls, indexed by contract address
+    contract_gas: HashMap<Addr, u64>,
+}
+
+impl GasMeter {
+    /// Creates a new gas meter with the provided schedule
+    pub fn new(schedule: GasSchedule) -> Self {
+        Self {
+            schedule,
+            ..Default::default()
+        }
+    }
+
+    /// Gas schedule used by this meter
+    pub fn schedule(&self) -> &GasSchedule {
+        &self.schedule
+    }
+
+    /// Charges the upload of a code and returns the gas used.
+    /// `code_size` is the size of the wasm code, if available.
+    pub fn charge_upload(&mut self, code_id: u64, code_size: usize) -> u64 {
+        let gas_used =
+            self.schedule.tx_cost(code_size) + self.schedule.upload_byte_cost * code_size as u64;
+        *self.code_gas.entry(code_id).or_default() += gas_used;
+        gas_used
+    }
+
+    /// Charges a contract call and returns the gas used.
+    /// `contract_id` is used to find the execution cost of the message in the schedule.
+    pub fn charge_contract_call(
+        &mut self,
+        contract: &Addr,
+        contract_id: Option<&str>,
+        call: ContractCall,
+        msg: &[u8],
+        events: &[Event],
+    ) -> u64 {
+        let execution_cost = contract_id
+            .map(|id| self.schedule.message_cost(id, &message_name(call, msg)))
+            .unwrap_or_default();
+        self.charge_call(contract, msg, execution_cost, events)
+    }
+
+    /// Charges a call to a contract run by the cosmwasm-vm and returns the gas used.
+    /// `vm_gas` is the gas used by the contract in the VM, storage accesses included.
+    pub fn charge_wasm_call(
+        &mut self,
+        contract: &Addr,
+        msg: &[u8],
+        vm_gas: u64,
+        events: &[Event],
+    ) -> u64 {
+        self.charge_call(contract, msg, vm_gas, events)
+    }
+
+    fn charge_call(
+        &mut self,
+        contract: &Addr,
+        msg: &[u8],
+        execution_cost: u64,
+        events: &[Event],
+    ) -> u64 {
+        let gas_used = self.schedule.tx_cost(msg.len())
+            + self.schedule.instance_cost
+            + execution_cost
+            + self.schedule.events_cost(events);
+        *self.contract_gas.entry(contract.clone()).or_default() += gas_used;
+        gas_used
+    }
+
+    /// Total gas used by the uploads of a code and the calls to a contract
+    pub fn gas_used(&self, contract: Option<&Addr>, code_id: Option<u64>) -> u64 {
+        contract
+            .and_then(|addr| self.contract_gas.get(addr))
+            .copied()
+            .unwrap_or_default()
+            + code_id
+                .and_then(|code_id| self.code_gas.get(&code_id))
+                .copied()
+                .unwrap_or_default()
+    }
+
+    /// Total gas used by the uploads and calls of a contract registered in the `state` of an environment
+    pub fn gas_used_by(&self, state: &impl StateInterface, contract_id: &str) -> u64 {
+        self.gas_used(
+            state.get_address(contract_id).ok().as_ref(),
+            state.get_code_id(contract_id).ok(),
+        )
+    }
+
+    /// Event used to report the gas used in a transaction response
+    pub fn gas_used_event(gas_used: u64) -> Event {
+        Event::new(GAS_USED_EVENT.0).add_attribute(GAS_USED_EVENT.1, gas_used.to_string())
+    }
+
+    /// Charges the upload of a code if metering is enabled on the environment,
+    /// reporting the gas used in the `events` of the transaction
+    pub fn meter_upload(
+        gas_meter: &RefCell<Option<GasMeter>>,
+        code_id: u64,
+        code_size: usize,
+        events: &mut Vec<Event>,
+    ) {
+        if let Some(gas_meter) = gas_meter.borrow_mut().as_mut() {
+            let gas_used = gas_meter.charge_upload(code_id, code_size);
+            events.push(Self::gas_used_event(gas_used));
+        }
+    }
+
+    /// Charges a contract call if metering is enabled on the environment,
+    /// reporting the gas used in the `events` of the transaction.
+    /// The contract id is looked up in the `state` of the environment.
+    pub fn meter_contract_call<M: Serialize>(
+        gas_meter: &RefCell<Option<GasMeter>>,
+        state: &impl StateInterface,
+        contract_address: &Addr,
+        code_id: Option<u64>,
+        call: ContractCall,
+        msg: &M,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        if let Some(gas_meter) = gas_meter.borrow_mut().as_mut() {
+            let contract_id = contract_id(state, contract_address, code_id);
+            let gas_used = gas_meter.charge_contract_call(
+                contract_address,
+                contract_id.as_deref(),
+                call,
+                &to_json_vec(msg)?,
+                events,
+            );
+            events.push(Self::gas_used_event(gas_used));
+        }
+        Ok(())
+    }
+
+    /// Charges a call to a contract run by the cosmwasm-vm if metering is enabled on the environment,
+    /// reporting the gas used in the `events` of the transaction
+    pub fn meter_wasm_call<M: Serialize>(
+        gas_meter: &RefCell<Option<GasMeter>>,
+        contract_address: &Addr,
+        vm_gas: u64,
+        msg: &M,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        if let Some(gas_meter) = gas_meter.borrow_mut().as_mut() {
+            let gas_used =
+                gas_meter.charge_wasm_call(contract_address, &to_json_vec(msg)?, vm_gas, events);
+            events.push(Self::gas_used_event(gas_used));
+        }
+        Ok(())
+    }
+}
+
+/// Finds the id of a contract in the state, from its address or code id
+fn contract_id(
+    state: &impl StateInterface,
+    address: &Addr,
+    code_id: Option<u64>,
+) -> Option<String> {
+    let by_address = state
+        .get_all_addresses()
+        .ok()?
+        .into_iter()
+        .find(|(_, contract_addr)| contract_addr == address)
+        .map(|(contract_id, _)| contract_id);
+    by_address.or_else(|| {
+        state
+            .get_all_code_ids()
+            .ok()?
+            .into_iter()
+            .find(|(_, id)| Some(*id) == code_id)
+            .map(|(contract_id, _)| contract_id)
+    })
+}
+
+/// Name of the message sent to the contract.
+/// Executions are named after their JSON variant name, instantiations and migrations after the call kind.
+fn message_name(call: ContractCall, msg: &[u8]) -> String {
+    match call {
+        ContractCall::Instantiate => "instantiate".to_string(),
+        ContractCall::Migrate => "migrate".to_string(),
+        ContractCall::Execute => match serde_json::from_slice(msg) {
+            Ok(serde_json::Value::Object(variant)) if variant.len() == 1 => {
+                variant.keys().next().cloned().unwrap_or_default()
+            }
+            Ok(serde_json::Value::String(variant)) => variant,
+            _ => String::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use cosmwasm_std::{to_json_vec, Addr, Event};
+    use cw_multi_test::AppResponse;
+    use cw_orch_core::environment::{IndexResponse, StateInterface};
+
+    use super::{message_name, ContractCall, GasMeter, GasSchedule};
+    use crate::MockState;
+
+    #[test]
+    fn message_costs_are_charged_per_contract() -> anyhow::Result<()> {
+        let schedule = GasSchedule::default().with_message_cost("counter", "increment", 100_000);
+        let mut meter = GasMeter::new(schedule.clone());
+        let counter = Addr::unchecked("counter");
+        let msg = to_json_vec(&serde_json::json!({ "increment": {} }))?;
+        let events = vec![Event::new("wasm").add_attribute("action", "increment")];
+
+        let gas_used = meter.charge_contract_call(
+            &counter,
+            Some("counter"),
+            ContractCall::Execute,
+            &msg,
+            &events,
+        );
+        assert_eq!(
+            gas_used,
+            schedule.tx_cost(msg.len()) + schedule.instance_cost + 100_000 + 20 + 15
+        );
+
+        let upload_gas = meter.charge_upload(1, 1000);
+        assert_eq!(
+            meter.gas_used(Some(&counter), Some(1)),
+            gas_used + upload_gas
+        );
+        assert_eq!(meter.gas_used(None, Some(2)), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn wasm_calls_are_charged_from_the_vm_gas() -> anyhow::Result<()> {
+        let schedule = GasSchedule::default().with_message_cost("counter", "increment", 100_000);
+        let mut meter = GasMeter::new(schedule.clone());
+        let counter = Addr::unchecked("counter");
+        let msg = to_json_vec(&serde_json::json!({ "increment": {} }))?;
+
+        let gas_used = meter.charge_wasm_call(&counter, &msg, 42_000, &[]);
+        assert_eq!(
+            gas_used,
+            schedule.tx_cost(msg.len()) + schedule.instance_cost + 42_000
+        );
+        assert_eq!(meter.gas_used(Some(&counter), None), gas_used);
+        Ok(())
+    }
+
+    #[test]
+    fn message_names() -> anyhow::Result<()> {
+        let msg = to_json_vec(&serde_json::json!({ "reset": { "count": 0 } }))?;
+        assert_eq!(message_name(ContractCall::Execute, &msg), "reset");
+        let msg = to_json_vec(&serde_json::json!("increment"))?;
+        assert_eq!(message_name(ContractCall::Execute, &msg), "increment");
+        assert_eq!(message_name(ContractCall::Migrate, &msg), "migrate");
+        Ok(())
+    }
+
+    #[test]
+    fn metering_reports_gas_of_registered_contracts() -> anyhow::Result<()> {
+        let counter = Addr::unchecked("counter");
+        let mut state = MockState::new();
+        state.set_address("counter", &counter);
+        let msg = serde_json::json!({ "increment": {} });
+
+        let gas_meter = RefCell::new(None);
+        let mut events = vec![];
+        GasMeter::meter_contract_call(
+            &gas_meter,
+            &state,
+            &counter,
+            None,
+            ContractCall::Execute,
+            &msg,
+            &mut events,
+        )?;
+        assert!(events.is_empty());
+
+        let schedule = GasSchedule::default().with_message_cost("counter", "increment", 100_000);
+        *gas_meter.borrow_mut() = Some(GasMeter::new(schedule));
+        GasMeter::meter_contract_call(
+            &gas_meter,
+            &state,
+            &counter,
+            None,
+            ContractCall::Execute,
+            &msg,
+            &mut events,
+        )?;
+        let resp = AppResponse {
+            events,
+            ..Default::default()
+        };
+        let gas_used = resp.gas_used().unwrap();
+        assert!(gas_used > 100_000);
+        assert_eq!(
+            gas_meter
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .gas_used_by(&state, "counter"),
+            gas_used
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use cosmwasm_std::{BlockInfo, HexBinary};
+use cw_multi_test::AppResponse;
+use cw_orch_core::CwEnvError;
+use sha2::{Digest, Sha256};
+
+use std::collections::BTreeMap;
+
+/// Synthetic transaction recorded by the mock environment.
+#[derive(Clone, Debug)]
+pub struct MockTx {
+    /// Deterministic hash of the transaction (uppercase hex, like on-chain tx hashes)
+    pub hash: String,
+    /// Block the transaction was executed in
+    pub block: BlockInfo,
+    /// Response of the transaction
+    pub response: AppResponse,
+}
+
+#[derive(Clone, Debug, Default)]
+/// Transaction and block history of a mock chain.
+/// Every [`TxHandler`](cw_orch_core::environment::TxHandler) call on a mock environment is recorded here.
+pub struct MockTxHistory {
+    /// Executed transactions, in execution order
+    txs: Vec<MockTx>,
+    /// Blocks that have been observed by the environment, indexed by height
+    blocks: BTreeMap<u64, BlockInfo>,
+}
+
+impl MockTxHistory {
+    /// Creates a new empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a transaction executed in `block` and returns its hash.
+    /// The hash only depends on the chain id, block height and the position of the tx in the history.
+    pub fn record_tx(&mut self, block: BlockInfo, response: AppResponse) -> String {
+        let preimage = format!("{}/{}/{}", block.chain_id, block.height, self.txs.len());
+        let hash: [u8; 32] = Sha256::digest(preimage).into();
+        let hash = HexBinary::from(hash).to_hex().to_uppercase();
+
+        self.record_block(block.clone());
+        self.txs.push(MockTx {
+            hash: hash.clone(),
+            block,
+            response,
+        });
+        hash
+    }
+
+    /// Records a block so it can later be retrieved by height.
+    pub fn record_block(&mut self, block: BlockInfo) {
+        self.blocks.insert(block.height, block);
+    }
+
+    /// All recorded transactions, in execution order
+    pub fn txs(&self) -> &[MockTx] {
+        &self.txs
+    }
+
+    /// Last recorded transaction
+    pub fn last_tx(&self) -> Option<&MockTx> {
+        self.txs.last()
+    }
+
+    /// Finds a transaction by hash (case-insensitive)
+    pub fn find_tx(&self, hash: &str) -> Result<&MockTx, CwEnvError> {
+        self.txs
+            .iter()
+            .find(|tx| tx.hash.eq_ignore_ascii_case(hash))
+            .ok_or_else(|| {
+                CwEnvError::StdErr(format!("Transaction {hash} not found in mock history"))
+            })
+    }
+
+    /// Finds a recorded block by height
+    pub fn block_by_height(&self, height: u64) -> Result<&BlockInfo, CwEnvError> {
+        self.blocks
+            .get(&height)
+            .ok_or_else(|| CwEnvError::StdErr(format!("Block {height} not found in mock history")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cosmwasm_std::testing::mock_env;
+    use cw_multi_test::AppResponse;
+
+    use super::MockTxHistory;
+
+    #[test]
+    fn tx_hashes_are_deterministic() {
+        let block = mock_env().block;
+
+        let mut first = MockTxHistory::new();
+        let mut second = MockTxHistory::new();
+        let hash_1 = first.record_tx(block.clone(), AppResponse::default());
+        let hash_2 = first.record_tx(block.clone(), AppResponse::default());
+
+        assert_ne!(hash_1, hash_2);
+        assert_eq!(
+            hash_1,
+            second.record_tx(block.clone(), AppResponse::default())
+        );
+        assert_eq!(first.find_tx(&hash_2.to_lowercase()).unwrap().hash, hash_2);
+        assert_eq!(first.block_by_height(block.height).unwrap(), &block);
+        assert!(first.find_tx("unknown").is_err());
+        assert!(first.block_by_height(block.height + 1).is_err());
+    }
+}
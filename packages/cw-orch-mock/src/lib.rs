@@ -5,12 +5,22 @@
 pub extern crate cw_multi_test;
 
 mod bech32;
+mod checkpoint;
 mod core;
+mod gas;
+mod history;
 pub mod queriers;
 mod simple;
+mod simulate;
+mod snapshot;
 mod state;
 
 pub use self::core::{Mock, MockBase, MockBech32};
+pub use checkpoint::CheckpointId;
+pub use gas::{ContractCall, GasMeter, GasSchedule};
+pub use history::{MockTx, MockTxHistory};
+pub use simulate::SimulatedMsg;
+pub use snapshot::SNAPSHOT_VERSION;
 
 pub type MockApp = self::core::MockApp<MockApi>;
 pub type MockAppBech32 = self::core::MockApp<MockApiBech32>;
@@ -12,10 +12,19 @@ mod env;
 pub mod node;
 pub mod wasm;
 
+impl<A: Api, S: StateInterface> MockBase<A, S> {
+    /// Saves the current block in the history before the chain moves to a new block
+    fn record_current_block(&self) {
+        let block = self.app.borrow().block_info();
+        self.history.borrow_mut().record_block(block);
+    }
+}
+
 impl<A: Api, S: StateInterface> QueryHandler for MockBase<A, S> {
     type Error = CwEnvError;
 
     fn wait_blocks(&self, amount: u64) -> Result<(), CwEnvError> {
+        self.record_current_block();
         self.app.borrow_mut().update_block(|b| {
             b.height += amount;
             b.time = b.time.plus_seconds(5 * amount);
@@ -24,6 +33,7 @@ impl<A: Api, S: StateInterface> QueryHandler for MockBase<A, S> {
     }
 
     fn wait_seconds(&self, secs: u64) -> Result<(), CwEnvError> {
+        self.record_current_block();
         self.app.borrow_mut().update_block(|b| {
             b.time = b.time.plus_seconds(secs);
             b.height += secs / 5;
@@ -32,6 +42,7 @@ impl<A: Api, S: StateInterface> QueryHandler for MockBase<A, S> {
     }
 
     fn next_block(&self) -> Result<(), CwEnvError> {
+        self.record_current_block();
         self.app.borrow_mut().update_block(next_block);
         Ok(())
     }
@@ -40,5 +51,5 @@ impl<A: Api, S: StateInterface> QueryHandler for MockBase<A, S> {
 impl<A: Api, S: StateInterface> DefaultQueriers for MockBase<A, S> {
     type Bank = bank::MockBankQuerier<A>;
     type Wasm = wasm::MockWasmQuerier<A, S>;
-    type Node = node::MockNodeQuerier<A>;
+    type Node = node::MockNodeQuerier<A, S>;
 }
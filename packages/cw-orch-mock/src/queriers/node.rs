@@ -1,45 +1,46 @@
-use std::{cell::RefCell, rc::Rc};
-
-use cosmwasm_std::Api;
-use cw_multi_test::AppResponse;
+use cosmwasm_std::{Api, BankMsg};
+use cw_multi_test::{AppResponse, Executor};
 use cw_orch_core::{
-    environment::{NodeQuerier, Querier, QuerierGetter, StateInterface},
+    environment::{IndexResponse, NodeQuerier, Querier, QuerierGetter, StateInterface, TxHandler},
     CwEnvError,
 };
 
-use crate::{core::MockApp, MockBase};
+use crate::{GasMeter, MockBase, SimulatedMsg};
 
-pub struct MockNodeQuerier<A: Api> {
-    app: Rc<RefCell<MockApp<A>>>,
+pub struct MockNodeQuerier<A: Api, S: StateInterface> {
+    mock: MockBase<A, S>,
 }
 
-impl<A: Api> MockNodeQuerier<A> {
-    fn new<S: StateInterface>(mock: &MockBase<A, S>) -> Self {
-        Self {
-            app: mock.app.clone(),
-        }
+impl<A: Api, S: StateInterface> MockNodeQuerier<A, S> {
+    fn new(mock: &MockBase<A, S>) -> Self {
+        Self { mock: mock.clone() }
     }
 }
 
-impl<A: Api> Querier for MockNodeQuerier<A> {
+impl<A: Api, S: StateInterface> Querier for MockNodeQuerier<A, S> {
     type Error = CwEnvError;
 }
 
-impl<A: Api, S: StateInterface> QuerierGetter<MockNodeQuerier<A>> for MockBase<A, S> {
-    fn querier(&self) -> MockNodeQuerier<A> {
+impl<A: Api, S: StateInterface> QuerierGetter<MockNodeQuerier<A, S>> for MockBase<A, S> {
+    fn querier(&self) -> MockNodeQuerier<A, S> {
         MockNodeQuerier::new(self)
     }
 }
 
-impl<A: Api> NodeQuerier for MockNodeQuerier<A> {
+impl<A: Api, S: StateInterface> NodeQuerier for MockNodeQuerier<A, S> {
     type Response = AppResponse;
 
     fn latest_block(&self) -> Result<cosmwasm_std::BlockInfo, Self::Error> {
-        Ok(self.app.borrow().block_info())
+        Ok(self.mock.app.borrow().block_info())
     }
 
-    fn block_by_height(&self, _height: u64) -> Result<cosmwasm_std::BlockInfo, Self::Error> {
-        unimplemented!()
+    /// Only the current block and blocks that were observed by the environment (during a transaction or before a block change) can be retrieved
+    fn block_by_height(&self, height: u64) -> Result<cosmwasm_std::BlockInfo, Self::Error> {
+        let latest_block = self.latest_block()?;
+        if latest_block.height == height {
+            return Ok(latest_block);
+        }
+        self.mock.history.borrow().block_by_height(height).cloned()
     }
 
     fn block_height(&self) -> Result<u64, Self::Error> {
@@ -54,11 +55,147 @@ impl<A: Api> NodeQuerier for MockNodeQuerier<A> {
         Ok(block_info.time.nanos() as u128)
     }
 
-    fn simulate_tx(&self, _tx_bytes: Vec<u8>) -> Result<u64, Self::Error> {
-        unimplemented!()
+    /// Runs the messages of the transaction on a fork of the environment and returns the gas they used,
+    /// metered with the gas schedule of the environment (or the default one if gas metering is not enabled).
+    /// Uploaded wasm codes are only charged: the mock environment can't run them.
+    fn simulate_tx(&self, tx_bytes: Vec<u8>) -> Result<u64, Self::Error> {
+        let msgs = SimulatedMsg::decode_tx(&tx_bytes)?;
+        let schedule = self
+            .mock
+            .gas_meter
+            .borrow()
+            .as_ref()
+            .map(GasMeter::schedule)
+            .cloned()
+            .unwrap_or_default();
+
+        // The costs of the transaction are charged once, not for every message
+        let mut msg_schedule = schedule.clone();
+        msg_schedule.tx_base_cost = 0;
+        msg_schedule.tx_byte_cost = 0;
+        let mut fork = self.mock.fork()?;
+        fork.enable_gas_meter(msg_schedule);
+
+        let mut gas_used = schedule.tx_cost(tx_bytes.len());
+        for msg in msgs {
+            gas_used += simulate_msg(&mut fork, msg)?;
+        }
+        Ok(gas_used)
     }
 
-    fn find_tx(&self, _hash: String) -> Result<Self::Response, Self::Error> {
-        unimplemented!()
+    fn find_tx(&self, hash: String) -> Result<Self::Response, Self::Error> {
+        self.mock
+            .history
+            .borrow()
+            .find_tx(&hash)
+            .map(|tx| tx.response.clone())
+    }
+}
+
+/// Runs a message on a metered fork and returns the gas it used
+fn simulate_msg<A: Api, S: StateInterface>(
+    fork: &mut MockBase<A, S>,
+    msg: SimulatedMsg,
+) -> Result<u64, CwEnvError> {
+    let response = match msg {
+        SimulatedMsg::StoreCode { wasm_byte_code, .. } => {
+            let mut events = vec![];
+            GasMeter::meter_upload(&fork.gas_meter, 0, wasm_byte_code.len(), &mut events);
+            AppResponse {
+                events,
+                ..Default::default()
+            }
+        }
+        SimulatedMsg::Instantiate {
+            sender,
+            admin,
+            code_id,
+            label,
+            msg,
+            funds,
+            salt,
+        } => {
+            fork.set_sender(sender);
+            let msg: serde_json::Value = serde_json::from_slice(&msg)?;
+            match salt {
+                Some(salt) => {
+                    fork.instantiate2(code_id, &msg, Some(&label), admin.as_ref(), &funds, salt)?
+                }
+                None => fork.instantiate(code_id, &msg, Some(&label), admin.as_ref(), &funds)?,
+            }
+        }
+        SimulatedMsg::Execute {
+            sender,
+            contract,
+            msg,
+            funds,
+        } => {
+            fork.set_sender(sender);
+            let msg: serde_json::Value = serde_json::from_slice(&msg)?;
+            fork.execute(&msg, &funds, &contract)?
+        }
+        SimulatedMsg::Migrate {
+            sender,
+            contract,
+            code_id,
+            msg,
+        } => {
+            fork.set_sender(sender);
+            let msg: serde_json::Value = serde_json::from_slice(&msg)?;
+            fork.migrate(&msg, code_id, &contract)?
+        }
+        SimulatedMsg::Send {
+            from_address,
+            to_address,
+            amount,
+        } => fork.app.borrow_mut().execute(
+            from_address,
+            BankMsg::Send {
+                to_address: to_address.to_string(),
+                amount,
+            }
+            .into(),
+        )?,
+    };
+    Ok(response.gas_used().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Binary, Empty, Response, StdError};
+    use cw_multi_test::ContractWrapper;
+    use cw_orch_core::environment::{DefaultQueriers, NodeQuerier, QueryHandler, TxHandler};
+
+    use crate::MockBech32;
+
+    #[test]
+    fn find_tx_and_block_by_height() -> anyhow::Result<()> {
+        let mock = MockBech32::new("mock");
+
+        let upload_height = mock.block_info()?.height;
+        let upload = mock.upload_custom(
+            "test-contract",
+            Box::new(ContractWrapper::new_with_empty(
+                |_, _, _, _: Empty| Ok::<_, StdError>(Response::new()),
+                |_, _, _, _: Empty| Ok::<_, StdError>(Response::new()),
+                |_, _, _: Empty| Ok::<_, StdError>(Binary(b"dummy-response".to_vec())),
+            )),
+        )?;
+        let upload_hash = mock.last_tx_hash().unwrap();
+        mock.wait_blocks(3)?;
+
+        let instantiate = mock.instantiate(1, &Empty {}, None, None, &[])?;
+        let instantiate_hash = mock.last_tx_hash().unwrap();
+
+        let node = mock.node_querier();
+        assert_eq!(node.find_tx(upload_hash)?.events, upload.events);
+        assert_eq!(node.find_tx(instantiate_hash)?.events, instantiate.events);
+        assert!(node.find_tx("not-a-hash".to_string()).is_err());
+
+        assert_eq!(node.block_by_height(upload_height)?.height, upload_height);
+        assert_eq!(node.block_by_height(upload_height + 3)?, mock.block_info()?);
+        assert!(node.block_by_height(upload_height + 1).is_err());
+
+        Ok(())
     }
 }
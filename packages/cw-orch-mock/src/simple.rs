@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
 use cosmwasm_std::testing::MockApi;
@@ -12,7 +13,7 @@ use cw_orch_core::{
 use cw_utils::NativeBalance;
 
 use crate::queriers::bank::MockBankQuerier;
-use crate::{Mock, MockState};
+use crate::{Mock, MockApp, MockState, MockTxHistory};
 
 impl<S: StateInterface> Mock<S> {
     /// Set the bank balance of an address.
@@ -114,12 +115,20 @@ impl<S: StateInterface> Mock<S> {
     /// The state is customizable by implementing the `StateInterface` trait on a custom struct and providing it on the custom constructor.
     pub fn new_custom(sender: impl Into<String>, custom_state: S) -> Self {
         let state = Rc::new(RefCell::new(custom_state));
-        let app = Rc::new(RefCell::new(AppBuilder::new_custom().build(|_, _, _| {})));
+        let app_factory: Rc<dyn Fn() -> MockApp> =
+            Rc::new(|| AppBuilder::new_custom().build(|_, _, _| {}));
+        let app = Rc::new(RefCell::new(app_factory()));
 
         Self {
             sender: Addr::unchecked(sender),
             state,
             app,
+            history: Rc::new(RefCell::new(MockTxHistory::new())),
+            gas_meter: Rc::new(RefCell::new(None)),
+            snapshot_codes: Rc::new(RefCell::new(BTreeMap::new())),
+            codes: Rc::new(RefCell::new(BTreeMap::new())),
+            checkpoints: Rc::new(RefCell::new(vec![])),
+            app_factory,
         }
     }
 }
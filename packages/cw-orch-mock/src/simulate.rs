@@ -0,0 +1,216 @@
+use cosmos_sdk_proto::{
+    cosmos::{
+        bank::v1beta1::MsgSend,
+        base::v1beta1::Coin as ProtoCoin,
+        tx::v1beta1::{TxBody, TxRaw},
+    },
+    cosmwasm::wasm::v1::{
+        MsgExecuteContract, MsgInstantiateContract, MsgInstantiateContract2, MsgMigrateContract,
+        MsgStoreCode,
+    },
+    Any,
+};
+use cosmwasm_std::{Addr, Binary, Coin};
+use cw_orch_core::CwEnvError;
+use prost::Message;
+
+/// Message of a transaction simulated with [`NodeQuerier::simulate_tx`](cw_orch_core::environment::NodeQuerier::simulate_tx) on a simulated environment
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulatedMsg {
+    /// Upload of a wasm code
+    StoreCode {
+        sender: Addr,
+        wasm_byte_code: Vec<u8>,
+    },
+    /// Instantiation of a contract, with a salt for `instantiate2`
+    Instantiate {
+        sender: Addr,
+        admin: Option<Addr>,
+        code_id: u64,
+        label: String,
+        msg: Vec<u8>,
+        funds: Vec<Coin>,
+        salt: Option<Binary>,
+    },
+    /// Execution of a contract
+    Execute {
+        sender: Addr,
+        contract: Addr,
+        msg: Vec<u8>,
+        funds: Vec<Coin>,
+    },
+    /// Migration of a contract
+    Migrate {
+        sender: Addr,
+        contract: Addr,
+        code_id: u64,
+        msg: Vec<u8>,
+    },
+    /// Bank transfer
+    Send {
+        from_address: Addr,
+        to_address: Addr,
+        amount: Vec<Coin>,
+    },
+}
+
+impl SimulatedMsg {
+    /// Decodes the messages of an encoded transaction (`TxRaw` or `Tx`).
+    /// Errors on the messages that can't be run in a simulated environment.
+    pub fn decode_tx(tx_bytes: &[u8]) -> Result<Vec<Self>, CwEnvError> {
+        let tx = TxRaw::decode(tx_bytes).map_err(decode_error)?;
+        let body = TxBody::decode(tx.body_bytes.as_slice()).map_err(decode_error)?;
+        body.messages.iter().map(Self::decode).collect()
+    }
+
+    fn decode(msg: &Any) -> Result<Self, CwEnvError> {
+        let value = msg.value.as_slice();
+        let msg = match msg.type_url.as_str() {
+            "/cosmwasm.wasm.v1.MsgStoreCode" => {
+                let msg = MsgStoreCode::decode(value).map_err(decode_error)?;
+                Self::StoreCode {
+                    sender: Addr::unchecked(msg.sender),
+                    wasm_byte_code: msg.wasm_byte_code,
+                }
+            }
+            "/cosmwasm.wasm.v1.MsgInstantiateContract" => {
+                let msg = MsgInstantiateContract::decode(value).map_err(decode_error)?;
+                Self::Instantiate {
+                    sender: Addr::unchecked(msg.sender),
+                    admin: admin(msg.admin),
+                    code_id: msg.code_id,
+                    label: msg.label,
+                    msg: msg.msg,
+                    funds: coins(msg.funds)?,
+                    salt: None,
+                }
+            }
+            "/cosmwasm.wasm.v1.MsgInstantiateContract2" => {
+                let msg = MsgInstantiateContract2::decode(value).map_err(decode_error)?;
+                Self::Instantiate {
+                    sender: Addr::unchecked(msg.sender),
+                    admin: admin(msg.admin),
+                    code_id: msg.code_id,
+                    label: msg.label,
+                    msg: msg.msg,
+                    funds: coins(msg.funds)?,
+                    salt: Some(msg.salt.into()),
+                }
+            }
+            "/cosmwasm.wasm.v1.MsgExecuteContract" => {
+                let msg = MsgExecuteContract::decode(value).map_err(decode_error)?;
+                Self::Execute {
+                    sender: Addr::unchecked(msg.sender),
+                    contract: Addr::unchecked(msg.contract),
+                    msg: msg.msg,
+                    funds: coins(msg.funds)?,
+                }
+            }
+            "/cosmwasm.wasm.v1.MsgMigrateContract" => {
+                let msg = MsgMigrateContract::decode(value).map_err(decode_error)?;
+                Self::Migrate {
+                    sender: Addr::unchecked(msg.sender),
+                    contract: Addr::unchecked(msg.contract),
+                    code_id: msg.code_id,
+                    msg: msg.msg,
+                }
+            }
+            "/cosmos.bank.v1beta1.MsgSend" => {
+                let msg = MsgSend::decode(value).map_err(decode_error)?;
+                Self::Send {
+                    from_address: Addr::unchecked(msg.from_address),
+                    to_address: Addr::unchecked(msg.to_address),
+                    amount: coins(msg.amount)?,
+                }
+            }
+            type_url => {
+                return Err(CwEnvError::StdErr(format!(
+                    "Message {type_url} can't be simulated on a simulated environment"
+                )))
+            }
+        };
+        Ok(msg)
+    }
+}
+
+fn admin(admin: String) -> Option<Addr> {
+    (!admin.is_empty()).then(|| Addr::unchecked(admin))
+}
+
+fn coins(coins: Vec<ProtoCoin>) -> Result<Vec<Coin>, CwEnvError> {
+    coins
+        .into_iter()
+        .map(|coin| -> Result<Coin, CwEnvError> {
+            Ok(Coin::new(coin.amount.parse::<u128>()?, coin.denom))
+        })
+        .collect()
+}
+
+fn decode_error(err: prost::DecodeError) -> CwEnvError {
+    CwEnvError::StdErr(format!("Transaction can't be decoded: {err}"))
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_sdk_proto::{
+        cosmos::{
+            base::v1beta1::Coin as ProtoCoin,
+            tx::v1beta1::{TxBody, TxRaw},
+        },
+        cosmwasm::wasm::v1::MsgExecuteContract,
+        Any,
+    };
+    use cosmwasm_std::{coins, Addr};
+    use prost::Message;
+
+    use super::SimulatedMsg;
+
+    fn tx_bytes(messages: Vec<Any>) -> Vec<u8> {
+        TxRaw {
+            body_bytes: TxBody {
+                messages,
+                ..Default::default()
+            }
+            .encode_to_vec(),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn decodes_contract_messages() -> anyhow::Result<()> {
+        let execute = MsgExecuteContract {
+            sender: "sender".to_string(),
+            contract: "contract".to_string(),
+            msg: br#"{"increment":{}}"#.to_vec(),
+            funds: vec![ProtoCoin {
+                denom: "ujuno".to_string(),
+                amount: "100".to_string(),
+            }],
+        };
+        let msgs = SimulatedMsg::decode_tx(&tx_bytes(vec![Any {
+            type_url: "/cosmwasm.wasm.v1.MsgExecuteContract".to_string(),
+            value: execute.encode_to_vec(),
+        }]))?;
+
+        assert_eq!(
+            msgs,
+            vec![SimulatedMsg::Execute {
+                sender: Addr::unchecked("sender"),
+                contract: Addr::unchecked("contract"),
+                msg: br#"{"increment":{}}"#.to_vec(),
+                funds: coins(100, "ujuno"),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_messages_are_rejected() {
+        let tx = tx_bytes(vec![Any {
+            type_url: "/cosmos.staking.v1beta1.MsgDelegate".to_string(),
+            value: vec![],
+        }]);
+        assert!(SimulatedMsg::decode_tx(&tx).is_err());
+    }
+}
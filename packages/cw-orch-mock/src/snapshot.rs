@@ -0,0 +1,156 @@
+use cosmwasm_std::{Addr, Api, Binary, BlockInfo, Empty, Order, Storage};
+use cw_multi_test::Contract;
+use cw_orch_core::{
+    contract::interface_traits::{ContractInstance, Uploadable},
+    environment::StateInterface,
+    CwEnvError,
+};
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    path::Path,
+};
+
+use crate::{
+    checkpoint::{code_creators, SharedCode},
+    GasMeter, MockBase, MockTxHistory,
+};
+
+/// Version of the snapshot file format.
+/// Bumped every time the format changes, snapshots with a different version can't be loaded.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Serialized state of a mock environment
+#[derive(Serialize, Deserialize, Debug)]
+struct MockSnapshot {
+    version: u32,
+    block: BlockInfo,
+    /// Raw app storage (bank balances, contract info and storage, ibc state...)
+    storage: Vec<(Binary, Binary)>,
+    /// Creators of the registered codes, indexed by code id
+    codes: BTreeMap<u64, Addr>,
+    /// cw-orch addresses state
+    addresses: HashMap<String, Addr>,
+    /// cw-orch code ids state
+    code_ids: HashMap<String, u64>,
+}
+
+impl<A: Api, S: StateInterface> MockBase<A, S> {
+    /// Saves the state of the environment to a file.
+    /// This includes the app storage (bank balances, contracts and their storage), the block info, the code registry and the cw-orch state.
+    ///
+    /// The contract codes of a mock environment are `ContractWrapper`s, Rust functions without a wasm, which can't be serialized.
+    /// The snapshot only holds the code ids and their creators: after loading it, the codes need to be registered again
+    /// under their code id with [`MockBase::restore_code`] or [`MockBase::restore_custom_code`], before their contracts are called.
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<(), CwEnvError> {
+        let app = self.app.borrow();
+        let state = self.state.borrow();
+
+        let storage = app
+            .storage()
+            .range(None, None, Order::Ascending)
+            .map(|(key, value)| (Binary(key), Binary(value)))
+            .collect();
+
+        let snapshot = MockSnapshot {
+            version: SNAPSHOT_VERSION,
+            block: app.block_info(),
+            storage,
+            codes: code_creators(&app),
+            addresses: state.get_all_addresses()?,
+            code_ids: state.get_all_code_ids()?,
+        };
+
+        serde_json::to_writer(File::create(path)?, &snapshot)?;
+        Ok(())
+    }
+
+    /// Replaces the state of the environment with a snapshot saved with [`MockBase::save_snapshot`].
+    /// Everything the environment held before is dropped: its storage, registered codes, cw-orch state,
+    /// transaction history, gas used and checkpoints.
+    ///
+    /// The code registry can't be restored from a file, the codes used by the snapshot need to be registered again with [`MockBase::restore_code`].
+    pub fn load_snapshot(&self, path: impl AsRef<Path>) -> Result<(), CwEnvError> {
+        let snapshot: MockSnapshot = serde_json::from_reader(File::open(path)?)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(CwEnvError::StdErr(format!(
+                "Unsupported mock snapshot version {}, expected {}",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+
+        // A new app doesn't hold the codes registered in the environment before
+        let mut app = (self.app_factory)();
+        let storage = app.storage_mut();
+        for (key, value) in snapshot.storage {
+            storage.set(key.as_slice(), value.as_slice());
+        }
+        app.set_block(snapshot.block.clone());
+        *self.app.borrow_mut() = app;
+        self.codes.borrow_mut().clear();
+
+        // The history, gas used and checkpoints describe the app that was replaced
+        let mut history = MockTxHistory::new();
+        history.record_block(snapshot.block);
+        *self.history.borrow_mut() = history;
+        if let Some(gas_meter) = self.gas_meter.borrow_mut().as_mut() {
+            *gas_meter = GasMeter::new(gas_meter.schedule().clone());
+        }
+        self.checkpoints.borrow_mut().clear();
+
+        let mut state = self.state.borrow_mut();
+        for contract_id in state.get_all_addresses()?.into_keys() {
+            state.remove_address(&contract_id);
+        }
+        for contract_id in state.get_all_code_ids()?.into_keys() {
+            state.remove_code_id(&contract_id);
+        }
+        for (contract_id, address) in snapshot.addresses {
+            state.set_address(&contract_id, &address);
+        }
+        for (contract_id, code_id) in snapshot.code_ids {
+            state.set_code_id(&contract_id, code_id);
+        }
+        drop(state);
+
+        self.snapshot_codes.borrow_mut().clone_from(&snapshot.codes);
+        Ok(())
+    }
+
+    /// Registers the code of a contract after loading a snapshot, under the code id the contract had in the snapshot.
+    /// Returns the code id of the contract.
+    pub fn restore_code<T: Uploadable + ContractInstance<Self>>(
+        &self,
+        contract: &T,
+    ) -> Result<u64, CwEnvError> {
+        let code_id = contract.code_id()?;
+        self.restore_custom_code(code_id, T::wrapper())?;
+        Ok(code_id)
+    }
+
+    /// Registers a custom contract wrapper under a code id after loading a snapshot.
+    pub fn restore_custom_code(
+        &self,
+        code_id: u64,
+        wrapper: Box<dyn Contract<Empty, Empty>>,
+    ) -> Result<(), CwEnvError> {
+        let creator = self
+            .snapshot_codes
+            .borrow()
+            .get(&code_id)
+            .cloned()
+            .ok_or_else(|| {
+                CwEnvError::StdErr(format!(
+                    "Code id {code_id} not found in the loaded snapshot"
+                ))
+            })?;
+        let code = SharedCode::from(wrapper);
+        self.app
+            .borrow_mut()
+            .store_code_with_id(creator, code_id, Box::new(code.clone()))?;
+        self.codes.borrow_mut().insert(code_id, code);
+        Ok(())
+    }
+}
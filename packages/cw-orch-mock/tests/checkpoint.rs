@@ -0,0 +1,77 @@
+use cosmwasm_std::coins;
+use cw_orch_core::contract::interface_traits::{
+    ContractInstance, CwOrchInstantiate, CwOrchQuery, CwOrchUpload,
+};
+use cw_orch_core::environment::{BankQuerier, DefaultQueriers, TxHandler};
+use cw_orch_mock::{Mock, MockBech32};
+use mock_contract::{InstantiateMsg, MockContract, QueryMsg};
+
+#[test]
+fn rollback_to_checkpoint() -> anyhow::Result<()> {
+    let app = MockBech32::new("mock");
+    let mock_contract = MockContract::new("mock-contract", app.clone());
+    mock_contract.upload()?;
+    mock_contract.instantiate(&InstantiateMsg {}, None, None)?;
+    let checkpoint = app.checkpoint()?;
+
+    for _ in 0..2 {
+        let other_contract = MockContract::new("other-contract", app.clone());
+        other_contract.upload()?;
+        other_contract.instantiate(&InstantiateMsg {}, None, None)?;
+        app.set_balance(&app.sender_addr(), coins(100, "ujuno"))?;
+
+        app.rollback(checkpoint)?;
+
+        // The cw-orch state, bank and code registry are restored
+        assert!(other_contract.address().is_err());
+        assert!(other_contract.code_id().is_err());
+        assert_eq!(
+            app.bank_querier()
+                .balance(app.sender_addr(), Some("ujuno".to_string()))?,
+            coins(0, "ujuno")
+        );
+        let response: String = mock_contract.query(&QueryMsg::FirstQuery {})?;
+        assert_eq!(response, "first query passed");
+    }
+
+    // Code ids and addresses are reused after a rollback
+    let other_contract = MockContract::new("other-contract", app.clone());
+    other_contract.upload()?;
+    assert_eq!(other_contract.code_id()?, mock_contract.code_id()? + 1);
+
+    Ok(())
+}
+
+#[test]
+fn fork_is_independent() -> anyhow::Result<()> {
+    let app = Mock::new("sender");
+    let mock_contract = MockContract::new("mock-contract", app.clone());
+    mock_contract.upload()?;
+    mock_contract.instantiate(&InstantiateMsg {}, None, None)?;
+
+    let fork = app.fork()?;
+    let forked_contract = MockContract::new("mock-contract", fork.clone());
+    assert_eq!(forked_contract.address()?, mock_contract.address()?);
+
+    // Changes on the fork don't affect the original environment
+    forked_contract.instantiate(&InstantiateMsg {}, None, None)?;
+    fork.set_balance("sender", coins(100, "ujuno"))?;
+    assert_ne!(forked_contract.address()?, mock_contract.address()?);
+    assert_eq!(
+        app.bank_querier()
+            .balance("sender", Some("ujuno".to_string()))?,
+        coins(0, "ujuno")
+    );
+
+    // And the other way around
+    app.set_balance("sender", coins(50, "ujuno"))?;
+    assert_eq!(
+        fork.bank_querier()
+            .balance("sender", Some("ujuno".to_string()))?,
+        coins(100, "ujuno")
+    );
+    let response: String = forked_contract.query(&QueryMsg::FirstQuery {})?;
+    assert_eq!(response, "first query passed");
+
+    Ok(())
+}
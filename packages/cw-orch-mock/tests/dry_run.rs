@@ -0,0 +1,106 @@
+use cosmwasm_std::coins;
+use cw_orch_core::contract::interface_traits::{
+    ContractInstance, CwOrchExecute, CwOrchInstantiate, CwOrchMigrate, CwOrchUpload,
+};
+use cw_orch_core::environment::{
+    ChainState, DefaultQueriers, DryRun, EnvironmentQuerier, PlannedTxKind, StateInterface,
+    TxHandler, WasmQuerier,
+};
+use cw_orch_mock::{GasSchedule, MockBech32};
+use mock_contract::{ExecuteMsg, InstantiateMsg, MigrateMsg, MockContract};
+
+#[test]
+fn dry_run_records_deployment_plan() -> anyhow::Result<()> {
+    let chain = MockBech32::new("mock");
+    chain.enable_gas_meter(GasSchedule::default());
+    chain.add_balance(&chain.sender_addr(), coins(100, "ujuno"))?;
+    let dry_run = DryRun::new(chain.clone())?;
+
+    let mock_contract = MockContract::new("mock-contract", dry_run.clone());
+    mock_contract.upload()?;
+    mock_contract.instantiate(&InstantiateMsg {}, Some(&chain.sender_addr()), None)?;
+    mock_contract.execute(&ExecuteMsg::FirstMessage {}, Some(&coins(10, "ujuno")))?;
+    mock_contract.migrate(
+        &MigrateMsg {
+            t: "success".to_string(),
+        },
+        mock_contract.code_id()?,
+    )?;
+
+    let plan = dry_run.plan()?;
+    assert_eq!(plan.chain_id, chain.env_info().chain_id);
+    let kinds: Vec<_> = plan.txs.iter().map(|tx| tx.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            PlannedTxKind::Upload,
+            PlannedTxKind::Instantiate,
+            PlannedTxKind::Execute,
+            PlannedTxKind::Migrate
+        ]
+    );
+    assert!(plan
+        .txs
+        .iter()
+        .all(|tx| tx.contract_id.as_deref() == Some("mock-contract") && tx.gas_estimate.is_some()));
+
+    let instantiate = &plan.txs[1];
+    assert_eq!(instantiate.admin, Some(chain.sender_addr()));
+    assert_eq!(instantiate.msg, Some(serde_json::json!({})));
+    assert_eq!(instantiate.contract_address, Some(mock_contract.address()?));
+
+    let execute = &plan.txs[2];
+    assert_eq!(
+        execute.msg,
+        Some(serde_json::json!({ "first_message": {} }))
+    );
+    assert_eq!(execute.funds, coins(10, "ujuno"));
+
+    assert_eq!(
+        plan.total_gas(),
+        plan.txs
+            .iter()
+            .map(|tx| tx.gas_estimate.unwrap())
+            .sum::<u64>()
+    );
+    assert!(plan.to_string().contains("2. instantiate mock-contract"));
+
+    // The deployment is stored by the dry run only
+    assert!(dry_run.state().get_code_id("mock-contract").is_ok());
+    assert!(chain.state().get_code_id("mock-contract").is_err());
+    assert!(chain.state().get_address("mock-contract").is_err());
+
+    // The transactions ran on a fork, the app of the environment is unchanged
+    assert_eq!(
+        chain.query_balance(&chain.sender_addr(), "ujuno")?.u128(),
+        100
+    );
+    assert!(chain
+        .wasm_querier()
+        .contract_info(mock_contract.address()?)
+        .is_err());
+    assert!(chain.wasm_querier().code_id_hash(1).is_err());
+
+    dry_run.clear_plan();
+    assert!(dry_run.plan()?.txs.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn dry_run_rejects_unserializable_payloads() -> anyhow::Result<()> {
+    let chain = MockBech32::new("mock");
+    let dry_run = DryRun::new(chain.clone())?;
+
+    let mock_contract = MockContract::new("mock-contract", dry_run.clone());
+    mock_contract.upload()?;
+    mock_contract.instantiate(&InstantiateMsg {}, None, None)?;
+
+    // JSON maps only have string keys
+    let msg = std::collections::HashMap::from([((1u8, 2u8), 3u8)]);
+    let res = dry_run.execute(&msg, &[], &mock_contract.address()?);
+    assert!(res.is_err());
+    assert_eq!(dry_run.plan()?.txs.len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use cosmos_sdk_proto::{
+    cosmos::tx::v1beta1::{TxBody, TxRaw},
+    cosmwasm::wasm::v1::MsgExecuteContract,
+    Any,
+};
+use cosmwasm_std::to_json_vec;
+use cw_orch_core::contract::interface_traits::{
+    ContractInstance, CwOrchExecute, CwOrchInstantiate, CwOrchUpload,
+};
+use cw_orch_core::environment::{DefaultQueriers, IndexResponse, NodeQuerier, TxHandler};
+use cw_orch_mock::{GasSchedule, MockBech32};
+use mock_contract::{ExecuteMsg, InstantiateMsg, MockContract};
+use prost::Message;
+
+#[test]
+fn gas_metering() -> anyhow::Result<()> {
+    let app = MockBech32::new("mock");
+    let mock_contract = MockContract::new("mock-contract", app.clone());
+
+    // Gas is not reported without a gas meter
+    let upload = mock_contract.upload()?;
+    assert_eq!(upload.gas_used(), None);
+    assert_eq!(app.gas_used_by("mock-contract"), None);
+
+    let schedule = GasSchedule::default()
+        .with_message_cost("mock-contract", "first_message", 250_000)
+        .with_message_cost("mock-contract", "instantiate", 100_000);
+    app.enable_gas_meter(schedule.clone());
+
+    let instantiate = mock_contract.instantiate(&InstantiateMsg {}, None, None)?;
+    let first_message = mock_contract.execute(&ExecuteMsg::FirstMessage {}, None)?;
+    let fourth_message = mock_contract.execute(&ExecuteMsg::<String>::FourthMessage, None)?;
+
+    let instantiate_gas = instantiate.gas_used().unwrap();
+    let first_message_gas = first_message.gas_used().unwrap();
+    let fourth_message_gas = fourth_message.gas_used().unwrap();
+    assert!(instantiate_gas > schedule.instance_cost + 100_000);
+    assert!(first_message_gas > schedule.instance_cost + 250_000);
+    assert!(fourth_message_gas < schedule.instance_cost + 250_000);
+
+    assert_eq!(
+        app.gas_used_by("mock-contract"),
+        Some(instantiate_gas + first_message_gas + fourth_message_gas)
+    );
+
+    // Simulation runs the messages of the transaction on a fork of the environment
+    let last_tx_hash = app.last_tx_hash();
+    let execute = MsgExecuteContract {
+        sender: app.sender_addr().to_string(),
+        contract: mock_contract.address()?.to_string(),
+        msg: to_json_vec(&ExecuteMsg::<String>::FirstMessage {})?,
+        funds: vec![],
+    };
+    let tx = TxRaw {
+        body_bytes: TxBody {
+            messages: vec![Any {
+                type_url: "/cosmwasm.wasm.v1.MsgExecuteContract".to_string(),
+                value: execute.encode_to_vec(),
+            }],
+            ..Default::default()
+        }
+        .encode_to_vec(),
+        ..Default::default()
+    }
+    .encode_to_vec();
+    let simulation = app.node_querier().simulate_tx(tx.clone())?;
+    assert!(simulation > schedule.tx_cost(tx.len()) + schedule.instance_cost + 250_000);
+
+    assert_eq!(app.last_tx_hash(), last_tx_hash);
+    assert_eq!(
+        app.gas_used_by("mock-contract"),
+        Some(instantiate_gas + first_message_gas + fourth_message_gas)
+    );
+
+    Ok(())
+}
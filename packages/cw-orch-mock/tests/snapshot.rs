@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use cosmwasm_std::coins;
+use cw_orch_core::contract::interface_traits::{
+    ContractInstance, CwOrchInstantiate, CwOrchQuery, CwOrchUpload,
+};
+use cw_orch_core::environment::{
+    BankQuerier, ChainState, DefaultQueriers, NodeQuerier, QueryHandler, StateInterface, TxHandler,
+};
+use cw_orch_mock::{GasSchedule, MockBech32};
+use mock_contract::{InstantiateMsg, MockContract, QueryMsg};
+
+/// Snapshot file of the test, unique to the test process so concurrent test runs don't overwrite it
+fn snapshot_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("cw-orch-mock-{name}-{}.json", std::process::id()))
+}
+
+#[test]
+fn save_and_load_snapshot() -> anyhow::Result<()> {
+    let path = snapshot_path("snapshot");
+
+    let app = MockBech32::new("mock");
+    let mock_contract = MockContract::new("mock-contract", app.clone());
+    mock_contract.upload()?;
+    mock_contract.instantiate(&InstantiateMsg {}, None, None)?;
+    app.set_balance(&app.sender_addr(), coins(100, "ujuno"))?;
+    app.wait_blocks(10)?;
+    app.save_snapshot(&path)?;
+
+    let loaded = MockBech32::new("mock");
+    loaded.load_snapshot(&path)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(
+        loaded.node_querier().latest_block()?,
+        app.node_querier().latest_block()?
+    );
+    assert_eq!(
+        loaded
+            .bank_querier()
+            .balance(loaded.sender_addr(), Some("ujuno".to_string()))?,
+        coins(100, "ujuno")
+    );
+    assert_eq!(
+        loaded.state().borrow().get_all_addresses()?,
+        app.state().borrow().get_all_addresses()?
+    );
+    assert_eq!(
+        loaded.state().borrow().get_all_code_ids()?,
+        app.state().borrow().get_all_code_ids()?
+    );
+
+    // Contract storage is restored, code needs to be registered again
+    let loaded_contract = MockContract::new("mock-contract", loaded.clone());
+    assert_eq!(loaded_contract.address()?, mock_contract.address()?);
+    loaded.restore_code(&loaded_contract)?;
+    let response: String = loaded_contract.query(&QueryMsg::FirstQuery {})?;
+    assert_eq!(response, "first query passed");
+
+    // New contracts don't collide with the restored ones
+    let new_contract = MockContract::new("new-contract", loaded.clone());
+    new_contract.set_code_id(loaded_contract.code_id()?);
+    new_contract.instantiate(&InstantiateMsg {}, None, None)?;
+    assert_ne!(new_contract.address()?, loaded_contract.address()?);
+
+    Ok(())
+}
+
+#[test]
+fn loading_a_snapshot_replaces_the_state() -> anyhow::Result<()> {
+    let path = snapshot_path("replaced-snapshot");
+
+    let app = MockBech32::new("mock");
+    app.set_balance(&app.sender_addr(), coins(100, "ujuno"))?;
+    app.save_snapshot(&path)?;
+
+    // The environment the snapshot is loaded in was already used
+    let loaded = MockBech32::new("mock");
+    loaded.enable_gas_meter(GasSchedule::default());
+    let checkpoint = loaded.checkpoint()?;
+    let mock_contract = MockContract::new("mock-contract", loaded.clone());
+    mock_contract.upload()?;
+    mock_contract.instantiate(&InstantiateMsg {}, None, None)?;
+    loaded.set_balance(&loaded.sender_addr(), coins(50, "uosmo"))?;
+    let instantiate_hash = loaded.last_tx_hash().unwrap();
+    let (code_id, address) = (mock_contract.code_id()?, mock_contract.address()?);
+    loaded.wait_blocks(10)?;
+
+    loaded.load_snapshot(&path)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(
+        loaded.bank_querier().balance(loaded.sender_addr(), None)?,
+        coins(100, "ujuno")
+    );
+    assert!(loaded.state().borrow().get_all_addresses()?.is_empty());
+    assert!(loaded.state().borrow().get_all_code_ids()?.is_empty());
+    assert!(mock_contract.address().is_err());
+
+    // The history, gas used and checkpoints of the replaced app are dropped
+    assert!(loaded.last_tx_hash().is_none());
+    assert!(loaded.node_querier().find_tx(instantiate_hash).is_err());
+    assert_eq!(
+        loaded.node_querier().latest_block()?,
+        app.node_querier().latest_block()?
+    );
+    assert!(loaded.rollback(checkpoint).is_err());
+    mock_contract.set_code_id(code_id);
+    mock_contract.set_address(&address);
+    assert_eq!(loaded.gas_used_by("mock-contract"), Some(0));
+    mock_contract.remove_address();
+
+    // The code registry of the environment is dropped too, the code ids are available again
+    mock_contract.upload()?;
+    assert_eq!(mock_contract.code_id()?, 1);
+    Ok(())
+}
@@ -15,6 +15,7 @@ pub const VOTA_ASH: ChainInfo = ChainInfo {
     gas_denom: "peaka",
     gas_price: 100000000000f64,
     grpc_urls: &["https://vota-grpc.dorafactory.org:443"],
+    rpc_urls: &[],
     network_info: DORAVOTA_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -26,6 +27,7 @@ pub const VOTA_TESTNET: ChainInfo = ChainInfo {
     gas_denom: "peaka",
     gas_price: 100000000000f64,
     grpc_urls: &["https://vota-testnet-grpc.dorafactory.org:443"],
+    rpc_urls: &[],
     network_info: DORAVOTA_NETWORK,
     lcd_url: None,
     fcd_url: None,
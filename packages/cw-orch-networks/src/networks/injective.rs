@@ -16,6 +16,7 @@ pub const INJECTIVE_1: ChainInfo = ChainInfo {
     gas_denom: "inj",
     gas_price: 500_000_000.0,
     grpc_urls: &["https://sentry.chain.grpc.injective.network:443"],
+    rpc_urls: &["https://sentry.tm.injective.network:443"],
     network_info: INJECTIVE_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -29,6 +30,7 @@ pub const INJECTIVE_888: ChainInfo = ChainInfo {
     gas_denom: "inj",
     gas_price: 500_000_000.0,
     grpc_urls: &["https://k8s.testnet.chain.grpc.injective.network:443"],
+    rpc_urls: &["https://k8s.testnet.tm.injective.network:443"],
     network_info: INJECTIVE_NETWORK,
     lcd_url: None,
     fcd_url: None,
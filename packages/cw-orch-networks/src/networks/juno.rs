@@ -15,6 +15,7 @@ pub const UNI_6: ChainInfo = ChainInfo {
     gas_denom: "ujunox",
     gas_price: 0.025,
     grpc_urls: &["http://juno-testnet-grpc.polkachu.com:12690"],
+    rpc_urls: &["https://juno-testnet-rpc.polkachu.com"],
     network_info: JUNO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -26,6 +27,7 @@ pub const JUNO_1: ChainInfo = ChainInfo {
     gas_denom: "ujuno",
     gas_price: 0.0750,
     grpc_urls: &["http://juno-grpc.polkachu.com:12690"],
+    rpc_urls: &["https://juno-rpc.polkachu.com"],
     network_info: JUNO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -37,6 +39,7 @@ pub const LOCAL_JUNO: ChainInfo = ChainInfo {
     gas_denom: "ujunox",
     gas_price: 0.0,
     grpc_urls: &["http://localhost:9090"],
+    rpc_urls: &["http://localhost:26657"],
     network_info: JUNO_NETWORK,
     lcd_url: None,
     fcd_url: None,
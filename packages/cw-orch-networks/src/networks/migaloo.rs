@@ -13,6 +13,7 @@ pub const LOCAL_MIGALOO: ChainInfo = ChainInfo {
     gas_denom: "uwhale",
     gas_price: 0.1,
     grpc_urls: &["http://localhost:9090"],
+    rpc_urls: &["http://localhost:26657"],
     network_info: MIGALOO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -25,6 +26,7 @@ pub const NARWHAL_1: ChainInfo = ChainInfo {
     gas_denom: "uwhale",
     gas_price: 0.1,
     grpc_urls: &["migaloo-testnet-grpc.polkachu.com:20790"],
+    rpc_urls: &["https://migaloo-testnet-rpc.polkachu.com"],
     network_info: MIGALOO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -37,6 +39,7 @@ pub const MIGALOO_1: ChainInfo = ChainInfo {
     gas_denom: "uwhale",
     gas_price: 0.1,
     grpc_urls: &["migaloo-grpc.polkachu.com:20790"],
+    rpc_urls: &["https://migaloo-rpc.polkachu.com"],
     network_info: MIGALOO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -14,6 +14,7 @@ pub const PION_1: ChainInfo = ChainInfo {
     gas_denom: "untrn",
     gas_price: 0.075,
     grpc_urls: &["http://grpc-palvus.pion-1.ntrn.tech:80"],
+    rpc_urls: &["https://rpc-palvus.pion-1.ntrn.tech"],
     network_info: NEUTRON_NETWORK,
     lcd_url: Some("https://rest-palvus.pion-1.ntrn.tech"),
     fcd_url: None,
@@ -26,6 +27,7 @@ pub const NEUTRON_1: ChainInfo = ChainInfo {
     gas_denom: "untrn",
     gas_price: 0.075,
     grpc_urls: &["http://grpc-kralum.neutron-1.neutron.org:80"],
+    rpc_urls: &["https://rpc-kralum.neutron-1.neutron.org"],
     network_info: NEUTRON_NETWORK,
     lcd_url: Some("https://rest-kralum.neutron-1.neutron.org"),
     fcd_url: None,
@@ -37,6 +39,7 @@ pub const LOCAL_NEUTRON: ChainInfo = ChainInfo {
     gas_denom: "untrn",
     gas_price: 0.0025,
     grpc_urls: &["http://localhost:8090"],
+    rpc_urls: &[],
     network_info: NEUTRON_NETWORK,
     lcd_url: None,
     fcd_url: None,
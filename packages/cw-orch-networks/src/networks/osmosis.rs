@@ -13,6 +13,7 @@ pub const OSMOSIS_1: ChainInfo = ChainInfo {
     gas_denom: "uosmo",
     gas_price: 0.025,
     grpc_urls: &["https://grpc.osmosis.zone:443"],
+    rpc_urls: &["https://rpc.osmosis.zone:443"],
     network_info: OSMO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -24,6 +25,7 @@ pub const OSMO_5: ChainInfo = ChainInfo {
     gas_denom: "uosmo",
     gas_price: 0.025,
     grpc_urls: &["https://grpc.osmotest5.osmosis.zone:443"],
+    rpc_urls: &["https://rpc.osmotest5.osmosis.zone:443"],
     network_info: OSMO_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -35,6 +37,7 @@ pub const LOCAL_OSMO: ChainInfo = ChainInfo {
     gas_denom: "uosmo",
     gas_price: 0.0026,
     grpc_urls: &["http://65.108.235.46:9094"],
+    rpc_urls: &[],
     network_info: OSMO_NETWORK,
     lcd_url: None,
     fcd_url: None,
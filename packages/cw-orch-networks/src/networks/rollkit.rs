@@ -13,6 +13,7 @@ pub const LOCAL_ROLLKIT: ChainInfo = ChainInfo {
     gas_denom: "uwasm",
     gas_price: 0.025,
     grpc_urls: &["http://localhost:9290"],
+    rpc_urls: &[],
     network_info: ROLLKIT_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -24,6 +25,7 @@ pub const ROLLKIT_TESTNET: ChainInfo = ChainInfo {
     gas_denom: "urosm",
     gas_price: 0.025,
     grpc_urls: &["http://grpc.rosm.rollkit.dev:9290"],
+    rpc_urls: &[],
     network_info: ROLLKIT_NETWORK,
     lcd_url: None,
     fcd_url: None,
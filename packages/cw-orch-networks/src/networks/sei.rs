@@ -13,6 +13,7 @@ pub const LOCAL_SEI: ChainInfo = ChainInfo {
     gas_denom: "usei",
     gas_price: 0.1,
     grpc_urls: &["http://localhost:9090"],
+    rpc_urls: &["http://localhost:26657"],
     network_info: SEI_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -24,6 +25,7 @@ pub const SEI_DEVNET_3: ChainInfo = ChainInfo {
     gas_denom: "usei",
     gas_price: 0.1,
     grpc_urls: &["http://sei_devnet-testnet-grpc.polkachu.com:11990"],
+    rpc_urls: &[],
     network_info: SEI_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -35,6 +37,7 @@ pub const ATLANTIC_2: ChainInfo = ChainInfo {
     gas_denom: "usei",
     gas_price: 0.1,
     grpc_urls: &["http://sei-testnet-grpc.polkachu.com:11990"],
+    rpc_urls: &["https://sei-testnet-rpc.polkachu.com"],
     network_info: SEI_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -46,6 +49,7 @@ pub const PACIFIC_1: ChainInfo = ChainInfo {
     gas_denom: "usei",
     gas_price: 0.1,
     grpc_urls: &["http://sei-grpc.polkachu.com:11990"],
+    rpc_urls: &["https://sei-rpc.polkachu.com"],
     network_info: SEI_NETWORK,
     lcd_url: None,
     fcd_url: None,
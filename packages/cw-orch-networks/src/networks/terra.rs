@@ -15,6 +15,7 @@ pub const PISCO_1: ChainInfo = ChainInfo {
     gas_denom: "uluna",
     gas_price: 0.015,
     grpc_urls: &["http://terra-testnet-grpc.polkachu.com:11790"],
+    rpc_urls: &["https://terra-testnet-rpc.polkachu.com"],
     network_info: TERRA_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -28,6 +29,7 @@ pub const PHOENIX_1: ChainInfo = ChainInfo {
     gas_denom: "uluna",
     gas_price: 0.015,
     grpc_urls: &["http://terra-grpc.polkachu.com:11790"],
+    rpc_urls: &["https://terra-rpc.polkachu.com"],
     network_info: TERRA_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -41,6 +43,7 @@ pub const LOCAL_TERRA: ChainInfo = ChainInfo {
     gas_denom: "uluna",
     gas_price: 0.15,
     grpc_urls: &["http://localhost:9090"],
+    rpc_urls: &["http://localhost:26657"],
     network_info: TERRA_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -13,6 +13,7 @@ pub const XION_TESTNET_1: ChainInfo = ChainInfo {
     gas_denom: "uxion",
     gas_price: 0.0,
     grpc_urls: &["http://xion-testnet-grpc.polkachu.com:22390"],
+    rpc_urls: &["https://xion-testnet-rpc.polkachu.com"],
     network_info: XION_NETWORK,
     lcd_url: None,
     fcd_url: None,
@@ -25,7 +25,7 @@ use serde::Serialize;
 
 use cw_orch_core::{
     environment::TxHandler,
-    environment::{ChainState, StateInterface},
+    environment::{ChainState, SimulatedEnv, StateInterface},
 };
 
 use cw_orch_mock::MockState;
@@ -40,6 +40,7 @@ pub const MOCK_CHAIN_INFO: ChainInfo = ChainInfo {
     gas_denom: "uosmo",
     gas_price: 0.0,
     grpc_urls: &[],
+    rpc_urls: &[],
     lcd_url: None,
     fcd_url: None,
     network_info: NetworkInfo {
@@ -204,6 +205,8 @@ impl<S: StateInterface> ChainState for OsmosisTestTube<S> {
     }
 }
 
+impl<S: StateInterface> SimulatedEnv for OsmosisTestTube<S> {}
+
 // Execute on the test chain, returns test response type
 impl<S: StateInterface> TxHandler for OsmosisTestTube<S> {
     type Error = CwEnvError;
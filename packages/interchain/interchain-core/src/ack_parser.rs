@@ -1,3 +1,8 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    sync::Arc,
+};
+
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{from_json, Binary};
 use cw_orch_core::environment::CwEnv;
@@ -6,81 +11,250 @@ use prost::Message;
 
 use crate::{
     env::decode_ack_error,
-    types::{parse::SuccessIbcPacket, IbcTxAnalysis},
+    ica::IcaAckParser,
+    types::{parse::SuccessIbcPacket, FullIbcPacketAnalysis, IbcPacketOutcome, IbcTxAnalysis},
     InterchainError,
 };
 
 use self::acknowledgement::{Acknowledgement, Response};
 
-/// Struct used to centralize all the pre-defined ack types
-pub enum IbcAckParser {}
+/// Decoder of the acknowledgements of an IBC application.
+/// Registered with [`IbcAckParser::register`], it's used to check the success of the packets
+/// and to retrieve their typed acknowledgement with [`FullIbcPacketAnalysis::ack_as_with`].
+pub trait AckParser: Send + Sync + 'static {
+    /// Acknowledgement decoded by the parser
+    type Output: 'static;
+
+    /// Decodes the acknowledgement.
+    ///
+    /// Returns `Ok(None)` if the ack isn't of this type and an error if it's a failure ack of this type
+    fn parse(&self, ack: &Binary) -> Result<Option<Self::Output>, InterchainError>;
+}
+
+/// Object-safe [`AckParser`], used to store parsers of different outputs together
+trait DynAckParser: Send + Sync {
+    fn parser(&self) -> TypeId;
+
+    fn output(&self) -> TypeId;
+
+    fn parse_dyn(&self, ack: &Binary) -> Result<Option<Box<dyn Any>>, InterchainError>;
+}
+
+impl<P: AckParser> DynAckParser for P {
+    fn parser(&self) -> TypeId {
+        TypeId::of::<P>()
+    }
+
+    fn output(&self) -> TypeId {
+        TypeId::of::<P::Output>()
+    }
+
+    fn parse_dyn(&self, ack: &Binary) -> Result<Option<Box<dyn Any>>, InterchainError> {
+        Ok(self
+            .parse(ack)?
+            .map(|output| Box::new(output) as Box<dyn Any>))
+    }
+}
+
+/// Parsers of the IBC acknowledgements, used to check the success of the packets with [`IbcTxAnalysis::into_result_with`]
+/// and to retrieve their typed acknowledgement with [`FullIbcPacketAnalysis::ack_as_with`].
+///
+/// The parsers registered on it are tried before the pre-defined Polytone, ICS-27, ICS-20 and ICS-004 parsers.
+/// The default value only holds the pre-defined parsers, it's the one used by `into_result` and `ack_as`.
+#[derive(Clone, Default)]
+pub struct IbcAckParser {
+    registered: Vec<Arc<dyn DynAckParser>>,
+}
 
 impl IbcAckParser {
-    /// Verifies if the given ack is an Polytone type and returns the acknowledgement if it is
+    /// Parser of the acks with the pre-defined parsers only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a parser for the acks of a custom IBC application.
+    /// The acks it decodes are considered successful by `into_result_with`, and can be retrieved with `ack_as_with::<P::Output>`.
     ///
-    /// Returns an error if there was an error in the process
-    pub fn polytone_ack(ack: &Binary) -> Result<Callback, InterchainError> {
-        // We try decoding the ack with Polytone
-        let decoded_polytone_packet: Result<Callback, _> = from_json(ack);
-        if let Ok(decoded_polytone_packet) = decoded_polytone_packet {
-            match &decoded_polytone_packet {
-                Callback::Query(query_result) => match query_result {
-                    Ok(_) => return Ok(decoded_polytone_packet),
-                    Err(e) => {
-                        return Err(InterchainError::FailedAckReceived(format!(
-                            "Error during query on remote chain : {:?}",
-                            e
-                        )))
-                    }
-                },
-                Callback::Execute(execute_response) => match execute_response {
-                    Ok(_) => return Ok(decoded_polytone_packet),
-                    Err(e) => {
-                        return Err(InterchainError::FailedAckReceived(format!(
-                            "Error during execution on remote chain : {}",
-                            e
-                        )))
-                    }
-                },
-                Callback::FatalError(e) => {
-                    return Err(InterchainError::FailedAckReceived(e.to_string()))
+    /// The registered parsers are tried in the order they were registered, before the pre-defined parsers.
+    /// Registering a parser of the same type again replaces the previous one, keeping its position.
+    pub fn register<P: AckParser>(&mut self, parser: P) -> &mut Self {
+        let parser = Arc::new(parser);
+        match self
+            .registered
+            .iter_mut()
+            .find(|registered| registered.parser() == TypeId::of::<P>())
+        {
+            Some(registered) => *registered = parser,
+            None => self.registered.push(parser),
+        }
+        self
+    }
+
+    /// Removes the registered parser of type `P`, if any
+    pub fn unregister<P: AckParser>(&mut self) -> &mut Self {
+        self.registered
+            .retain(|registered| registered.parser() != TypeId::of::<P>());
+        self
+    }
+
+    /// Registered and pre-defined parsers, in the order they're tried
+    fn parsers(&self) -> Vec<Arc<dyn DynAckParser>> {
+        let mut parsers = self.registered.clone();
+        parsers.push(Arc::new(PolytoneAckParser));
+        parsers.push(Arc::new(IcaAckParser));
+        parsers.push(Arc::new(Ics20AckParser));
+        parsers.push(Arc::new(Ics004AckParser));
+        parsers
+    }
+
+    /// Verifies the ack is successful with any of the registered or pre-defined parsers.
+    ///
+    /// Returns the error of the failure ack if a parser recognized one, or an error if no parser decoded the ack
+    pub fn any_ack(&self, ack: &Binary) -> Result<(), InterchainError> {
+        let mut failure = None;
+        for parser in self.parsers() {
+            match parser.parse_dyn(ack) {
+                Ok(Some(_)) => return Ok(()),
+                Ok(None) => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
                 }
             }
         }
+        Err(failure.unwrap_or_else(|| decode_ack_error(ack)))
+    }
 
-        Err(decode_ack_error(ack))
+    /// Decodes the ack with the registered or pre-defined parsers whose output is `T`.
+    /// They're tried in order, the first one decoding the ack is used.
+    ///
+    /// Returns the error of the failure ack if a parser recognized one, or an error if no parser decoded the ack
+    pub fn ack_as<T: 'static>(&self, ack: &Binary) -> Result<T, InterchainError> {
+        let mut found = false;
+        let mut failure = None;
+        for parser in self
+            .parsers()
+            .into_iter()
+            .filter(|parser| parser.output() == TypeId::of::<T>())
+        {
+            found = true;
+            match parser.parse_dyn(ack) {
+                Ok(Some(output)) => {
+                    return Ok(*output
+                        .downcast::<T>()
+                        .expect("The parser output has the requested type"))
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        if !found {
+            return Err(InterchainError::AckParserNotFound(
+                type_name::<T>().to_string(),
+            ));
+        }
+        Err(failure.unwrap_or_else(|| decode_ack_error(ack)))
+    }
+
+    /// Verifies if the given ack is an Polytone type and returns the acknowledgement if it is
+    ///
+    /// Returns an error if there was an error in the process
+    pub fn polytone_ack(ack: &Binary) -> Result<Callback, InterchainError> {
+        PolytoneAckParser
+            .parse(ack)?
+            .ok_or_else(|| decode_ack_error(ack))
     }
 
     /// Verifies if the given ack is an IBC20 type
     ///
     /// Returns an error if there was an error in the parsing process
     pub fn ics20_ack(ack: &Binary) -> Result<(), InterchainError> {
-        let decoded_fungible_packet: Result<FungibleTokenPacketAcknowledgement, _> = from_json(ack);
-        if let Ok(decoded_fungible_packet) = decoded_fungible_packet {
-            match decoded_fungible_packet {
-                FungibleTokenPacketAcknowledgement::Result(_) => return Ok(()),
-                FungibleTokenPacketAcknowledgement::Error(e) => {
-                    return Err(InterchainError::FailedAckReceived(e))
-                }
-            }
-        }
-        Err(decode_ack_error(ack))
+        Ics20AckParser
+            .parse(ack)?
+            .map(|_| ())
+            .ok_or_else(|| decode_ack_error(ack))
     }
 
     /// Verifies if the given ack is an ICS004 type and returns the ack result if it is
     ///
     /// Returns an error if there was an error in the parsing process
     pub fn ics004_ack(ack: &Binary) -> Result<Vec<u8>, InterchainError> {
-        if let Ok(decoded_ics_004) = Acknowledgement::decode(ack.as_slice()) {
-            if let Some(response) = decoded_ics_004.response {
-                log::debug!("Decoded ack using ICS-004 : {:x?}", response);
-                match response {
-                    Response::Result(result) => return Ok(result),
-                    Response::Error(e) => return Err(InterchainError::FailedAckReceived(e)),
-                }
+        Ics004AckParser
+            .parse(ack)?
+            .ok_or_else(|| decode_ack_error(ack))
+    }
+}
+
+/// Parser of the Polytone acks, the successful query and execution callbacks are returned
+pub struct PolytoneAckParser;
+
+impl AckParser for PolytoneAckParser {
+    type Output = Callback;
+
+    fn parse(&self, ack: &Binary) -> Result<Option<Callback>, InterchainError> {
+        // We try decoding the ack with Polytone
+        let Ok(decoded_polytone_packet) = from_json::<Callback>(ack) else {
+            return Ok(None);
+        };
+        match &decoded_polytone_packet {
+            Callback::Query(query_result) => match query_result {
+                Ok(_) => Ok(Some(decoded_polytone_packet)),
+                Err(e) => Err(InterchainError::FailedAckReceived(format!(
+                    "Error during query on remote chain : {:?}",
+                    e
+                ))),
+            },
+            Callback::Execute(execute_response) => match execute_response {
+                Ok(_) => Ok(Some(decoded_polytone_packet)),
+                Err(e) => Err(InterchainError::FailedAckReceived(format!(
+                    "Error during execution on remote chain : {}",
+                    e
+                ))),
+            },
+            Callback::FatalError(e) => Err(InterchainError::FailedAckReceived(e.to_string())),
+        }
+    }
+}
+
+/// Parser of the ICS-20 acks, the successful acks are returned
+pub struct Ics20AckParser;
+
+impl AckParser for Ics20AckParser {
+    type Output = FungibleTokenPacketAcknowledgement;
+
+    fn parse(
+        &self,
+        ack: &Binary,
+    ) -> Result<Option<FungibleTokenPacketAcknowledgement>, InterchainError> {
+        match from_json(ack) {
+            Ok(FungibleTokenPacketAcknowledgement::Error(e)) => {
+                Err(InterchainError::FailedAckReceived(e))
             }
+            Ok(ack) => Ok(Some(ack)),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Parser of the ICS-004 acks, the result of the successful acks is returned
+pub struct Ics004AckParser;
+
+impl AckParser for Ics004AckParser {
+    type Output = Vec<u8>;
+
+    fn parse(&self, ack: &Binary) -> Result<Option<Vec<u8>>, InterchainError> {
+        let Some(response) = Acknowledgement::decode(ack.as_slice())
+            .ok()
+            .and_then(|decoded_ics_004| decoded_ics_004.response)
+        else {
+            return Ok(None);
+        };
+        log::debug!("Decoded ack using ICS-004 : {:x?}", response);
+        match response {
+            Response::Result(result) => Ok(Some(result)),
+            Response::Error(e) => Err(InterchainError::FailedAckReceived(e)),
         }
-        Err(decode_ack_error(ack))
     }
 }
 
@@ -93,7 +267,54 @@ pub enum FungibleTokenPacketAcknowledgement {
     Error(String),
 }
 
+impl<Chain: CwEnv> FullIbcPacketAnalysis<Chain> {
+    /// Decodes the ack of the packet with the pre-defined parser whose output is `T`.
+    /// Errors if the packet has timed-out
+    pub fn ack_as<T: 'static>(&self) -> Result<T, InterchainError> {
+        self.ack_as_with(&IbcAckParser::default())
+    }
+
+    /// Decodes the ack of the packet with the registered or pre-defined parser of `parser` whose output is `T`.
+    /// Errors if the packet has timed-out
+    pub fn ack_as_with<T: 'static>(&self, parser: &IbcAckParser) -> Result<T, InterchainError> {
+        match &self.outcome {
+            IbcPacketOutcome::Success { ack, .. } => parser.ack_as(ack),
+            IbcPacketOutcome::Timeout { .. } => Err(InterchainError::PacketTimeout {}),
+        }
+    }
+}
+
+impl<Chain: CwEnv> SuccessIbcPacket<Chain> {
+    /// Decodes the ack of the packet with the pre-defined parser whose output is `T`
+    pub fn ack_as<T: 'static>(&self) -> Result<T, InterchainError> {
+        self.ack_as_with(&IbcAckParser::default())
+    }
+
+    /// Decodes the ack of the packet with the registered or pre-defined parser of `parser` whose output is `T`
+    pub fn ack_as_with<T: 'static>(&self, parser: &IbcAckParser) -> Result<T, InterchainError> {
+        parser.ack_as(&self.packet_ack)
+    }
+}
+
 impl<Chain: CwEnv> IbcTxAnalysis<Chain> {
+    /// Decodes the acks of the packets sent by the transaction with the pre-defined parser whose output is `T`.
+    /// The packets sent by the resulting transactions are not included
+    pub fn acks_as<T: 'static>(&self) -> Result<Vec<T>, InterchainError> {
+        self.acks_as_with(&IbcAckParser::default())
+    }
+
+    /// Decodes the acks of the packets sent by the transaction with the registered or pre-defined parser of `parser` whose output is `T`.
+    /// The packets sent by the resulting transactions are not included
+    pub fn acks_as_with<T: 'static>(
+        &self,
+        parser: &IbcAckParser,
+    ) -> Result<Vec<T>, InterchainError> {
+        self.packets
+            .iter()
+            .map(|packet| packet.ack_as_with(parser))
+            .collect()
+    }
+
     /// Assert that all packets were not timeout
     pub fn assert_no_timeout(&self) -> Result<Vec<SuccessIbcPacket<Chain>>, InterchainError> {
         Ok(self
@@ -147,3 +368,129 @@ pub mod acknowledgement {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{from_json, to_json_binary, Binary};
+    use cw_orch_mock::{cw_multi_test::AppResponse, Mock};
+
+    use prost::Message;
+
+    use super::{
+        acknowledgement::{Acknowledgement, Response},
+        AckParser, FungibleTokenPacketAcknowledgement,
+    };
+    use crate::{
+        types::{FullIbcPacketAnalysis, IbcPacketOutcome, IbcTxAnalysis, TxId},
+        IbcAckParser, InterchainError,
+    };
+
+    #[cw_serde]
+    enum CounterAck {
+        Count(u32),
+        Failure(String),
+    }
+
+    /// Count returned by the counter acks, only decoded by the parsers of this test
+    #[derive(Debug, PartialEq)]
+    struct Count(u32);
+
+    struct CounterAckParser;
+
+    impl AckParser for CounterAckParser {
+        type Output = Count;
+
+        fn parse(&self, ack: &Binary) -> Result<Option<Count>, InterchainError> {
+            match from_json(ack) {
+                Ok(CounterAck::Count(count)) => Ok(Some(Count(count))),
+                Ok(CounterAck::Failure(e)) => Err(InterchainError::FailedAckReceived(e)),
+                Err(_) => Ok(None),
+            }
+        }
+    }
+
+    /// Parser with the same output as the ICS-004 parser, that never decodes an ack
+    struct RawBytesParser;
+
+    impl AckParser for RawBytesParser {
+        type Output = Vec<u8>;
+
+        fn parse(&self, _ack: &Binary) -> Result<Option<Vec<u8>>, InterchainError> {
+            Ok(None)
+        }
+    }
+
+    fn packet(ack: Binary) -> FullIbcPacketAnalysis<Mock> {
+        let tx = || IbcTxAnalysis {
+            tx_id: TxId {
+                chain_id: "juno-1".to_string(),
+                response: AppResponse::default(),
+            },
+            packets: vec![],
+        };
+        FullIbcPacketAnalysis {
+            send_tx: None,
+            outcome: IbcPacketOutcome::Success {
+                receive_tx: tx(),
+                ack_tx: tx(),
+                ack,
+            },
+        }
+    }
+
+    #[test]
+    fn registered_parsers_decode_typed_acks() -> cw_orch::anyhow::Result<()> {
+        let counter_ack = packet(to_json_binary(&CounterAck::Count(5))?);
+        assert!(matches!(
+            counter_ack.ack_as::<Count>(),
+            Err(InterchainError::AckParserNotFound(_))
+        ));
+
+        let mut parser = IbcAckParser::new();
+        parser.register(CounterAckParser);
+        // Registering the parser again doesn't add it twice
+        parser.register(CounterAckParser);
+        counter_ack.into_result_with(&parser)?;
+        assert_eq!(counter_ack.ack_as_with::<Count>(&parser)?, Count(5));
+
+        let failure = packet(to_json_binary(&CounterAck::Failure(
+            "overflow".to_string(),
+        ))?);
+        assert!(matches!(
+            failure.into_result_with(&parser),
+            Err(InterchainError::FailedAckReceived(e)) if e == "overflow"
+        ));
+
+        // The pre-defined parsers are still used
+        let ics20_ack = packet(Binary::from(br#"{"result":"AQ=="}"#.as_slice()));
+        ics20_ack.into_result_with(&parser)?;
+        assert_eq!(
+            ics20_ack.ack_as_with::<FungibleTokenPacketAcknowledgement>(&parser)?,
+            FungibleTokenPacketAcknowledgement::Result("AQ==".to_string())
+        );
+        assert!(ics20_ack.ack_as_with::<Count>(&parser).is_err());
+
+        parser.unregister::<CounterAckParser>();
+        assert!(matches!(
+            counter_ack.ack_as_with::<Count>(&parser),
+            Err(InterchainError::AckParserNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn parsers_with_the_same_output_are_all_tried() -> cw_orch::anyhow::Result<()> {
+        let mut parser = IbcAckParser::new();
+        parser.register(RawBytesParser);
+
+        let ack = Binary::from(
+            Acknowledgement {
+                response: Some(Response::Result(vec![1, 2, 3])),
+            }
+            .encode_to_vec(),
+        );
+        assert_eq!(parser.ack_as::<Vec<u8>>(&ack)?, vec![1, 2, 3]);
+        Ok(())
+    }
+}
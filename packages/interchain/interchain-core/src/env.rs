@@ -187,77 +187,18 @@ pub trait InterchainEnv<Chain: IbcQueryHandler> {
         order: Option<IbcOrder>,
     ) -> Result<ChannelCreationResult<Chain>, InterchainError> {
         // We create a channel internally
-        let InternalChannelCreationResult {
-            result,
-            src_connection_id,
-        } = self
+        let internal_result = self
             ._internal_create_channel(src_chain, dst_chain, src_port, dst_port, version, order)
             .map_err(Into::into)?;
 
-        // We create the temporary InterchainChannel Object
-        let mut ibc_channel = InterchainChannel::new(
-            IbcPort {
-                chain: self.get_chain(src_chain).map_err(Into::into)?.ibc_handler(),
-                chain_id: src_chain.to_string(),
-                connection_id: Some(src_connection_id),
-                port: src_port.clone(),
-                channel: None,
-            },
-            IbcPort {
-                chain: self.get_chain(dst_chain).map_err(Into::into)?.ibc_handler(),
-                chain_id: dst_chain.to_string(),
-                connection_id: None,
-                port: dst_port.clone(),
-                channel: None,
-            },
-        );
-
-        // We get the channel creation txs
-        let ChannelCreationTransactionsResult {
-            src_channel_id,
-            dst_channel_id,
-            channel_creation_txs,
-        } = self
-            .get_channel_creation_txs(src_chain, &mut ibc_channel, result)
-            .map_err(Into::into)?;
-
-        // We get the connection on the receiving chain
-        let dst_connection_id = channel_creation_txs
-            .r#try
-            .event_attr_value("channel_open_try", "connection_id")
-            .map_err(InterchainError::StdError)?
-            .clone();
-
-        // We follow all packets that were created in these transactions
-        let packet_results = (
-            self.await_packets(src_chain, channel_creation_txs.init)
-                .map_err(Into::into)?,
-            self.await_packets(dst_chain, channel_creation_txs.r#try)
-                .map_err(Into::into)?,
-            self.await_packets(src_chain, channel_creation_txs.ack)
-                .map_err(Into::into)?,
-            self.await_packets(dst_chain, channel_creation_txs.confirm)
-                .map_err(Into::into)?,
-        );
-
-        // We return them
-        let packet_results = ChannelCreation {
-            init: packet_results.0,
-            r#try: packet_results.1,
-            ack: packet_results.2,
-            confirm: packet_results.3,
-        };
-
-        let (src_port, dst_port) = ibc_channel.get_mut_ordered_ports_from(src_chain)?;
-
-        dst_port.connection_id = Some(dst_connection_id);
-        src_port.channel = Some(src_channel_id);
-        dst_port.channel = Some(dst_channel_id);
-
-        Ok(ChannelCreationResult {
-            interchain_channel: ibc_channel,
-            channel_creation_txs: packet_results,
-        })
+        follow_channel_creation(
+            self,
+            src_chain,
+            dst_chain,
+            src_port,
+            dst_port,
+            internal_result,
+        )
     }
 
     /// This function creates a channel between 2 wasm contracts.
@@ -367,7 +308,7 @@ pub trait InterchainEnv<Chain: IbcQueryHandler> {
     ) -> Result<IbcTxAnalysis<Chain>, Self::Error>;
 
     /// Follow every IBC packets sent out during the transaction
-    /// Parses the acks according to usual ack formats (ICS20, Polytone, ICS-004)
+    /// Parses the acks according to usual ack formats (ICS20, ICS-27, Polytone, ICS-004)
     /// Errors if the acks and't be parsed, correspond to a failed result or there is a timeout
     /// If you only want to await without validation, use [`Self::await_packets`]
     ///
@@ -439,6 +380,100 @@ pub trait InterchainEnv<Chain: IbcQueryHandler> {
     ) -> Result<SimpleIbcPacketAnalysis<Chain>, Self::Error>;
 }
 
+/// Finishes the creation of a channel that was opened internally by the environment
+/// Queries the channel creation txs and follows all packets sent out during the channel creation
+pub(crate) fn follow_channel_creation<
+    Chain: IbcQueryHandler,
+    IBC: InterchainEnv<Chain> + ?Sized,
+>(
+    interchain: &IBC,
+    src_chain: ChainId,
+    dst_chain: ChainId,
+    src_port: &PortId,
+    dst_port: &PortId,
+    internal_result: InternalChannelCreationResult<IBC::ChannelCreationResult>,
+) -> Result<ChannelCreationResult<Chain>, InterchainError> {
+    let InternalChannelCreationResult {
+        result,
+        src_connection_id,
+    } = internal_result;
+
+    // We create the temporary InterchainChannel Object
+    let mut ibc_channel = InterchainChannel::new(
+        IbcPort {
+            chain: interchain
+                .get_chain(src_chain)
+                .map_err(Into::into)?
+                .ibc_handler(),
+            chain_id: src_chain.to_string(),
+            connection_id: Some(src_connection_id),
+            port: src_port.clone(),
+            channel: None,
+        },
+        IbcPort {
+            chain: interchain
+                .get_chain(dst_chain)
+                .map_err(Into::into)?
+                .ibc_handler(),
+            chain_id: dst_chain.to_string(),
+            connection_id: None,
+            port: dst_port.clone(),
+            channel: None,
+        },
+    );
+
+    // We get the channel creation txs
+    let ChannelCreationTransactionsResult {
+        src_channel_id,
+        dst_channel_id,
+        channel_creation_txs,
+    } = interchain
+        .get_channel_creation_txs(src_chain, &mut ibc_channel, result)
+        .map_err(Into::into)?;
+
+    // We get the connection on the receiving chain
+    let dst_connection_id = channel_creation_txs
+        .r#try
+        .event_attr_value("channel_open_try", "connection_id")
+        .map_err(InterchainError::StdError)?
+        .clone();
+
+    // We follow all packets that were created in these transactions
+    let packet_results = (
+        interchain
+            .await_packets(src_chain, channel_creation_txs.init)
+            .map_err(Into::into)?,
+        interchain
+            .await_packets(dst_chain, channel_creation_txs.r#try)
+            .map_err(Into::into)?,
+        interchain
+            .await_packets(src_chain, channel_creation_txs.ack)
+            .map_err(Into::into)?,
+        interchain
+            .await_packets(dst_chain, channel_creation_txs.confirm)
+            .map_err(Into::into)?,
+    );
+
+    // We return them
+    let packet_results = ChannelCreation {
+        init: packet_results.0,
+        r#try: packet_results.1,
+        ack: packet_results.2,
+        confirm: packet_results.3,
+    };
+
+    let (src_port, dst_port) = ibc_channel.get_mut_ordered_ports_from(src_chain)?;
+
+    dst_port.connection_id = Some(dst_connection_id);
+    src_port.channel = Some(src_channel_id);
+    dst_port.channel = Some(dst_channel_id);
+
+    Ok(ChannelCreationResult {
+        interchain_channel: ibc_channel,
+        channel_creation_txs: packet_results,
+    })
+}
+
 /// format the port for a contract
 pub fn contract_port<Chain: CwEnv>(contract: &dyn ContractInstance<Chain>) -> PortId {
     format!("wasm.{}", contract.addr_str().unwrap())
@@ -447,42 +482,43 @@ pub fn contract_port<Chain: CwEnv>(contract: &dyn ContractInstance<Chain>) -> Po
 }
 
 impl<Chain: CwEnv> IbcTxAnalysis<Chain> {
-    /// Tries to parses all acknowledgements into polytone, ics20 and ics004 acks.
+    /// Tries to parses all acknowledgements into polytone, ics27, ics20 and ics004 acks.
     /// Errors if some packet doesn't conform to those results.
     pub fn into_result(&self) -> Result<(), InterchainError> {
-        self.packets.iter().try_for_each(|p| p.into_result())?;
+        self.into_result_with(&IbcAckParser::default())
+    }
+
+    /// Tries to parses all acknowledgements with the registered parsers of `parser` and into polytone, ics27, ics20 and ics004 acks.
+    /// Errors if some packet doesn't conform to those results.
+    pub fn into_result_with(&self, parser: &IbcAckParser) -> Result<(), InterchainError> {
+        self.packets
+            .iter()
+            .try_for_each(|p| p.into_result_with(parser))?;
         Ok(())
     }
 }
 
 impl<Chain: CwEnv> FullIbcPacketAnalysis<Chain> {
-    /// Tries to parses all acknowledgements into polytone, ics20 and ics004 acks.
+    /// Tries to parses all acknowledgements into polytone, ics27, ics20 and ics004 acks.
     /// Errors if some packet doesn't conform to those results.
-    /// Use [`InterchainEnv::parse_ibc`] if you want to handle your own acks
+    /// Use [`Self::into_result_with`] if you want to handle your own acks
     pub fn into_result(&self) -> Result<(), InterchainError> {
+        self.into_result_with(&IbcAckParser::default())
+    }
+
+    /// Tries to parses all acknowledgements with the registered parsers of `parser` and into polytone, ics27, ics20 and ics004 acks.
+    /// Errors if some packet doesn't conform to those results.
+    pub fn into_result_with(&self, parser: &IbcAckParser) -> Result<(), InterchainError> {
         match &self.outcome {
             IbcPacketOutcome::Success {
                 ack,
                 receive_tx,
                 ack_tx,
             } => {
-                receive_tx.into_result()?;
-                ack_tx.into_result()?;
-
-                if IbcAckParser::polytone_ack(ack).is_ok() {
-                    return Ok(());
-                }
-                if IbcAckParser::ics20_ack(ack).is_ok() {
-                    return Ok(());
-                }
-                if IbcAckParser::ics004_ack(ack).is_ok() {
-                    return Ok(());
-                }
-
-                Err(InterchainError::AckDecodingFailed(
-                    ack.clone(),
-                    String::from_utf8_lossy(ack.as_slice()).to_string(),
-                ))
+                receive_tx.into_result_with(parser)?;
+                ack_tx.into_result_with(parser)?;
+
+                parser.any_ack(ack)
             }
             IbcPacketOutcome::Timeout { .. } => Err(InterchainError::PacketTimeout {}),
         }
@@ -43,7 +43,7 @@ pub enum InterchainError {
     PacketTimeout {},
 
     #[error(
-        "Acknowledgement decoding failed with ack: {0:x?}, tried decoding it as json : {1:x?}. Try using `into_result_custom` instead !"
+        "Acknowledgement decoding failed with ack: {0:x?}, tried decoding it as json : {1:x?}. Try registering a parser with `IbcAckParser::register` and checking the acks with `into_result_with` !"
     )]
     AckDecodingFailed(Binary, String),
 
@@ -58,4 +58,19 @@ pub enum InterchainError {
 
     #[error("Failure acknowledgment received: {0:?}")]
     FailedAckReceived(String),
+
+    #[error("No acknowledgement parser registered for {0}")]
+    AckParserNotFound(String),
+
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    ProstDecode(#[from] prost::DecodeError),
+
+    #[error("Interchain account address not found in the channel version {0}")]
+    IcaAddressNotFound(String),
+
+    #[error("No message response at index {0}")]
+    MsgResponseNotFound(usize),
 }
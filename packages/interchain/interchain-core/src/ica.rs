@@ -0,0 +1,434 @@
+//! Helpers for using ICS-27 interchain accounts from a controller chain.
+//!
+//! An [`InterchainAccount`] is registered on a controller chain, which opens a channel with the `icahost` port of the host chain.
+//! The account then executes protobuf messages on the host chain and returns the responses of those messages in its acknowledgements.
+//!
+//! The helpers work with any [`InterchainAccountEnv`], the interchain environments whose chains run the ICS-27 modules.
+//! The `DaemonInterchainEnv` sends the controller messages to actual chains (e.g. Starship) and the `MockInterchainEnv` implements the ICS-27 modules itself.
+//!
+//! ```rust,no_run
+//! use cw_orch::prelude::*;
+//! use cw_orch_interchain::prelude::*;
+//! use cw_orch_interchain::ica::InterchainAccount;
+//! use cosmos_sdk_proto::{
+//!     cosmos::{
+//!         bank::v1beta1::{MsgSend, MsgSendResponse},
+//!         base::v1beta1::Coin,
+//!     },
+//!     traits::{Message, Name},
+//!     Any,
+//! };
+//! use std::time::Duration;
+//!
+//! let starship = Starship::new(None).unwrap();
+//! let interchain = starship.interchain_env();
+//!
+//! // The account of the sender of juno is registered on osmosis
+//! let ica = InterchainAccount::register(&interchain, "juno-1", "osmosis-1", "connection-0").unwrap();
+//!
+//! // The account sends funds it holds on osmosis
+//! let response = ica
+//!     .execute(
+//!         &interchain,
+//!         vec![Any {
+//!             type_url: MsgSend::type_url(),
+//!             value: MsgSend {
+//!                 from_address: ica.address.clone(),
+//!                 to_address: "osmo1hhgdc2agn8wtqr8fqyh4wdqwnvpvmhgjqfr32p".to_string(),
+//!                 amount: vec![Coin {
+//!                     denom: "uosmo".to_string(),
+//!                     amount: "100000".to_string(),
+//!                 }],
+//!             }
+//!             .encode_to_vec(),
+//!         }],
+//!         None,
+//!         Duration::from_secs(600),
+//!     )
+//!     .unwrap();
+//! let _send_response: MsgSendResponse = response.msg_response(0).unwrap();
+//! ```
+
+use std::time::Duration;
+
+use base64::{engine::general_purpose, Engine};
+use cosmos_sdk_proto::{
+    cosmos::base::abci::v1beta1::TxMsgData,
+    ibc::applications::interchain_accounts::v1::{CosmosTx, InterchainAccountPacketData, Type},
+};
+use cosmwasm_std::{from_json, Binary};
+use cw_orch_core::environment::{IndexResponse, TxHandler};
+use ibc_relayer_types::core::ics24_host::identifier::PortId;
+use prost::{Message, Name};
+use prost_types::Any;
+
+use crate::{
+    ack_parser::{AckParser, FungibleTokenPacketAcknowledgement},
+    channel::InterchainChannel,
+    env::{follow_channel_creation, ChainId},
+    types::{ChannelCreationResult, IbcTxAnalysis, InternalChannelCreationResult},
+    IbcQueryHandler, InterchainEnv, InterchainError,
+};
+
+/// Port of the ICS-27 host module
+pub const ICA_HOST_PORT: &str = "icahost";
+
+/// Interchain environment whose chains run the ICS-27 controller and host modules.
+///
+/// The environment commits the messages of the controller module, [`InterchainAccount`] follows the channels and packets they create
+pub trait InterchainAccountEnv<Chain: IbcQueryHandler>: InterchainEnv<Chain> {
+    /// Commits a [`MsgRegisterInterchainAccount`] on the controller chain, which initializes the handshake of the account channel
+    /// This code is only for internal use and for most cases shouldn't be used outside of the [`InterchainAccount::register`] function
+    fn _internal_register_interchain_account(
+        &self,
+        controller_chain: ChainId,
+        msg: MsgRegisterInterchainAccount,
+    ) -> Result<<Chain as TxHandler>::Response, Self::Error>;
+
+    /// Commits a [`MsgSendTx`] on the controller chain, which sends the ICS-27 packet of the account
+    /// This code is only for internal use and for most cases shouldn't be used outside of the [`InterchainAccount::send_msgs`] function
+    fn _internal_send_interchain_account_tx(
+        &self,
+        controller_chain: ChainId,
+        msg: MsgSendTx,
+    ) -> Result<<Chain as TxHandler>::Response, Self::Error>;
+
+    /// Follows the handshake of a channel that was initialized on `src_chain` in `channel_init_tx` by the ICS-27 controller module
+    /// Returns a channel creation receipt as well as as the connection_id on the src_chain side, like [`InterchainEnv::_internal_create_channel`]
+    /// This code is only for internal use and for most cases shouldn't be used outside of the [`InterchainAccountEnv::await_channel_handshake`] function
+    fn _internal_await_channel_handshake(
+        &self,
+        src_chain: ChainId,
+        dst_chain: ChainId,
+        channel_init_tx: &<Chain as TxHandler>::Response,
+    ) -> Result<InternalChannelCreationResult<Self::ChannelCreationResult>, Self::Error>;
+
+    /// Awaits the opening of a channel whose handshake was initialized on `src_chain` in `channel_init_tx`
+    /// This is used for the channels that are not opened by [`InterchainEnv::create_channel`], like the ones initialized by the ICS-27 controller module when registering an interchain account
+    /// Returns the same result as [`InterchainEnv::create_channel`]
+    fn await_channel_handshake(
+        &self,
+        src_chain: ChainId,
+        dst_chain: ChainId,
+        channel_init_tx: <Chain as TxHandler>::Response,
+    ) -> Result<ChannelCreationResult<Chain>, InterchainError> {
+        let src_port: PortId = channel_init_tx
+            .event_attr_value("channel_open_init", "port_id")?
+            .parse()?;
+        let dst_port: PortId = channel_init_tx
+            .event_attr_value("channel_open_init", "counterparty_port_id")?
+            .parse()?;
+
+        let internal_result = self
+            ._internal_await_channel_handshake(src_chain, dst_chain, &channel_init_tx)
+            .map_err(Into::into)?;
+
+        follow_channel_creation(
+            self,
+            src_chain,
+            dst_chain,
+            &src_port,
+            &dst_port,
+            internal_result,
+        )
+    }
+}
+
+/// Interchain account registered by an owner on a controller chain
+#[derive(Clone)]
+pub struct InterchainAccount<Chain: IbcQueryHandler> {
+    /// Address of the owner of the account, on the controller chain
+    pub owner: String,
+    /// Address of the account, on the host chain
+    pub address: String,
+    /// Channel used by the account. `port_a` is the controller port and `port_b` the host port
+    pub channel: InterchainChannel<<Chain as IbcQueryHandler>::Handler>,
+}
+
+impl<Chain: IbcQueryHandler> InterchainAccount<Chain> {
+    /// Chain id of the controller chain
+    pub fn controller_chain_id(&self) -> &str {
+        &self.channel.port_a.chain_id
+    }
+
+    /// Chain id of the host chain
+    pub fn host_chain_id(&self) -> &str {
+        &self.channel.port_b.chain_id
+    }
+
+    /// Connection used by the account, on the controller chain
+    pub fn connection_id(&self) -> String {
+        self.channel
+            .port_a
+            .connection_id
+            .clone()
+            .unwrap_or_default()
+    }
+}
+
+impl<Chain: IbcQueryHandler> InterchainAccount<Chain> {
+    /// Registers an interchain account for the sender of the controller chain on the host chain.
+    ///
+    /// Awaits the opening of the account channel and resolves the address of the account from the version of the channel.
+    /// `connection_id` is the connection with the host chain, on the controller chain
+    pub fn register<IBC: InterchainAccountEnv<Chain>>(
+        interchain: &IBC,
+        controller_chain: ChainId,
+        host_chain: ChainId,
+        connection_id: &str,
+    ) -> Result<Self, InterchainError> {
+        let controller = interchain.get_chain(controller_chain).map_err(Into::into)?;
+        let owner = controller.sender_addr().to_string();
+
+        // The controller module fills the version with the default metadata of the connection
+        let register_msg = MsgRegisterInterchainAccount {
+            owner: owner.clone(),
+            connection_id: connection_id.to_string(),
+            version: String::new(),
+        };
+        let register_tx = interchain
+            ._internal_register_interchain_account(controller_chain, register_msg)
+            .map_err(Into::into)?;
+
+        let channel_creation =
+            interchain.await_channel_handshake(controller_chain, host_chain, register_tx)?;
+
+        // The host module sets the address of the account in the version of the channel
+        let version = channel_creation
+            .channel_creation_txs
+            .r#try
+            .tx_id
+            .response
+            .event_attr_value("channel_open_try", "version")?;
+        let address = serde_json::from_str::<serde_json::Value>(&version)?
+            .get("address")
+            .and_then(|address| address.as_str())
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| InterchainError::IcaAddressNotFound(version.clone()))?
+            .to_string();
+
+        log::info!(
+            "Registered interchain account {} on {} for {} on {}",
+            address,
+            host_chain,
+            owner,
+            controller_chain
+        );
+
+        Ok(Self {
+            owner,
+            address,
+            channel: channel_creation.interchain_channel,
+        })
+    }
+
+    /// Sends messages to be executed by the account on the host chain and follows the ICS-27 packet.
+    ///
+    /// The packet times out if it's not received on the host chain `timeout` after being sent
+    pub fn send_msgs<IBC: InterchainAccountEnv<Chain>>(
+        &self,
+        interchain: &IBC,
+        msgs: Vec<Any>,
+        memo: Option<&str>,
+        timeout: Duration,
+    ) -> Result<IbcTxAnalysis<Chain>, InterchainError> {
+        let packet_data = InterchainAccountPacketData {
+            r#type: Type::ExecuteTx as i32,
+            data: CosmosTx { messages: msgs }.encode_to_vec(),
+            memo: memo.unwrap_or_default().to_string(),
+        };
+        let send_msg = MsgSendTx {
+            owner: self.owner.clone(),
+            connection_id: self.connection_id(),
+            packet_data: Some(packet_data),
+            relative_timeout: timeout.as_nanos() as u64,
+        };
+        let send_tx = interchain
+            ._internal_send_interchain_account_tx(self.controller_chain_id(), send_msg)
+            .map_err(Into::into)?;
+
+        interchain
+            .await_packets(self.controller_chain_id(), send_tx)
+            .map_err(Into::into)
+    }
+
+    /// Executes messages with the account on the host chain and returns the responses of those messages.
+    ///
+    /// Errors if the packet timed out or if the execution failed on the host chain
+    pub fn execute<IBC: InterchainAccountEnv<Chain>>(
+        &self,
+        interchain: &IBC,
+        msgs: Vec<Any>,
+        memo: Option<&str>,
+        timeout: Duration,
+    ) -> Result<IcaTxResponse, InterchainError> {
+        let analysis = self.send_msgs(interchain, msgs, memo, timeout)?;
+        analysis.into_result()?;
+
+        analysis
+            .packets
+            .first()
+            .ok_or(InterchainError::NoPacketsFound {})?
+            .ack_as()
+    }
+}
+
+/// Responses of the messages executed by an interchain account, decoded from the acknowledgement of the ICS-27 packet
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcaTxResponse {
+    /// Responses of the executed messages, in the order the messages were sent
+    pub msg_responses: Vec<Any>,
+}
+
+impl IcaTxResponse {
+    /// Decodes the response of the message at `index`
+    pub fn msg_response<M: Message + Default>(&self, index: usize) -> Result<M, InterchainError> {
+        let response = self
+            .msg_responses
+            .get(index)
+            .ok_or(InterchainError::MsgResponseNotFound(index))?;
+        Ok(M::decode(response.value.as_slice())?)
+    }
+}
+
+/// Parser of the ICS-27 acks, the responses of the successful executions are returned
+pub struct IcaAckParser;
+
+impl AckParser for IcaAckParser {
+    type Output = IcaTxResponse;
+
+    fn parse(&self, ack: &Binary) -> Result<Option<IcaTxResponse>, InterchainError> {
+        match from_json(ack) {
+            Ok(FungibleTokenPacketAcknowledgement::Result(result)) => {
+                let Some(tx_msg_data) = general_purpose::STANDARD
+                    .decode(result)
+                    .ok()
+                    .and_then(|data| TxMsgData::decode(data.as_slice()).ok())
+                else {
+                    return Ok(None);
+                };
+                Ok(Some(IcaTxResponse {
+                    msg_responses: tx_msg_data.msg_responses,
+                }))
+            }
+            Ok(FungibleTokenPacketAcknowledgement::Error(e)) => {
+                Err(InterchainError::FailedAckReceived(e))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+// The controller messages of ibc-go are not part of cosmos-sdk-proto, we copy them here
+
+/// MsgRegisterInterchainAccount defines the payload for Msg/RegisterAccount
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgRegisterInterchainAccount {
+    /// Owner of the account, on the controller chain
+    #[prost(string, tag = "1")]
+    pub owner: ::prost::alloc::string::String,
+    /// Connection with the host chain, on the controller chain
+    #[prost(string, tag = "2")]
+    pub connection_id: ::prost::alloc::string::String,
+    /// Version of the account channel, filled by the controller module if empty
+    #[prost(string, tag = "3")]
+    pub version: ::prost::alloc::string::String,
+}
+
+impl Name for MsgRegisterInterchainAccount {
+    const NAME: &'static str = "MsgRegisterInterchainAccount";
+    const PACKAGE: &'static str = "ibc.applications.interchain_accounts.controller.v1";
+}
+
+/// MsgRegisterInterchainAccountResponse defines the response for Msg/RegisterAccount
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgRegisterInterchainAccountResponse {
+    /// Channel of the account, on the controller chain
+    #[prost(string, tag = "1")]
+    pub channel_id: ::prost::alloc::string::String,
+    /// Port of the account, on the controller chain
+    #[prost(string, tag = "2")]
+    pub port_id: ::prost::alloc::string::String,
+}
+
+/// MsgSendTx defines the payload for Msg/SendTx
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgSendTx {
+    /// Owner of the account, on the controller chain
+    #[prost(string, tag = "1")]
+    pub owner: ::prost::alloc::string::String,
+    /// Connection with the host chain, on the controller chain
+    #[prost(string, tag = "2")]
+    pub connection_id: ::prost::alloc::string::String,
+    /// Messages executed by the account
+    #[prost(message, optional, tag = "3")]
+    pub packet_data: ::core::option::Option<InterchainAccountPacketData>,
+    /// Relative timeout timestamp provided will be added to the current block time during transaction execution.
+    /// The timeout timestamp must be non-zero.
+    #[prost(uint64, tag = "4")]
+    pub relative_timeout: u64,
+}
+
+impl Name for MsgSendTx {
+    const NAME: &'static str = "MsgSendTx";
+    const PACKAGE: &'static str = "ibc.applications.interchain_accounts.controller.v1";
+}
+
+/// MsgSendTxResponse defines the response for MsgSendTx
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MsgSendTxResponse {
+    /// Sequence of the ICS-27 packet
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSendResponse;
+    use cosmwasm_std::to_json_binary;
+
+    use super::*;
+    use crate::IbcAckParser;
+
+    #[test]
+    fn ica_acks_decode_msg_responses() -> cw_orch::anyhow::Result<()> {
+        let tx_msg_data = TxMsgData {
+            msg_responses: vec![Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSendResponse".to_string(),
+                value: MsgSendResponse {}.encode_to_vec(),
+            }],
+            ..Default::default()
+        };
+        let ack = to_json_binary(&FungibleTokenPacketAcknowledgement::Result(
+            general_purpose::STANDARD.encode(tx_msg_data.encode_to_vec()),
+        ))?;
+
+        let parser = IbcAckParser::default();
+        parser.any_ack(&ack)?;
+        let response: IcaTxResponse = parser.ack_as(&ack)?;
+        let _: MsgSendResponse = response.msg_response(0)?;
+        assert!(matches!(
+            response.msg_response::<MsgSendResponse>(1),
+            Err(InterchainError::MsgResponseNotFound(1))
+        ));
+
+        let error_ack = to_json_binary(&FungibleTokenPacketAcknowledgement::Error(
+            "ABCI code: 5: error handling packet: see events for details".to_string(),
+        ))?;
+        assert!(matches!(
+            parser.ack_as::<IcaTxResponse>(&error_ack),
+            Err(InterchainError::FailedAckReceived(_))
+        ));
+
+        // ICS-20 acks are not ICS-27 acks
+        let ics20_ack = to_json_binary(&FungibleTokenPacketAcknowledgement::Result(
+            "AQ==".to_string(),
+        ))?;
+        assert!(IcaAckParser.parse(&ics20_ack)?.is_none());
+        Ok(())
+    }
+}
@@ -8,6 +8,8 @@ pub mod channel;
 /// Contains definitions of the main trait exposed by this crate
 pub mod env;
 
+pub mod ica;
+
 /// Contains default acknowledgment parsers
 mod ack_parser;
 mod error;
@@ -15,7 +17,10 @@ mod error;
 /// Type definition for interchain structure and return types
 pub mod types;
 
-pub use ack_parser::IbcAckParser;
+pub use ack_parser::{
+    AckParser, FungibleTokenPacketAcknowledgement, IbcAckParser, Ics004AckParser, Ics20AckParser,
+    PolytoneAckParser,
+};
 pub use env::InterchainEnv;
 pub use error::InterchainError;
 pub use types::IbcQueryHandler;
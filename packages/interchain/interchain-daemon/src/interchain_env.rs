@@ -1,9 +1,11 @@
+use cosmrs::proto::traits::{Message, Name};
 use cosmwasm_std::IbcOrder;
 use cw_orch_core::environment::{ChainInfoOwned, ChainState, IndexResponse};
 use cw_orch_daemon::queriers::{Ibc, Node};
-use cw_orch_daemon::{CosmTxResponse, Daemon, DaemonError, RUNTIME};
+use cw_orch_daemon::{CosmTxResponse, Daemon, DaemonError, TxSender, RUNTIME};
 use cw_orch_interchain_core::channel::{IbcPort, InterchainChannel};
 use cw_orch_interchain_core::env::{ChainId, ChannelCreation};
+use cw_orch_interchain_core::ica::{InterchainAccountEnv, MsgRegisterInterchainAccount, MsgSendTx};
 use cw_orch_interchain_core::InterchainEnv;
 
 use ibc_relayer_types::core::ics04_channel::packet::Sequence;
@@ -291,7 +293,59 @@ impl<C: ChannelCreator> InterchainEnv<Daemon> for DaemonInterchainEnv<C> {
     }
 }
 
+// The messages of the controller module are sent to the controller chain, its ICS-27 modules open the channels and send the packets
+impl<C: ChannelCreator> InterchainAccountEnv<Daemon> for DaemonInterchainEnv<C> {
+    fn _internal_register_interchain_account(
+        &self,
+        controller_chain: ChainId,
+        msg: MsgRegisterInterchainAccount,
+    ) -> Result<CosmTxResponse, Self::Error> {
+        self.commit_controller_msg(controller_chain, msg)
+    }
+
+    fn _internal_send_interchain_account_tx(
+        &self,
+        controller_chain: ChainId,
+        msg: MsgSendTx,
+    ) -> Result<CosmTxResponse, Self::Error> {
+        self.commit_controller_msg(controller_chain, msg)
+    }
+
+    // The handshake is relayed by the external relayer, the channel creation txs are then found like for any other channel
+    fn _internal_await_channel_handshake(
+        &self,
+        _src_chain: ChainId,
+        _dst_chain: ChainId,
+        channel_init_tx: &CosmTxResponse,
+    ) -> Result<InternalChannelCreationResult<()>, Self::Error> {
+        let connection_id =
+            channel_init_tx.event_attr_value("channel_open_init", "connection_id")?;
+
+        Ok(InternalChannelCreationResult {
+            result: (),
+            src_connection_id: connection_id,
+        })
+    }
+}
+
 impl<C: ChannelCreator> DaemonInterchainEnv<C> {
+    /// Commits a message of the ICS-27 controller module on the controller chain
+    fn commit_controller_msg<M: Message + Name>(
+        &self,
+        controller_chain: ChainId,
+        msg: M,
+    ) -> IcDaemonResult<CosmTxResponse> {
+        let controller = self.get_chain(controller_chain)?;
+        let tx_response = self.rt_handle.block_on(controller.sender().commit_tx_any(
+            vec![cosmrs::Any {
+                type_url: M::type_url(),
+                value: msg.encode_to_vec(),
+            }],
+            None,
+        ))?;
+        Ok(tx_response)
+    }
+
     /// This function follows every IBC packet sent out in a tx result
     /// This allows only providing the transaction hash when you don't have access to the whole response object
     ///
@@ -24,6 +24,21 @@ pub enum InterchainMockError {
 
     #[error("mock for chain {0} not found")]
     MockNotFound(String),
+
+    #[error("No packet at index {0} in the queue")]
+    PacketNotFound(usize),
+
+    #[error("The mock interchain can't relay the handshake of the channel initialized on {0} for port {1}, channels need to be created with `create_channel`")]
+    ChannelHandshakeNotRelayed(String, String),
+
+    #[error("Denom trace {0} not found on chain {1}")]
+    DenomTraceNotFound(String, String),
+
+    #[error("Invalid forward timeout {0}")]
+    InvalidForwardTimeout(String),
+
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
 }
 
 impl From<InterchainMockError> for InterchainError {
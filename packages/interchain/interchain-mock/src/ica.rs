@@ -0,0 +1,590 @@
+//! ICS-27 interchain accounts of the mock chains.
+//!
+//! cw-multi-test doesn't implement the ICS-27 modules, the mock interchain runs them on top of the connections of the chains:
+//! - Registering an account initializes the channel of its `icacontroller-{owner}` port, the handshake is relayed by [`InterchainAccountEnv::await_channel_handshake`].
+//! - The packets of the accounts are relayed by [`InterchainEnv::await_packets`], the host chain executes their messages with the account.
+//!
+//! The channels of the accounts are tracked by the mock interchain, they're numbered `channel-ica-{n}` apart from the channels of cw-multi-test.
+//! The accounts execute the bank `MsgSend` and the wasm `MsgExecuteContract` messages.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use cosmrs::proto::{
+    cosmos::{
+        bank::v1beta1::{MsgSend, MsgSendResponse},
+        base::{abci::v1beta1::TxMsgData, v1beta1::Coin as ProtoCoin},
+    },
+    cosmwasm::wasm::v1::{MsgExecuteContract, MsgExecuteContractResponse},
+    ibc::applications::interchain_accounts::v1::{CosmosTx, Type},
+    traits::{Message, Name},
+    Any,
+};
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Event, Timestamp,
+    WasmMsg,
+};
+use cw_orch_core::environment::IndexResponse;
+use cw_orch_interchain_core::{
+    env::ChainId,
+    ica::{
+        InterchainAccountEnv, MsgRegisterInterchainAccount, MsgRegisterInterchainAccountResponse,
+        MsgSendTx, MsgSendTxResponse, ICA_HOST_PORT,
+    },
+    types::{IbcPacketOutcome, InternalChannelCreationResult, TxId},
+    InterchainEnv,
+};
+use cw_orch_mock::cw_multi_test::{
+    ibc::{
+        relayer::ChannelCreationResult,
+        types::{Connection, MockIbcQuery},
+    },
+    AppResponse,
+};
+use serde_json::json;
+
+use crate::{
+    ics20::derive_address,
+    interchain::{MockBase, MockInterchainEnvBase},
+    InterchainMockError,
+};
+
+/// Prefix of the ports of the interchain accounts on the controller chain
+const ICA_CONTROLLER_PORT_PREFIX: &str = "icacontroller-";
+/// Version of the ICS-27 channels
+const ICA_VERSION: &str = "ics27-1";
+
+/// Channel of an interchain account
+struct IcaChannel {
+    host_chain_id: String,
+    controller_connection_id: String,
+    host_connection_id: String,
+    controller_channel_id: String,
+    /// Set once the handshake is relayed
+    host_channel_id: Option<String>,
+    /// Address of the account on the host chain, set once the handshake is relayed
+    address: Option<String>,
+    /// Ordered channels are closed when one of their packets times out
+    closed: bool,
+    next_sequence: u64,
+}
+
+/// Packet sent by an interchain account
+struct IcaPacket {
+    msgs: Vec<Any>,
+    packet_type: i32,
+    timeout: Timestamp,
+    relayed: bool,
+}
+
+/// ICS-27 state of the chains of an interchain environment
+#[derive(Default)]
+pub(crate) struct IcaChains {
+    /// Channels of the accounts, by controller chain and port
+    channels: HashMap<(String, String), IcaChannel>,
+    /// Packets of the accounts, by controller chain, port and sequence
+    packets: HashMap<(String, String, u64), IcaPacket>,
+    /// Number of channels opened for interchain accounts, by chain
+    channel_count: HashMap<String, u64>,
+}
+
+/// ICS-27 state of the chains of an interchain environment, shared by its clones
+pub(crate) type IcaState = Rc<RefCell<IcaChains>>;
+
+impl<A: Api> InterchainAccountEnv<MockBase<A>> for MockInterchainEnvBase<A> {
+    // The channel is initialized on the controller chain only, its handshake is relayed with `await_channel_handshake`
+    fn _internal_register_interchain_account(
+        &self,
+        controller_chain: ChainId,
+        msg: MsgRegisterInterchainAccount,
+    ) -> Result<AppResponse, InterchainMockError> {
+        let controller = self.get_chain(controller_chain)?;
+        let port_id = format!("{ICA_CONTROLLER_PORT_PREFIX}{}", msg.owner);
+
+        let connection: Connection = from_json(controller.app.borrow().ibc_query(
+            MockIbcQuery::ConnectedChain {
+                connection_id: msg.connection_id.clone(),
+            },
+        )?)?;
+        let host = self.get_chain(&connection.counterparty_chain_id)?;
+        // The host chain is connected to the controller chain on its first connection with it
+        let host_connections: Vec<(String, Connection)> = from_json(host.app.borrow().ibc_query(
+            MockIbcQuery::ChainConnections {
+                chain_id: controller_chain.to_string(),
+            },
+        )?)?;
+        let (host_connection_id, _) = host_connections.into_iter().next().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No connection of {} with {}",
+                connection.counterparty_chain_id,
+                controller_chain
+            )
+        })?;
+
+        let mut ica = self.ica.borrow_mut();
+        let key = (controller_chain.to_string(), port_id.clone());
+        if ica
+            .channels
+            .get(&key)
+            .is_some_and(|channel| !channel.closed)
+        {
+            return Err(anyhow::anyhow!(
+                "An interchain account is already registered for {} on {}",
+                msg.owner,
+                msg.connection_id
+            )
+            .into());
+        }
+
+        let channel_count = ica
+            .channel_count
+            .entry(controller_chain.to_string())
+            .or_default();
+        let controller_channel_id = format!("channel-ica-{channel_count}");
+        *channel_count += 1;
+
+        let version = ica_version(&msg.connection_id, &host_connection_id, "");
+        ica.channels.insert(
+            key,
+            IcaChannel {
+                host_chain_id: connection.counterparty_chain_id,
+                controller_connection_id: msg.connection_id.clone(),
+                host_connection_id,
+                controller_channel_id: controller_channel_id.clone(),
+                host_channel_id: None,
+                address: None,
+                closed: false,
+                next_sequence: 1,
+            },
+        );
+
+        Ok(AppResponse {
+            events: vec![Event::new("channel_open_init")
+                .add_attribute("port_id", &port_id)
+                .add_attribute("channel_id", &controller_channel_id)
+                .add_attribute("counterparty_port_id", ICA_HOST_PORT)
+                .add_attribute("connection_id", &msg.connection_id)
+                .add_attribute("version", version)],
+            data: Some(
+                MsgRegisterInterchainAccountResponse {
+                    channel_id: controller_channel_id,
+                    port_id,
+                }
+                .encode_to_vec()
+                .into(),
+            ),
+        })
+    }
+
+    fn _internal_send_interchain_account_tx(
+        &self,
+        controller_chain: ChainId,
+        msg: MsgSendTx,
+    ) -> Result<AppResponse, InterchainMockError> {
+        let controller = self.get_chain(controller_chain)?;
+        let port_id = format!("{ICA_CONTROLLER_PORT_PREFIX}{}", msg.owner);
+        let packet_data = msg
+            .packet_data
+            .ok_or_else(|| anyhow::anyhow!("The ICS-27 packet data is missing"))?;
+        if msg.relative_timeout == 0 {
+            return Err(
+                anyhow::anyhow!("The timeout of the ICS-27 packet must be non-zero").into(),
+            );
+        }
+        let msgs = CosmosTx::decode(packet_data.data.as_slice())
+            .map_err(|e| anyhow::anyhow!("Invalid ICS-27 packet data: {e}"))?
+            .messages;
+
+        let mut ica = self.ica.borrow_mut();
+        let channel = ica
+            .channels
+            .get_mut(&(controller_chain.to_string(), port_id.clone()))
+            .filter(|channel| {
+                channel.controller_connection_id == msg.connection_id
+                    && channel.host_channel_id.is_some()
+                    && !channel.closed
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No open interchain account channel for {} on {}",
+                    msg.owner,
+                    msg.connection_id
+                )
+            })?;
+        let sequence = channel.next_sequence;
+        channel.next_sequence += 1;
+
+        let timeout = controller
+            .app
+            .borrow()
+            .block_info()
+            .time
+            .plus_nanos(msg.relative_timeout);
+        let event = Event::new("send_packet")
+            .add_attribute("packet_connection", &channel.controller_connection_id)
+            .add_attribute("packet_src_port", &port_id)
+            .add_attribute("packet_src_channel", &channel.controller_channel_id)
+            .add_attribute("packet_dst_port", ICA_HOST_PORT)
+            .add_attribute(
+                "packet_dst_channel",
+                channel.host_channel_id.clone().unwrap_or_default(),
+            )
+            .add_attribute("packet_sequence", sequence.to_string())
+            .add_attribute("packet_timeout_timestamp", timeout.nanos().to_string())
+            .add_attribute("packet_channel_ordering", "ORDER_ORDERED")
+            .add_attribute(
+                "packet_data",
+                json!({
+                    "type": packet_data.r#type,
+                    "data": Binary::from(packet_data.data.clone()),
+                    "memo": packet_data.memo,
+                })
+                .to_string(),
+            );
+        ica.packets.insert(
+            (controller_chain.to_string(), port_id, sequence),
+            IcaPacket {
+                msgs,
+                packet_type: packet_data.r#type,
+                timeout,
+                relayed: false,
+            },
+        );
+
+        Ok(AppResponse {
+            events: vec![event],
+            data: Some(MsgSendTxResponse { sequence }.encode_to_vec().into()),
+        })
+    }
+
+    // Only the handshakes of the interchain accounts channels are relayed, the other channels are opened by the mock relayer in `create_channel`
+    fn _internal_await_channel_handshake(
+        &self,
+        src_chain: ChainId,
+        _dst_chain: ChainId,
+        channel_init_tx: &AppResponse,
+    ) -> Result<InternalChannelCreationResult<ChannelCreationResult>, InterchainMockError> {
+        if let Some(channel_creation) = self.relay_ica_handshake(src_chain, channel_init_tx)? {
+            return Ok(channel_creation);
+        }
+        let port_id = channel_init_tx.event_attr_value("channel_open_init", "port_id")?;
+        Err(InterchainMockError::ChannelHandshakeNotRelayed(
+            src_chain.to_string(),
+            port_id,
+        ))
+    }
+}
+
+impl<A: Api> MockInterchainEnvBase<A> {
+    /// Relays the handshake of the channel of an interchain account registered in `channel_init_tx`.
+    /// Returns `None` when the channel was not initialized for an interchain account.
+    pub(crate) fn relay_ica_handshake(
+        &self,
+        controller_chain: ChainId,
+        channel_init_tx: &AppResponse,
+    ) -> Result<Option<InternalChannelCreationResult<ChannelCreationResult>>, InterchainMockError>
+    {
+        let port_id = channel_init_tx.event_attr_value("channel_open_init", "port_id")?;
+        let mut ica = self.ica.borrow_mut();
+        let ica = &mut *ica;
+        let Some(channel) = ica
+            .channels
+            .get_mut(&(controller_chain.to_string(), port_id.clone()))
+        else {
+            return Ok(None);
+        };
+        if channel.host_channel_id.is_some() {
+            return Err(anyhow::anyhow!(
+                "The handshake of channel {} was already relayed",
+                channel.controller_channel_id
+            )
+            .into());
+        }
+
+        let host = self.get_chain(&channel.host_chain_id)?;
+        // The account is derived from the connection and controller port, like the host module does
+        let address = derive_address(
+            &host,
+            &format!("{ICA_VERSION}/{}/{}", channel.host_connection_id, port_id),
+        )
+        .to_string();
+        let channel_count = ica
+            .channel_count
+            .entry(channel.host_chain_id.clone())
+            .or_default();
+        let host_channel_id = format!("channel-ica-{channel_count}");
+        *channel_count += 1;
+        let version = ica_version(
+            &channel.controller_connection_id,
+            &channel.host_connection_id,
+            &address,
+        );
+
+        let handshake_event = |ty: &str,
+                               port: &str,
+                               channel_id: &str,
+                               counterparty_port: &str,
+                               counterparty_channel: &str,
+                               connection_id: &str| {
+            AppResponse {
+                events: vec![Event::new(ty)
+                    .add_attribute("port_id", port)
+                    .add_attribute("channel_id", channel_id)
+                    .add_attribute("counterparty_port_id", counterparty_port)
+                    .add_attribute("counterparty_channel_id", counterparty_channel)
+                    .add_attribute("connection_id", connection_id)
+                    .add_attribute("version", &version)],
+                data: None,
+            }
+        };
+        let r#try = handshake_event(
+            "channel_open_try",
+            ICA_HOST_PORT,
+            &host_channel_id,
+            &port_id,
+            &channel.controller_channel_id,
+            &channel.host_connection_id,
+        );
+        let ack = handshake_event(
+            "channel_open_ack",
+            &port_id,
+            &channel.controller_channel_id,
+            ICA_HOST_PORT,
+            &host_channel_id,
+            &channel.controller_connection_id,
+        );
+        let confirm = handshake_event(
+            "channel_open_confirm",
+            ICA_HOST_PORT,
+            &host_channel_id,
+            &port_id,
+            &channel.controller_channel_id,
+            &channel.host_connection_id,
+        );
+
+        channel.host_channel_id = Some(host_channel_id.clone());
+        channel.address = Some(address);
+
+        Ok(Some(InternalChannelCreationResult {
+            result: ChannelCreationResult {
+                src_channel: channel.controller_channel_id.clone(),
+                dst_channel: host_channel_id,
+                init: channel_init_tx.clone(),
+                r#try,
+                ack,
+                confirm,
+            },
+            src_connection_id: channel.controller_connection_id.clone(),
+        }))
+    }
+
+    /// Timeout of a packet sent by an interchain account, `None` when the packet wasn't sent by an interchain account
+    pub(crate) fn ica_packet_timeout(
+        &self,
+        controller_chain: ChainId,
+        port_id: &str,
+        sequence: u64,
+    ) -> Option<Timestamp> {
+        self.ica
+            .borrow()
+            .packets
+            .get(&(controller_chain.to_string(), port_id.to_string(), sequence))
+            .map(|packet| packet.timeout)
+    }
+
+    /// Relays a packet sent by an interchain account and delivers its acknowledgement or timeout.
+    /// Returns `None` when the packet wasn't sent by an interchain account.
+    pub(crate) fn relay_ica_packet(
+        &self,
+        controller_chain: ChainId,
+        port_id: &str,
+        sequence: u64,
+    ) -> Result<Option<IbcPacketOutcome<TxId<MockBase<A>>>>, InterchainMockError> {
+        let key = (controller_chain.to_string(), port_id.to_string());
+        let mut ica = self.ica.borrow_mut();
+        let Some(packet) = ica
+            .packets
+            .get_mut(&(key.0.clone(), key.1.clone(), sequence))
+        else {
+            return Ok(None);
+        };
+        if packet.relayed {
+            return Err(
+                anyhow::anyhow!("You can't receive the same packet twice on the chain").into(),
+            );
+        }
+        packet.relayed = true;
+        let msgs = packet.msgs.clone();
+        let packet_type = packet.packet_type;
+        let timeout = packet.timeout;
+
+        let channel = ica
+            .channels
+            .get_mut(&key)
+            .ok_or_else(|| anyhow::anyhow!("No interchain account channel for {}", port_id))?;
+        let host_chain_id = channel.host_chain_id.clone();
+        let controller_channel_id = channel.controller_channel_id.clone();
+        let host_channel_id = channel.host_channel_id.clone().unwrap_or_default();
+        let address = channel.address.clone().unwrap_or_default();
+        let packet_event = |ty: &str| {
+            Event::new(ty)
+                .add_attribute("packet_sequence", sequence.to_string())
+                .add_attribute("packet_src_port", port_id)
+                .add_attribute("packet_src_channel", &controller_channel_id)
+                .add_attribute("packet_dst_port", ICA_HOST_PORT)
+                .add_attribute("packet_dst_channel", &host_channel_id)
+        };
+
+        let host = self.get_chain(&host_chain_id)?;
+        let block = host.app.borrow().block_info();
+        if block.time >= timeout {
+            let timeout_tx = AppResponse {
+                events: vec![packet_event("timeout_packet")],
+                data: None,
+            };
+            channel.closed = true;
+            return Ok(Some(IbcPacketOutcome::Timeout {
+                timeout_tx: TxId {
+                    chain_id: controller_chain.to_string(),
+                    response: timeout_tx,
+                },
+            }));
+        }
+
+        let mut receive_tx = AppResponse::default();
+        let result = if packet_type == Type::ExecuteTx as i32 {
+            execute_msgs(&host, &address, &msgs, &mut receive_tx)
+        } else {
+            Err(anyhow::anyhow!("Unsupported ICS-27 packet type {packet_type}").into())
+        };
+        let ack = match result {
+            Ok(msg_responses) => json!({
+                "result": Binary::from(TxMsgData {
+                    msg_responses,
+                    ..Default::default()
+                }
+                .encode_to_vec()),
+            }),
+            Err(e) => json!({ "error": format!("error handling packet: {e}") }),
+        };
+        let ack_binary = to_json_binary(&ack)?;
+        receive_tx.events.push(
+            packet_event("write_acknowledgement")
+                .add_attribute("packet_ack", String::from_utf8_lossy(ack_binary.as_slice())),
+        );
+        let ack_tx = AppResponse {
+            events: vec![packet_event("acknowledge_packet")],
+            data: None,
+        };
+
+        Ok(Some(IbcPacketOutcome::Success {
+            receive_tx: TxId {
+                chain_id: host_chain_id,
+                response: receive_tx,
+            },
+            ack_tx: TxId {
+                chain_id: controller_chain.to_string(),
+                response: ack_tx,
+            },
+            ack: ack_binary,
+        }))
+    }
+}
+
+/// Executes the messages of a packet with the account, atomically.
+/// Returns the responses of the messages
+fn execute_msgs<A: Api>(
+    host: &MockBase<A>,
+    address: &str,
+    msgs: &[Any],
+    receive_tx: &mut AppResponse,
+) -> Result<Vec<Any>, InterchainMockError> {
+    let cosmos_msgs = msgs
+        .iter()
+        .map(|msg| cosmos_msg(msg, address))
+        .collect::<Result<Vec<_>, _>>()?;
+    let responses = host
+        .app
+        .borrow_mut()
+        .execute_multi(Addr::unchecked(address), cosmos_msgs)?;
+
+    msgs.iter()
+        .zip(responses)
+        .map(|(msg, response)| {
+            let value = if msg.type_url == MsgSend::type_url() {
+                MsgSendResponse {}.encode_to_vec()
+            } else {
+                MsgExecuteContractResponse {
+                    data: response.data.clone().unwrap_or_default().to_vec(),
+                }
+                .encode_to_vec()
+            };
+            receive_tx.events.extend(response.events);
+            Ok(Any {
+                type_url: format!("{}Response", msg.type_url),
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Message executed by the account on the host chain, the account must be the signer of the message
+fn cosmos_msg(msg: &Any, address: &str) -> Result<CosmosMsg, InterchainMockError> {
+    let (signer, cosmos_msg) = if msg.type_url == MsgSend::type_url() {
+        let send = MsgSend::decode(msg.value.as_slice()).map_err(anyhow::Error::from)?;
+        let msg = BankMsg::Send {
+            to_address: send.to_address,
+            amount: coins(send.amount)?,
+        };
+        (send.from_address, msg.into())
+    } else if msg.type_url == MsgExecuteContract::type_url() {
+        let execute =
+            MsgExecuteContract::decode(msg.value.as_slice()).map_err(anyhow::Error::from)?;
+        let msg = WasmMsg::Execute {
+            contract_addr: execute.contract,
+            msg: execute.msg.into(),
+            funds: coins(execute.funds)?,
+        };
+        (execute.sender, msg.into())
+    } else {
+        return Err(anyhow::anyhow!(
+            "The mock interchain accounts can't execute {} messages",
+            msg.type_url
+        )
+        .into());
+    };
+
+    if signer != address {
+        return Err(anyhow::anyhow!(
+            "The signer {signer} of the {} message is not the interchain account {address}",
+            msg.type_url
+        )
+        .into());
+    }
+    Ok(cosmos_msg)
+}
+
+fn coins(coins: Vec<ProtoCoin>) -> Result<Vec<Coin>, InterchainMockError> {
+    coins
+        .into_iter()
+        .map(|c| {
+            let amount: u128 = c
+                .amount
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid amount {}", c.amount))?;
+            Ok(coin(amount, c.denom))
+        })
+        .collect()
+}
+
+/// Version of an ICS-27 channel, the address is set by the host chain during the handshake
+fn ica_version(controller_connection_id: &str, host_connection_id: &str, address: &str) -> String {
+    json!({
+        "version": ICA_VERSION,
+        "controller_connection_id": controller_connection_id,
+        "host_connection_id": host_connection_id,
+        "address": address,
+        "encoding": "proto3",
+        "tx_type": "sdk_multi_msg",
+    })
+    .to_string()
+}
@@ -0,0 +1,758 @@
+//! ICS-20 transfers of the mock chains, with denom traces and the packet-forward and ibc-hooks middlewares.
+//!
+//! cw-multi-test relays the transfers without their memo and wraps the received denoms as `ibc/{channel}/{denom}`.
+//! The chains registered with [`MockInterchainEnvBase::enable_ics20`] receive the transfers like a Cosmos-SDK chain instead:
+//! - The received tokens are `ibc/{HASH}` denoms, traced with [`MockInterchainEnvBase::denom_trace`] or the [`crate::MockIbcQuerier`] of the chain.
+//! - The `forward` memo of the packet-forward-middleware sends the tokens on to the next chain. The forwarded transfer is relayed before the packet is acknowledged,
+//!   when it fails or times out the packet fails and its tokens are refunded on every hop.
+//! - The `wasm` memo of Osmosis' ibc-hooks executes a contract with the tokens, and the `ibc_callback` memo notifies the sending contract of the acknowledgement or timeout of its transfer.
+//!
+//! The tokens sent are escrowed by the transfer module of cw-multi-test, so the transfers between chains with and without ICS-20 stay compatible.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use cosmrs::proto::ibc::applications::transfer::v1::DenomTrace;
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, Addr, Api, BankMsg, Binary, BlockInfo, CanonicalAddr, Coin,
+    Event, IbcTimeout, Timestamp, Uint128, WasmMsg,
+};
+use cw_orch_interchain_core::{
+    env::ChainId,
+    types::{IbcPacketOutcome, TxId},
+    InterchainEnv,
+};
+use cw_orch_mock::cw_multi_test::{
+    ibc::{types::IbcPacketData, IbcPacketRelayingMsg},
+    AppResponse, BankSudo, Executor, SudoMsg,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    interchain::{find_ibc_packets_sent_in_tx, MockBase, MockInterchainEnvBase},
+    querier::{read_denom_trace, write_denom_trace},
+    InterchainMockError,
+};
+
+/// Port of the ICS-20 transfers
+pub(crate) const TRANSFER_PORT: &str = "transfer";
+/// Account escrowing the tokens sent by the transfer module of cw-multi-test
+const ESCROW_ADDRESS: &str = "ibc_bank_lock_module";
+/// Timeout of the forwarded transfers without a `timeout` in their memo, like the packet-forward-middleware
+const DEFAULT_FORWARD_TIMEOUT_NANOS: u64 = 10 * 60 * 1_000_000_000;
+
+/// Middlewares running on top of the ICS-20 transfers of a chain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ics20Options {
+    /// Forwards the transfers with a `forward` memo to another chain
+    pub packet_forward: bool,
+    /// Executes the contracts of the transfers with a `wasm` memo and calls back the senders of the transfers with an `ibc_callback` memo
+    pub ibc_hooks: bool,
+}
+
+/// ICS-20 transfer sent with [`MockInterchainEnvBase::ics20_transfer`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ics20Transfer {
+    /// Channel of the `transfer` port the tokens are sent on
+    pub channel_id: String,
+    /// Receiver of the tokens on the remote chain
+    pub receiver: String,
+    /// Tokens sent
+    pub amount: Coin,
+    /// Timeout of the transfer on the remote chain
+    pub timeout: IbcTimeout,
+    /// Memo of the transfer, read by the middlewares of the remote chain
+    pub memo: Option<String>,
+}
+
+/// ICS-20 state of a mock chain
+#[derive(Default)]
+pub(crate) struct Ics20Chain {
+    options: Ics20Options,
+    /// Packets received, by destination port, channel and sequence
+    received: HashSet<(String, String, u64)>,
+    /// Outcomes of the transfers forwarded by the packet-forward-middleware, by source port, channel and sequence.
+    /// They're relayed with the packet they forward, and returned when they're awaited afterwards
+    forwarded: HashMap<(String, String, u64), IbcPacketOutcome<(String, AppResponse)>>,
+}
+
+/// ICS-20 state of the chains of an interchain environment, shared by its clones
+pub(crate) type Ics20State = Rc<RefCell<HashMap<String, Ics20Chain>>>;
+
+/// Packet data of an ICS-20 transfer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Ics20Packet {
+    denom: String,
+    amount: Uint128,
+    sender: String,
+    receiver: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+}
+
+/// Acknowledgement of an ICS-20 transfer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Ics20Ack {
+    Result(Binary),
+    Error(String),
+}
+
+/// `forward` memo of the packet-forward-middleware
+#[derive(Deserialize)]
+struct ForwardMemo {
+    receiver: String,
+    port: String,
+    channel: String,
+    /// Duration in nanoseconds or Go duration (`10m`)
+    timeout: Option<Value>,
+    /// Memo of the forwarded transfer
+    next: Option<Value>,
+}
+
+/// `wasm` memo of ibc-hooks
+#[derive(Deserialize)]
+struct WasmMemo {
+    contract: String,
+    msg: Value,
+}
+
+/// Sudo message sent to the contracts registered with an `ibc_callback` memo
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
+
+impl<A: Api> MockInterchainEnvBase<A> {
+    /// Runs the ICS-20 implementation of the mock interchain on a chain, with the middlewares selected in `options`.
+    /// The transfers received by the chain are then traced as `ibc/{HASH}` denoms and their memo is executed by the middlewares.
+    pub fn enable_ics20(
+        &self,
+        chain_id: ChainId,
+        options: Ics20Options,
+    ) -> Result<(), InterchainMockError> {
+        self.get_chain(chain_id)?;
+        self.ics20
+            .borrow_mut()
+            .entry(chain_id.to_string())
+            .or_default()
+            .options = options;
+        Ok(())
+    }
+
+    /// Sends an ICS-20 transfer with a memo from `sender`.
+    /// The packets of the transfer are relayed with [`InterchainEnv::await_packets`] like those of an [`cosmwasm_std::IbcMsg::Transfer`].
+    pub fn ics20_transfer(
+        &self,
+        chain_id: ChainId,
+        sender: &Addr,
+        transfer: Ics20Transfer,
+    ) -> Result<AppResponse, InterchainMockError> {
+        let chain = self.get_chain(chain_id)?;
+        let mut app = chain.app.borrow_mut();
+
+        let escrow = app.execute(
+            sender.clone(),
+            BankMsg::Send {
+                to_address: ESCROW_ADDRESS.to_string(),
+                amount: vec![transfer.amount.clone()],
+            }
+            .into(),
+        )?;
+        // The denom isn't unwrapped to its trace, the transfer module of cw-multi-test refunds the escrowed denom on timeout
+        let packet = Ics20Packet {
+            denom: transfer.amount.denom.clone(),
+            amount: transfer.amount.amount,
+            sender: sender.to_string(),
+            receiver: transfer.receiver,
+            memo: transfer.memo,
+        };
+        let send = app.sudo(SudoMsg::Ibc(IbcPacketRelayingMsg::Send {
+            port_id: TRANSFER_PORT.to_string(),
+            channel_id: transfer.channel_id,
+            data: to_json_binary(&packet)?,
+            timeout: transfer.timeout,
+        }));
+        let send = match send {
+            Ok(send) => send,
+            Err(e) => {
+                app.execute(
+                    Addr::unchecked(ESCROW_ADDRESS),
+                    BankMsg::Send {
+                        to_address: sender.to_string(),
+                        amount: vec![transfer.amount],
+                    }
+                    .into(),
+                )?;
+                return Err(e.into());
+            }
+        };
+
+        Ok(AppResponse {
+            events: [escrow.events, send.events].concat(),
+            data: None,
+        })
+    }
+
+    /// Trace of an `ibc/{HASH}` denom received by a chain running ICS-20, `hash` is given with or without the `ibc/` prefix
+    pub fn denom_trace(
+        &self,
+        chain_id: ChainId,
+        hash: &str,
+    ) -> Result<DenomTrace, InterchainMockError> {
+        read_denom_trace(&self.get_chain(chain_id)?, hash).ok_or_else(|| {
+            InterchainMockError::DenomTraceNotFound(hash.to_string(), chain_id.to_string())
+        })
+    }
+
+    /// Whether the transfers received by the chain are handled by the ICS-20 implementation of the mock interchain
+    pub(crate) fn runs_ics20(&self, chain_id: ChainId) -> bool {
+        self.ics20.borrow().contains_key(chain_id)
+    }
+
+    /// Outcome of a transfer forwarded by the packet-forward-middleware of the chain, once it's relayed
+    pub(crate) fn take_forwarded_outcome(
+        &self,
+        chain_id: ChainId,
+        port_id: &str,
+        channel_id: &str,
+        sequence: u64,
+    ) -> Option<IbcPacketOutcome<TxId<MockBase<A>>>> {
+        let outcome = self
+            .ics20
+            .borrow_mut()
+            .get_mut(chain_id)?
+            .forwarded
+            .remove(&(port_id.to_string(), channel_id.to_string(), sequence))?;
+        let tx_id = |(chain_id, response)| TxId { chain_id, response };
+        Some(match outcome {
+            IbcPacketOutcome::Timeout { timeout_tx } => IbcPacketOutcome::Timeout {
+                timeout_tx: tx_id(timeout_tx),
+            },
+            IbcPacketOutcome::Success {
+                receive_tx,
+                ack_tx,
+                ack,
+            } => IbcPacketOutcome::Success {
+                receive_tx: tx_id(receive_tx),
+                ack_tx: tx_id(ack_tx),
+                ack,
+            },
+        })
+    }
+
+    fn options(&self, chain_id: ChainId) -> Ics20Options {
+        self.ics20
+            .borrow()
+            .get(chain_id)
+            .map(|chain| chain.options)
+            .unwrap_or_default()
+    }
+
+    /// Relays an ICS-20 packet to a chain running ICS-20 and delivers its acknowledgement or timeout
+    pub(crate) fn relay_ics20_packet(
+        &self,
+        src_chain: ChainId,
+        dst_chain: ChainId,
+        packet: IbcPacketData,
+    ) -> Result<IbcPacketOutcome<TxId<MockBase<A>>>, InterchainMockError> {
+        let src_mock = self.get_chain(src_chain)?;
+        let dst_mock = self.get_chain(dst_chain)?;
+
+        let newly_received = self
+            .ics20
+            .borrow_mut()
+            .entry(dst_chain.to_string())
+            .or_default()
+            .received
+            .insert((
+                packet.dst_port_id.clone(),
+                packet.dst_channel_id.clone(),
+                packet.sequence,
+            ));
+        if !newly_received {
+            return Err(
+                anyhow::anyhow!("You can't receive the same packet twice on the chain").into(),
+            );
+        }
+        let data: Option<Ics20Packet> = from_json(&packet.data).ok();
+
+        let block = dst_mock.app.borrow().block_info();
+        if has_timed_out(&packet.timeout, &block) {
+            let mut timeout_tx =
+                src_mock
+                    .app
+                    .borrow_mut()
+                    .sudo(SudoMsg::Ibc(IbcPacketRelayingMsg::Timeout {
+                        packet: packet.clone(),
+                    }))?;
+            if let Some(data) = &data {
+                let callback = IbcLifecycleComplete::IbcTimeout {
+                    channel: packet.src_channel_id.clone(),
+                    sequence: packet.sequence,
+                };
+                self.ibc_callback(src_chain, data, &callback, &mut timeout_tx)?;
+            }
+            return Ok(IbcPacketOutcome::Timeout {
+                timeout_tx: TxId {
+                    chain_id: src_chain.to_string(),
+                    response: timeout_tx,
+                },
+            });
+        }
+
+        let mut receive_tx = AppResponse::default();
+        let result = match &data {
+            Some(data) => self.receive(src_chain, dst_chain, &packet, data, &mut receive_tx),
+            None => Err(anyhow::anyhow!("Invalid ICS-20 packet data").into()),
+        };
+        let ack = match result {
+            Ok(()) => Ics20Ack::Result(Binary::from(vec![1])),
+            Err(e) => Ics20Ack::Error(e.to_string()),
+        };
+        let ack_binary = to_json_binary(&ack)?;
+        receive_tx.events.push(
+            Event::new("write_acknowledgement")
+                .add_attribute("packet_sequence", packet.sequence.to_string())
+                .add_attribute("packet_src_port", &packet.src_port_id)
+                .add_attribute("packet_src_channel", &packet.src_channel_id)
+                .add_attribute("packet_dst_port", &packet.dst_port_id)
+                .add_attribute("packet_dst_channel", &packet.dst_channel_id)
+                .add_attribute("packet_ack", String::from_utf8_lossy(ack_binary.as_slice())),
+        );
+
+        let mut ack_tx =
+            src_mock
+                .app
+                .borrow_mut()
+                .sudo(SudoMsg::Ibc(IbcPacketRelayingMsg::Acknowledge {
+                    packet: packet.clone(),
+                    ack: ack_binary.clone(),
+                }))?;
+        if let Some(data) = &data {
+            let success = matches!(ack, Ics20Ack::Result(_));
+            // The transfer module of cw-multi-test doesn't refund the failed transfers, it escrows the tokens on the same address
+            if !success {
+                let refund = src_mock.app.borrow_mut().execute(
+                    Addr::unchecked(ESCROW_ADDRESS),
+                    BankMsg::Send {
+                        to_address: data.sender.clone(),
+                        amount: vec![coin(data.amount.u128(), &data.denom)],
+                    }
+                    .into(),
+                )?;
+                ack_tx.events.extend(refund.events);
+            }
+            let callback = IbcLifecycleComplete::IbcAck {
+                channel: packet.src_channel_id.clone(),
+                sequence: packet.sequence,
+                ack: String::from_utf8_lossy(ack_binary.as_slice()).to_string(),
+                success,
+            };
+            self.ibc_callback(src_chain, data, &callback, &mut ack_tx)?;
+        }
+
+        Ok(IbcPacketOutcome::Success {
+            receive_tx: TxId {
+                chain_id: dst_chain.to_string(),
+                response: receive_tx,
+            },
+            ack_tx: TxId {
+                chain_id: src_chain.to_string(),
+                response: ack_tx,
+            },
+            ack: ack_binary,
+        })
+    }
+
+    /// Credits the tokens of the transfer on the destination chain and runs its memo.
+    /// Nothing is credited when this fails.
+    fn receive(
+        &self,
+        src_chain: ChainId,
+        dst_chain: ChainId,
+        packet: &IbcPacketData,
+        data: &Ics20Packet,
+        receive_tx: &mut AppResponse,
+    ) -> Result<(), InterchainMockError> {
+        let dst_mock = self.get_chain(dst_chain)?;
+        let options = self.options(dst_chain);
+        let memo: Value = data
+            .memo
+            .as_deref()
+            .and_then(|memo| serde_json::from_str(memo).ok())
+            .unwrap_or_default();
+        let forward = match memo.get("forward") {
+            Some(forward) if options.packet_forward => {
+                Some(serde_json::from_value::<ForwardMemo>(forward.clone())?)
+            }
+            _ => None,
+        };
+        let wasm = match memo.get("wasm") {
+            Some(wasm) if options.ibc_hooks => {
+                Some(serde_json::from_value::<WasmMemo>(wasm.clone())?)
+            }
+            _ => None,
+        };
+
+        // The middlewares receive the tokens on an account derived from the channel and sender
+        let receiver = if forward.is_some() {
+            derive_address(
+                &dst_mock,
+                &format!(
+                    "packet-forward-middleware/{}/{}",
+                    packet.dst_channel_id, data.sender
+                ),
+            )
+        } else if let Some(wasm) = &wasm {
+            if wasm.contract != data.receiver {
+                return Err(anyhow::anyhow!(
+                    "The receiver of the transfer must be the contract executed by its wasm memo"
+                )
+                .into());
+            }
+            derive_address(
+                &dst_mock,
+                &format!(
+                    "ibc-wasm-hook-intermediary/{}/{}",
+                    packet.dst_channel_id, data.sender
+                ),
+            )
+        } else {
+            dst_mock.app.borrow().api().addr_validate(&data.receiver)?
+        };
+
+        let (denom, returning) = self.receive_denom(src_chain, &dst_mock, packet, &data.denom);
+        let amount = coin(data.amount.u128(), denom);
+        let credit = if returning {
+            dst_mock.app.borrow_mut().execute(
+                Addr::unchecked(ESCROW_ADDRESS),
+                BankMsg::Send {
+                    to_address: receiver.to_string(),
+                    amount: vec![amount.clone()],
+                }
+                .into(),
+            )?
+        } else {
+            dst_mock
+                .app
+                .borrow_mut()
+                .sudo(SudoMsg::Bank(BankSudo::Mint {
+                    to_address: receiver.to_string(),
+                    amount: vec![amount.clone()],
+                }))?
+        };
+        receive_tx.events.extend(credit.events);
+
+        // The events of a forwarded transfer are kept when it fails on the next chain, its packet was sent
+        let mut forwarded = false;
+        let middleware = if let Some(forward) = forward {
+            self.forward(dst_chain, &receiver, amount.clone(), forward)
+                .and_then(|forward_tx| {
+                    receive_tx.events.extend(forward_tx.events.clone());
+                    forwarded = true;
+                    self.relay_forward(dst_chain, &forward_tx)
+                })
+        } else if let Some(wasm) = wasm {
+            dst_mock
+                .app
+                .borrow_mut()
+                .execute(
+                    receiver.clone(),
+                    WasmMsg::Execute {
+                        contract_addr: wasm.contract,
+                        msg: to_json_binary(&wasm.msg)?,
+                        funds: vec![amount.clone()],
+                    }
+                    .into(),
+                )
+                .map(|response| receive_tx.events.extend(response.events))
+                .map_err(Into::into)
+        } else {
+            return Ok(());
+        };
+
+        if let Err(e) = middleware {
+            // The credited tokens are taken back, like the failed receive txs are reverted
+            let revert: cosmwasm_std::CosmosMsg = if returning {
+                BankMsg::Send {
+                    to_address: ESCROW_ADDRESS.to_string(),
+                    amount: vec![amount],
+                }
+                .into()
+            } else {
+                BankMsg::Burn {
+                    amount: vec![amount],
+                }
+                .into()
+            };
+            let revert = dst_mock.app.borrow_mut().execute(receiver, revert)?;
+            if forwarded {
+                receive_tx.events.extend(revert.events);
+            } else {
+                receive_tx.events.clear();
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Forwards the tokens received by the packet-forward-middleware
+    fn forward(
+        &self,
+        chain_id: ChainId,
+        sender: &Addr,
+        amount: Coin,
+        forward: ForwardMemo,
+    ) -> Result<AppResponse, InterchainMockError> {
+        if forward.port != TRANSFER_PORT {
+            return Err(
+                anyhow::anyhow!("Cannot forward a transfer on port {}", forward.port).into(),
+            );
+        }
+        let block = self.get_chain(chain_id)?.app.borrow().block_info();
+        let timeout = forward
+            .timeout
+            .as_ref()
+            .map(parse_duration)
+            .transpose()?
+            .unwrap_or(DEFAULT_FORWARD_TIMEOUT_NANOS);
+        let timeout = block
+            .time
+            .nanos()
+            .checked_add(timeout)
+            .map(Timestamp::from_nanos)
+            .ok_or_else(|| InterchainMockError::InvalidForwardTimeout(timeout.to_string()))?;
+        let memo = forward.next.map(|next| match next {
+            Value::String(next) => next,
+            next => next.to_string(),
+        });
+
+        self.ics20_transfer(
+            chain_id,
+            sender,
+            Ics20Transfer {
+                channel_id: forward.channel,
+                receiver: forward.receiver,
+                amount,
+                timeout: IbcTimeout::with_timestamp(timeout),
+                memo,
+            },
+        )
+    }
+
+    /// Relays the transfer sent by the packet-forward-middleware, so that the packet it forwards is acknowledged once it completes.
+    /// Fails when the forwarded transfer fails or times out, its tokens are then refunded to the middleware
+    fn relay_forward(
+        &self,
+        chain_id: ChainId,
+        forward_tx: &AppResponse,
+    ) -> Result<(), InterchainMockError> {
+        let packets = find_ibc_packets_sent_in_tx(&self.get_chain(chain_id)?, forward_tx)?;
+        for packet in packets {
+            let outcome = self
+                .await_single_packet(
+                    chain_id,
+                    packet.src_port.clone(),
+                    packet.src_channel.clone(),
+                    &packet.dst_chain_id,
+                    packet.sequence,
+                )?
+                .outcome;
+
+            let failure = match &outcome {
+                IbcPacketOutcome::Timeout { .. } => Some("timeout".to_string()),
+                IbcPacketOutcome::Success { ack, .. } => match from_json(ack) {
+                    Ok(Ics20Ack::Error(error)) => Some(error),
+                    _ => None,
+                },
+            };
+            let tx = |tx: TxId<MockBase<A>>| (tx.chain_id, tx.response);
+            let outcome = match outcome {
+                IbcPacketOutcome::Timeout { timeout_tx } => IbcPacketOutcome::Timeout {
+                    timeout_tx: tx(timeout_tx),
+                },
+                IbcPacketOutcome::Success {
+                    receive_tx,
+                    ack_tx,
+                    ack,
+                } => IbcPacketOutcome::Success {
+                    receive_tx: tx(receive_tx),
+                    ack_tx: tx(ack_tx),
+                    ack,
+                },
+            };
+            self.ics20
+                .borrow_mut()
+                .entry(chain_id.to_string())
+                .or_default()
+                .forwarded
+                .insert(
+                    (
+                        packet.src_port.to_string(),
+                        packet.src_channel.to_string(),
+                        packet.sequence.into(),
+                    ),
+                    outcome,
+                );
+
+            if let Some(failure) = failure {
+                return Err(anyhow::anyhow!("The forwarded transfer failed: {failure}").into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Local denom of the tokens received and whether they return to the chain they were sent from.
+    /// The new `ibc/{HASH}` denoms are traced.
+    fn receive_denom(
+        &self,
+        src_chain: ChainId,
+        dst_mock: &MockBase<A>,
+        packet: &IbcPacketData,
+        denom: &str,
+    ) -> (String, bool) {
+        // The packets carry the escrowed denom, traced on the source chain
+        let full_denom = match denom
+            .strip_prefix("ibc/")
+            .and_then(|hash| self.denom_trace(src_chain, hash).ok())
+        {
+            Some(trace) => format!("{}/{}", trace.path, trace.base_denom),
+            None => denom.to_string(),
+        };
+
+        let source_prefix = format!("{}/{}/", packet.src_port_id, packet.src_channel_id);
+        if let Some(unprefixed) = full_denom.strip_prefix(&source_prefix) {
+            return (local_denom(&parse_denom_trace(unprefixed)), true);
+        }
+
+        let trace = parse_denom_trace(&format!(
+            "{}/{}/{}",
+            packet.dst_port_id, packet.dst_channel_id, full_denom
+        ));
+        let denom = local_denom(&trace);
+        write_denom_trace(dst_mock, &denom_hash(&trace), &trace);
+        (denom, false)
+    }
+
+    /// Calls back the contract that sent a transfer with an `ibc_callback` memo
+    fn ibc_callback(
+        &self,
+        chain_id: ChainId,
+        data: &Ics20Packet,
+        callback: &IbcLifecycleComplete,
+        tx: &mut AppResponse,
+    ) -> Result<(), InterchainMockError> {
+        if !self.options(chain_id).ibc_hooks {
+            return Ok(());
+        }
+        let contract = data
+            .memo
+            .as_deref()
+            .and_then(|memo| serde_json::from_str::<Value>(memo).ok())
+            .and_then(|memo| memo.get("ibc_callback")?.as_str().map(str::to_string));
+        // Only the contract sending the transfer can be called back
+        let Some(contract) = contract.filter(|contract| *contract == data.sender) else {
+            return Ok(());
+        };
+
+        let msg = serde_json::json!({ "ibc_lifecycle_complete": callback });
+        let response = self
+            .get_chain(chain_id)?
+            .app
+            .borrow_mut()
+            .wasm_sudo(Addr::unchecked(contract), &msg)?;
+        tx.events.extend(response.events);
+        Ok(())
+    }
+}
+
+fn has_timed_out(timeout: &IbcTimeout, block: &BlockInfo) -> bool {
+    timeout
+        .block()
+        .is_some_and(|timeout| block.height >= timeout.height)
+        || timeout
+            .timestamp()
+            .is_some_and(|timeout| block.time >= timeout)
+}
+
+/// Splits a full denom (`transfer/channel-0/uatom`) into its path and base denom
+fn parse_denom_trace(full_denom: &str) -> DenomTrace {
+    let parts: Vec<_> = full_denom.split('/').collect();
+    let mut path_length = 0;
+    while path_length + 1 < parts.len() && parts[path_length + 1].starts_with("channel-") {
+        path_length += 2;
+    }
+    DenomTrace {
+        path: parts[..path_length].join("/"),
+        base_denom: parts[path_length..].join("/"),
+    }
+}
+
+fn denom_hash(trace: &DenomTrace) -> String {
+    let hash = Sha256::digest(format!("{}/{}", trace.path, trace.base_denom));
+    hash.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+/// Denom of the traced tokens on the chain
+fn local_denom(trace: &DenomTrace) -> String {
+    if trace.path.is_empty() {
+        trace.base_denom.clone()
+    } else {
+        format!("ibc/{}", denom_hash(trace))
+    }
+}
+
+/// Account derived from `seed`, a bech32 address on the chains with bech32 addresses
+pub(crate) fn derive_address<A: Api>(chain: &MockBase<A>, seed: &str) -> Addr {
+    let hash = Sha256::digest(seed);
+    let app = chain.app.borrow();
+    let api = app.api();
+    api.addr_humanize(&CanonicalAddr::from(hash.to_vec()))
+        .ok()
+        .filter(|addr| api.addr_validate(addr.as_str()).is_ok())
+        .unwrap_or_else(|| {
+            Addr::unchecked(
+                hash.iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<String>(),
+            )
+        })
+}
+
+/// Parses a duration in nanoseconds or a Go duration with a `h`, `m`, `s` or `ms` unit
+fn parse_duration(duration: &Value) -> Result<u64, InterchainMockError> {
+    let invalid = || InterchainMockError::InvalidForwardTimeout(duration.to_string());
+    if let Some(nanos) = duration.as_u64() {
+        return Ok(nanos);
+    }
+    let duration = duration.as_str().ok_or_else(invalid)?;
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (value, unit) = duration.split_at(split);
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    let unit_nanos: u64 = match unit {
+        "h" => 3_600_000_000_000,
+        "m" => 60_000_000_000,
+        "s" => 1_000_000_000,
+        "ms" => 1_000_000,
+        _ => return Err(invalid()),
+    };
+    value.checked_mul(unit_nanos).ok_or_else(invalid)
+}
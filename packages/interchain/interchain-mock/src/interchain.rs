@@ -1,7 +1,7 @@
 #![warn(missing_docs)]
 
 use cosmwasm_std::{from_json, testing::MockApi, Api, Event, IbcOrder};
-use cw_orch_core::environment::QueryHandler;
+use cw_orch_core::environment::{DefaultQueriers, NodeQuerier, QueryHandler};
 use cw_orch_interchain_core::{
     channel::InterchainChannel,
     env::{ChainId, ChannelCreation},
@@ -10,13 +10,13 @@ use cw_orch_interchain_core::{
         IbcPacketOutcome, IbcTxAnalysis, InternalChannelCreationResult, SimpleIbcPacketAnalysis,
         TxId,
     },
-    InterchainEnv,
+    InterchainEnv, InterchainError,
 };
 use cw_orch_mock::{
     cw_multi_test::{
         ibc::{
             relayer::{self, ChannelCreationResult},
-            types::{Connection, MockIbcQuery},
+            types::{Connection, IbcPacketData, MockIbcQuery},
         },
         AppResponse, MockApiBech32,
     },
@@ -31,7 +31,12 @@ use std::{
     str::FromStr,
 };
 
-use crate::InterchainMockError;
+use crate::{
+    ica::IcaState,
+    ics20::{Ics20State, TRANSFER_PORT},
+    relayer::{PacketQueue, PendingPacket},
+    InterchainMockError,
+};
 
 pub type MockBase<A> = cw_orch_mock::MockBase<A, MockState>;
 
@@ -41,12 +46,18 @@ pub type MockBase<A> = cw_orch_mock::MockBase<A, MockState>;
 pub struct MockInterchainEnvBase<A: Api> {
     /// Mock chains registered within the structure
     pub mocks: HashMap<String, MockBase<A>>,
+    /// ICS-20 state of the chains running the ICS-20 implementation of the mock interchain
+    pub(crate) ics20: Ics20State,
+    /// ICS-27 state of the interchain accounts of the chains
+    pub(crate) ica: IcaState,
 }
 
 impl<A: Api> Clone for MockInterchainEnvBase<A> {
     fn clone(&self) -> Self {
         Self {
             mocks: self.mocks.clone(),
+            ics20: self.ics20.clone(),
+            ica: self.ica.clone(),
         }
     }
 }
@@ -62,6 +73,8 @@ impl<A: Api> MockInterchainEnvBase<A> {
                     (chain_id, d.clone())
                 })
                 .collect(),
+            ics20: Default::default(),
+            ica: Default::default(),
         }
     }
 
@@ -73,6 +86,125 @@ impl<A: Api> MockInterchainEnvBase<A> {
                 .map(|m| (m.block_info().unwrap().chain_id, m.clone())),
         );
     }
+
+    /// Connection of `src_chain` with `dst_chain`, on `src_chain`.
+    /// The chains are connected first if they aren't yet
+    pub fn connection_id(
+        &self,
+        src_chain: ChainId,
+        dst_chain: ChainId,
+    ) -> Result<String, InterchainMockError> {
+        let src_mock = self.get_chain(src_chain)?;
+        let dst_mock = self.get_chain(dst_chain)?;
+
+        // We verify there is a connection. If there is none, we create one
+        let connections: Vec<(String, Connection)> = from_json(src_mock.app.borrow().ibc_query(
+            MockIbcQuery::ChainConnections {
+                chain_id: dst_chain.to_string(),
+            },
+        )?)?;
+        if let Some((connection_id, _)) = connections.first() {
+            return Ok(connection_id.clone());
+        }
+
+        let (src_connection_id, _) = relayer::create_connection(
+            &mut src_mock.app.borrow_mut(),
+            &mut dst_mock.app.borrow_mut(),
+        )?;
+        Ok(src_connection_id)
+    }
+
+    /// This function follows every IBC packet sent out in a tx result
+    /// This allows only providing the transaction hash when you don't have access to the whole response object
+    /// The transaction hash can be retrieved using [`cw_orch_mock::MockBase::last_tx_hash`]
+    pub fn await_packets_for_txhash(
+        &self,
+        chain_id: ChainId,
+        packet_send_tx_hash: String,
+    ) -> Result<IbcTxAnalysis<MockBase<A>>, InterchainMockError> {
+        let tx = self
+            .get_chain(chain_id)?
+            .node_querier()
+            .find_tx(packet_send_tx_hash)
+            .map_err(InterchainError::from)?;
+
+        self.await_packets(chain_id, tx)
+    }
+
+    /// Holds the packets sent in a tx result instead of relaying them.
+    /// The returned queue relays, drops or times out each packet on demand, to test the packets received out of order or not at all.
+    pub fn hold_packets(
+        &self,
+        chain_id: ChainId,
+        tx_response: &AppResponse,
+    ) -> Result<PacketQueue<A>, InterchainMockError> {
+        let packets = find_ibc_packets_sent_in_tx(&self.get_chain(chain_id)?, tx_response)?;
+        Ok(PacketQueue::new(
+            self.clone(),
+            TxId {
+                chain_id: chain_id.to_string(),
+                response: tx_response.clone(),
+            },
+            packets
+                .into_iter()
+                .map(|packet| PendingPacket {
+                    src_chain_id: chain_id.to_string(),
+                    dst_chain_id: packet.dst_chain_id,
+                    src_port: packet.src_port,
+                    src_channel: packet.src_channel,
+                    sequence: packet.sequence,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Relays a packet sent in `send_tx` and follows the packets sent by its receive and acknowledgement txs
+    pub(crate) fn follow_packet(
+        &self,
+        chain_id: ChainId,
+        packet: &IbcPacketInfo,
+        send_tx_id: TxId<MockBase<A>>,
+    ) -> Result<FullIbcPacketAnalysis<MockBase<A>>, InterchainMockError> {
+        let ibc_result = self.await_single_packet(
+            chain_id,
+            packet.src_port.clone(),
+            packet.src_channel.clone(),
+            &packet.dst_chain_id,
+            packet.sequence,
+        )?;
+
+        // for each resulting tx, we analyze them
+        let txs_to_analyze = match ibc_result.outcome.clone() {
+            IbcPacketOutcome::Timeout { timeout_tx } => vec![timeout_tx],
+            IbcPacketOutcome::Success {
+                receive_tx, ack_tx, ..
+            } => vec![receive_tx, ack_tx],
+        };
+        let txs_results = txs_to_analyze
+            .iter()
+            .map(|tx| {
+                let chain_id = tx.chain_id.clone();
+                let response = tx.response.clone();
+                self.await_packets(&chain_id, response)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let analyzed_outcome = match ibc_result.outcome {
+            IbcPacketOutcome::Timeout { .. } => IbcPacketOutcome::Timeout {
+                timeout_tx: txs_results[0].clone(),
+            },
+            IbcPacketOutcome::Success { ack, .. } => IbcPacketOutcome::Success {
+                ack: ack.clone(),
+                receive_tx: txs_results[0].clone(),
+                ack_tx: txs_results[1].clone(),
+            },
+        };
+
+        Ok(FullIbcPacketAnalysis {
+            send_tx: Some(send_tx_id),
+            outcome: analyzed_outcome,
+        })
+    }
 }
 type Sender<'a> = &'a str;
 type Prefix = &'static str;
@@ -96,6 +228,8 @@ impl MockInterchainEnvBase<MockApi> {
                     (chain_id.to_string(), mock)
                 })
                 .collect(),
+            ics20: Default::default(),
+            ica: Default::default(),
         }
     }
 }
@@ -113,6 +247,8 @@ impl MockInterchainEnvBase<MockApiBech32> {
                     (chain_id.to_string(), mock)
                 })
                 .collect(),
+            ics20: Default::default(),
+            ica: Default::default(),
         }
     }
 }
@@ -148,26 +284,8 @@ impl<A: Api> InterchainEnv<MockBase<A>> for MockInterchainEnvBase<A> {
         let src_mock = self.get_chain(src_chain)?;
         let dst_mock = self.get_chain(dst_chain)?;
 
-        // We verify that there is a connection between the 2 chains (this requires indexed-map or reverse mapping )
-        // We need to specify the connection id no ?
-        // We need to register connections if we want to create channels !
-        // We connect the first connection
-        let connections: Vec<(String, Connection)> = from_json(src_mock.app.borrow().ibc_query(
-            MockIbcQuery::ChainConnections {
-                chain_id: dst_chain.to_string(),
-            },
-        )?)?;
-
-        // We verify there is a connection. If there is none, we create one
-        let connection_id = if let Some((connection_id, _)) = connections.first() {
-            connection_id.clone()
-        } else {
-            let (src_connection_id, _) = relayer::create_connection(
-                &mut src_mock.app.borrow_mut(),
-                &mut dst_mock.app.borrow_mut(),
-            )?;
-            src_connection_id
-        };
+        // We connect the chains on their first connection
+        let connection_id = self.connection_id(src_chain, dst_chain)?;
 
         let channel_creation = relayer::create_channel(
             &mut src_mock.app.borrow_mut(),
@@ -239,51 +357,7 @@ impl<A: Api> InterchainEnv<MockBase<A>> for MockInterchainEnvBase<A> {
 
         let packet_analysis = packets
             .iter()
-            .map(|packet| {
-                let ibc_result = self.await_single_packet(
-                    chain_id,
-                    packet.src_port.clone(),
-                    packet.src_channel.clone(),
-                    &packet.dst_chain_id,
-                    packet.sequence,
-                )?;
-
-                // for each resulting tx, we analyze them
-                let txs_to_analyze = match ibc_result.outcome.clone() {
-                    IbcPacketOutcome::Timeout { timeout_tx } => vec![timeout_tx],
-                    IbcPacketOutcome::Success {
-                        receive_tx, ack_tx, ..
-                    } => vec![receive_tx, ack_tx],
-                };
-                let txs_results = txs_to_analyze
-                    .iter()
-                    .map(|tx| {
-                        let chain_id = tx.chain_id.clone();
-                        let response = tx.response.clone();
-                        self.await_packets(&chain_id, response)
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let analyzed_outcome = match ibc_result.outcome {
-                    IbcPacketOutcome::Timeout { .. } => IbcPacketOutcome::Timeout {
-                        timeout_tx: txs_results[0].clone(),
-                    },
-                    IbcPacketOutcome::Success { ack, .. } => IbcPacketOutcome::Success {
-                        ack: ack.clone(),
-                        receive_tx: txs_results[0].clone(),
-                        ack_tx: txs_results[1].clone(),
-                    },
-                };
-
-                let analyzed_result = FullIbcPacketAnalysis {
-                    send_tx: Some(send_tx_id.clone()),
-                    outcome: analyzed_outcome,
-                };
-
-                // We return the packet analysis
-
-                Ok(analyzed_result)
-            })
+            .map(|packet| self.follow_packet(chain_id, packet, send_tx_id.clone()))
             .collect::<Result<Vec<_>, InterchainMockError>>()?;
 
         let response = IbcTxAnalysis {
@@ -307,6 +381,46 @@ impl<A: Api> InterchainEnv<MockBase<A>> for MockInterchainEnvBase<A> {
         let src_mock = self.get_chain(src_chain)?;
         let dst_mock = self.get_chain(dst_chain)?;
 
+        // The transfers forwarded by the packet-forward-middleware are relayed with the packet they forward
+        if let Some(outcome) = self.take_forwarded_outcome(
+            src_chain,
+            src_port.as_str(),
+            src_channel.as_str(),
+            sequence.into(),
+        ) {
+            return Ok(IbcPacketAnalysis {
+                send_tx: None,
+                outcome,
+            });
+        }
+
+        // The packets of the interchain accounts are relayed by the mock interchain
+        if let Some(outcome) =
+            self.relay_ica_packet(src_chain, src_port.as_str(), sequence.into())?
+        {
+            return Ok(IbcPacketAnalysis {
+                send_tx: None,
+                outcome,
+            });
+        }
+
+        // The transfers to chains running ICS-20 are relayed by the mock interchain
+        if src_port.as_str() == TRANSFER_PORT && self.runs_ics20(dst_chain) {
+            let packet: IbcPacketData =
+                from_json(src_mock.app.borrow().ibc_query(MockIbcQuery::SendPacket {
+                    channel_id: src_channel.to_string(),
+                    port_id: src_port.to_string(),
+                    sequence: sequence.into(),
+                })?)?;
+            if packet.dst_port_id == TRANSFER_PORT {
+                let outcome = self.relay_ics20_packet(src_chain, dst_chain, packet)?;
+                return Ok(IbcPacketAnalysis {
+                    send_tx: None,
+                    outcome,
+                });
+            }
+        }
+
         // We get the packet data from the chain directly
         let relay_result = relayer::relay_packet(
             &mut src_mock.app.borrow_mut(),
@@ -379,7 +493,7 @@ fn get_all_events_values(events: &[Event], attribute: &str) -> Vec<String> {
         .collect()
 }
 
-fn find_ibc_packets_sent_in_tx<A: Api>(
+pub(crate) fn find_ibc_packets_sent_in_tx<A: Api>(
     chain: &MockBase<A>,
     tx: &AppResponse,
 ) -> Result<Vec<IbcPacketInfo>, InterchainMockError> {
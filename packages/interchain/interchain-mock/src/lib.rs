@@ -1,11 +1,18 @@
 //! Implementation of the interchain traits for the [cw_orch::prelude::Mock] environment
 
 mod error;
+mod ica;
+mod ics20;
 mod interchain;
+mod querier;
+mod relayer;
 
 use cosmwasm_std::testing::MockApi;
 use cw_orch_mock::cw_multi_test::MockApiBech32;
 pub use error::InterchainMockError;
+pub use ics20::{Ics20Options, Ics20Transfer};
+pub use querier::MockIbcQuerier;
+pub use relayer::{PacketQueue, PendingPacket};
 
 pub type MockInterchainEnv = interchain::MockInterchainEnvBase<MockApi>;
 pub type MockBech32InterchainEnv = interchain::MockInterchainEnvBase<MockApiBech32>;
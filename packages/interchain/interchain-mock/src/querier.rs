@@ -0,0 +1,80 @@
+//! Queries of the IBC modules of the mock chains
+
+use cosmrs::proto::{ibc::applications::transfer::v1::DenomTrace, traits::Message};
+use cosmwasm_std::{Api, StdError};
+use cw_orch_core::{
+    environment::{Querier, QuerierGetter},
+    CwEnvError,
+};
+
+use crate::interchain::MockBase;
+
+/// Prefix of the keys of the denom traces in the storage of the mock chains
+const DENOM_TRACE_PREFIX: &[u8] = b"ibc_transfer/denom_trace/";
+
+/// Querier of the IBC modules of a mock chain, like the `Ibc` querier of the daemon.
+///
+/// The traces of the `ibc/{HASH}` denoms are written in the storage of the chains running the ICS-20 implementation of the mock interchain
+/// ```
+/// use cw_orch_core::environment::QuerierGetter;
+/// use cw_orch_interchain_core::InterchainEnv;
+/// use cw_orch_interchain_mock::{MockIbcQuerier, MockInterchainEnv};
+///
+/// let interchain = MockInterchainEnv::new(vec![("juno-1", "sender"), ("osmosis-1", "sender")]);
+/// let osmosis = interchain.get_chain("osmosis-1").unwrap();
+///
+/// let ibc: MockIbcQuerier<_> = osmosis.querier();
+/// assert!(ibc.denom_trace("ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2").is_err());
+/// ```
+pub struct MockIbcQuerier<A: Api> {
+    chain: MockBase<A>,
+}
+
+impl<A: Api> QuerierGetter<MockIbcQuerier<A>> for MockBase<A> {
+    fn querier(&self) -> MockIbcQuerier<A> {
+        MockIbcQuerier {
+            chain: self.clone(),
+        }
+    }
+}
+
+impl<A: Api> Querier for MockIbcQuerier<A> {
+    type Error = CwEnvError;
+}
+
+impl<A: Api> MockIbcQuerier<A> {
+    /// Trace of an `ibc/{HASH}` denom, `hash` is given with or without the `ibc/` prefix
+    pub fn denom_trace(&self, hash: &str) -> Result<DenomTrace, CwEnvError> {
+        read_denom_trace(&self.chain, hash).ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Denom trace {hash} not found on chain {}",
+                self.chain.app.borrow().block_info().chain_id
+            ))
+            .into()
+        })
+    }
+}
+
+/// Trace of an `ibc/{HASH}` denom in the storage of the chain
+pub(crate) fn read_denom_trace<A: Api>(chain: &MockBase<A>, hash: &str) -> Option<DenomTrace> {
+    let key = denom_trace_key(hash);
+    let trace = chain
+        .app
+        .borrow()
+        .read_module(|_, _, storage| storage.get(&key))?;
+    DenomTrace::decode(trace.as_slice()).ok()
+}
+
+/// Writes the trace of an `ibc/{HASH}` denom in the storage of the chain
+pub(crate) fn write_denom_trace<A: Api>(chain: &MockBase<A>, hash: &str, trace: &DenomTrace) {
+    let key = denom_trace_key(hash);
+    chain
+        .app
+        .borrow_mut()
+        .init_modules(|_, _, storage| storage.set(&key, &trace.encode_to_vec()));
+}
+
+fn denom_trace_key(hash: &str) -> Vec<u8> {
+    let hash = hash.strip_prefix("ibc/").unwrap_or(hash).to_uppercase();
+    [DENOM_TRACE_PREFIX, hash.as_bytes()].concat()
+}
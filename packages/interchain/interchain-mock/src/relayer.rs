@@ -0,0 +1,185 @@
+//! Manual relaying of the packets held in the mock environment
+
+use std::collections::VecDeque;
+
+use cosmwasm_std::{from_json, Api, IbcTimeout};
+use cw_orch_interchain_core::{
+    types::{FullIbcPacketAnalysis, IbcPacketInfo, TxId},
+    InterchainEnv,
+};
+use cw_orch_mock::cw_multi_test::ibc::types::{IbcPacketData, MockIbcQuery};
+use ibc_relayer_types::core::{
+    ics04_channel::packet::Sequence,
+    ics24_host::identifier::{ChannelId, PortId},
+};
+
+use crate::{
+    interchain::{MockBase, MockInterchainEnvBase},
+    InterchainMockError,
+};
+
+/// Packet sent on a chain and not relayed yet
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingPacket {
+    /// Chain the packet was sent from
+    pub src_chain_id: String,
+    /// Chain the packet is sent to
+    pub dst_chain_id: String,
+    /// Port the packet was sent from
+    pub src_port: PortId,
+    /// Channel the packet was sent on
+    pub src_channel: ChannelId,
+    /// Sequence of the packet on its channel
+    pub sequence: Sequence,
+}
+
+impl PendingPacket {
+    fn info(&self) -> IbcPacketInfo {
+        IbcPacketInfo {
+            src_port: self.src_port.clone(),
+            src_channel: self.src_channel.clone(),
+            sequence: self.sequence,
+            dst_chain_id: self.dst_chain_id.clone(),
+        }
+    }
+}
+
+/// Packets of a tx held by the mock relayer, created with [`MockInterchainEnvBase::hold_packets`].
+///
+/// The packets are only relayed when asked, in any order. A packet can also be dropped, timed out or delivered twice.
+/// The packets sent while relaying a packet (by the receive or acknowledgement txs) are relayed right away.
+pub struct PacketQueue<A: Api> {
+    interchain: MockInterchainEnvBase<A>,
+    send_tx: TxId<MockBase<A>>,
+    packets: VecDeque<PendingPacket>,
+}
+
+impl<A: Api> PacketQueue<A> {
+    pub(crate) fn new(
+        interchain: MockInterchainEnvBase<A>,
+        send_tx: TxId<MockBase<A>>,
+        packets: VecDeque<PendingPacket>,
+    ) -> Self {
+        Self {
+            interchain,
+            send_tx,
+            packets,
+        }
+    }
+
+    /// Packets still held, in the order they were sent
+    pub fn packets(&self) -> &VecDeque<PendingPacket> {
+        &self.packets
+    }
+
+    /// Number of packets still held
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    /// Whether all the packets were relayed or dropped
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Relays the oldest packet held
+    pub fn relay_next(
+        &mut self,
+    ) -> Result<FullIbcPacketAnalysis<MockBase<A>>, InterchainMockError> {
+        self.relay(0)
+    }
+
+    /// Relays the packet at `index` in the queue, before the packets sent earlier
+    pub fn relay(
+        &mut self,
+        index: usize,
+    ) -> Result<FullIbcPacketAnalysis<MockBase<A>>, InterchainMockError> {
+        let packet = self.get(index)?;
+        let analysis = self.follow(&packet)?;
+        self.packets.remove(index);
+        Ok(analysis)
+    }
+
+    /// Relays all the packets held, in order
+    pub fn relay_all(
+        &mut self,
+    ) -> Result<Vec<FullIbcPacketAnalysis<MockBase<A>>>, InterchainMockError> {
+        let mut analyses = vec![];
+        while !self.is_empty() {
+            analyses.push(self.relay_next()?);
+        }
+        Ok(analyses)
+    }
+
+    /// Relays the packet at `index` but keeps it in the queue, so it can be delivered again.
+    /// The chains reject a packet received twice, relaying it again returns an error.
+    pub fn relay_and_keep(
+        &mut self,
+        index: usize,
+    ) -> Result<FullIbcPacketAnalysis<MockBase<A>>, InterchainMockError> {
+        let packet = self.get(index)?;
+        self.follow(&packet)
+    }
+
+    /// Drops the packet at `index`, it's never relayed
+    pub fn drop_packet(&mut self, index: usize) -> Result<PendingPacket, InterchainMockError> {
+        self.packets
+            .remove(index)
+            .ok_or(InterchainMockError::PacketNotFound(index))
+    }
+
+    /// Times out the packet at `index`.
+    /// The destination chain is advanced past the timeout height and timestamp of the packet,
+    /// then the packet is relayed, which delivers the timeout to the source chain.
+    pub fn timeout(
+        &mut self,
+        index: usize,
+    ) -> Result<FullIbcPacketAnalysis<MockBase<A>>, InterchainMockError> {
+        let packet = self.get(index)?;
+        let src_mock = self.interchain.get_chain(&packet.src_chain_id)?;
+        let dst_mock = self.interchain.get_chain(&packet.dst_chain_id)?;
+
+        // The packets of the interchain accounts are not sent through cw-multi-test
+        let timeout = match self.interchain.ica_packet_timeout(
+            &packet.src_chain_id,
+            packet.src_port.as_str(),
+            packet.sequence.into(),
+        ) {
+            Some(timestamp) => IbcTimeout::with_timestamp(timestamp),
+            None => {
+                let packet_data: IbcPacketData =
+                    from_json(src_mock.app.borrow().ibc_query(MockIbcQuery::SendPacket {
+                        channel_id: packet.src_channel.to_string(),
+                        port_id: packet.src_port.to_string(),
+                        sequence: packet.sequence.into(),
+                    })?)?;
+                packet_data.timeout
+            }
+        };
+        dst_mock.app.borrow_mut().update_block(|block| {
+            if let Some(timeout_block) = timeout.block() {
+                block.height = block.height.max(timeout_block.height);
+            }
+            if let Some(timeout_timestamp) = timeout.timestamp() {
+                block.time = block.time.max(timeout_timestamp);
+            }
+        });
+
+        self.relay(index)
+    }
+
+    fn get(&self, index: usize) -> Result<PendingPacket, InterchainMockError> {
+        self.packets
+            .get(index)
+            .cloned()
+            .ok_or(InterchainMockError::PacketNotFound(index))
+    }
+
+    fn follow(
+        &self,
+        packet: &PendingPacket,
+    ) -> Result<FullIbcPacketAnalysis<MockBase<A>>, InterchainMockError> {
+        self.interchain
+            .follow_packet(&packet.src_chain_id, &packet.info(), self.send_tx.clone())
+    }
+}